@@ -0,0 +1,496 @@
+//! The tipping ledger without ink!: the arithmetic of fees, spendable
+//! balances, spending limits and leaderboards, and the `Ledger`, the binding
+//! registry and balance accounting of the contract in memory.
+//!
+//! It has no ink! dependency, so it could be tested natively, and used by
+//! the bot to simulate the contract off-chain. The contract calls the
+//! arithmetic directly, and keeps the rules of the `Ledger` over its own
+//! storage, which the `Ledger` runs for the `simulator`.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// The balance type of the contract.
+pub type Balance = u128;
+
+/// The block number type of the contract.
+pub type BlockNumber = u32;
+
+/// The index of an era of the treasury history.
+pub type EraIndex = u32;
+
+/// A Telegram User Id.
+pub type TelegramId = u32;
+
+/// The denominator of the basis points, i.e. 100%.
+pub const MAX_BPS: u16 = 10_000;
+
+/// The most users a single rain could pay.
+pub const MAX_RAIN_RECIPIENTS: usize = 100;
+
+/// The fee of `amount` in basis points, without overflowing.
+pub fn fee_of(amount: Balance, bps: u16) -> Balance {
+    let bps = Balance::from(bps);
+    let max = Balance::from(MAX_BPS);
+    amount / max * bps + amount % max * bps / max
+}
+
+/// `amount * part / total` rounded down, where `part <= total`, even if
+/// the product does not fit in a Balance.
+pub fn pro_rata(amount: Balance, part: Balance, total: Balance) -> Balance {
+    if let Some(product) = amount.checked_mul(part) {
+        return product / total;
+    }
+    // the 256 bits product, as the high and the low halves.
+    let half = |value: u128| (value >> 64, value & u128::from(u64::MAX));
+    let ((a1, a0), (b1, b0)) = (half(amount), half(part));
+    let (mid, carry) = (a0 * b1).overflowing_add(a1 * b0);
+    let (low, low_carry) = (a0 * b0).overflowing_add(mid << 64);
+    let high = a1 * b1
+        + (u128::from(carry) << 64)
+        + (mid >> 64)
+        + u128::from(low_carry);
+    // long division, the quotient fits as it is at most `amount`.
+    let (mut rem, mut quotient) = (high, 0);
+    for bit in (0..128).rev() {
+        let overflow = rem >> 127 == 1;
+        rem = rem << 1 | (low >> bit & 1);
+        quotient <<= 1;
+        if overflow || rem >= total {
+            rem = rem.wrapping_sub(total);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// How much of the `balance` could be spent at block `now`, that is what is
+/// not held by pending slashes, nor `locked` by votes until `locked_until`.
+pub fn spendable(
+    balance: Balance,
+    pending_slash: Balance,
+    locked: Balance,
+    locked_until: BlockNumber,
+    now: BlockNumber,
+) -> Balance {
+    let free = balance - pending_slash;
+    if now < locked_until {
+        free.saturating_sub(locked)
+    } else {
+        free
+    }
+}
+
+/// How much `amount` has to be rounded up by to be a multiple of `unit`.
+pub fn round_up(amount: Balance, unit: Balance) -> Balance {
+    (unit - amount % unit) % unit
+}
+
+/// How much was spent in the `era`, given that `spent` was counted in the
+/// `spent_era`.
+pub fn spent_in(spent: Balance, spent_era: EraIndex, era: EraIndex) -> Balance {
+    if spent_era == era {
+        spent
+    } else {
+        0
+    }
+}
+
+/// Check that spending `charged` on top of `spent` stays within the daily
+/// `limit`, if there is one.
+///
+/// Errors:
+/// Returns what is left of the limit if it would be exceeded.
+pub fn check_daily_limit(
+    spent: Balance,
+    charged: Balance,
+    limit: Option<Balance>,
+) -> Result<(), Balance> {
+    match limit {
+        Some(limit) if spent.saturating_add(charged) > limit => {
+            Err(limit.saturating_sub(spent))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// The index of the rate limit window of `window` blocks that `now` falls
+/// in, and the block it ends at.
+pub fn rate_window(
+    now: BlockNumber,
+    window: BlockNumber,
+) -> (BlockNumber, BlockNumber) {
+    let index = now / window;
+    (index, (index + 1).saturating_mul(window))
+}
+
+/// Move `account` to its place on a leaderboard of at most `size` entries
+/// given its new `total`, dropping whoever falls off the end.
+pub fn rank<A: PartialEq>(
+    board: &mut Vec<(A, Balance)>,
+    account: A,
+    total: Balance,
+    size: usize,
+) {
+    board.retain(|(ranked, _)| *ranked != account);
+    let index = board
+        .iter()
+        .position(|(_, other)| *other < total)
+        .unwrap_or(board.len());
+    if index < size {
+        board.insert(index, (account, total));
+        board.truncate(size);
+    }
+}
+
+/// The balance of a bound user, and what holds part of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    /// The balance the user holds in the contract.
+    pub balance: Balance,
+    /// How much of the balance is held by pending slashes.
+    pub pending_slash: Balance,
+    /// How much of the balance is locked by votes.
+    pub locked: Balance,
+    /// The block the lock expires at.
+    pub locked_until: BlockNumber,
+}
+
+impl Entry {
+    /// How much of the balance could be spent at block `now`, see
+    /// `spendable`.
+    pub fn spendable(&self, now: BlockNumber) -> Balance {
+        spendable(
+            self.balance,
+            self.pending_slash,
+            self.locked,
+            self.locked_until,
+            now,
+        )
+    }
+}
+
+/// Why the `Ledger` refuses a change, named after the error the contract
+/// returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerError {
+    /// The account is already bound.
+    AccountAlreadyBound,
+    /// The TelegramId is bound to another account.
+    TelegramIdTaken,
+    /// The account, or the TelegramId, is not bound.
+    NotFound,
+    /// The amount is zero.
+    ZeroAmount,
+    /// The balance can not cover the amount.
+    InsufficientFunds {
+        /// How much is needed.
+        required: Balance,
+        /// How much is available.
+        available: Balance,
+    },
+    /// A balance would overflow.
+    Overflow,
+}
+
+/// The binding registry and the balance accounting of the contract, in
+/// memory.
+///
+/// The balances are kept by TelegramId, so they move with the binding when
+/// it is rebound to another account, as they do in the contract.
+#[derive(Debug, Clone)]
+pub struct Ledger<A> {
+    bindings: BTreeMap<TelegramId, (A, Entry)>,
+    accounts: BTreeMap<A, TelegramId>,
+}
+
+impl<A: Ord + Copy> Default for Ledger<A> {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+        }
+    }
+}
+
+impl<A: Ord + Copy> Ledger<A> {
+    /// An empty ledger.
+    pub fn new() -> Self { Self::default() }
+
+    /// How many accounts are bound.
+    pub fn len(&self) -> usize { self.bindings.len() }
+
+    /// Whether no account is bound.
+    pub fn is_empty(&self) -> bool { self.bindings.is_empty() }
+
+    /// The account bound to the TelegramId.
+    pub fn address_of(&self, tg_id: TelegramId) -> Option<A> {
+        self.bindings.get(&tg_id).map(|(account, _)| *account)
+    }
+
+    /// The TelegramId the account is bound to.
+    pub fn telegram_id_of(&self, account: A) -> Option<TelegramId> {
+        self.accounts.get(&account).copied()
+    }
+
+    /// The entry of the TelegramId.
+    pub fn entry(&self, tg_id: TelegramId) -> Option<&Entry> {
+        self.bindings.get(&tg_id).map(|(_, entry)| entry)
+    }
+
+    /// The entry of the TelegramId, to change what holds its balance.
+    pub fn entry_mut(&mut self, tg_id: TelegramId) -> Option<&mut Entry> {
+        self.bindings.get_mut(&tg_id).map(|(_, entry)| entry)
+    }
+
+    /// The balance of the TelegramId, zero if it is not bound.
+    pub fn balance_of(&self, tg_id: TelegramId) -> Balance {
+        self.entry(tg_id).map_or(0, |entry| entry.balance)
+    }
+
+    /// The total of the balances, what the contract owes to the bound
+    /// users.
+    pub fn total(&self) -> Balance {
+        self.bindings
+            .values()
+            .fold(0, |total, (_, entry)| total.saturating_add(entry.balance))
+    }
+
+    /// Bind the account to the TelegramId, with an empty balance.
+    ///
+    /// Errors:
+    /// * Returns `LedgerError::AccountAlreadyBound` if the account is already
+    ///   bound.
+    ///
+    /// * Returns `LedgerError::TelegramIdTaken` if the TelegramId is bound to
+    ///   another account.
+    pub fn bind(
+        &mut self,
+        account: A,
+        tg_id: TelegramId,
+    ) -> Result<(), LedgerError> {
+        if self.accounts.contains_key(&account) {
+            return Err(LedgerError::AccountAlreadyBound);
+        }
+        if self.bindings.contains_key(&tg_id) {
+            return Err(LedgerError::TelegramIdTaken);
+        }
+        self.bindings.insert(tg_id, (account, Entry::default()));
+        self.accounts.insert(account, tg_id);
+        Ok(())
+    }
+
+    /// Unbind the account, returning the balance to refund it.
+    ///
+    /// Errors:
+    /// Returns `LedgerError::NotFound` if the account is not bound.
+    pub fn unbind(&mut self, account: A) -> Result<Balance, LedgerError> {
+        let tg_id = self
+            .accounts
+            .remove(&account)
+            .ok_or(LedgerError::NotFound)?;
+        let (_, entry) = self
+            .bindings
+            .remove(&tg_id)
+            .expect("every bound account has an entry");
+        Ok(entry.balance)
+    }
+
+    /// Move the binding of `from`, and its balance, to the account `to`.
+    ///
+    /// Errors:
+    /// * Returns `LedgerError::NotFound` if `from` is not bound.
+    ///
+    /// * Returns `LedgerError::AccountAlreadyBound` if `to` is already bound.
+    pub fn rebind(&mut self, from: A, to: A) -> Result<(), LedgerError> {
+        let tg_id = self.telegram_id_of(from).ok_or(LedgerError::NotFound)?;
+        if self.accounts.contains_key(&to) {
+            return Err(LedgerError::AccountAlreadyBound);
+        }
+        self.accounts.remove(&from);
+        self.accounts.insert(to, tg_id);
+        if let Some((account, _)) = self.bindings.get_mut(&tg_id) {
+            *account = to;
+        }
+        Ok(())
+    }
+
+    /// Add `amount` to the balance of the TelegramId.
+    ///
+    /// Errors:
+    /// * Returns `LedgerError::NotFound` if the TelegramId is not bound.
+    ///
+    /// * Returns `LedgerError::Overflow` if the balance would overflow.
+    pub fn credit(
+        &mut self,
+        tg_id: TelegramId,
+        amount: Balance,
+    ) -> Result<(), LedgerError> {
+        let entry = self.entry_mut(tg_id).ok_or(LedgerError::NotFound)?;
+        entry.balance = entry
+            .balance
+            .checked_add(amount)
+            .ok_or(LedgerError::Overflow)?;
+        Ok(())
+    }
+
+    /// Take `amount` out of what the TelegramId could spend at block `now`.
+    ///
+    /// Errors:
+    /// * Returns `LedgerError::NotFound` if the TelegramId is not bound.
+    ///
+    /// * Returns `LedgerError::ZeroAmount` if the `amount` is zero.
+    ///
+    /// * Returns `LedgerError::InsufficientFunds` if the spendable balance
+    ///   could not cover it.
+    pub fn debit(
+        &mut self,
+        tg_id: TelegramId,
+        amount: Balance,
+        now: BlockNumber,
+    ) -> Result<(), LedgerError> {
+        let entry = self.entry_mut(tg_id).ok_or(LedgerError::NotFound)?;
+        if amount == 0 {
+            return Err(LedgerError::ZeroAmount);
+        }
+        let available = entry.spendable(now);
+        if available < amount {
+            return Err(LedgerError::InsufficientFunds {
+                required: amount,
+                available,
+            });
+        }
+        entry.balance -= amount;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pro_rata_does_not_overflow() {
+        assert_eq!(pro_rata(10, 3, 4), 7);
+        assert_eq!(pro_rata(0, 3, 4), 0);
+        assert_eq!(pro_rata(Balance::MAX, 1, 2), Balance::MAX / 2);
+        assert_eq!(pro_rata(Balance::MAX, 7, 7), Balance::MAX);
+        assert_eq!(
+            pro_rata(Balance::MAX, Balance::MAX, Balance::MAX),
+            Balance::MAX
+        );
+        assert_eq!(
+            pro_rata(Balance::MAX, Balance::MAX - 1, Balance::MAX),
+            Balance::MAX - 1
+        );
+        assert_eq!(pro_rata(1 << 100, 1 << 100, 1 << 101), 1 << 99);
+    }
+
+    #[test]
+    fn fee_of_does_not_overflow() {
+        assert_eq!(fee_of(0, 10_000), 0);
+        assert_eq!(fee_of(12_345, 0), 0);
+        assert_eq!(fee_of(12_345, 100), 123);
+        assert_eq!(fee_of(Balance::MAX, 10_000), Balance::MAX);
+        assert_eq!(fee_of(Balance::MAX, 5_000), Balance::MAX / 2);
+    }
+
+    #[test]
+    fn spending_limits_works() {
+        assert_eq!(spendable(100, 10, 50, 5, 4), 40);
+        assert_eq!(spendable(100, 10, 50, 5, 5), 90);
+        assert_eq!(spendable(100, 10, 200, 5, 4), 0);
+
+        assert_eq!(spent_in(30, 1, 1), 30);
+        assert_eq!(spent_in(30, 1, 2), 0);
+        assert_eq!(check_daily_limit(30, 20, None), Ok(()));
+        assert_eq!(check_daily_limit(30, 20, Some(50)), Ok(()));
+        assert_eq!(check_daily_limit(30, 21, Some(50)), Err(20));
+        assert_eq!(check_daily_limit(60, 1, Some(50)), Err(0));
+
+        assert_eq!(round_up(15, 10), 5);
+        assert_eq!(round_up(20, 10), 0);
+        assert_eq!(rate_window(25, 10), (2, 30));
+    }
+
+    #[test]
+    fn rank_keeps_the_leaderboard_bounded() {
+        let mut board = Vec::new();
+        for i in 0..12u8 {
+            rank(&mut board, i, Balance::from(i), 10);
+        }
+        assert_eq!(board.len(), 10);
+        assert_eq!(board[0], (11, 11));
+        // moving up does not duplicate the entry.
+        rank(&mut board, 2, 20, 10);
+        assert_eq!(board[0], (2, 20));
+        assert_eq!(board.len(), 10);
+        // too little to make it on the board.
+        rank(&mut board, 0, 0, 10);
+        assert!(!board.contains(&(0, 0)));
+    }
+
+    #[test]
+    fn ledger_binds_and_rebinds() {
+        let mut ledger = Ledger::new();
+        assert!(ledger.is_empty());
+        assert_eq!(ledger.bind('a', 42), Ok(()));
+        assert_eq!(ledger.bind('a', 43), Err(LedgerError::AccountAlreadyBound));
+        assert_eq!(ledger.bind('b', 42), Err(LedgerError::TelegramIdTaken));
+        assert_eq!(ledger.bind('b', 142), Ok(()));
+        assert_eq!(ledger.address_of(42), Some('a'));
+        assert_eq!(ledger.telegram_id_of('b'), Some(142));
+        assert_eq!(ledger.len(), 2);
+
+        // the balance moves with the binding.
+        assert_eq!(ledger.credit(42, 100), Ok(()));
+        assert_eq!(
+            ledger.rebind('a', 'b'),
+            Err(LedgerError::AccountAlreadyBound)
+        );
+        assert_eq!(ledger.rebind('c', 'd'), Err(LedgerError::NotFound));
+        assert_eq!(ledger.rebind('a', 'c'), Ok(()));
+        assert_eq!(ledger.address_of(42), Some('c'));
+        assert_eq!(ledger.telegram_id_of('a'), None);
+        assert_eq!(ledger.balance_of(42), 100);
+        // the old account is free to bind again.
+        assert_eq!(ledger.bind('a', 43), Ok(()));
+
+        assert_eq!(ledger.unbind('c'), Ok(100));
+        assert_eq!(ledger.unbind('c'), Err(LedgerError::NotFound));
+        assert_eq!(ledger.address_of(42), None);
+        assert_eq!(ledger.balance_of(42), 0);
+        assert_eq!(ledger.len(), 2);
+    }
+
+    #[test]
+    fn ledger_accounts_for_balances() {
+        let mut ledger = Ledger::new();
+        assert_eq!(ledger.credit(42, 1), Err(LedgerError::NotFound));
+        assert_eq!(ledger.bind(1u8, 42), Ok(()));
+        assert_eq!(ledger.bind(2u8, 142), Ok(()));
+        assert_eq!(ledger.credit(42, 100), Ok(()));
+        assert_eq!(ledger.credit(142, Balance::MAX), Ok(()));
+        assert_eq!(ledger.credit(142, 1), Err(LedgerError::Overflow));
+        assert_eq!(ledger.total(), Balance::MAX);
+
+        let entry = ledger.entry_mut(42).unwrap();
+        entry.pending_slash = 10;
+        entry.locked = 50;
+        entry.locked_until = 5;
+        assert_eq!(ledger.debit(42, 0, 4), Err(LedgerError::ZeroAmount));
+        assert_eq!(
+            ledger.debit(42, 41, 4),
+            Err(LedgerError::InsufficientFunds {
+                required: 41,
+                available: 40,
+            })
+        );
+        assert_eq!(ledger.debit(42, 40, 4), Ok(()));
+        assert_eq!(ledger.balance_of(42), 60);
+        // the lock expired, but the slash still holds its part.
+        assert_eq!(ledger.entry(42).unwrap().spendable(5), 50);
+        assert_eq!(ledger.debit(42, 50, 5), Ok(()));
+        assert_eq!(ledger.debit(43, 1, 5), Err(LedgerError::NotFound));
+    }
+}
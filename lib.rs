@@ -35,453 +35,22360 @@
 //! must have a mapping between thier telegram id to an AccountId) in contract
 //! to work properly.
 
+extern crate alloc;
+
+use ink_env::{DefaultEnvironment, Environment};
 use ink_lang as ink;
+use ink_prelude::vec::Vec;
+
+pub mod ledger;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+/// The chain extension the runtime provides to the contract.
+///
+/// It is only called when the contract is built with the `chain-extension`
+/// feature, as Edgeware does not provide it.
+#[ink::chain_extension]
+pub trait TipbotExtension {
+    type ErrorCode = ExtensionError;
+
+    /// Check that `mac` is the HMAC-SHA256 Telegram made of the login
+    /// `payload`, the data-check-string of the login widget, against the bot
+    /// token the runtime is configured with.
+    #[ink(
+        extension = 0x5447_0001,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn verify_telegram_auth(payload: Vec<u8>, mac: [u8; 32]) -> bool;
+
+    /// Read the display name of the on-chain identity of the account, if it
+    /// has a `Reasonable` or `KnownGood` judgement.
+    #[ink(
+        extension = 0x5447_0002,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn identity_display_name(account: ink_env::AccountId) -> Option<Vec<u8>>;
+
+    /// Send `amount` of the balance of the contract to the `account` on the
+    /// parachain `para_id` with an XCM reserve transfer, returning whether
+    /// it was dispatched, nothing is sent otherwise.
+    #[ink(
+        extension = 0x5447_0003,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn transfer_to_parachain(
+        para_id: u32,
+        account: ink_env::AccountId,
+        amount: <DefaultEnvironment as Environment>::Balance,
+    ) -> bool;
+
+    /// Bond `amount` more of the balance of the contract with the staking
+    /// pallet, returning whether it was bonded, nothing is bonded otherwise.
+    #[ink(
+        extension = 0x5447_0004,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn bond(amount: <DefaultEnvironment as Environment>::Balance) -> bool;
+
+    /// Start unbonding `amount` of the bonded balance of the contract,
+    /// returning whether it started.
+    #[ink(
+        extension = 0x5447_0005,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn unbond(amount: <DefaultEnvironment as Environment>::Balance) -> bool;
+
+    /// Unlock the balance of the contract that finished unbonding, returning
+    /// how much was unlocked.
+    #[ink(
+        extension = 0x5447_0006,
+        handle_status = false,
+        returns_result = false
+    )]
+    fn withdraw_unbonded() -> <DefaultEnvironment as Environment>::Balance;
+}
+
+/// The status codes of the `TipbotExtension` chain extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ExtensionError {
+    /// The runtime could not serve the call.
+    Failed,
+}
+
+impl ink_env::chain_extension::FromStatusCode for ExtensionError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::Failed),
+        }
+    }
+}
+
+/// The default environment, with the `TipbotExtension` chain extension.
+pub enum TipbotEnvironment {}
+
+impl Environment for TipbotEnvironment {
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type ChainExtension = TipbotExtension;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    const MAX_EVENT_TOPICS: usize =
+        <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+}
+
+// The `deploy` and `call` entry points ink! generates in the contract module
+// are `#[no_mangle]`, which is linted as unsafe code. They are not generated
+// while testing, where the rest of the module is still denied unsafe code.
+#[ink::contract(env = crate::TipbotEnvironment)]
+#[cfg_attr(not(test), allow(unsafe_code))]
+mod tipbot {
+    use crate::ledger::{self, fee_of, pro_rata, MAX_BPS, MAX_RAIN_RECIPIENTS};
+    use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+    use ink_env::hash::Blake2x256;
+    use ink_prelude::string::String;
+    use ink_prelude::vec::Vec;
+    use ink_primitives::Key;
+    use ink_storage::traits::{PackedLayout, SpreadAllocate, SpreadLayout};
+    use ink_storage::Mapping;
+
+    /// A Telegram User Id.
+    type TelegramId = u32;
+
+    /// The Id of an admin proposal.
+    type ProposalId = u32;
+
+    /// The index of an era in the treasury history.
+    type EraIndex = u32;
+
+    /// The Id of a pending slash.
+    type SlashId = u32;
+
+    /// The Id of a tip held in the reversal window.
+    type HeldTipId = u32;
+
+    /// The sequential Id of a successful tip.
+    type TipId = u32;
+
+    /// A Telegram group chat Id, these are negative for groups.
+    type GroupId = i64;
+
+    /// The Id of a team wallet.
+    type TeamId = u32;
+
+    /// The Id of a tip proposed from a team wallet.
+    type TeamTipId = u32;
+
+    /// The Id of a soulbound achievement token.
+    type AchievementId = u32;
+
+    /// The Id of a thank-you receipt token of a tip.
+    type TipNftId = u32;
+
+    /// The Id of a raffle.
+    type RaffleId = u32;
+
+    /// The Id of a coin-flip challenge.
+    type ChallengeId = u32;
+
+    /// The Id of a prediction poll.
+    type PollId = u32;
+
+    /// The Id of an external charity cause.
+    type CauseId = u32;
+
+    /// The Id of a milestone escrow.
+    type EscrowId = u32;
+
+    /// The index of a statistics epoch, see `EPOCH_LENGTH`.
+    type EpochIndex = u32;
+
+    /// The Id of a tipping contest.
+    type ContestId = u32;
+
+    /// The Id of a scheduled job.
+    type JobId = u32;
+
+    /// The Id of a parachain.
+    type ParaId = u32;
+
+    /// The version of the layout of the `Tipped`, `Bound` and `Unbound`
+    /// events, bumped whenever thier fields change so indexers could tell
+    /// the layouts apart.
+    const EVENT_SCHEMA_VERSION: u16 = 1;
+
+    /// The revision of the ABI of the contract, bumped whenever a message is
+    /// added, removed, or changes its arguments or return type.
+    ///
+    /// Every message has its selector pinned, so renaming or reordering the
+    /// messages doesn't change the ABI. The signatures of the messages are
+    /// pinned along with it by `spec_version_tracks_the_messages`, a change
+    /// to the types they take or return has to be noticed by hand.
+    const SPEC_VERSION: u32 = 52;
+
+    /// The version of the storage layout of the contract, bumped whenever a
+    /// stored type changes so an upgraded code could tell it has to migrate.
+    const STORAGE_VERSION: u16 = 1;
+
+    /// The storage key of the reentrancy lock, set while the contract calls
+    /// other contracts.
+    ///
+    /// It is written right away, rather than with the rest of the contract
+    /// when the message returns, so the called contracts could see it.
+    const REENTRANCY_LOCK: Key =
+        Key::new(*b"tipbot::reentrancy-lock\0\0\0\0\0\0\0\0\0");
+
+    /// The selector of the `on_tip(from, to, amount)` message every
+    /// registered hook contract must implement.
+    ///
+    /// it is the default ink! selector of a message called `on_tip`.
+    const ON_TIP_SELECTOR: [u8; 4] = [0xB3, 0xDE, 0x75, 0xA2];
+
+    /// The maximum amount of gas each hook call is allowed to use, until the
+    /// owners change it.
+    const DEFAULT_HOOK_GAS_LIMIT: u64 = 5_000_000_000;
+
+    /// The selector of the `edg_usd_price()` message of the price oracle,
+    /// returning how much of EDG one US cent is worth, and the time the price
+    /// was updated at.
+    ///
+    /// it is the default ink! selector of a message called `edg_usd_price`.
+    const EDG_USD_PRICE_SELECTOR: [u8; 4] = [0xC3, 0xA5, 0x71, 0x59];
+
+    /// The maximum amount of gas the price oracle query is allowed to use.
+    const ORACLE_GAS_LIMIT: u64 = 5_000_000_000;
+
+    /// How old the price of the oracle could be, 10 minutes in milliseconds.
+    const MAX_PRICE_AGE: Timestamp = 600_000;
+
+    /// The selector of `PSP22::transfer(to, value, data)`.
+    const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xDB, 0x20, 0xF9, 0xF5];
+
+    /// The selector of `PSP22::transfer_from(from, to, value, data)`.
+    const PSP22_TRANSFER_FROM_SELECTOR: [u8; 4] = [0x54, 0xB3, 0xC7, 0x6E];
+
+    /// The selector of `PSP22::approve(spender, value)`.
+    const PSP22_APPROVE_SELECTOR: [u8; 4] = [0xB2, 0x0F, 0x1B, 0xBD];
+
+    /// The selector of `PSP22::balance_of(owner)`.
+    const PSP22_BALANCE_OF_SELECTOR: [u8; 4] = [0x65, 0x68, 0x38, 0x2F];
+
+    /// The selector of `Router::swap_exact_tokens_for_native(amount_in,
+    /// amount_out_min, path, to, deadline)` of the DEX router.
+    const SWAP_FOR_NATIVE_SELECTOR: [u8; 4] = [0xCB, 0x57, 0x74, 0x23];
+
+    /// The maximum amount of gas each call to a token, or the DEX router, is
+    /// allowed to use, until the owners change it.
+    const DEFAULT_TOKEN_CALL_GAS_LIMIT: u64 = 5_000_000_000;
+
+    /// The maximum number of hooks that could be registered at the same time.
+    const MAX_HOOKS: usize = 8;
+
+    /// How many blocks to wait between scheduling the termination of the
+    /// contract and terminating it, about a week of 6 seconds blocks.
+    const TERMINATION_DELAY: BlockNumber = 100_800;
+
+    /// How many blocks a parameter change is open for voting, about a week
+    /// of 6 seconds blocks.
+    const VOTING_PERIOD: BlockNumber = 100_800;
+
+    /// The default of how much balance has to vote on a parameter change
+    /// for it to pass.
+    const DEFAULT_PARAM_QUORUM: Balance = 1_000 * EDG;
+
+    /// How many blocks an era of the treasury history spans, about a day of 6
+    /// seconds blocks.
+    const ERA_LENGTH: BlockNumber = 14_400;
+
+    /// The most eras a single `treasury_report` returns.
+    const MAX_REPORT_ERAS: u32 = 90;
+
+    /// How many blocks a user has to contest a slash, about 3 days of 6
+    /// seconds blocks.
+    const APPEAL_WINDOW: BlockNumber = 43_200;
+
+    /// The maximum number of held tips a user could be receiving at the same
+    /// time.
+    const MAX_PENDING_TIPS: usize = 16;
+
+    /// The maximum number of those held tips that could come from the same
+    /// sender, so one sender could not take all the slots of a recipient.
+    const MAX_PENDING_TIPS_PER_SENDER: usize = 4;
+
+    /// The maximum number of open positions an account could have, so
+    /// moving its binding to a new key stays bounded.
+    const MAX_POSITIONS: usize = 32;
+
+    /// The share of an expired tip paid to whoever expires it, in basis
+    /// points.
+    const EXPIRY_REWARD_BPS: u16 = 10;
+
+    /// The maximum number of jobs waiting to be executed.
+    const MAX_JOBS: usize = 256;
+
+    /// The maximum number of jobs a user could have waiting at a time.
+    const MAX_JOBS_PER_USER: usize = 16;
+
+    /// What the scheduler of a job deposits for the keeper who executes it.
+    const KEEPER_INCENTIVE: Balance = EDG / 100;
+
+    /// How many of the latest tips are kept in the tip log.
+    const TIP_LOG_SIZE: u32 = 1024;
+
+    /// The most tips a single `recent_tips`, `tips_sent_by` or
+    /// `tips_received_by` returns.
+    const MAX_RECENT_TIPS: u32 = 100;
+
+    /// The most tips pruned from a user's history on a single write, once
+    /// the `history_limit` got lowered.
+    const MAX_HISTORY_PRUNE: u32 = 8;
+
+    /// How many of the latest sent, and received, tips are kept per user
+    /// until the owners change it.
+    const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+    /// How many users are ranked on each of the group leaderboards.
+    const GROUP_LEADERBOARD_SIZE: usize = 10;
+
+    /// The maximum number of moderators a group could have.
+    const MAX_GROUP_MODERATORS: usize = 16;
+
+    /// The most users `export_users` returns at once.
+    const MAX_EXPORT_PAGE: u32 = 100;
+
+    /// The most items a single `wind_down` call refunds.
+    const MAX_WIND_DOWN: u32 = 50;
+
+    /// The most users a single weighted tip could pay.
+    const MAX_WEIGHTED_RECIPIENTS: usize = 20;
+
+    /// The most milestones an escrow could be split into.
+    const MAX_MILESTONES: usize = 20;
+
+    /// The maximum number of members a team could have.
+    const MAX_TEAM_MEMBERS: usize = 32;
+
+    /// The maximum number of accounts that could fund the same team.
+    const MAX_TEAM_CONTRIBUTORS: usize = 32;
+
+    /// How many blocks a team could go without paying a tip before it could
+    /// be cancelled, about 30 days of 6 seconds blocks.
+    const TEAM_IDLE_PERIOD: BlockNumber = 432_000;
+
+    /// One EDG, in the smallest unit of the balance.
+    const EDG: Balance = 1_000_000_000_000_000_000;
+
+    /// About a year of 6 seconds blocks.
+    const YEAR: BlockNumber = 5_256_000;
+
+    /// How many blocks a user has to be inactive for before thier dust could
+    /// be swept, until the owners change it.
+    const DEFAULT_DUST_DORMANCY: BlockNumber = 2 * YEAR;
+
+    /// How many blocks a dust sweep is announced for before it could happen,
+    /// about a month of 6 seconds blocks.
+    const DUST_GRACE_PERIOD: BlockNumber = 432_000;
+
+    /// How many blocks after asking to unlock thier account a user could
+    /// unlock it without an owner, about 3 days of 6 seconds blocks.
+    const SELF_LOCK_DELAY: BlockNumber = 43_200;
+
+    /// How many blocks a recovery of a binding has to wait before it could
+    /// be executed, about a week of 6 seconds blocks.
+    const RECOVERY_DELAY: BlockNumber = 100_800;
+
+    /// The maximum number of guardians a user could have.
+    const MAX_GUARDIANS: usize = 8;
+
+    /// The maximum number of secondary addresses linked to a binding.
+    const MAX_LINKED_ADDRESSES: usize = 4;
+
+    /// A day, in milliseconds of the block timestamp.
+    const DAY: Timestamp = 86_400_000;
+
+    /// The expected time between two blocks, in milliseconds, to compare
+    /// the withdrawal delays with the periods counted in blocks.
+    const BLOCK_TIME: Timestamp = 6_000;
+
+    /// How long a statistics epoch lasts, about a month, in milliseconds of
+    /// the block timestamp.
+    const EPOCH_LENGTH: Timestamp = 30 * DAY;
+
+    /// How many tips the totals of the current era and epoch are kept in
+    /// `stats_cache` for, before they are written to `fee_history` and
+    /// `epoch_stats`.
+    const STATS_FLUSH_INTERVAL: u32 = 16;
+
+    /// The decimals of EDG, and so of the tipEDG receipt token.
+    const EDG_DECIMALS: u8 = 18;
+
+    /// How old a Telegram login payload could be to bind with it.
+    #[cfg(feature = "chain-extension")]
+    const TELEGRAM_AUTH_MAX_AGE: Timestamp = DAY;
+
+    /// How many blocks after a raffle ends its seed could be revealed to draw
+    /// it, about a day of 6 seconds blocks. After that, the tickets could be
+    /// refunded instead.
+    const RAFFLE_REVEAL_WINDOW: BlockNumber = 14_400;
+
+    /// How many blocks a coin-flip challenge could be accepted for, and then
+    /// flipped for, before it could be refunded, about a day of 6 seconds
+    /// blocks.
+    const CHALLENGE_TIMEOUT: BlockNumber = 14_400;
+
+    /// How many blocks the arbiters have to vote on a dispute, about 3 days
+    /// of 6 seconds blocks.
+    const DISPUTE_VOTING_PERIOD: BlockNumber = 43_200;
+
+    /// The maximum number of arbiters on the panel.
+    const MAX_ARBITERS: usize = 16;
+
+    /// The maximum length of an alias, in bytes.
+    const MAX_ALIAS_LEN: usize = 32;
+
+    /// The maximum length of the symbol of an asset, in bytes.
+    const MAX_SYMBOL_LEN: usize = 16;
+
+    /// The maximum length of the label of a moderator note, in bytes.
+    const MAX_NOTE_LEN: usize = 64;
+
+    /// The most winners a tipping contest could have.
+    const MAX_CONTEST_WINNERS: usize = 10;
+
+    /// The maximum number of tip categories.
+    const MAX_CATEGORIES: usize = 32;
+
+    /// The maximum number of senders a user could block, or allow.
+    const MAX_INBOX_SENDERS: usize = 64;
+
+    /// The maximum length of the name of a relayer, in bytes.
+    const MAX_RELAYER_NAME_LEN: usize = 32;
+
+    /// The maximum length of the name of a tip category, in bytes.
+    const MAX_CATEGORY_NAME_LEN: usize = 32;
+
+    /// Badge: sent the first tip.
+    pub const BADGE_FIRST_TIP: u32 = 1 << 0;
+
+    /// Badge: sent 10 tips.
+    pub const BADGE_TEN_TIPS: u32 = 1 << 1;
+
+    /// Badge: tipped 100 EDG in total.
+    pub const BADGE_HUNDRED_EDG: u32 = 1 << 2;
+
+    /// Badge: bound for a year.
+    pub const BADGE_ONE_YEAR: u32 = 1 << 3;
+
+    /// Badge: tipped 1000 EDG in total.
+    pub const BADGE_THOUSAND_EDG: u32 = 1 << 4;
+
+    /// Feature: could escrow work with `create_escrow`.
+    pub const FEATURE_ESCROW: u32 = 1 << 0;
+
+    /// Feature: could challenge other users to a coin-flip.
+    pub const FEATURE_CHALLENGE: u32 = 1 << 1;
+
+    /// Feature: tips could be held in the reversal window, or until the
+    /// recipient accepts them.
+    pub const FEATURE_PENDING_TIPS: u32 = 1 << 2;
+
+    /// Feature: could play contests, raffles, challenges and polls.
+    pub const FEATURE_GAMES: u32 = 1 << 3;
+
+    /// Feature: could mint receipt and achievement tokens.
+    pub const FEATURE_NFTS: u32 = 1 << 4;
+
+    /// All the `FEATURE_*` flags this code supports.
+    pub const SUPPORTED_FEATURES: u32 = FEATURE_ESCROW
+        | FEATURE_CHALLENGE
+        | FEATURE_PENDING_TIPS
+        | FEATURE_GAMES
+        | FEATURE_NFTS;
+
+    /// Tipping statistics of a user.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct UserStats {
+        /// How many tips this user sent.
+        #[codec(compact)]
+        pub tips_sent: u32,
+        /// How many tips this user received.
+        #[codec(compact)]
+        pub tips_received: u32,
+        /// The total amount this user tipped others.
+        #[codec(compact)]
+        pub total_sent: Balance,
+        /// The total amount this user got tipped.
+        #[codec(compact)]
+        pub total_received: Balance,
+    }
+
+    /// The running totals of everything that moved the balance of a user,
+    /// so they could check that it adds up, see `AuditReport`.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct AuditCounters {
+        /// The total deposited to the balance.
+        #[codec(compact)]
+        pub deposited: Balance,
+        /// The total withdrawn, including the queued withdrawal.
+        #[codec(compact)]
+        pub withdrawn: Balance,
+        /// The total paid out of the balance for tips, with the charity
+        /// round-ups.
+        #[codec(compact)]
+        pub tips_out: Balance,
+        /// The total of the tips kept in the balance, after the fees.
+        #[codec(compact)]
+        pub tips_in: Balance,
+        /// The total of the relay fees and the dispute fees.
+        #[codec(compact)]
+        pub fees_paid: Balance,
+        /// The total credited by everything else, like refunds, rewards and
+        /// payouts.
+        #[codec(compact)]
+        pub other_in: Balance,
+        /// The total debited by everything else, like burns, stakes, slashes
+        /// and escrows.
+        #[codec(compact)]
+        pub other_out: Balance,
+    }
+
+    /// Everything we know about a bound user, stored under thier `AccountId`
+    /// so the hot paths need only one storage access per party.
+    ///
+    /// Its balances and counters are compact encoded, as they are far below
+    /// the maximum of thier types for almost every user.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct UserRecord {
+        /// The Telegram Id this account is bound to.
+        pub tg_id: TelegramId,
+        /// The balance this user holds in the contract.
+        #[codec(compact)]
+        pub balance: Balance,
+        /// The tipping statistics of this user.
+        pub stats: UserStats,
+        /// The position of this account in the list of bound accounts.
+        pub index: u32,
+        /// How much of the balance is locked by votes.
+        #[codec(compact)]
+        pub locked: Balance,
+        /// The block the lock expires at.
+        pub locked_until: BlockNumber,
+        /// How much of the balance is held by pending slashes.
+        #[codec(compact)]
+        pub pending_slash: Balance,
+        /// The preferences of this user.
+        pub settings: UserSettings,
+        /// How much this user tipped during `spent_era`.
+        #[codec(compact)]
+        pub spent_today: Balance,
+        /// The era `spent_today` is counted in.
+        pub spent_era: EraIndex,
+        /// How many tips were added to the sent history of this user.
+        #[codec(compact)]
+        pub sent_seq: u32,
+        /// How many tips were added to the received history of this user.
+        #[codec(compact)]
+        pub received_seq: u32,
+        /// The non-transferable loyalty points this user earned by tipping.
+        #[codec(compact)]
+        pub points: Balance,
+        /// The badges this user earned, as a bitfield of the `BADGE_*` flags.
+        pub badges: u32,
+        /// The block this account got bound at.
+        pub bound_at: BlockNumber,
+        /// For how many consecutive days this user tipped.
+        #[codec(compact)]
+        pub streak: u32,
+        /// The last day this user tipped, as days since the epoch.
+        pub last_tip_day: u64,
+        /// The total this user gave to the charity by rounding up tips.
+        #[codec(compact)]
+        pub charity_contributed: Balance,
+        /// Where the tips this user receives, and thier balance when they
+        /// unbind, are sent, `None` for the bound account itself.
+        pub payout: Option<AccountId>,
+        /// The last block this user did anything at.
+        pub last_active: BlockNumber,
+        /// Who could claim the balance of this user after they are inactive
+        /// for long enough, as the beneficiary and the inactivity period in
+        /// blocks.
+        pub beneficiary: Option<(AccountId, BlockNumber)>,
+        /// How many milliseconds the withdrawals of this user are queued for,
+        /// zero if they are paid right away.
+        pub withdrawal_delay: Timestamp,
+        /// A shorter withdrawal delay waiting to take effect, as the new
+        /// delay and the time it takes effect at.
+        pub delay_change: Option<(Timestamp, Timestamp)>,
+        /// The queued withdrawal, as the amount, already taken out of the
+        /// balance, and the time it could be executed at.
+        pub pending_withdrawal: Option<(Balance, Timestamp)>,
+        /// Whether this user locked thier account, blocking everything that
+        /// moves funds out of it.
+        pub self_locked: bool,
+        /// The block this user asked to unlock thier account at, if they
+        /// did.
+        pub unlock_requested_at: Option<BlockNumber>,
+        /// The accounts who could recover this binding to a new key.
+        pub guardians: Vec<AccountId>,
+        /// How many guardians have to approve a recovery.
+        pub guardian_threshold: u32,
+        /// The soulbound achievement tokens of the user, oldest first.
+        pub achievements: Vec<AchievementId>,
+        /// The secondary addresses linked to this account.
+        pub linked: Vec<AccountId>,
+        /// The deposit paid to bind this account, returned on unbind.
+        #[codec(compact)]
+        pub bind_deposit: Balance,
+        /// The last block this user tipped at, if they did.
+        pub last_tip_at: Option<BlockNumber>,
+        /// How many withdrawals the operator relayed for this user, signed
+        /// into the next one so they could not be replayed.
+        #[codec(compact)]
+        pub relay_nonce: u32,
+        /// The running totals of what moved the balance.
+        pub audit: AuditCounters,
+        /// How far the identity of this user is verified, picking thier
+        /// `LevelLimits`.
+        pub verification: VerificationLevel,
+    }
+
+    /// A thank-you receipt token, minted to the recipient of a tip on the
+    /// request of the sender.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TipNft {
+        /// The sender of the tip.
+        pub from: AccountId,
+        /// The TelegramId the token was minted to.
+        pub to: TelegramId,
+        /// The tipped amount.
+        pub amount: Balance,
+        /// The hash of the metadata of the token, published off-chain.
+        pub uri_hash: Hash,
+        /// The block it was minted at.
+        pub minted_at: BlockNumber,
+    }
+
+    /// A raffle, where users buy tickets from thier balances and one of the
+    /// tickets wins the pot.
+    ///
+    /// The winner is drawn from the seed committed to by the creator, mixed
+    /// with the entropy of every ticket purchase. The creator could not pick
+    /// the winner since the purchases come after the commitment, nor could a
+    /// buyer since the seed is secret until the draw. A creator who withholds
+    /// the seed only cancels the raffle, as the tickets are refunded after
+    /// `RAFFLE_REVEAL_WINDOW`.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Raffle {
+        /// The owner, or operator, who created the raffle.
+        pub creator: AccountId,
+        /// The price of a single ticket.
+        pub ticket_price: Balance,
+        /// The block the raffle ends at, no tickets are sold from it on.
+        pub ends_at: BlockNumber,
+        /// The blake2x256 hash of the encoded seed of the draw.
+        pub seed_hash: Hash,
+        /// The entropy mixed in by the ticket purchases.
+        pub entropy: Hash,
+        /// How many tickets were sold.
+        pub tickets: u32,
+        /// How many purchases were made.
+        pub purchases: u32,
+        /// The rake taken into the treasury, fixed when the raffle is
+        /// created.
+        pub rake_bps: u16,
+    }
+
+    /// A timed contest whose prize pot is split among the top tippers.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Contest {
+        /// The owner who funded the prize pot, paid what is not won.
+        pub funder: AccountId,
+        /// The prize pot.
+        pub pot: Balance,
+        /// The block the contest ends at, tips from it on do not count.
+        pub ends_at: BlockNumber,
+        /// The share of the pot of each place, in basis points, first place
+        /// first, adding up to `MAX_BPS`.
+        pub weights: Vec<u16>,
+        /// The top tippers of the contest, with thier tipped volume, at most
+        /// one per weight.
+        pub board: Vec<(AccountId, Balance)>,
+        /// Whether the prizes were paid.
+        pub finalized: bool,
+    }
+
+    /// Where a milestone of an escrow is at.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum MilestoneState {
+        /// The worker did not deliver it yet.
+        Pending,
+        /// The worker delivered it at the block, waiting to be released.
+        Delivered(BlockNumber),
+        /// It was paid to the worker.
+        Released,
+        /// It was refunded to the funder before being delivered.
+        Refunded,
+        /// The funder and the worker disagree on it, it is waiting for the
+        /// vote of the arbiters.
+        Disputed,
+    }
+
+    /// A tranche of the budget of an escrow.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Milestone {
+        /// The amount paid to the worker when it is released.
+        pub amount: Balance,
+        /// Where the milestone is at.
+        pub state: MilestoneState,
+    }
+
+    /// A budget escrowed by a funder for some work, split into milestones
+    /// that are paid to the worker as they are delivered.
+    ///
+    /// The funder releases a delivered milestone, or the arbiter does if the
+    /// funder did not within `arbiter_timeout` blocks of the delivery.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Escrow {
+        /// Who escrowed the budget.
+        pub funder: AccountId,
+        /// Who is paid for the work.
+        pub worker: AccountId,
+        /// Who could release delivered milestones after the timeout.
+        pub arbiter: Option<AccountId>,
+        /// How many blocks after a delivery the arbiter could release it.
+        pub arbiter_timeout: BlockNumber,
+        /// The milestones, in the order they are expected to be delivered.
+        pub milestones: Vec<Milestone>,
+    }
+
+    /// A dispute over a milestone of an escrow, decided by the majority of
+    /// the arbiters on the panel when it was opened.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Dispute {
+        /// The funder, or worker, who opened the dispute.
+        pub opened_by: AccountId,
+        /// The fee paid to open it, shared by the arbiters who voted with
+        /// the decision.
+        pub fee: Balance,
+        /// The state of the milestone before the dispute, restored if the
+        /// vote is tied.
+        pub previous: MilestoneState,
+        /// The block the voting ends at.
+        pub ends_at: BlockNumber,
+        /// The arbiters who could vote on it.
+        pub arbiters: Vec<AccountId>,
+        /// The arbiters who voted to release the milestone to the worker.
+        pub release_votes: Vec<AccountId>,
+        /// The arbiters who voted to refund the milestone to the funder.
+        pub refund_votes: Vec<AccountId>,
+    }
+
+    /// A coin-flip challenge of one user to another, where both stake the
+    /// same amount and the winner of the flip gets both stakes.
+    ///
+    /// The flip is decided by a seed of each side, the challenger committing
+    /// to thiers before the opponent picks one, see `flip`.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Challenge {
+        /// The challenger.
+        pub challenger: AccountId,
+        /// The challenged TelegramId.
+        pub opponent: TelegramId,
+        /// The stake of each side.
+        pub stake: Balance,
+        /// The block the challenge was made at.
+        pub created_at: BlockNumber,
+        /// The blake2x256 hash of the encoded seed of the challenger.
+        pub seed_hash: Hash,
+        /// The account that accepted the challenge, the block it was
+        /// accepted at, and its seed.
+        pub accepted: Option<(AccountId, BlockNumber, Hash)>,
+    }
+
+    /// A prediction poll, where users stake on one of its options and those
+    /// who staked on the winning option share all the stakes pro-rata.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Poll {
+        /// The owner, or operator, who created the poll.
+        pub creator: AccountId,
+        /// How many options the poll has, they are numbered from zero.
+        pub options: u8,
+        /// The block the poll closes at, no stakes are taken from it on.
+        pub closes_at: BlockNumber,
+        /// The total staked on all the options.
+        pub total: Balance,
+        /// The winning option, once resolved.
+        pub winning_option: Option<u8>,
+        /// How many accounts staked on the poll.
+        pub stakers: u32,
+    }
+
+    /// A tip jar of a piece of content, collecting tips before its author
+    /// is known.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct ContentJar {
+        /// The tips collected, and not claimed yet.
+        pub balance: Balance,
+        /// The author of the content, as attested by the owners or the
+        /// operator.
+        pub author: Option<TelegramId>,
+    }
+
+    /// An external charity the users could donate to, registered by the
+    /// owners.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Cause {
+        /// The account the donations are paid to.
+        pub account: AccountId,
+        /// The hash of the description of the cause, published off-chain.
+        pub metadata_hash: Hash,
+        /// The total donated to the cause.
+        pub total: Balance,
+    }
+
+    /// How the bot displays the amounts of an asset, registered by the
+    /// owners.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct AssetMeta {
+        /// The ticker of the asset, like `EDG`.
+        pub symbol: String,
+        /// How many decimals the amounts of the asset have.
+        pub decimals: u8,
+    }
+
+    /// The total tipped in a category.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct CategoryStats {
+        /// How many tips were sent in the category.
+        #[codec(compact)]
+        pub tips: u32,
+        /// The total amount tipped in the category.
+        #[codec(compact)]
+        pub volume: Balance,
+    }
+
+    /// Who a user accepts tips from, by the TelegramIds of the senders so it
+    /// survives them rebinding.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct InboxPolicy {
+        /// Whether only the `allowed` senders could tip the user.
+        pub allowlist_only: bool,
+        /// The senders who could never tip the user.
+        pub blocked: Vec<TelegramId>,
+        /// The senders who could tip the user in allowlist-only mode.
+        pub allowed: Vec<TelegramId>,
+    }
+
+    /// What a relayer could do on behalf of the users, the operator could do
+    /// all of it.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct RelayerPermissions {
+        /// Could tip on behalf of users with `tip_from`.
+        pub can_tip_from: bool,
+        /// Could bind users with `bind_verified_for`.
+        pub can_bind_verified: bool,
+        /// Could settle the withdrawals users signed, with
+        /// `request_relayed_withdrawal` and `execute_withdrawal_for`.
+        pub can_settle_batches: bool,
+    }
+
+    /// How the owners configured a relayer.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct RelayerConfig {
+        /// The name of the relayer, like `discord-bridge`.
+        pub name: String,
+        /// What the relayer could do.
+        pub permissions: RelayerPermissions,
+        /// The most the relayer could move per era, `None` if it is not
+        /// capped.
+        pub volume_cap: Option<Balance>,
+    }
+
+    /// A bot, other than the operator, trusted to relay the actions of the
+    /// users.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Relayer {
+        /// How the owners configured the relayer.
+        pub config: RelayerConfig,
+        /// How much the relayer moved during `volume_era`.
+        pub volume: Balance,
+        /// The era `volume` is counted in.
+        pub volume_era: EraIndex,
+        /// The relay fees the relayer did not claim yet.
+        pub reimbursement: Balance,
+    }
+
+    /// What a job does once its time comes.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum JobKind {
+        /// Tip `amount` to the TelegramId, looked up when the tip is sent.
+        Tip {
+            /// The recipient.
+            to: TelegramId,
+            /// The amount tipped.
+            amount: Balance,
+        },
+    }
+
+    /// A deferred action of a user, run once its time comes by whoever
+    /// calls `execute_due`.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Job {
+        /// The account the job acts for.
+        pub owner: AccountId,
+        /// What the job does.
+        pub kind: JobKind,
+        /// The time the job could be executed at.
+        pub execute_at: Timestamp,
+        /// What the owner deposited for the keeper.
+        pub incentive: Balance,
+    }
+
+    /// The bounds of the queue of the waiting jobs, whose Ids run from `head`
+    /// to `tail`, but for the jobs already executed or cancelled.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct JobQueue {
+        /// The Id of the oldest job that could still be waiting.
+        pub head: JobId,
+        /// The Id of the next job.
+        pub tail: JobId,
+        /// How many jobs are waiting.
+        pub len: u32,
+    }
+
+    /// A note the moderators keep about an account, the full text lives off
+    /// chain and only its hash and a short label are stored.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Note {
+        /// The hash of the full text of the note.
+        pub hash: Hash,
+        /// A short label shown next to the account, like `spam`.
+        pub label: Vec<u8>,
+        /// Who set the note.
+        pub set_by: AccountId,
+        /// When the note was set.
+        pub at: BlockNumber,
+    }
+
+    /// A soulbound achievement token, minted when a user earns a badge, it
+    /// could not be transferred.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Achievement {
+        /// The TelegramId that earned it.
+        pub tg_id: TelegramId,
+        /// The earned badge, one of the `BADGE_*` flags.
+        pub badge: u32,
+        /// The block it was minted at.
+        pub minted_at: BlockNumber,
+    }
+
+    /// A recovery of a binding to a new key, waiting for approvals and its
+    /// delay.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Recovery {
+        /// The key the binding moves to.
+        pub new_account: AccountId,
+        /// The guardians who approved it so far.
+        pub approvals: Vec<AccountId>,
+        /// The owner who approved it, if any.
+        pub owner: Option<AccountId>,
+        /// The block the recovery started at.
+        pub started_at: BlockNumber,
+    }
+
+    /// A fee rebate for users on a tipping streak.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct StreakRebate {
+        /// The streak, in days, from which the rebate applies.
+        pub min_streak: u32,
+        /// The part of the fee given back, in basis points.
+        pub rebate_bps: u16,
+    }
+
+    /// The DEX tips are swapped through.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Dex {
+        /// The router contract of the DEX.
+        pub router: AccountId,
+        /// The wrapped EDG token the router swaps to EDG through.
+        pub wrapped_edg: AccountId,
+    }
+
+    /// The privacy preferences of a user.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Privacy {
+        /// Leave this user out of the events of the tips they receive.
+        pub hide_received: bool,
+        /// Hide the balance of this user from everyone but them and the
+        /// owners.
+        pub hide_balance: bool,
+    }
+
+    /// The preferences of a user.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct UserSettings {
+        /// Whether the bot should withdraw the tips this user receives for
+        /// them.
+        pub auto_withdraw: bool,
+        /// Whether the operator could tip on behalf of this user.
+        pub bot_tipping_enabled: bool,
+        /// The privacy preferences of this user.
+        pub privacy: Privacy,
+        /// The most this user could tip per era, `None` if unlimited.
+        pub daily_limit: Option<Balance>,
+        /// The amount the bot tips when the user does not give one.
+        pub default_tip: Balance,
+        /// Whether every tip is rounded up to a whole EDG, giving the
+        /// difference to the charity.
+        pub round_up: bool,
+        /// The balance tips could never dip below, kept for withdrawals.
+        pub reserve: Balance,
+        /// Whether the value transferred with a tip is deposited, covering
+        /// the shortfall of the balance.
+        pub auto_top_up: bool,
+    }
+
+    impl Default for UserSettings {
+        fn default() -> Self {
+            Self {
+                auto_withdraw: false,
+                bot_tipping_enabled: true,
+                privacy: Privacy::default(),
+                daily_limit: None,
+                default_tip: 0,
+                round_up: false,
+                reserve: 0,
+                auto_top_up: false,
+            }
+        }
+    }
+
+    /// A change to the preferences of a user, `None` fields are left as they
+    /// are.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct UserSettingsUpdate {
+        /// See `UserSettings::auto_withdraw`.
+        pub auto_withdraw: Option<bool>,
+        /// See `UserSettings::bot_tipping_enabled`.
+        pub bot_tipping_enabled: Option<bool>,
+        /// See `UserSettings::privacy`.
+        pub privacy: Option<Privacy>,
+        /// See `UserSettings::daily_limit`.
+        pub daily_limit: Option<Option<Balance>>,
+        /// See `UserSettings::default_tip`.
+        pub default_tip: Option<Balance>,
+        /// See `UserSettings::round_up`.
+        pub round_up: Option<bool>,
+        /// See `UserSettings::reserve`.
+        pub reserve: Option<Balance>,
+        /// See `UserSettings::auto_top_up`.
+        pub auto_top_up: Option<bool>,
+    }
+
+    /// Everything the bot shows about a user, returned by `profile_of`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Profile {
+        /// The account the TelegramId is bound to.
+        pub address: AccountId,
+        /// The balance of the user, zero if it is hidden from the caller as
+        /// in `balance_of`.
+        pub balance: Balance,
+        /// The tipping statistics of the user.
+        pub stats: UserStats,
+        /// The preferences of the user.
+        pub settings: UserSettings,
+        /// The badges of the user, as a bitfield of the `BADGE_*` flags.
+        pub badges: u32,
+    }
+
+    /// The balance of a user with the totals it is made of, returned by
+    /// `audit_of`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AuditReport {
+        /// The running totals of what moved the balance.
+        pub totals: AuditCounters,
+        /// The current balance.
+        pub balance: Balance,
+    }
+
+    impl AuditReport {
+        /// Whether the balance is what goes in minus what goes out, that is
+        /// `deposited + tips_in + other_in - withdrawn - tips_out - fees_paid
+        /// - other_out`.
+        pub fn adds_up(&self) -> bool {
+            let t = &self.totals;
+            let ins = t
+                .deposited
+                .checked_add(t.tips_in)
+                .and_then(|sum| sum.checked_add(t.other_in));
+            let outs = t
+                .withdrawn
+                .checked_add(t.tips_out)
+                .and_then(|sum| sum.checked_add(t.fees_paid))
+                .and_then(|sum| sum.checked_add(t.other_out));
+            ins.zip(outs).and_then(|(ins, outs)| ins.checked_sub(outs))
+                == Some(self.balance)
+        }
+    }
+
+    /// The version and the configuration of the deployment, returned by
+    /// `info`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractInfo {
+        /// The revision of the ABI, see `spec_version`.
+        pub spec_version: u32,
+        /// The version of the storage layout.
+        pub storage_version: u16,
+        /// The version of the layout of the events, see `Tipped`.
+        pub event_schema_version: u16,
+        /// The contract owners.
+        pub owners: Vec<AccountId>,
+        /// How many owners have to approve an admin action.
+        pub threshold: u32,
+        /// The account allowed to tip on behalf of the users.
+        pub operator: AccountId,
+        /// The current tipping policy, the fee and the tip limits.
+        pub params: Params,
+        /// The stricter tip limits of newly bound accounts, if enabled.
+        pub newcomer_limits: Option<NewcomerLimits>,
+        /// The block the contract could be terminated at, if its termination
+        /// is scheduled.
+        pub terminates_at: Option<BlockNumber>,
+        /// The supported features that are enabled, as a bitfield of the
+        /// `FEATURE_*` flags.
+        pub features: u32,
+    }
+
+    /// An optional subsystem the owners could turn off, see
+    /// `AdminCall::SetFeature`.
+    ///
+    /// Turning one off only stops new activity, whatever is already open
+    /// could still be settled or refunded.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum Feature {
+        /// Milestone escrows, `create_escrow`.
+        Escrow,
+        /// Held tips, tips are paid right away while it is off.
+        PendingTips,
+        /// Contests, raffles, challenges and polls.
+        Games,
+        /// Receipt tokens, and the achievement tokens of the badges.
+        Nfts,
+    }
+
+    impl Feature {
+        /// The `FEATURE_*` flag of the feature.
+        pub fn flag(self) -> u32 {
+            match self {
+                Feature::Escrow => FEATURE_ESCROW,
+                Feature::PendingTips => FEATURE_PENDING_TIPS,
+                Feature::Games => FEATURE_GAMES,
+                Feature::Nfts => FEATURE_NFTS,
+            }
+        }
+    }
+
+    /// A slash waiting for its appeal window to end.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Slash {
+        /// The slashed account.
+        pub account: AccountId,
+        /// The amount moved to the treasury once finalized.
+        pub amount: Balance,
+        /// The hash of the reason, published off-chain.
+        pub reason_hash: Hash,
+        /// The block the appeal window ends at.
+        pub appeal_ends_at: BlockNumber,
+        /// Whether the user contested the slash, leaving it to the owners.
+        pub contested: bool,
+    }
+
+    /// What `wind_down` refunds, in this order, the bound users after what is
+    /// held for them so it is paid out with thier balance, and the tipEDG
+    /// holders last, bound or not.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum WindDownStage {
+        /// The group pots, paid to the registrants of the groups.
+        Groups,
+        /// The team balances, refunded to thier contributors.
+        Teams,
+        /// The milestones of the escrows that were not released, refunded to
+        /// thier funders, with the fees of thier disputes to who opened them.
+        Escrows,
+        /// The stakes of the coin-flip challenges, refunded to each side.
+        Challenges,
+        /// The tickets of the raffles that were not drawn, refunded to thier
+        /// buyers.
+        Raffles,
+        /// The stakes of the polls, refunded to thier stakers, or paid as
+        /// thier share if the poll was resolved.
+        Polls,
+        /// The tip jars of content, paid to thier attested authors.
+        ContentJars,
+        /// The unclaimed relay fees, paid to the relayers and the operator.
+        Relayers,
+        /// The incentives of the waiting jobs, given back to thier owners.
+        Jobs,
+        /// The bound users, paid thier balance and bind deposit.
+        Accounts,
+        /// The balances left by removed bindings, paid to thier accounts.
+        Expired,
+        /// The tipEDG holders, paid the EDG backing it.
+        Receipts,
+    }
+
+    /// Something an account is part of, that moves with its binding to a
+    /// new key.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum Position {
+        /// A held tip the account sent.
+        Tip(HeldTipId),
+        /// A raffle the account bought tickets of.
+        Raffle(RaffleId),
+        /// A poll the account staked on.
+        Poll(PollId),
+        /// A group the account registered, or moderates.
+        Group(GroupId),
+    }
+
+    /// The tipping policy, governed by the users.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Params {
+        /// The fee taken from every tip into the treasury, in basis points.
+        pub fee_bps: u16,
+        /// The smallest allowed tip.
+        pub min_tip: Balance,
+        /// The largest allowed tip.
+        pub max_tip: Balance,
+    }
+
+    impl Default for Params {
+        fn default() -> Self {
+            Self {
+                fee_bps: 0,
+                min_tip: 0,
+                max_tip: Balance::MAX,
+            }
+        }
+    }
+
+    /// The fees collected in one era.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct EraFees {
+        /// The era these fees were collected in.
+        pub era: EraIndex,
+        /// The total amount tipped during the era, fees included.
+        #[codec(compact)]
+        pub volume: Balance,
+        /// The fees taken into the treasury during the era.
+        #[codec(compact)]
+        pub fees: Balance,
+    }
+
+    /// The tips paid in one statistics epoch.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct EpochStats {
+        /// The epoch these tips were paid in.
+        pub epoch: EpochIndex,
+        /// How many tips were paid.
+        #[codec(compact)]
+        pub tips: u32,
+        /// The total amount tipped, fees included.
+        #[codec(compact)]
+        pub volume: Balance,
+        /// Who tipped the most during the epoch, with thier total, leaving
+        /// out anonymous tips.
+        pub top_tipper: Option<(TelegramId, Balance)>,
+    }
+
+    /// The totals of the era, and of the epoch, tipped in last, kept ahead of
+    /// thier history for up to `STATS_FLUSH_INTERVAL` tips.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct StatsCache {
+        /// The fees of the era tipped in last.
+        pub era_fees: EraFees,
+        /// The tips of the epoch tipped in last.
+        pub epoch_totals: EpochStats,
+        /// How many tips were counted since the totals were last written to
+        /// thier history.
+        pub unflushed_tips: u32,
+    }
+
+    /// The receipt of a successful tip.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TipRecord {
+        /// The sender, `None` if the tip is anonymous or they are no longer
+        /// bound.
+        pub from_tg: Option<TelegramId>,
+        /// The recipient, `None` if they hide the tips they receive.
+        pub to_tg: Option<TelegramId>,
+        /// The tipped amount, fees included.
+        pub amount: Balance,
+        /// The block the tip was paid at.
+        pub at: BlockNumber,
+    }
+
+    /// Where on another parachain a user receives the tips sent with
+    /// `tip_remote`.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct RemoteAddress {
+        /// The parachain.
+        pub para_id: ParaId,
+        /// The account on the parachain.
+        pub account: AccountId,
+    }
+
+    /// The balance of the contract bonded with the staking pallet, see
+    /// `stake_idle`.
+    ///
+    /// The bonded balance stays in the contract, locked until it is unbonded
+    /// and withdrawn.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Staking {
+        /// How much is bonded.
+        pub bonded: Balance,
+        /// How much is unbonding, still locked until it is withdrawn.
+        pub unbonding: Balance,
+        /// The share of the deposits, in basis points, that could be bonded
+        /// on top of the treasury.
+        pub deposit_share_bps: u16,
+    }
+
+    /// A tip held in the reversal window, so the sender could revoke it.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct PendingTip {
+        /// The sender of the tip.
+        pub from: AccountId,
+        /// The recipient of the tip.
+        pub to: AccountId,
+        /// The tipped amount, fees included.
+        pub amount: Balance,
+        /// The block the tip could be finalized at.
+        pub release_at: BlockNumber,
+        /// Whether the recipient has to accept the tip before it is paid.
+        pub needs_acceptance: bool,
+        /// The commitment to the sender, if the tip is anonymous.
+        pub commitment: Option<Hash>,
+        /// The group the tip was sent in, if any.
+        pub group: Option<GroupId>,
+        /// The block after which the tip, if the recipient did not accept it
+        /// by then, could be given back to the sender by anyone, `None` if it
+        /// never expires.
+        pub expires_at: Option<BlockNumber>,
+    }
+
+    /// A Telegram group hosting the bot, with its own pot and statistics.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Group {
+        /// The account who registered the group, and manages its
+        /// moderators.
+        pub registrant: AccountId,
+        /// The accounts allowed to rain from the group pot, to ban users
+        /// from the group and to configure it, besides the registrant.
+        pub moderators: Vec<AccountId>,
+        /// The tipping policy of the group.
+        pub config: GroupConfig,
+        /// The donations to the group pot.
+        pub treasury: Balance,
+        /// How many tips were sent in the group.
+        pub tips: u32,
+        /// The total amount tipped in the group.
+        pub volume: Balance,
+        /// The users who tipped the most in the group, most first.
+        pub top_senders: Vec<(AccountId, Balance)>,
+        /// The users who got tipped the most in the group, most first.
+        pub top_receivers: Vec<(AccountId, Balance)>,
+    }
+
+    /// The tipping policy of a group, overriding the global `Params` for the
+    /// tips sent in it, every field left as `None` falls back to them.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct GroupConfig {
+        /// The smallest allowed tip.
+        pub min_tip: Option<Balance>,
+        /// The fee taken from every tip, in basis points.
+        pub fee_bps: Option<u16>,
+        /// How many blocks a user has to wait between two tips in the group,
+        /// there is no cooldown by default.
+        pub cooldown: Option<BlockNumber>,
+    }
+
+    /// A wallet shared by a team, spent by `threshold` of its members.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Team {
+        /// The hash of the name of the team.
+        pub name_hash: Hash,
+        /// The members of the team.
+        pub members: Vec<TelegramId>,
+        /// How many members have to approve a tip.
+        pub threshold: u32,
+        /// The pooled balance of the team.
+        pub balance: Balance,
+        /// The accounts who funded the team, with how much each of them
+        /// funded, refunded thier share if the team is cancelled.
+        pub contributors: Vec<(AccountId, Balance)>,
+        /// The total funded by all the contributors.
+        pub contributed: Balance,
+        /// The block the team could be cancelled at, unless it pays a tip
+        /// before.
+        pub idle_until: BlockNumber,
+    }
+
+    /// A tip from a team wallet, waiting for approvals.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TeamTip {
+        /// The team paying the tip.
+        pub team_id: TeamId,
+        /// The recipient of the tip.
+        pub to: TelegramId,
+        /// The tipped amount, fees included.
+        pub amount: Balance,
+        /// The members who approved it so far.
+        pub approvals: Vec<TelegramId>,
+    }
+
+    /// Which of the group leaderboards to query.
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Ranking {
+        /// The users who tipped the most.
+        Senders,
+        /// The users who got tipped the most.
+        Receivers,
+    }
+
+    /// A governed parameter, see `Params`.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum Param {
+        /// `Params::fee_bps`, at most 10000.
+        FeeBps,
+        /// `Params::min_tip`.
+        MinTip,
+        /// `Params::max_tip`.
+        MaxTip,
+    }
+
+    /// A proposed parameter change, open for voting.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct ParamProposal {
+        /// The parameter to change.
+        pub param: Param,
+        /// The new value of the parameter.
+        pub value: Balance,
+        /// The block the voting ends at.
+        pub ends_at: BlockNumber,
+        /// The total balance that voted for the change.
+        pub ayes: Balance,
+        /// The total balance that voted against the change.
+        pub nays: Balance,
+    }
+
+    /// A privileged action, executed once enough owners approve it.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum AdminAction {
+        /// Unbind an account, refunding its balance.
+        ForceUnbind(AccountId),
+        /// Register a contract to be notified on every successful tip.
+        RegisterHook(AccountId),
+        /// Remove a registered hook contract.
+        UnregisterHook(AccountId),
+        /// Schedule the termination of the contract, in favour of the
+        /// beneficiary.
+        ScheduleTermination(AccountId),
+        /// Cancel the scheduled termination.
+        CancelTermination,
+        /// Terminate the contract after the timelock, once it is wound down,
+        /// see `wind_down`.
+        Terminate,
+        /// Set the account allowed to tip on behalf of the users.
+        SetOperator(AccountId),
+        /// Add a new owner.
+        AddOwner(AccountId),
+        /// Remove an owner.
+        RemoveOwner(AccountId),
+        /// Set how many approvals an action needs.
+        SetThreshold(u32),
+        /// Remove all the owners, leaving the contract without an owner.
+        RenounceOwnership,
+        /// Slash an account into the treasury, after an appeal window.
+        ProposeSlash {
+            /// The account to slash.
+            account: AccountId,
+            /// The amount to slash.
+            amount: Balance,
+            /// The hash of the reason, published off-chain.
+            reason_hash: Hash,
+        },
+        /// Drop a pending slash, e.g. after a successful appeal.
+        CancelSlash(SlashId),
+        /// Finalize a contested slash, rejecting the appeal.
+        UpholdSlash(SlashId),
+        /// Change a setting of the contract.
+        Configure(Setting),
+    }
+
+    /// A privileged call, of the owners, the operator or a group moderator,
+    /// with its parameters, as logged by the `AdminActionLogged` event.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PrivilegedAction {
+        /// An admin action approved by the owners.
+        Admin(AdminAction),
+        /// `admin_rebind` of the target to the TelegramId.
+        AdminRebind(TelegramId),
+        /// `announce_dust_sweep` of the target.
+        AnnounceDustSweep,
+        /// `reserve_alias` of the alias.
+        ReserveAlias {
+            /// The normalized alias.
+            name: String,
+            /// Whether it was reserved, or released.
+            reserved: bool,
+            /// Who it was assigned to.
+            holder: Option<TelegramId>,
+        },
+        /// `attest_author` of the content to the TelegramId.
+        AttestAuthor {
+            /// The hash of the content.
+            content_hash: Hash,
+            /// The TelegramId of the author.
+            tg_id: TelegramId,
+        },
+        /// `resolve` of the poll to its winning option.
+        ResolvePoll {
+            /// The Id of the poll.
+            poll_id: PollId,
+            /// The winning option.
+            winning_option: u8,
+        },
+        /// `ban_from_group` of the TelegramId.
+        BanFromGroup {
+            /// The group.
+            group_id: GroupId,
+            /// The TelegramId.
+            tg_id: TelegramId,
+            /// Whether it was banned, or unbanned.
+            banned: bool,
+        },
+        /// `register_cause` of the cause, paid to the target.
+        RegisterCause {
+            /// The Id of the cause.
+            cause_id: CauseId,
+            /// The hash of the description of the cause.
+            metadata_hash: Hash,
+        },
+        /// `claim_relay_reimbursement` of the amount.
+        ClaimRelayReimbursement(Balance),
+        /// `open_contest` of the contest.
+        OpenContest {
+            /// The Id of the contest.
+            contest_id: ContestId,
+            /// The prize pot.
+            pot: Balance,
+        },
+        /// `set_category` of the category.
+        SetCategory {
+            /// The Id of the category.
+            category: u8,
+            /// The new name, `None` if the category was removed.
+            name: Option<String>,
+        },
+        /// `set_asset_metadata` of the asset.
+        SetAssetMetadata {
+            /// The token contract, `None` for the native EDG.
+            asset: Option<AccountId>,
+            /// The new metadata, `None` if it was removed.
+            meta: Option<AssetMeta>,
+        },
+        /// `set_note` of the account.
+        SetNote {
+            /// The hash of the new note, `None` if it was removed.
+            hash: Option<Hash>,
+        },
+    }
+
+    /// A setting of the contract, changed by `AdminAction::Configure`.
+    ///
+    /// These live in thier own enum since the storage layout derive of an
+    /// enum grows exponentially with the number of its variants.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum Setting {
+        /// For how many blocks tips are held before they are final, zero
+        /// disables holding tips.
+        ReversalWindow(BlockNumber),
+        /// The amount above which the recipient has to accept a tip, `None`
+        /// disables it.
+        AcceptanceThreshold(Option<Balance>),
+        /// How many of the latest sent, and received, tips are kept per user.
+        HistoryLimit(u32),
+        /// How much has to be tipped to earn one loyalty point, zero disables
+        /// earning points.
+        PointsRate(Balance),
+        /// How much one loyalty point is worth when redeemed, zero disables
+        /// redeeming points.
+        RedemptionRate(Balance),
+        /// The fee rebate for users on a tipping streak, `None` disables it.
+        StreakRebate(Option<StreakRebate>),
+        /// The charity the rounded up tips go to, `None` disables rounding
+        /// up.
+        Charity(Option<AccountId>),
+        /// The address burned funds are sent to, `None` disables burning.
+        BurnAddress(Option<AccountId>),
+        /// How many blocks a user has to be inactive for before thier dust
+        /// could be swept, counted from `UserRecord::last_active` like every
+        /// other period of inactivity, not zero.
+        DustDormancy(BlockNumber),
+        /// The flat fee charged to the sender of every tip relayed by the
+        /// operator, zero disables it.
+        RelayFee(Balance),
+        /// The EDG/USD price oracle contract, `None` disables tips in USD.
+        PriceOracle(Option<AccountId>),
+        /// The DEX tips are swapped through, `None` disables swapping tips.
+        Dex(Option<Dex>),
+        /// The rake taken from the pot of new raffles, in basis points.
+        RaffleRake(u16),
+        /// A setting of the bindings.
+        Binding(BindingSetting),
+        /// A setting of the transfers.
+        Transfer(TransferSetting),
+        /// A setting of the governance.
+        Admin(AdminSetting),
+    }
+
+    /// A setting of the governance, nested in `Setting` to keep its number
+    /// of variants down.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum AdminSetting {
+        /// How much balance has to vote on a parameter change, for or
+        /// against it, for it to pass.
+        ParamQuorum(Balance),
+        /// The lowest fee a group could take from its tips, in basis points,
+        /// at most `MAX_BPS`.
+        GroupFeeFloor(u16),
+        /// A privileged call, see `AdminCall`.
+        Call(AdminCall),
+    }
+
+    /// A privileged call of the owners, executed once enough of them approve
+    /// it like any other admin action, nested in `AdminSetting` to keep the
+    /// number of variants of `AdminAction` down.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum AdminCall {
+        /// Burn part of the treasury, by sending it to the burn address.
+        BurnTreasury(Balance),
+        /// Sweep the announced dust of a dormant user into the treasury.
+        SweepDust(TelegramId),
+        /// Co-sign the request of a user to unlock thier account.
+        CosignUnlock(TelegramId),
+        /// Unbind many accounts at once, refunding thier balances.
+        ForceUnbindMany(Vec<AccountId>),
+        /// Correct the TelegramId an account is bound to, see
+        /// `admin_rebind`.
+        AdminRebind {
+            /// The bound account.
+            account: AccountId,
+            /// The TelegramId to bind it to instead.
+            new_tg_id: TelegramId,
+        },
+        /// Configure a relayer, or remove it with `None`.
+        SetRelayer {
+            /// The account of the relayer.
+            relayer: AccountId,
+            /// The new configuration, `None` to remove the relayer.
+            config: Option<RelayerConfig>,
+        },
+        /// Bond part of the idle balance of the contract, see `stake_idle`.
+        StakeIdle(Balance),
+        /// Start unbonding part of the bonded balance of the contract.
+        Unstake(Balance),
+        /// Unlock the balance of the contract that finished unbonding.
+        WithdrawUnbonded,
+        /// Appoint the panel of arbiters, and the fee paid to open a dispute.
+        SetArbiters {
+            /// The arbiters, thier order and duplicates do not matter.
+            arbiters: Vec<AccountId>,
+            /// The fee paid to open a dispute.
+            dispute_fee: Balance,
+        },
+        /// Set the verification level of the user of the TelegramId.
+        SetVerificationLevel {
+            /// The TelegramId of the user.
+            tg_id: TelegramId,
+            /// The new verification level.
+            level: VerificationLevel,
+        },
+        /// Set the limits of a verification level, or lift them with `None`.
+        SetLevelLimits {
+            /// The verification level.
+            level: VerificationLevel,
+            /// The new limits, `None` to lift them.
+            limits: Option<LevelLimits>,
+        },
+        /// Turn a feature on, or off, so a risky subsystem could be stopped
+        /// without upgrading the contract.
+        SetFeature {
+            /// The feature.
+            feature: Feature,
+            /// Whether to turn it on.
+            enabled: bool,
+        },
+        /// Cap what a user could hold by depositing, and what the contract
+        /// could hold in total, see `deposit_caps`.
+        SetDepositCaps {
+            /// The cap of each user, `None` to lift it.
+            per_account: Option<Balance>,
+            /// The cap of the contract, `None` to lift it.
+            global: Option<Balance>,
+        },
+    }
+
+    /// A setting of the transfers, and of the calls to other contracts,
+    /// nested in `Setting` to keep its number of variants down.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum TransferSetting {
+        /// The existential deposit of the chain, tips paid below it are kept
+        /// in the balance of the recipient instead of being transferred, zero
+        /// disables it, and `None` sets it to the one the chain reports.
+        ExistentialDeposit(Option<Balance>),
+        /// The maximum amount of gas each hook call is allowed to use, it
+        /// could not be zero as that would allow all the gas left.
+        HookGasLimit(u64),
+        /// The maximum amount of gas each call to a token, or the DEX
+        /// router, is allowed to use, it could not be zero either.
+        TokenCallGasLimit(u64),
+        /// The share of the deposits, in basis points, `stake_idle` could
+        /// bond on top of the treasury, at most `MAX_BPS`.
+        StakeShare(u16),
+    }
+
+    /// A setting of the bindings, nested in `Setting` to keep its number
+    /// of variants down.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum BindingSetting {
+        /// For how many blocks of no activity a binding lasts before its
+        /// TelegramId could be bound by another account, zero disables
+        /// expiring bindings.
+        Ttl(BlockNumber),
+        /// The refundable deposit required to bind a new account, zero
+        /// disables it.
+        Deposit(Balance),
+        /// How many bindings could be made per window, `None` disables the
+        /// limit.
+        RateLimit(Option<BindRateLimit>),
+        /// The stricter tip limits of newly bound accounts, `None` disables
+        /// them.
+        NewcomerLimits(Option<NewcomerLimits>),
+    }
+
+    /// How many bindings could be made per window of blocks.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct BindRateLimit {
+        /// How many blocks a window spans.
+        pub window: BlockNumber,
+        /// How many times a single account could bind per window, zero for
+        /// no limit.
+        pub per_account: u32,
+        /// How many bindings could be made by all accounts per window, zero
+        /// for no limit.
+        pub total: u32,
+    }
+
+    /// The stricter tip limits of newly bound accounts, the main source of
+    /// tip spam.
+    ///
+    /// An account is a newcomer until it sent `tips` tips and `blocks`
+    /// blocks passed since it got bound, either could be zero to only count
+    /// the other.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct NewcomerLimits {
+        /// How many tips a newcomer has to send.
+        pub tips: u32,
+        /// For how many blocks after binding an account is a newcomer.
+        pub blocks: BlockNumber,
+        /// The most a newcomer could tip at once, on top of the `max_tip`.
+        pub max_tip: Balance,
+        /// How many blocks a newcomer has to wait between tips.
+        pub cooldown: BlockNumber,
+    }
+
+    /// How far the identity of a user is verified, set by the owners.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum VerificationLevel {
+        /// Nothing is known about the user, the default.
+        #[default]
+        Unverified,
+        /// The bot verified the user, like by thier Telegram account age.
+        BotVerified,
+        /// The user passed a KYC check, whose result is kept off chain by
+        /// its hash.
+        KycHashed,
+    }
+
+    /// The limits of the users of a `VerificationLevel`, a level without
+    /// limits is not limited at all.
+    #[derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct LevelLimits {
+        /// The most a user could hold by depositing, the tips they receive
+        /// are not limited.
+        pub max_balance: Balance,
+        /// The most a user could tip at once, on top of the `max_tip`.
+        pub max_tip: Balance,
+        /// What a user could use, as a bitfield of the `FEATURE_*` flags.
+        pub features: u32,
+    }
+
+    /// An admin action waiting for approvals.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Proposal {
+        /// The proposed action.
+        pub action: AdminAction,
+        /// The owners who approved it so far.
+        pub approvals: Vec<AccountId>,
+    }
+
+    /// Edgeware Tipping Bot
+    ///
+    /// All the per-user data lives in lazy `Mapping`s, so every message only
+    /// loads the keys it needs instead of the whole map.
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Tipbot {
+        /// The contract owners, initially the account who deployed the
+        /// contract.
+        owners: Vec<AccountId>,
+        /// How many owners have to approve an admin action.
+        threshold: u32,
+        /// The account allowed to tip on behalf of the users, the bot.
+        operator: AccountId,
+        /// The admin actions waiting for approvals.
+        proposals: Mapping<ProposalId, Proposal>,
+        /// The Id of the next proposal.
+        next_proposal_id: ProposalId,
+        /// The bound users.
+        users: Mapping<AccountId, UserRecord>,
+        /// Reverse index of the bound users.
+        tg_address: Mapping<TelegramId, AccountId>,
+        /// The list of bound accounts, so we could visit all of them.
+        accounts: Mapping<u32, AccountId>,
+        /// How many accounts are bound.
+        accounts_count: u32,
+        /// The scheduled termination of the contract, if any, as the
+        /// beneficiary and the block it could be executed at.
+        termination: Option<(AccountId, BlockNumber)>,
+        /// How far `wind_down` got, as the stage and the index of the next
+        /// item to refund in it, `None` before it started.
+        wind_down_cursor: Option<(WindDownStage, u32)>,
+        /// Contracts that get notified on every successful tip, by thier
+        /// index.
+        hooks: Mapping<u32, AccountId>,
+        /// How many hook contracts are registered.
+        hooks_count: u32,
+        /// The current tipping policy.
+        params: Params,
+        /// The fees collected so far.
+        treasury: Balance,
+        /// The fees collected in every era.
+        fee_history: Mapping<EraIndex, EraFees>,
+        /// The tips paid in each statistics epoch.
+        epoch_stats: Mapping<EpochIndex, EpochStats>,
+        /// The totals of the era, and epoch, tipped in last, ahead of
+        /// `fee_history` and `epoch_stats`, in a mapping of its own so the
+        /// messages that do not tip never load it, as they do the root.
+        stats_cache: Mapping<(), StatsCache>,
+        /// The tipping statistics of each user in each epoch, by TelegramId
+        /// so they survive rebinding.
+        user_epoch_stats: Mapping<(TelegramId, EpochIndex), UserStats>,
+        /// The parameter changes open for voting.
+        param_proposals: Mapping<ProposalId, ParamProposal>,
+        /// Who voted on which parameter change.
+        param_votes: Mapping<(ProposalId, AccountId), bool>,
+        /// The Id of the next parameter change.
+        next_param_proposal_id: ProposalId,
+        /// How much balance has to vote on a parameter change for it to pass.
+        param_quorum: Balance,
+        /// The slashes waiting for thier appeal window to end.
+        slashes: Mapping<SlashId, Slash>,
+        /// The Id of the next slash.
+        next_slash_id: SlashId,
+        /// For how many blocks tips are held, zero if they are final right
+        /// away.
+        reversal_window: BlockNumber,
+        /// Tips larger than this have to be accepted by the recipient.
+        acceptance_threshold: Option<Balance>,
+        /// The tips held in the reversal window.
+        pending_tips: Mapping<HeldTipId, PendingTip>,
+        /// The held tips of every recipient.
+        incoming_tips: Mapping<AccountId, Vec<HeldTipId>>,
+        /// How many tips are held.
+        pending_tips_count: u32,
+        /// The Id of the next held tip.
+        next_tip_id: HeldTipId,
+        /// The latest `TIP_LOG_SIZE` tips, as a ring buffer indexed by the
+        /// `TipId` modulo its size.
+        tip_log: Mapping<u32, TipRecord>,
+        /// The Id of the next successful tip.
+        next_receipt_id: TipId,
+        /// The tips sent by every user, by thier position in the history.
+        sent_history: Mapping<(AccountId, u32), TipId>,
+        /// The tips received by every user, by thier position in the history.
+        received_history: Mapping<(AccountId, u32), TipId>,
+        /// How many of the latest sent, and received, tips are kept per user.
+        history_limit: u32,
+        /// How much has to be tipped to earn one loyalty point.
+        points_rate: Balance,
+        /// How much one loyalty point is worth when redeemed.
+        redemption_rate: Balance,
+        /// The funds the owners set aside to redeem loyalty points.
+        rewards_pool: Balance,
+        /// The fee rebate for users on a tipping streak.
+        streak_rebate: Option<StreakRebate>,
+        /// The charity the rounded up tips go to.
+        charity: Option<AccountId>,
+        /// The address burned funds are sent to.
+        burn_address: Option<AccountId>,
+        /// The total burned so far.
+        total_burned: Balance,
+        /// How many blocks a user has to be inactive for before thier dust
+        /// could be swept.
+        dust_dormancy: BlockNumber,
+        /// The announced dust sweeps, by the block they were announced at.
+        dust_sweeps: Mapping<AccountId, BlockNumber>,
+        /// The recoveries of bindings to new keys, by the current key.
+        recoveries: Mapping<AccountId, Recovery>,
+        /// The registered groups.
+        groups: Mapping<GroupId, Group>,
+        /// The lowest fee the groups could take from thier tips, in basis
+        /// points.
+        group_fee_floor: u16,
+        /// The registered groups, by the order they were registered in.
+        group_ids: Mapping<u32, GroupId>,
+        /// How many groups were registered.
+        groups_count: u32,
+        /// The tipping statistics of every user in every group.
+        group_stats: Mapping<(GroupId, AccountId), UserStats>,
+        /// The users banned from the features of a group.
+        group_bans: Mapping<(GroupId, TelegramId), ()>,
+        /// The block every user last tipped at in a group with a cooldown.
+        group_last_tip: Mapping<(GroupId, AccountId), BlockNumber>,
+        /// The team wallets.
+        teams: Mapping<TeamId, Team>,
+        /// The Id of the next team.
+        next_team_id: TeamId,
+        /// The tips from team wallets waiting for approvals.
+        team_tips: Mapping<TeamTipId, TeamTip>,
+        /// The Id of the next team tip.
+        next_team_tip_id: TeamTipId,
+        /// The flat fee charged for every tip relayed by the operator.
+        relay_fee: Balance,
+        /// The relay fees the operator did not claim yet.
+        relay_reimbursement: Balance,
+        /// The total relay fees charged so far.
+        total_relay_fees: Balance,
+        /// The EDG/USD price oracle contract.
+        price_oracle: Option<AccountId>,
+        /// The last EDG/USD price tipped at, and the time it was updated at.
+        usd_price: Option<(Balance, Timestamp)>,
+        /// The DEX tips are swapped through.
+        dex: Option<Dex>,
+        /// The tipEDG receipt token balances.
+        receipt_balances: Mapping<AccountId, Balance>,
+        /// The tipEDG receipt token allowances, by the owner and the spender.
+        receipt_allowances: Mapping<(AccountId, AccountId), Balance>,
+        /// The total supply of the tipEDG receipt token.
+        receipt_supply: Balance,
+        /// The accounts holding tipEDG, by thier index, so we could visit
+        /// all of them.
+        receipt_holders: Mapping<u32, AccountId>,
+        /// Reverse index of the tipEDG holders.
+        receipt_holder_index: Mapping<AccountId, u32>,
+        /// How many accounts hold tipEDG.
+        receipt_holders_count: u32,
+        /// The soulbound achievement tokens.
+        achievements: Mapping<AchievementId, Achievement>,
+        /// The Id of the next achievement token, and so how many were minted.
+        next_achievement_id: AchievementId,
+        /// The thank-you receipt tokens of tips.
+        tip_nfts: Mapping<TipNftId, TipNft>,
+        /// The Id of the next thank-you receipt token.
+        next_tip_nft_id: TipNftId,
+        /// The thank-you receipt tokens of every TelegramId, by thier index.
+        tip_nfts_by_owner: Mapping<(TelegramId, u32), TipNftId>,
+        /// How many thank-you receipt tokens every TelegramId has.
+        tip_nft_counts: Mapping<TelegramId, u32>,
+        /// The metadata hash of the thank-you receipt token of every held tip
+        /// sent with one, minted once the tip is paid.
+        tip_nft_requests: Mapping<HeldTipId, Hash>,
+        /// The raffles that were not drawn yet.
+        raffles: Mapping<RaffleId, Raffle>,
+        /// The Id of the next raffle.
+        next_raffle_id: RaffleId,
+        /// The ticket purchases of every raffle, by thier index, as the buyer
+        /// and the end of thier range of tickets.
+        raffle_purchases: Mapping<(RaffleId, u32), (AccountId, u32)>,
+        /// How much every account spent on tickets of a raffle.
+        raffle_spent: Mapping<(RaffleId, AccountId), Balance>,
+        /// The rake taken into the treasury from the pot of new raffles, in
+        /// basis points.
+        raffle_rake_bps: u16,
+        /// The coin-flip challenges that were not flipped nor refunded yet.
+        challenges: Mapping<ChallengeId, Challenge>,
+        /// The Id of the next coin-flip challenge.
+        next_challenge_id: ChallengeId,
+        /// The prediction polls.
+        polls: Mapping<PollId, Poll>,
+        /// The Id of the next prediction poll.
+        next_poll_id: PollId,
+        /// The total staked on every option of a poll.
+        poll_option_stakes: Mapping<(PollId, u8), Balance>,
+        /// The stakes of every account on every option of a poll.
+        poll_stakes: Mapping<(PollId, AccountId, u8), Balance>,
+        /// The total staked by every account on a poll.
+        poll_staked: Mapping<(PollId, AccountId), Balance>,
+        /// The positions of every account, some of them could have closed
+        /// since.
+        positions: Mapping<AccountId, Vec<Position>>,
+        /// The key every buyer of a raffle moved thier binding to, the
+        /// tickets they bought before are paid to it.
+        raffle_moves: Mapping<(RaffleId, AccountId), AccountId>,
+        /// The accounts that staked on every poll, by the order they first
+        /// staked in.
+        poll_stakers: Mapping<(PollId, u32), AccountId>,
+        /// The key every staker of a poll moved thier binding to, along
+        /// with thier stakes.
+        poll_moves: Mapping<(PollId, AccountId), AccountId>,
+        /// The tip jars of content, by the hash of the content.
+        content_jars: Mapping<Hash, ContentJar>,
+        /// The hashes of the content with a tip jar, by the order the jars
+        /// were made in.
+        content_hashes: Mapping<u32, Hash>,
+        /// How many pieces of content have a tip jar.
+        content_jars_count: u32,
+        /// The TelegramId every alias resolves to.
+        alias_holders: Mapping<String, TelegramId>,
+        /// The alias of every TelegramId.
+        aliases: Mapping<TelegramId, String>,
+        /// The aliases reserved by the owners, that could not be claimed.
+        reserved_aliases: Mapping<String, bool>,
+        /// The bound account every secondary address is linked to.
+        linked_accounts: Mapping<AccountId, AccountId>,
+        /// For how many blocks of no activity a binding lasts, zero if
+        /// bindings do not expire.
+        binding_ttl: BlockNumber,
+        /// The balances left by expired, or swept, bindings, to be withdrawn
+        /// by thier accounts.
+        expired_balances: Mapping<AccountId, Balance>,
+        /// The accounts with a balance left by a removed binding, by thier
+        /// index, so we could visit all of them.
+        expired_accounts: Mapping<u32, AccountId>,
+        /// Reverse index of the accounts with a balance left.
+        expired_index: Mapping<AccountId, u32>,
+        /// How many accounts have a balance left.
+        expired_count: u32,
+        /// The refundable deposit required to bind a new account.
+        bind_deposit: Balance,
+        /// How many bindings could be made per window.
+        bind_rate_limit: Option<BindRateLimit>,
+        /// The current window of the binding rate limit, and how many
+        /// bindings were made in it.
+        bind_window: (BlockNumber, u32),
+        /// The window every account last bound in, and how many times it
+        /// bound in it.
+        bind_counts: Mapping<AccountId, (BlockNumber, u32)>,
+        /// The amount below which tips are not transferred, as they could be
+        /// lost if the recipient's account is empty.
+        existential_deposit: Balance,
+        /// The stricter tip limits of newly bound accounts.
+        newcomer_limits: Option<NewcomerLimits>,
+        /// The Id of the next logged privileged call.
+        next_admin_action_id: u64,
+        /// The maximum amount of gas each hook call is allowed to use.
+        hook_gas_limit: u64,
+        /// The maximum amount of gas each call to a token, or the DEX router,
+        /// is allowed to use.
+        token_call_gas_limit: u64,
+        /// The external charities the users could donate to.
+        causes: Mapping<CauseId, Cause>,
+        /// The milestone escrows.
+        escrows: Mapping<EscrowId, Escrow>,
+        /// The Id of the next milestone escrow.
+        next_escrow_id: EscrowId,
+        /// The arbiters deciding the disputes over escrows.
+        arbiters: Vec<AccountId>,
+        /// The fee paid to open a dispute, shared by the arbiters.
+        dispute_fee: Balance,
+        /// The disputes over the milestones of escrows.
+        disputes: Mapping<(EscrowId, u32), Dispute>,
+        /// The display metadata of the assets, by the token contract, `None`
+        /// for the native EDG.
+        asset_metadata: Mapping<Option<AccountId>, AssetMeta>,
+        /// The tip categories, by Id, with thier names.
+        categories: Vec<(u8, String)>,
+        /// The totals tipped per category, kept when a category is removed.
+        category_stats: Mapping<u8, CategoryStats>,
+        /// The tipping contests.
+        contests: Mapping<ContestId, Contest>,
+        /// The Id of the next tipping contest.
+        next_contest_id: ContestId,
+        /// The contest tips are counted towards, until it is finalized.
+        active_contest: Option<ContestId>,
+        /// The volume each account tipped during each contest.
+        contest_volumes: Mapping<(ContestId, AccountId), Balance>,
+        /// The notes the moderators keep about accounts.
+        notes: Mapping<AccountId, Note>,
+        /// The jobs waiting for thier time to be executed.
+        jobs: Mapping<JobId, Job>,
+        /// The bounds of the queue of the waiting jobs, in a mapping of its
+        /// own so only the messages of the jobs load it.
+        job_queue: Mapping<(), JobQueue>,
+        /// The Ids of the waiting jobs of each account, oldest first.
+        user_jobs: Mapping<AccountId, Vec<JobId>>,
+        /// The relayers other than the operator, by thier accounts.
+        relayers: Mapping<AccountId, Relayer>,
+        /// The relayers, by thier index, so we could visit all of them.
+        relayer_accounts: Mapping<u32, AccountId>,
+        /// Reverse index of the relayers.
+        relayer_index: Mapping<AccountId, u32>,
+        /// How many relayers are configured.
+        relayers_count: u32,
+        /// Who each account accepts tips from, if it restricted it.
+        inbox_policies: Mapping<AccountId, InboxPolicy>,
+        /// The limits of each verification level, by its index, `None` for
+        /// the levels that are not limited.
+        level_limits: [Option<LevelLimits>; 3],
+        /// The features the owners turned off, as a bitfield of the
+        /// `FEATURE_*` flags.
+        disabled_features: u32,
+        /// The most a user could hold by depositing, if capped.
+        deposit_cap: Option<Balance>,
+        /// The most the contract could hold when taking deposits, if capped.
+        global_deposit_cap: Option<Balance>,
+        /// Where on other parachains the users receive remote tips.
+        remote_addresses: Mapping<AccountId, RemoteAddress>,
+        /// The balance bonded with the staking pallet, in a mapping of its
+        /// own to keep it out of the root.
+        staking: Mapping<(), Staking>,
+    }
+
+    /// The errors of the tipEDG receipt token messages, as in PSP22.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        /// A custom error.
+        Custom(String),
+        /// Returned if the balance is not enough.
+        InsufficientBalance,
+        /// Returned if the allowance is not enough.
+        InsufficientAllowance,
+        /// Returned if the recipient is the zero address.
+        ZeroRecipientAddress,
+        /// Returned if the sender is the zero address.
+        ZeroSenderAddress,
+        /// Returned if the recipient contract rejected the transfer.
+        SafeTransferCheckFailed(String),
+    }
+
+    /// What could not be found, carried by `Error::NotFound`.
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Entity {
+        /// The account being acted upon is not bound.
+        Account,
+        /// The sender of a tip is not bound.
+        Sender,
+        /// The recipient of a tip is not bound.
+        Recipient,
+        /// The hook contract is not registered.
+        Hook,
+        /// The owner is not one of the owners.
+        Owner,
+        /// The admin proposal does not exist, or was already executed.
+        Proposal,
+        /// The parameter change does not exist, or was already enacted.
+        ParamProposal,
+        /// The slash does not exist, or was already finalized or cancelled.
+        Slash,
+        /// The held tip does not exist, or was already finalized or revoked.
+        Tip,
+        /// The group is not registered.
+        Group,
+        /// The account is not a moderator of the group.
+        Moderator,
+        /// The team does not exist.
+        Team,
+        /// The team tip does not exist, or was already paid.
+        TeamTip,
+        /// The dust sweep was not announced.
+        DustSweep,
+        /// There is no queued withdrawal.
+        Withdrawal,
+        /// There is no recovery of the binding.
+        Recovery,
+        /// The raffle does not exist, or was already drawn.
+        Raffle,
+        /// The challenge does not exist, or was already flipped or refunded.
+        Challenge,
+        /// The poll does not exist.
+        Poll,
+        /// The account has no stake to be paid.
+        Stake,
+        /// The content has no tip jar.
+        Content,
+        /// The alias is not held by the caller.
+        Alias,
+        /// The charity cause is not registered.
+        Cause,
+        /// The escrow does not exist.
+        Escrow,
+        /// The milestone does not exist, or was already released or
+        /// refunded.
+        Milestone,
+        /// The milestone is not disputed.
+        Dispute,
+        /// The account has no balance left by an expired binding.
+        ExpiredBalance,
+        /// The tip category is not registered.
+        Category,
+        /// There is no such contest, or none is running.
+        Contest,
+        /// There is no such job, or it was already executed.
+        Job,
+    }
+
+    /// The Error cases.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the AccountId is already bound to a TelegramId, or
+        /// linked to a bound account.
+        AccountAlreadyBound,
+        /// Returned if the caller is not one of the owners, or the operator,
+        /// of the contract.
+        NotAllowed,
+        /// Returned if the address/telegram id is not found.
+        NotFound {
+            /// What was not found.
+            which: Entity,
+        },
+        /// Returned if the transfer failed.
+        TransferFailed,
+        /// Insufficient funds to execute transfer.
+        InsufficientFunds {
+            /// The amount the operation needs.
+            required: Balance,
+            /// The amount the account has.
+            available: Balance,
+        },
+        /// Transfer failed because it would have brought the contract's
+        /// balance below the subsistence threshold.
+        /// This is necessary to keep enough funds in the contract to
+        /// allow for a tombstone to be created.
+        BelowSubsistenceThreshold,
+        /// Returned if a balance would overflow.
+        Overflow,
+        /// Returned if a tip of zero was requested.
+        ZeroAmount,
+        /// Returned if the sender tries to tip themselves.
+        SelfTip,
+        /// Returned if the termination of the contract is not scheduled.
+        TerminationNotScheduled,
+        /// Returned if the termination of the contract is still timelocked.
+        TerminationTimelocked {
+            /// The block the termination could be executed at.
+            at: BlockNumber,
+        },
+        /// Returned if there are tipEDG receipt tokens not redeemed yet.
+        ReceiptsOutstanding {
+            /// The total supply of the receipt token.
+            supply: Balance,
+        },
+        /// Returned if the hook contract, the owner, or the group, is already
+        /// registered.
+        AlreadyRegistered,
+        /// Returned if the owner already approved the proposal.
+        AlreadyApproved,
+        /// Returned if the threshold is zero or more than the number of
+        /// owners, team members or guardians.
+        InvalidThreshold,
+        /// Returned if the maximum number of hooks is already registered.
+        TooManyHooks,
+        /// Returned if the tip is smaller than the minimum tip.
+        BelowMinTip {
+            /// The smallest allowed tip.
+            min: Balance,
+        },
+        /// Returned if the tip is larger than the maximum tip.
+        AboveMaxTip {
+            /// The largest allowed tip.
+            max: Balance,
+        },
+        /// Returned if the value is not valid for the parameter.
+        InvalidParamValue,
+        /// Returned if the account already voted on the parameter change, or
+        /// the dispute.
+        AlreadyVoted,
+        /// Returned if the voting on the parameter change, or the dispute,
+        /// already ended.
+        VotingClosed,
+        /// Returned if the voting on the parameter change, or the dispute,
+        /// did not end yet.
+        VotingOpen {
+            /// The block the voting ends at.
+            ends_at: BlockNumber,
+        },
+        /// Returned if an account with no balance tries to vote.
+        NoVotingPower,
+        /// Returned if the balance is locked by votes.
+        BalanceLocked {
+            /// The block the lock expires at.
+            until: BlockNumber,
+        },
+        /// Returned if the account has a pending slash.
+        SlashPending,
+        /// Returned if the slash is still in its appeal window.
+        AppealOpen {
+            /// The block the appeal window ends at.
+            ends_at: BlockNumber,
+        },
+        /// Returned if the appeal window of the slash already ended.
+        AppealClosed,
+        /// Returned if the slash was contested, so only the owners could
+        /// finalize it.
+        SlashContested,
+        /// Returned if the recipient is already receiving too many held tips.
+        TooManyPendingTips,
+        /// Returned if the held tip is still in the reversal window.
+        TipHeld {
+            /// The block the tip could be finalized at.
+            release_at: BlockNumber,
+        },
+        /// Returned if the reversal window of the tip already ended.
+        ReversalWindowClosed,
+        /// Returned if the held tip is waiting for the recipient to accept it.
+        TipNotAccepted,
+        /// Returned if the held tip expired before the recipient accepted it.
+        TipExpired,
+        /// Returned if there are held tips that have to be finalized or
+        /// revoked first.
+        TipsPending,
+        /// Returned if the tip is above what the sender has left of thier
+        /// daily limit.
+        DailyLimitExceeded {
+            /// How much the sender could still tip in this era.
+            remaining: Balance,
+        },
+        /// Returned if the user does not have enough loyalty points.
+        InsufficientPoints {
+            /// The loyalty points of the user.
+            available: Balance,
+        },
+        /// Returned if redeeming loyalty points is disabled.
+        RedemptionDisabled,
+        /// Returned if the rewards pool can not cover the redemption.
+        InsufficientRewards {
+            /// What is left in the rewards pool.
+            available: Balance,
+        },
+        /// Returned if the group already has the maximum number of
+        /// moderators.
+        TooManyModerators,
+        /// Returned if a rain has more than `MAX_RAIN_RECIPIENTS` recipients,
+        /// or a weighted tip more than `MAX_WEIGHTED_RECIPIENTS`.
+        TooManyRecipients,
+        /// Returned if a recipient of a weighted tip has a zero weight.
+        ZeroWeight,
+        /// Returned if the user is banned from the group.
+        BannedFromGroup {
+            /// The banned user.
+            tg_id: TelegramId,
+        },
+        /// Returned if burning is disabled.
+        BurnDisabled,
+        /// Returned if the balance of the user is not below the existential
+        /// deposit.
+        NotDust,
+        /// Returned if the user already has a queued withdrawal.
+        WithdrawalPending,
+        /// Returned if the queued withdrawal could not be executed yet.
+        WithdrawalTimelocked {
+            /// The time the withdrawal could be executed at.
+            at: Timestamp,
+        },
+        /// Returned if the user has to withdraw thier balance through the
+        /// withdrawal delay before they could unbind.
+        WithdrawalDelayEnabled,
+        /// Returned if the user locked thier account.
+        AccountSelfLocked,
+        /// Returned if the user did not ask to unlock thier account.
+        UnlockNotRequested,
+        /// Returned if the account could not be unlocked yet without an
+        /// owner.
+        UnlockTimelocked {
+            /// The block the account could be unlocked at.
+            at: BlockNumber,
+        },
+        /// Returned if the user is not inactive for long enough for thier
+        /// balance to be claimed, or swept.
+        NotDormant {
+            /// The block the balance could be claimed at.
+            until: BlockNumber,
+        },
+        /// Returned if a team has more than `MAX_TEAM_MEMBERS` members, a user
+        /// more than `MAX_GUARDIANS` guardians or `MAX_LINKED_ADDRESSES`
+        /// linked addresses, or the panel more than `MAX_ARBITERS` arbiters.
+        TooManyMembers,
+        /// Returned if a recovery of the binding to another key is already
+        /// pending.
+        RecoveryPending,
+        /// Returned if the recovery does not have enough approvals yet.
+        RecoveryNotApproved,
+        /// Returned if the recovery could not be executed yet.
+        RecoveryTimelocked {
+            /// The block the recovery could be executed at.
+            at: BlockNumber,
+        },
+        /// Returned if the sender tipped in the group, or as a newcomer, too
+        /// recently.
+        TipCooldown {
+            /// The block the sender could tip again at.
+            until: BlockNumber,
+        },
+        /// Returned if the signature is not made by the expected key.
+        InvalidSignature,
+        /// Returned if there is no price oracle to tip in USD.
+        OracleDisabled,
+        /// Returned if the price oracle could not be queried.
+        OracleFailed,
+        /// Returned if the price of the oracle is too old.
+        StalePrice {
+            /// The time the price was updated at.
+            updated_at: Timestamp,
+        },
+        /// Returned if a tip in USD would cost more than the caller allowed
+        /// at the current price.
+        PriceSlippage {
+            /// The current price.
+            price: Balance,
+        },
+        /// Returned if the Telegram login payload is not signed by Telegram,
+        /// is for another TelegramId or account, or is too old.
+        InvalidTelegramAuth,
+        /// Returned if there is no DEX to swap tips through.
+        SwapDisabled,
+        /// Returned if the tokens could not be pulled or swapped, or the swap
+        /// gave less than the minimum.
+        SwapFailed,
+        /// Returned if the raffle ended, or its end is in the past.
+        RaffleEnded,
+        /// Returned if the raffle did not end yet.
+        RaffleNotEnded {
+            /// The block the raffle ends at.
+            ends_at: BlockNumber,
+        },
+        /// Returned if the contest ended, or its end is in the past.
+        ContestEnded,
+        /// Returned if the contest did not end yet, or, when opening one, if
+        /// the last one was not finalized yet.
+        ContestNotEnded {
+            /// The block the contest ends at.
+            ends_at: BlockNumber,
+        },
+        /// Returned if the seed does not match the commitment of the raffle,
+        /// or of the challenge.
+        InvalidSeed,
+        /// Returned if the seed of the raffle was not revealed in time, so
+        /// it could only be refunded.
+        RaffleExpired,
+        /// Returned if the tickets of the raffle, or the stakes of the
+        /// challenge, could not be refunded yet.
+        RefundNotOpen {
+            /// The block they could be refunded at.
+            at: BlockNumber,
+        },
+        /// Returned if the challenge was not accepted in time.
+        ChallengeExpired,
+        /// Returned if the challenge was already accepted.
+        ChallengeAccepted,
+        /// Returned if the challenge was not accepted yet.
+        ChallengeNotAccepted,
+        /// Returned if the randomness of the runtime could not be queried.
+        /// The coin-flips no longer use it, see `flip`, it is kept so the
+        /// errors after it keep thier encoding.
+        RandomnessUnavailable,
+        /// Returned if a poll has less than two options, or the option is
+        /// not one of its options.
+        InvalidOption,
+        /// Returned if the poll closed, or its close is in the past.
+        PollClosed,
+        /// Returned if the poll did not close yet.
+        PollNotClosed {
+            /// The block the poll closes at.
+            closes_at: BlockNumber,
+        },
+        /// Returned if the poll was already resolved.
+        PollResolved,
+        /// Returned if the poll was not resolved yet.
+        PollNotResolved,
+        /// Returned if the author of the content was not attested yet.
+        AuthorNotAttested,
+        /// Returned if too many bindings were made in the current window.
+        RateLimited {
+            /// The block the current window ends at.
+            until: BlockNumber,
+        },
+        /// Returned if the alias is empty, too long, or has characters other
+        /// than letters, digits and underscores.
+        InvalidAlias,
+        /// Returned if the alias is held by another TelegramId.
+        AliasTaken,
+        /// Returned if the alias is reserved by the owners.
+        AliasReserved,
+        /// Returned if a contract called by this one calls back into a
+        /// message that changes the state.
+        Reentered,
+        /// Returned if an escrow has more than `MAX_MILESTONES` milestones.
+        TooManyMilestones,
+        /// Returned if the milestone was already delivered.
+        AlreadyDelivered,
+        /// Returned if the milestone was not delivered yet.
+        MilestoneNotDelivered,
+        /// Returned if the arbiter releases a milestone before the timeout.
+        ReleaseNotOpen {
+            /// The block the arbiter could release it at.
+            at: BlockNumber,
+        },
+        /// Returned if the milestone is disputed, waiting for the arbiters.
+        Disputed,
+        /// Returned if a dispute is opened while there are no arbiters.
+        ArbitrationDisabled,
+        /// Returned if a tip to an unbound account is below the existential
+        /// deposit, so it could not be kept in thier balance instead.
+        BelowExistentialDeposit {
+            /// The existential deposit.
+            min: Balance,
+        },
+        /// Returned if a deposit would take the balance above the
+        /// `max_balance` of the verification level of the user.
+        AboveMaxBalance {
+            /// The largest allowed balance.
+            max: Balance,
+        },
+        /// Returned if the verification level of the user does not unlock
+        /// the feature.
+        FeatureLocked,
+        /// Returned if the recipient blocked the sender, or only accepts
+        /// tips from the senders they allowed.
+        RecipientBlockedSender,
+        /// Returned if the relayer would move more than its `volume_cap` in
+        /// the current era.
+        RelayerCapExceeded {
+            /// What is left of the cap in the current era.
+            remaining: Balance,
+        },
+        /// Returned if `MAX_JOBS` jobs are already waiting, or
+        /// `MAX_JOBS_PER_USER` of the user.
+        TooManyJobs,
+        /// Returned if the TelegramId is already bound to another AccountId.
+        TelegramIdTaken,
+        /// Returned if the owners turned the feature off.
+        FeatureDisabled,
+        /// Returned if a deposit would take the user, or the contract, above
+        /// a deposit cap.
+        DepositCapExceeded {
+            /// How much more could be deposited.
+            headroom: Balance,
+        },
+        /// Returned if the contract could not be terminated before
+        /// `wind_down` refunds everyone.
+        WindDownPending,
+        /// Returned if a team already has `MAX_TEAM_CONTRIBUTORS`
+        /// contributors.
+        TooManyContributors,
+        /// Returned if an account already has `MAX_POSITIONS` open
+        /// positions.
+        TooManyPositions,
+        /// Returned if the contract is built without the `chain-extension`
+        /// feature, so it could not call the runtime.
+        ExtensionUnavailable,
+        /// Returned if the runtime refused to bond, or unbond, the balance of
+        /// the contract.
+        StakingFailed,
+        /// Returned if the contract could not be terminated while part of its
+        /// balance is bonded, or unbonding.
+        StakeLocked,
+    }
+
+    /// Emitted when an owner proposes an admin action.
+    #[ink(event)]
+    pub struct ProposalCreated {
+        /// The Id of the proposal.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        /// The owner who proposed it.
+        #[ink(topic)]
+        proposer: AccountId,
+        /// The proposed action.
+        action: AdminAction,
+    }
+
+    /// Emitted when an owner approves an admin proposal.
+    #[ink(event)]
+    pub struct ProposalApproved {
+        /// The Id of the proposal.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        /// The owner who approved it.
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    /// Emitted when an admin proposal got enough approvals and executed.
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        /// The Id of the proposal.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    /// Emitted when a user proposes a parameter change.
+    #[ink(event)]
+    pub struct ParamChangeProposed {
+        /// The Id of the parameter change.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        /// The user who proposed it.
+        #[ink(topic)]
+        proposer: AccountId,
+        /// The parameter to change.
+        param: Param,
+        /// The new value of the parameter.
+        value: Balance,
+        /// The block the voting ends at.
+        ends_at: BlockNumber,
+    }
+
+    /// Emitted when a user votes on a parameter change.
+    #[ink(event)]
+    pub struct Voted {
+        /// The Id of the parameter change.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        /// The user who voted.
+        #[ink(topic)]
+        voter: AccountId,
+        /// Whether the vote is for the change.
+        aye: bool,
+        /// The balance of the voter.
+        weight: Balance,
+    }
+
+    /// Emitted when the voting on a parameter change is concluded.
+    #[ink(event)]
+    pub struct ParamChangeConcluded {
+        /// The Id of the parameter change.
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        /// Whether the change passed and got enacted.
+        passed: bool,
+    }
+
+    /// Emitted on every privileged call, so the whole admin history could
+    /// be reconstructed from the events alone.
+    #[ink(event)]
+    pub struct AdminActionLogged {
+        /// The sequential Id of the action.
+        #[ink(topic)]
+        action_id: u64,
+        /// Who made the call, the last approver of an admin action.
+        #[ink(topic)]
+        by: AccountId,
+        /// The account the action targets, if any.
+        #[ink(topic)]
+        target: Option<AccountId>,
+        /// The action, with its parameters.
+        action: PrivilegedAction,
+    }
+
+    /// Emitted when the owners give up the ownership of the contract.
+    #[ink(event)]
+    pub struct OwnershipRenounced {}
+
+    /// Emitted when the owner schedules the termination of the contract.
+    #[ink(event)]
+    pub struct TerminationScheduled {
+        /// Who will receive the remaining funds of the contract.
+        #[ink(topic)]
+        beneficiary: AccountId,
+        /// The block the termination could be executed at.
+        at: BlockNumber,
+    }
+
+    /// Emitted when the owner cancels a scheduled termination.
+    #[ink(event)]
+    pub struct TerminationCancelled {}
+
+    /// Emitted when the owners propose slashing an account.
+    #[ink(event)]
+    pub struct SlashProposed {
+        /// The Id of the slash.
+        #[ink(topic)]
+        slash_id: SlashId,
+        /// The slashed account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The amount to slash.
+        amount: Balance,
+        /// The hash of the reason, published off-chain.
+        reason_hash: Hash,
+        /// The block the appeal window ends at.
+        appeal_ends_at: BlockNumber,
+    }
+
+    /// Emitted when a user contests a slash.
+    #[ink(event)]
+    pub struct SlashContested {
+        /// The Id of the slash.
+        #[ink(topic)]
+        slash_id: SlashId,
+    }
+
+    /// Emitted when a slash is finalized, moving the amount to the treasury.
+    #[ink(event)]
+    pub struct SlashFinalized {
+        /// The Id of the slash.
+        #[ink(topic)]
+        slash_id: SlashId,
+        /// The amount moved to the treasury.
+        amount: Balance,
+    }
+
+    /// Emitted when a tip is paid to the recipient.
+    ///
+    /// The event is anonymous, so it has no topic for its signature, as
+    /// the runtime allows only 4 topics and it is indexed by both the
+    /// accounts and the TelegramIds of the parties.
+    #[ink(event, anonymous)]
+    pub struct Tipped {
+        /// The version of the layout of the event, `EVENT_SCHEMA_VERSION`.
+        schema_version: u16,
+        /// The Id of the tip receipt.
+        tip_id: TipId,
+        /// The sender of the tip, `None` if the tip is anonymous.
+        #[ink(topic)]
+        from: Option<AccountId>,
+        /// The recipient of the tip, `None` if they hide the tips they
+        /// receive.
+        #[ink(topic)]
+        to: Option<AccountId>,
+        /// The TelegramId of the sender, `None` if the tip is anonymous.
+        #[ink(topic)]
+        from_tg: Option<TelegramId>,
+        /// The TelegramId of the recipient, `None` if they hide the tips
+        /// they receive.
+        #[ink(topic)]
+        to_tg: Option<TelegramId>,
+        /// The tipped amount, fees included.
+        amount: Balance,
+        /// The fee taken into the treasury.
+        fee: Balance,
+        /// The commitment to the sender, if the tip is anonymous.
+        commitment: Option<Hash>,
+        /// The group the tip was sent in, if any.
+        group: Option<GroupId>,
+    }
+
+    /// Emitted when an account is bound to a TelegramId.
+    #[ink(event)]
+    pub struct Bound {
+        /// The version of the layout of the event, `EVENT_SCHEMA_VERSION`.
+        schema_version: u16,
+        /// The bound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The TelegramId it is bound to.
+        #[ink(topic)]
+        tg_id: TelegramId,
+        /// The TelegramId the account was bound to before, if any.
+        previous: Option<TelegramId>,
+    }
+
+    /// Emitted when the binding of an account is removed, by unbinding,
+    /// force unbinding, sweeping its dust, or its expiry.
+    #[ink(event)]
+    pub struct Unbound {
+        /// The version of the layout of the event, `EVENT_SCHEMA_VERSION`.
+        schema_version: u16,
+        /// The unbound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The TelegramId it was bound to.
+        #[ink(topic)]
+        tg_id: TelegramId,
+    }
+
+    /// Emitted when a group is registered.
+    #[ink(event)]
+    pub struct GroupRegistered {
+        /// The Id of the group.
+        #[ink(topic)]
+        group_id: GroupId,
+        /// The account who registered the group.
+        registrant: AccountId,
+    }
+
+    /// Emitted when someone donates to a group pot.
+    #[ink(event)]
+    pub struct GroupDonation {
+        /// The Id of the group.
+        #[ink(topic)]
+        group_id: GroupId,
+        /// The donor.
+        #[ink(topic)]
+        from: AccountId,
+        /// The donated amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a moderator bans a user from a group.
+    #[ink(event)]
+    pub struct GroupBan {
+        /// The Id of the group.
+        #[ink(topic)]
+        group_id: GroupId,
+        /// The banned user.
+        tg_id: TelegramId,
+        /// Whether the user is banned, or the ban is lifted.
+        banned: bool,
+    }
+
+    /// Emitted when a moderator rains from a group pot.
+    #[ink(event)]
+    pub struct GroupRain {
+        /// The Id of the group.
+        #[ink(topic)]
+        group_id: GroupId,
+        /// The moderator who made it rain.
+        #[ink(topic)]
+        moderator: AccountId,
+        /// How many users got paid.
+        recipients: u32,
+        /// The amount each of them got.
+        amount: Balance,
+    }
+
+    /// Emitted when a team wallet is created.
+    #[ink(event)]
+    pub struct TeamCreated {
+        /// The Id of the team.
+        #[ink(topic)]
+        team_id: TeamId,
+        /// The hash of the name of the team.
+        name_hash: Hash,
+    }
+
+    /// Emitted when a team is cancelled, refunding its contributors.
+    #[ink(event)]
+    pub struct TeamCancelled {
+        /// The Id of the team.
+        #[ink(topic)]
+        team_id: TeamId,
+        /// How much was refunded to the contributors.
+        refunded: Balance,
+    }
+
+    /// Emitted when a member proposes a tip from a team wallet.
+    #[ink(event)]
+    pub struct TeamTipProposed {
+        /// The Id of the team tip.
+        team_tip_id: TeamTipId,
+        /// The team paying the tip.
+        #[ink(topic)]
+        team_id: TeamId,
+        /// The recipient of the tip.
+        to: TelegramId,
+        /// The tipped amount, fees included.
+        amount: Balance,
+    }
+
+    /// Emitted when a tip from a team wallet got enough approvals and paid.
+    #[ink(event)]
+    pub struct TeamTipped {
+        /// The Id of the team tip.
+        team_tip_id: TeamTipId,
+        /// The team who paid the tip.
+        #[ink(topic)]
+        team_id: TeamId,
+        /// The recipient of the tip.
+        #[ink(topic)]
+        to: AccountId,
+        /// The tipped amount, fees included.
+        amount: Balance,
+        /// The fee taken into the treasury.
+        fee: Balance,
+    }
+
+    /// Emitted when a user changes thier payout address, so they could
+    /// notice if it was not them.
+    #[ink(event)]
+    pub struct PayoutAddressChanged {
+        /// The bound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new payout address.
+        #[ink(topic)]
+        payout: AccountId,
+    }
+
+    /// Emitted when a user sets, or removes, thier remote address.
+    #[ink(event)]
+    pub struct RemoteAddressChanged {
+        /// The bound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new remote address, `None` if it was removed.
+        remote: Option<RemoteAddress>,
+    }
+
+    /// Emitted after `Tipped` when a tip is sent to the remote address of
+    /// the recipient, instead of being paid locally.
+    #[ink(event)]
+    pub struct TipSentRemotely {
+        /// The Id of the tip.
+        #[ink(topic)]
+        tip_id: TipId,
+        /// The parachain the tip was sent to.
+        para_id: ParaId,
+    }
+
+    /// Emitted when a user sets, or removes, thier beneficiary.
+    #[ink(event)]
+    pub struct BeneficiarySet {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The beneficiary and the inactivity period, `None` if removed.
+        beneficiary: Option<(AccountId, BlockNumber)>,
+    }
+
+    /// Emitted when a beneficiary claims the balance of a dormant user.
+    #[ink(event)]
+    pub struct DormantClaimed {
+        /// The dormant user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The beneficiary.
+        #[ink(topic)]
+        beneficiary: AccountId,
+        /// The claimed balance.
+        amount: Balance,
+    }
+
+    /// Emitted when the owners announce sweeping the dust of a dormant user.
+    #[ink(event)]
+    pub struct DustSweepAnnounced {
+        /// The dormant user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The block the dust could be swept at, unless the user does
+        /// anything before.
+        at: BlockNumber,
+    }
+
+    /// Emitted when the dust of a dormant user is swept into the treasury.
+    #[ink(event)]
+    pub struct DustSwept {
+        /// The dormant user, who is no longer bound.
+        #[ink(topic)]
+        account: AccountId,
+        /// The swept balance.
+        amount: Balance,
+    }
+
+    /// Emitted when a user locks thier account.
+    #[ink(event)]
+    pub struct AccountLocked {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Emitted when a user asks to unlock thier account, so they could lock
+    /// it again if it was not them.
+    #[ink(event)]
+    pub struct UnlockRequested {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The block the account could be unlocked at without an owner.
+        at: BlockNumber,
+    }
+
+    /// Emitted when an account is unlocked.
+    #[ink(event)]
+    pub struct AccountUnlocked {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The owner who co-signed the unlock, if any.
+        owner: Option<AccountId>,
+    }
+
+    /// Emitted when a user changes thier guardians.
+    #[ink(event)]
+    pub struct GuardiansChanged {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new guardians.
+        guardians: Vec<AccountId>,
+        /// How many of them have to approve a recovery.
+        threshold: u32,
+    }
+
+    /// Emitted when a guardian, or an owner, approves recovering a binding to
+    /// a new key, so the user could cancel it if they still have thier key.
+    #[ink(event)]
+    pub struct RecoveryApproved {
+        /// The current key of the binding.
+        #[ink(topic)]
+        account: AccountId,
+        /// The key the binding moves to.
+        #[ink(topic)]
+        new_account: AccountId,
+        /// The guardian, or owner, who approved it.
+        by: AccountId,
+        /// The block the recovery could be executed at.
+        at: BlockNumber,
+    }
+
+    /// Emitted when the user cancels the recovery of thier binding.
+    #[ink(event)]
+    pub struct RecoveryCancelled {
+        /// The key of the binding.
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Emitted when a binding is recovered to a new key.
+    #[ink(event)]
+    pub struct BindingRecovered {
+        /// The old key of the binding.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new key of the binding.
+        #[ink(topic)]
+        new_account: AccountId,
+        /// The recovered TelegramId.
+        tg_id: TelegramId,
+    }
+
+    /// Emitted when a user moves thier binding to a new key.
+    #[ink(event)]
+    pub struct Rebound {
+        /// The old key of the binding.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new key of the binding.
+        #[ink(topic)]
+        new_account: AccountId,
+        /// The moved TelegramId.
+        tg_id: TelegramId,
+    }
+
+    /// Emitted when the owners or the operator correct the TelegramId of an
+    /// account.
+    #[ink(event)]
+    pub struct TelegramIdChanged {
+        /// The corrected account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The freed TelegramId.
+        old_tg_id: TelegramId,
+        /// The TelegramId the account is bound to now.
+        new_tg_id: TelegramId,
+    }
+
+    /// Emitted when tipEDG receipt tokens are transferred, minted or
+    /// burned, as in PSP22.
+    #[ink(event)]
+    pub struct Transfer {
+        /// The sender, `None` if minted.
+        #[ink(topic)]
+        from: Option<AccountId>,
+        /// The recipient, `None` if burned.
+        #[ink(topic)]
+        to: Option<AccountId>,
+        /// The transferred amount.
+        value: Balance,
+    }
+
+    /// Emitted when an allowance of tipEDG receipt tokens changes, as in
+    /// PSP22.
+    #[ink(event)]
+    pub struct Approval {
+        /// The owner of the tokens.
+        #[ink(topic)]
+        owner: AccountId,
+        /// The allowed spender.
+        #[ink(topic)]
+        spender: AccountId,
+        /// The new allowance.
+        value: Balance,
+    }
+
+    /// Emitted when a tip is swapped to another token.
+    #[ink(event)]
+    pub struct SwapTipped {
+        /// The sender of the tip.
+        #[ink(topic)]
+        from: AccountId,
+        /// The recipient of the tip.
+        #[ink(topic)]
+        to: AccountId,
+        /// The token the sender paid in.
+        pay_token: AccountId,
+        /// The amount paid.
+        amount: Balance,
+        /// The amount of EDG it was swapped to, and tipped.
+        received: Balance,
+    }
+
+    /// Emitted when the operator claims the relay fees.
+    #[ink(event)]
+    pub struct RelayReimbursed {
+        /// The operator, or the relayer.
+        #[ink(topic)]
+        operator: AccountId,
+        /// The claimed relay fees.
+        amount: Balance,
+    }
+
+    /// Emitted when the owners unbind an account.
+    #[ink(event)]
+    pub struct ForceUnbound {
+        /// The unbound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The TelegramId it was bound to.
+        tg_id: TelegramId,
+    }
+
+    /// Emitted when a user changes thier withdrawal delay.
+    #[ink(event)]
+    pub struct WithdrawalDelayChanged {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new delay, in milliseconds.
+        delay: Timestamp,
+        /// The time the new delay takes effect at.
+        effective_at: Timestamp,
+    }
+
+    /// Emitted when a withdrawal is queued.
+    #[ink(event)]
+    pub struct WithdrawalRequested {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The requested amount.
+        amount: Balance,
+        /// The time the withdrawal could be executed at.
+        at: Timestamp,
+    }
+
+    /// Emitted when a queued withdrawal is cancelled.
+    #[ink(event)]
+    pub struct WithdrawalCancelled {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The amount given back to thier balance.
+        amount: Balance,
+    }
+
+    /// Emitted when a user withdraws part of thier balance.
+    #[ink(event)]
+    pub struct Withdrawn {
+        /// The user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The withdrawn amount.
+        amount: Balance,
+    }
+
+    /// Emitted when funds are burned.
+    #[ink(event)]
+    pub struct Burned {
+        /// The user who burned part of thier balance, `None` if the owners
+        /// burned part of the treasury.
+        #[ink(topic)]
+        from: Option<AccountId>,
+        /// The burned amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a tip is rounded up for the charity.
+    #[ink(event)]
+    pub struct CharityDonated {
+        /// The user who rounded up the tip, `None` if the tip is anonymous.
+        #[ink(topic)]
+        account: Option<AccountId>,
+        /// The charity.
+        #[ink(topic)]
+        charity: AccountId,
+        /// The difference given to the charity.
+        amount: Balance,
+    }
+
+    /// Emitted when a tip is held in the reversal window.
+    #[ink(event)]
+    pub struct TipHeld {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+        /// The sender of the tip, `None` if the tip is anonymous.
+        #[ink(topic)]
+        from: Option<AccountId>,
+        /// The recipient of the tip, `None` if they hide the tips they
+        /// receive.
+        #[ink(topic)]
+        to: Option<AccountId>,
+        /// The tipped amount, fees included.
+        amount: Balance,
+        /// The block the tip could be finalized at.
+        release_at: BlockNumber,
+        /// Whether the recipient has to accept the tip before it is paid.
+        needs_acceptance: bool,
+    }
+
+    /// Emitted when the recipient accepts a held tip.
+    #[ink(event)]
+    pub struct TipAccepted {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+    }
+
+    /// Emitted when the recipient declines a held tip, refunding the sender.
+    #[ink(event)]
+    pub struct TipDeclined {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+    }
+
+    /// Emitted when the sender changes when a held tip expires.
+    #[ink(event)]
+    pub struct TipExpirySet {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+        /// The block the tip expires after, `None` if it never does.
+        expires_at: Option<BlockNumber>,
+    }
+
+    /// Emitted when a tip is scheduled.
+    #[ink(event)]
+    pub struct TipScheduled {
+        /// The Id of the job.
+        #[ink(topic)]
+        job_id: JobId,
+        /// The account sending the tip.
+        #[ink(topic)]
+        from: AccountId,
+        /// The recipient.
+        to: TelegramId,
+        /// The amount tipped.
+        amount: Balance,
+        /// The time the tip could be sent at.
+        execute_at: Timestamp,
+    }
+
+    /// Emitted when a keeper executes a job, whether it succeeded or not.
+    #[ink(event)]
+    pub struct JobExecuted {
+        /// The Id of the job.
+        #[ink(topic)]
+        job_id: JobId,
+        /// Why the job failed, `None` if it succeeded.
+        error: Option<Error>,
+    }
+
+    /// Emitted when the owner of a job cancels it.
+    #[ink(event)]
+    pub struct JobCancelled {
+        /// The Id of the job.
+        #[ink(topic)]
+        job_id: JobId,
+    }
+
+    /// Emitted when an unaccepted held tip expires, refunding the sender.
+    #[ink(event)]
+    pub struct TipExpired {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+        /// Who expired the tip.
+        #[ink(topic)]
+        by: AccountId,
+        /// The share of the tip paid to them.
+        reward: Balance,
+    }
+
+    /// Emitted when the sender revokes a held tip.
+    #[ink(event)]
+    pub struct TipRevoked {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+    }
+
+    /// Emitted when a held tip is finalized and paid to the recipient.
+    #[ink(event)]
+    pub struct TipFinalized {
+        /// The Id of the held tip.
+        #[ink(topic)]
+        tip_id: HeldTipId,
+    }
+
+    /// Emitted when a user redeems loyalty points into thier balance.
+    #[ink(event)]
+    pub struct PointsRedeemed {
+        /// The user who redeemed the points.
+        #[ink(topic)]
+        account: AccountId,
+        /// The redeemed points.
+        points: Balance,
+        /// The amount added to the user's balance.
+        amount: Balance,
+    }
+
+    /// Emitted when a user earns a badge.
+    #[ink(event)]
+    pub struct BadgeEarned {
+        /// The user who earned the badge.
+        #[ink(topic)]
+        account: AccountId,
+        /// The earned badge, one of the `BADGE_*` flags.
+        badge: u32,
+    }
+
+    /// Emitted when a thank-you receipt token is minted for a tip.
+    #[ink(event)]
+    pub struct TipNftMinted {
+        /// The sender of the tip.
+        #[ink(topic)]
+        from: AccountId,
+        /// The TelegramId the token was minted to.
+        #[ink(topic)]
+        to: TelegramId,
+        /// The Id of the token.
+        id: TipNftId,
+    }
+
+    /// Emitted when a raffle is created.
+    #[ink(event)]
+    pub struct RaffleCreated {
+        /// The Id of the raffle.
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        /// The price of a single ticket.
+        ticket_price: Balance,
+        /// The block the raffle ends at.
+        ends_at: BlockNumber,
+    }
+
+    /// Emitted when a tipping contest is opened.
+    #[ink(event)]
+    pub struct ContestOpened {
+        /// The Id of the contest.
+        #[ink(topic)]
+        contest_id: ContestId,
+        /// The prize pot.
+        pot: Balance,
+        /// The block the contest ends at.
+        ends_at: BlockNumber,
+        /// The share of the pot of each place, in basis points.
+        weights: Vec<u16>,
+    }
+
+    /// Emitted when the prizes of a tipping contest are paid.
+    #[ink(event)]
+    pub struct ContestFinalized {
+        /// The Id of the contest.
+        #[ink(topic)]
+        contest_id: ContestId,
+        /// The winners who were paid, with thier prizes, first place first.
+        winners: Vec<(AccountId, Balance)>,
+        /// What was not won, or could not be paid, paid back to the funder.
+        unclaimed: Balance,
+    }
+
+    /// Emitted when tickets of a raffle are bought.
+    #[ink(event)]
+    pub struct TicketsBought {
+        /// The Id of the raffle.
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        /// The buyer.
+        #[ink(topic)]
+        buyer: AccountId,
+        /// How many tickets were bought.
+        count: u32,
+    }
+
+    /// Emitted when a raffle is drawn.
+    #[ink(event)]
+    pub struct RaffleDrawn {
+        /// The Id of the raffle.
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        /// The winner, `None` if no tickets were sold.
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        /// The pot paid to the winner, after the rake.
+        prize: Balance,
+    }
+
+    /// Emitted when the tickets of an expired raffle are refunded.
+    #[ink(event)]
+    pub struct RaffleRefunded {
+        /// The Id of the raffle.
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        /// The refunded buyer.
+        #[ink(topic)]
+        account: AccountId,
+        /// The refunded amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a user challenges another to a coin-flip.
+    #[ink(event)]
+    pub struct ChallengeCreated {
+        /// The Id of the challenge.
+        #[ink(topic)]
+        challenge_id: ChallengeId,
+        /// The challenger.
+        #[ink(topic)]
+        challenger: AccountId,
+        /// The challenged TelegramId.
+        #[ink(topic)]
+        opponent: TelegramId,
+        /// The stake of each side.
+        stake: Balance,
+    }
+
+    /// Emitted when a coin-flip challenge is accepted.
+    #[ink(event)]
+    pub struct ChallengeAccepted {
+        /// The Id of the challenge.
+        #[ink(topic)]
+        challenge_id: ChallengeId,
+        /// The account that accepted it.
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Emitted when the coin of a challenge is flipped, or the challenger
+    /// forfeits it.
+    #[ink(event)]
+    pub struct CoinFlipped {
+        /// The Id of the challenge.
+        #[ink(topic)]
+        challenge_id: ChallengeId,
+        /// The winner.
+        #[ink(topic)]
+        winner: AccountId,
+        /// The pot paid to the winner.
+        pot: Balance,
+    }
+
+    /// Emitted when the stake of a challenge that was not accepted is
+    /// refunded.
+    #[ink(event)]
+    pub struct ChallengeRefunded {
+        /// The Id of the challenge.
+        #[ink(topic)]
+        challenge_id: ChallengeId,
+    }
+
+    /// Emitted when a prediction poll is created.
+    #[ink(event)]
+    pub struct PollCreated {
+        /// The Id of the poll.
+        #[ink(topic)]
+        poll_id: PollId,
+        /// How many options the poll has.
+        options: u8,
+        /// The block the poll closes at.
+        closes_at: BlockNumber,
+    }
+
+    /// Emitted when a user stakes on an option of a poll.
+    #[ink(event)]
+    pub struct StakedOnPoll {
+        /// The Id of the poll.
+        #[ink(topic)]
+        poll_id: PollId,
+        /// The user who staked.
+        #[ink(topic)]
+        account: AccountId,
+        /// The option staked on.
+        option: u8,
+        /// The staked amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a poll is resolved.
+    #[ink(event)]
+    pub struct PollResolved {
+        /// The Id of the poll.
+        #[ink(topic)]
+        poll_id: PollId,
+        /// The winning option.
+        winning_option: u8,
+    }
+
+    /// Emitted when a user is paid out of a resolved poll.
+    #[ink(event)]
+    pub struct PollPaid {
+        /// The Id of the poll.
+        #[ink(topic)]
+        poll_id: PollId,
+        /// The paid user.
+        #[ink(topic)]
+        account: AccountId,
+        /// The paid amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a piece of content is tipped.
+    #[ink(event)]
+    pub struct ContentTipped {
+        /// The hash of the content.
+        #[ink(topic)]
+        content_hash: Hash,
+        /// The sender of the tip.
+        #[ink(topic)]
+        from: AccountId,
+        /// The tipped amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a charity cause is registered, or changed.
+    #[ink(event)]
+    pub struct CauseRegistered {
+        /// The Id of the cause.
+        #[ink(topic)]
+        cause_id: CauseId,
+        /// The account the donations are paid to.
+        account: AccountId,
+        /// The hash of the description of the cause.
+        metadata_hash: Hash,
+    }
+
+    /// Emitted when a tip category is registered, renamed or removed.
+    #[ink(event)]
+    pub struct CategorySet {
+        /// The Id of the category.
+        #[ink(topic)]
+        category: u8,
+        /// The new name, `None` if the category was removed.
+        name: Option<String>,
+    }
+
+    /// Emitted when the display metadata of an asset is set, or removed.
+    #[ink(event)]
+    pub struct AssetMetadataSet {
+        /// The token contract, `None` for the native EDG.
+        #[ink(topic)]
+        asset: Option<AccountId>,
+        /// The new metadata, `None` if it was removed.
+        meta: Option<AssetMeta>,
+    }
+
+    /// Emitted when the moderator note of an account is set, or removed.
+    #[ink(event)]
+    pub struct NoteSet {
+        /// The account the note is about.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new note, `None` if it was removed.
+        note: Option<Note>,
+    }
+
+    /// Emitted when a relayer is configured, or removed.
+    #[ink(event)]
+    pub struct RelayerSet {
+        /// The account of the relayer.
+        #[ink(topic)]
+        relayer: AccountId,
+        /// The new configuration, `None` if the relayer was removed.
+        config: Option<RelayerConfig>,
+    }
+
+    /// Emitted when the verification level of an account is set.
+    #[ink(event)]
+    pub struct VerificationLevelSet {
+        /// The account whose level is set.
+        #[ink(topic)]
+        account: AccountId,
+        /// The new level.
+        level: VerificationLevel,
+    }
+
+    /// Emitted when the limits of a verification level are set, or removed.
+    #[ink(event)]
+    pub struct LevelLimitsSet {
+        /// The verification level.
+        level: VerificationLevel,
+        /// The new limits, `None` if they were removed.
+        limits: Option<LevelLimits>,
+    }
+
+    /// Emitted when a feature is turned on, or off.
+    #[ink(event)]
+    pub struct FeatureSet {
+        /// The feature.
+        feature: Feature,
+        /// Whether it was turned on.
+        enabled: bool,
+    }
+
+    /// Emitted when the deposit caps are set, or lifted.
+    #[ink(event)]
+    pub struct DepositCapsSet {
+        /// The most a user could hold by depositing, `None` if not capped.
+        per_account: Option<Balance>,
+        /// The most the contract could hold, `None` if not capped.
+        global: Option<Balance>,
+    }
+
+    /// Emitted when the owners bond part of the balance of the contract.
+    #[ink(event)]
+    pub struct Staked {
+        /// The bonded amount.
+        amount: Balance,
+    }
+
+    /// Emitted when the owners start unbonding part of the bonded balance.
+    #[ink(event)]
+    pub struct Unstaked {
+        /// The unbonding amount.
+        amount: Balance,
+    }
+
+    /// Emitted when the balance that finished unbonding is unlocked.
+    #[ink(event)]
+    pub struct UnbondedWithdrawn {
+        /// The unlocked amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a user donates to a charity cause.
+    #[ink(event)]
+    pub struct Donated {
+        /// The Id of the cause.
+        #[ink(topic)]
+        cause_id: CauseId,
+        /// The donor.
+        #[ink(topic)]
+        from: AccountId,
+        /// The donated amount.
+        amount: Balance,
+    }
+
+    /// Emitted when a hook failed, or ran out of gas, on a tip.
+    #[ink(event)]
+    pub struct HookFailed {
+        /// The hook contract.
+        #[ink(topic)]
+        hook: AccountId,
+    }
+
+    /// Emitted when a funder escrows a budget for some work.
+    #[ink(event)]
+    pub struct EscrowCreated {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// Who escrowed the budget.
+        #[ink(topic)]
+        funder: AccountId,
+        /// Who is paid for the work.
+        #[ink(topic)]
+        worker: AccountId,
+        /// The total of all the milestones.
+        total: Balance,
+    }
+
+    /// Emitted when the worker delivers a milestone of an escrow.
+    #[ink(event)]
+    pub struct MilestoneDelivered {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// The index of the milestone.
+        index: u32,
+    }
+
+    /// Emitted when a milestone of an escrow is paid to the worker.
+    #[ink(event)]
+    pub struct MilestoneReleased {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// The index of the milestone.
+        index: u32,
+        /// Who released it, the funder or the arbiter.
+        by: AccountId,
+        /// The amount paid.
+        amount: Balance,
+    }
+
+    /// Emitted when the undelivered milestones of an escrow are refunded.
+    #[ink(event)]
+    pub struct EscrowRefunded {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// The amount refunded to the funder.
+        amount: Balance,
+    }
+
+    /// Emitted when a milestone of an escrow is disputed.
+    #[ink(event)]
+    pub struct DisputeOpened {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// The index of the milestone.
+        index: u32,
+        /// The funder, or worker, who opened it.
+        by: AccountId,
+        /// The block the voting ends at.
+        ends_at: BlockNumber,
+    }
+
+    /// Emitted when the arbiters decide a dispute.
+    #[ink(event)]
+    pub struct DisputeResolved {
+        /// The Id of the escrow.
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        /// The index of the milestone.
+        index: u32,
+        /// Whether the milestone was released to the worker, or refunded to
+        /// the funder, `None` if the vote was tied.
+        released: Option<bool>,
+    }
+
+    /// Emitted when the author of a piece of content is attested.
+    #[ink(event)]
+    pub struct AuthorAttested {
+        /// The hash of the content.
+        #[ink(topic)]
+        content_hash: Hash,
+        /// The TelegramId of the author.
+        #[ink(topic)]
+        tg_id: TelegramId,
+    }
+
+    /// Emitted when the author claims the tip jar of thier content.
+    #[ink(event)]
+    pub struct ContentClaimed {
+        /// The hash of the content.
+        #[ink(topic)]
+        content_hash: Hash,
+        /// The author.
+        #[ink(topic)]
+        account: AccountId,
+        /// The amount paid to the author, after the fee.
+        received: Balance,
+    }
+
+    /// Emitted when an alias is claimed by, or assigned to, a TelegramId.
+    #[ink(event)]
+    pub struct AliasClaimed {
+        /// The TelegramId holding the alias.
+        #[ink(topic)]
+        tg_id: TelegramId,
+        /// The normalized alias.
+        name: String,
+    }
+
+    /// Emitted when the alias of a TelegramId is released.
+    #[ink(event)]
+    pub struct AliasReleased {
+        /// The TelegramId that held the alias.
+        #[ink(topic)]
+        tg_id: TelegramId,
+        /// The normalized alias.
+        name: String,
+    }
+
+    /// Emitted when the owners reserve an alias, or lift its reservation.
+    #[ink(event)]
+    pub struct AliasReservationChanged {
+        /// The normalized alias.
+        name: String,
+        /// Whether the alias is reserved now.
+        reserved: bool,
+    }
+
+    /// Emitted when a user renews thier binding.
+    #[ink(event)]
+    pub struct BindingRenewed {
+        /// The renewed account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The block the binding expires at now.
+        expires_at: BlockNumber,
+    }
+
+    /// Emitted when an expired binding is replaced by a new binding of its
+    /// TelegramId.
+    #[ink(event)]
+    pub struct BindingExpired {
+        /// The account of the expired binding.
+        #[ink(topic)]
+        account: AccountId,
+        /// The TelegramId that was bound.
+        #[ink(topic)]
+        tg_id: TelegramId,
+        /// The balance left to be withdrawn by `withdraw_expired`.
+        balance: Balance,
+    }
+
+    /// Emitted when a secondary address is linked to a bound account.
+    #[ink(event)]
+    pub struct AddressLinked {
+        /// The bound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The secondary address.
+        #[ink(topic)]
+        secondary: AccountId,
+    }
+
+    /// Emitted when a secondary address is unlinked from a bound account.
+    #[ink(event)]
+    pub struct AddressUnlinked {
+        /// The bound account.
+        #[ink(topic)]
+        account: AccountId,
+        /// The secondary address.
+        #[ink(topic)]
+        secondary: AccountId,
+    }
+
+    /// Emitted when a soulbound achievement token is minted.
+    #[ink(event)]
+    pub struct AchievementMinted {
+        /// The user who earned it.
+        #[ink(topic)]
+        account: AccountId,
+        /// The Id of the token.
+        id: AchievementId,
+        /// The earned badge, one of the `BADGE_*` flags.
+        badge: u32,
+    }
+
+    /// Emitted when the owners cancel a slash.
+    #[ink(event)]
+    pub struct SlashCancelled {
+        /// The Id of the slash.
+        #[ink(topic)]
+        slash_id: SlashId,
+    }
+
+    impl Tipbot {
+        /// Create new Tipbot.
+        /// The `caller` of this constructor will be set as the only Owner,
+        /// and the operator, of the contract.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            let caller = Self::env().caller();
+            Self::with_owners(ink_prelude::vec![caller], 1)
+        }
+
+        /// Create new Tipbot owned by a set of `owners`, where `threshold`
+        /// of them have to approve every admin action.
+        /// The `caller` of this constructor will be set as the operator of
+        /// the contract.
+        ///
+        /// Panics if the `threshold` is zero or more than the number of
+        /// owners.
+        #[ink(constructor)]
+        pub fn with_owners(owners: Vec<AccountId>, threshold: u32) -> Self {
+            assert!(
+                threshold > 0 && threshold as usize <= owners.len(),
+                "{:?}",
+                Error::InvalidThreshold
+            );
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                contract.owners = owners;
+                contract.threshold = threshold;
+                contract.operator = Self::env().caller();
+                contract.params = Params::default();
+                contract.history_limit = DEFAULT_HISTORY_LIMIT;
+                contract.param_quorum = DEFAULT_PARAM_QUORUM;
+                contract.dust_dormancy = DEFAULT_DUST_DORMANCY;
+                contract.hook_gas_limit = DEFAULT_HOOK_GAS_LIMIT;
+                contract.token_call_gas_limit = DEFAULT_TOKEN_CALL_GAS_LIMIT;
+            })
+        }
+
+        /// Query the revision of the ABI of the contract.
+        #[ink(message, selector = 0xF9718392)]
+        pub fn spec_version(&self) -> u32 { SPEC_VERSION }
+
+        /// Query the version and the configuration of the contract at once,
+        /// so a bot could tell what a deployment supports.
+        #[ink(message, selector = 0x0190E5AC)]
+        pub fn info(&self) -> ContractInfo {
+            ContractInfo {
+                spec_version: SPEC_VERSION,
+                storage_version: STORAGE_VERSION,
+                event_schema_version: EVENT_SCHEMA_VERSION,
+                owners: self.owners.clone(),
+                threshold: self.threshold,
+                operator: self.operator,
+                params: self.params.clone(),
+                newcomer_limits: self.newcomer_limits,
+                terminates_at: self.termination.map(|(_, at)| at),
+                features: SUPPORTED_FEATURES & !self.disabled_features,
+            }
+        }
+
+        /// Query the owners of the contract.
+        ///
+        /// An ownerless contract returns no owners.
+        #[ink(message, selector = 0x6884DAB0)]
+        pub fn owners(&self) -> Vec<AccountId> { self.owners.clone() }
+
+        /// Query how many owners have to approve an admin action.
+        #[ink(message, selector = 0x36D37DEF)]
+        pub fn threshold(&self) -> u32 { self.threshold }
+
+        /// Query the account allowed to tip on behalf of the users.
+        #[ink(message, selector = 0x5604E62B)]
+        pub fn operator(&self) -> AccountId { self.operator }
+
+        /// Query an admin proposal waiting for approvals.
+        #[ink(message, selector = 0x025AAC7E)]
+        pub fn proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+            self.proposals.get(proposal_id)
+        }
+
+        /// Propose an admin action, counting as the first approval.
+        ///
+        /// The action is executed right away if the threshold is one,
+        /// otherwise it waits for more owners to `approve` it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns any error of the action itself, if executed right away.
+        #[ink(message, selector = 0xD28C38AB)]
+        pub fn propose_admin_call(
+            &mut self,
+            call: AdminAction,
+        ) -> Result<ProposalId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let proposer = self.env().caller();
+            let proposal_id = self.next_proposal_id;
+            self.next_proposal_id += 1;
+            self.env().emit_event(ProposalCreated {
+                proposal_id,
+                proposer,
+                action: call.clone(),
+            });
+            let proposal = Proposal {
+                action: call,
+                approvals: ink_prelude::vec![proposer],
+            };
+            self.approve_proposal(proposal_id, proposal)?;
+            Ok(proposal_id)
+        }
+
+        /// Approve an admin proposal, executing it once it has `threshold`
+        /// approvals.
+        ///
+        /// If the action fails the approval is not counted, so it could be
+        /// retried later.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::NotFound` with `Entity::Proposal` if there is no
+        ///   such proposal.
+        ///
+        /// * Returns `Error::AlreadyApproved` if the caller already approved
+        ///   it.
+        ///
+        /// * Returns any error of the action itself, if executed.
+        #[ink(message, selector = 0x681266A0)]
+        pub fn approve(
+            &mut self,
+            proposal_id: ProposalId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let caller = self.env().caller();
+            let mut proposal =
+                self.proposals.get(proposal_id).ok_or(Error::NotFound {
+                    which: Entity::Proposal,
+                })?;
+            if proposal.approvals.contains(&caller) {
+                return Err(Error::AlreadyApproved);
+            }
+            proposal.approvals.push(caller);
+            self.approve_proposal(proposal_id, proposal)
+        }
+
+        /// Correct the TelegramId the account is bound to, freeing the old
+        /// one, and keeping its balance and record.
+        ///
+        /// Only the operator could call this, the owners correct it by an
+        /// approved `AdminCall::AdminRebind`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not the operator.
+        ///
+        /// * Returns `Error::NotFound` if the account is not bound.
+        ///
+        /// * Returns `Error::TelegramIdTaken` if `new_tg_id` is already bound.
+        #[ink(message, selector = 0xA8BDB784)]
+        pub fn admin_rebind(
+            &mut self,
+            account: AccountId,
+            new_tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            if self.env().caller() != self.operator {
+                return Err(Error::NotAllowed);
+            }
+            self.change_tg_id(account, new_tg_id)?;
+            self.log_admin_action(
+                Some(account),
+                PrivilegedAction::AdminRebind(new_tg_id),
+            );
+            Ok(())
+        }
+
+        /// Move the binding of the account to `new_tg_id`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the account is not bound.
+        ///
+        /// * Returns `Error::TelegramIdTaken` if `new_tg_id` is already bound.
+        fn change_tg_id(
+            &mut self,
+            account: AccountId,
+            new_tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            let mut record =
+                self.users.get(account).ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            if self.tg_address.contains(new_tg_id) {
+                return Err(Error::TelegramIdTaken);
+            }
+            let old_tg_id = record.tg_id;
+            self.tg_address.remove(old_tg_id);
+            self.tg_address.insert(new_tg_id, &account);
+            record.tg_id = new_tg_id;
+            self.users.insert(account, &record);
+            self.env().emit_event(TelegramIdChanged {
+                account,
+                old_tg_id,
+                new_tg_id,
+            });
+            Ok(())
+        }
+
+        /// Query up to `limit` bound accounts and thier records, starting
+        /// from the `start`th one, to snapshot the ledger off-chain.
+        ///
+        /// At most `MAX_EXPORT_PAGE` accounts are returned, and fewer once
+        /// the end of the list is reached.
+        #[ink(message, selector = 0x8EAF5F23)]
+        pub fn export_users(
+            &self,
+            start: u32,
+            limit: u32,
+        ) -> Vec<(AccountId, UserRecord)> {
+            let end = start
+                .saturating_add(limit.min(MAX_EXPORT_PAGE))
+                .min(self.accounts_count);
+            (start..end)
+                .filter_map(|index| {
+                    let account = self.accounts.get(index)?;
+                    Some((account, self.users.get(account)?))
+                })
+                .collect()
+        }
+
+        /// Query the current tipping policy.
+        #[ink(message, selector = 0xF5353FE9)]
+        pub fn params(&self) -> Params { self.params.clone() }
+
+        /// Query the fees collected so far.
+        #[ink(message, selector = 0xA9B78461)]
+        pub fn treasury(&self) -> Balance { self.treasury }
+
+        /// Query the fees collected in the last `last_n` eras, including the
+        /// current one, oldest first.
+        ///
+        /// Eras with no tips are reported with zero fees, and at most
+        /// `MAX_REPORT_ERAS` eras are returned.
+        #[ink(message, selector = 0x0FBB5EB5)]
+        pub fn treasury_report(&self, last_n: u32) -> Vec<EraFees> {
+            let current = self.current_era();
+            let last_n = last_n.min(MAX_REPORT_ERAS).min(current + 1);
+            let cache = self.stats_cache.get(()).unwrap_or_default();
+            (current + 1 - last_n..=current)
+                .map(|era| {
+                    if era == cache.era_fees.era {
+                        return cache.era_fees.clone();
+                    }
+                    self.fee_history.get(era).unwrap_or(EraFees {
+                        era,
+                        ..Default::default()
+                    })
+                })
+                .collect()
+        }
+
+        /// Query the current statistics epoch, see `EPOCH_LENGTH`.
+        #[ink(message, selector = 0x5F02A02E)]
+        pub fn current_epoch(&self) -> EpochIndex {
+            (self.env().block_timestamp() / EPOCH_LENGTH) as EpochIndex
+        }
+
+        /// Query the tips paid in a statistics epoch, zero if none were.
+        #[ink(message, selector = 0x52DA1069)]
+        pub fn epoch_stats(&self, epoch: EpochIndex) -> EpochStats {
+            let cache = self.stats_cache.get(()).unwrap_or_default();
+            if epoch == cache.epoch_totals.epoch {
+                return cache.epoch_totals;
+            }
+            self.epoch_stats.get(epoch).unwrap_or(EpochStats {
+                epoch,
+                ..Default::default()
+            })
+        }
+
+        /// Write the totals of the current era and epoch, which are kept
+        /// ahead for up to `STATS_FLUSH_INTERVAL` tips, to thier history.
+        ///
+        /// The queries already include them, this is only for reading the
+        /// storage directly, anyone could call it.
+        #[ink(message, selector = 0x841E6D43)]
+        pub fn flush_stats(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut cache = self.stats_cache.get(()).unwrap_or_default();
+            if cache.unflushed_tips > 0 {
+                self.write_stats(&mut cache);
+                self.stats_cache.insert((), &cache);
+            }
+            Ok(())
+        }
+
+        /// Query the tipping statistics of the TelegramId in a statistics
+        /// epoch.
+        ///
+        /// Anonymous tips, and tips to users who hide what they receive, are
+        /// left out.
+        #[ink(message, selector = 0x5E84CE8D)]
+        pub fn epoch_stats_of(
+            &self,
+            tg_id: TelegramId,
+            epoch: EpochIndex,
+        ) -> UserStats {
+            self.user_epoch_stats
+                .get((tg_id, epoch))
+                .unwrap_or_default()
+        }
+
+        /// Query a parameter change open for voting.
+        #[ink(message, selector = 0xF2124BA8)]
+        pub fn param_proposal(
+            &self,
+            proposal_id: ProposalId,
+        ) -> Option<ParamProposal> {
+            self.param_proposals.get(proposal_id)
+        }
+
+        /// Query how much balance has to vote on a parameter change, for or
+        /// against it, for it to pass.
+        #[ink(message, selector = 0x15AC04B4)]
+        pub fn param_quorum(&self) -> Balance { self.param_quorum }
+
+        /// Propose changing a parameter of the tipping policy.
+        ///
+        /// The bound users vote on it for `VOTING_PERIOD` blocks, weighted by
+        /// thier balance, then anyone could `enact` it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the value is not valid for
+        ///   the parameter.
+        #[ink(message, selector = 0x7438BD63)]
+        pub fn propose_param_change(
+            &mut self,
+            param: Param,
+            value: Balance,
+        ) -> Result<ProposalId, Error> {
+            ensure_not_reentered()?;
+            let proposer = self.env().caller();
+            if !self.users.contains(proposer) {
+                return Err(Error::NotFound {
+                    which: Entity::Account,
+                });
+            }
+            self.with_param(param, value)?;
+            let proposal_id = self.next_param_proposal_id;
+            self.next_param_proposal_id += 1;
+            let ends_at = self.env().block_number() + VOTING_PERIOD;
+            self.param_proposals.insert(
+                proposal_id,
+                &ParamProposal {
+                    param,
+                    value,
+                    ends_at,
+                    ayes: 0,
+                    nays: 0,
+                },
+            );
+            self.env().emit_event(ParamChangeProposed {
+                proposal_id,
+                proposer,
+                param,
+                value,
+                ends_at,
+            });
+            Ok(proposal_id)
+        }
+
+        /// Vote on a parameter change with the caller's whole balance.
+        ///
+        /// The balance is locked until the voting ends, so it could not be
+        /// moved to another account and vote twice.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or there is
+        ///   no such parameter change.
+        ///
+        /// * Returns `Error::VotingClosed` if the voting already ended.
+        ///
+        /// * Returns `Error::AlreadyVoted` if the caller already voted on it.
+        ///
+        /// * Returns `Error::NoVotingPower` if the caller has no balance.
+        #[ink(message, selector = 0x083BE260)]
+        pub fn vote(
+            &mut self,
+            proposal_id: ProposalId,
+            aye: bool,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let voter = self.env().caller();
+            let mut record = self.users.get(voter).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut proposal = self.param_proposals.get(proposal_id).ok_or(
+                Error::NotFound {
+                    which: Entity::ParamProposal,
+                },
+            )?;
+            if self.env().block_number() >= proposal.ends_at {
+                return Err(Error::VotingClosed);
+            }
+            if self.param_votes.contains((proposal_id, voter)) {
+                return Err(Error::AlreadyVoted);
+            }
+            let weight = record.balance;
+            if weight == 0 {
+                return Err(Error::NoVotingPower);
+            }
+            let tally = if aye {
+                &mut proposal.ayes
+            } else {
+                &mut proposal.nays
+            };
+            *tally = tally.checked_add(weight).ok_or(Error::Overflow)?;
+            record.locked = record.locked.max(weight);
+            record.locked_until = record.locked_until.max(proposal.ends_at);
+            record.last_active = self.env().block_number();
+            self.users.insert(voter, &record);
+            self.param_votes.insert((proposal_id, voter), &aye);
+            self.param_proposals.insert(proposal_id, &proposal);
+            self.env().emit_event(Voted {
+                proposal_id,
+                voter,
+                aye,
+                weight,
+            });
+            Ok(())
+        }
+
+        /// Conclude the voting on a parameter change, changing the parameter
+        /// if more balance voted for it than against it, and at least the
+        /// `param_quorum` voted in total.
+        ///
+        /// Anyone could call this once the voting ends.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such parameter change.
+        ///
+        /// * Returns `Error::VotingOpen` if the voting did not end yet.
+        #[ink(message, selector = 0x7AA10660)]
+        pub fn enact(&mut self, proposal_id: ProposalId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let proposal = self.param_proposals.get(proposal_id).ok_or(
+                Error::NotFound {
+                    which: Entity::ParamProposal,
+                },
+            )?;
+            if self.env().block_number() < proposal.ends_at {
+                return Err(Error::VotingOpen {
+                    ends_at: proposal.ends_at,
+                });
+            }
+            let passed = proposal.ayes > proposal.nays
+                && proposal.ayes.saturating_add(proposal.nays)
+                    >= self.param_quorum;
+            if passed {
+                self.params =
+                    self.with_param(proposal.param, proposal.value)?;
+            }
+            self.param_proposals.remove(proposal_id);
+            self.env().emit_event(ParamChangeConcluded {
+                proposal_id,
+                passed,
+            });
+            Ok(())
+        }
+
+        /// Query for the Telegram Id of some account.
+        /// if the account is not provided, will return the telegram id of the
+        /// caller.
+        #[ink(message, selector = 0x79D02302)]
+        pub fn telegram_id_of(
+            &self,
+            account: Option<AccountId>,
+        ) -> Option<TelegramId> {
+            let address = account.unwrap_or_else(|| self.env().caller());
+            let address = self.linked_accounts.get(address).unwrap_or(address);
+            self.users.get(address).map(|record| record.tg_id)
+        }
+
+        /// Query The AccountId of the TelegramId.
+        #[ink(message, selector = 0xAD9BDE4F)]
+        pub fn address_of(&self, tg_id: TelegramId) -> Option<AccountId> {
+            self.tg_address.get(tg_id)
+        }
+
+        /// Query the display name of the on-chain identity of the account
+        /// bound to the TelegramId, if the identity is judged as `Reasonable`
+        /// or `KnownGood`.
+        ///
+        /// The name is returned as is, usually UTF-8, and is always `None`
+        /// without the `chain-extension` feature.
+        #[ink(message, selector = 0x713CA232)]
+        pub fn identity_of(&self, tg_id: TelegramId) -> Option<Vec<u8>> {
+            let account = self.address_of(tg_id)?;
+            self.identity_display_name(account)
+        }
+
+        /// Query The Balance of the TelegramId.
+        ///
+        /// Returns zero if the user hides thier balance, unless the caller is
+        /// the user or one of the owners.
+        #[ink(message, selector = 0x0F755A56)]
+        pub fn balance_of(&self, tg_id: TelegramId) -> Balance {
+            self.address_of(tg_id)
+                .and_then(|address| {
+                    let record = self.users.get(address)?;
+                    Some(self.visible_balance(address, &record))
+                })
+                .unwrap_or(0)
+        }
+
+        /// Query The Balance of the account, or of the account it is linked
+        /// to.
+        ///
+        /// Returns zero if the account is not bound, or if the user hides
+        /// thier balance, unless the caller is the user or one of the owners.
+        #[ink(message, selector = 0x793EA731)]
+        pub fn balance_of_account(&self, account: AccountId) -> Balance {
+            let address = self.linked_accounts.get(account).unwrap_or(account);
+            self.users
+                .get(address)
+                .map(|record| self.visible_balance(address, &record))
+                .unwrap_or(0)
+        }
+
+        /// Query the address, balance, statistics, preferences and badges of
+        /// the TelegramId in one call.
+        ///
+        /// The balance is zero if it is hidden from the caller, as in
+        /// `balance_of`.
+        #[ink(message, selector = 0x86B91535)]
+        pub fn profile_of(&self, tg_id: TelegramId) -> Option<Profile> {
+            let address = self.address_of(tg_id)?;
+            let record = self.users.get(address)?;
+            Some(Profile {
+                address,
+                balance: self.visible_balance(address, &record),
+                stats: record.stats,
+                settings: record.settings,
+                badges: record.badges,
+            })
+        }
+
+        /// Set the privacy preferences of the caller, a shorthand for
+        /// `update_settings` with only the `privacy` set.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x08532ADF)]
+        pub fn set_privacy(
+            &mut self,
+            hide_received: bool,
+            hide_balance: bool,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_settings(UserSettingsUpdate {
+                privacy: Some(Privacy {
+                    hide_received,
+                    hide_balance,
+                }),
+                ..Default::default()
+            })
+        }
+
+        /// Set the balance the tips of the caller could never dip below, a
+        /// shorthand for `update_settings` with only the `reserve` set.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x7EEB8F97)]
+        pub fn set_reserve(&mut self, amount: Balance) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_settings(UserSettingsUpdate {
+                reserve: Some(amount),
+                ..Default::default()
+            })
+        }
+
+        /// Query the preferences of the TelegramId.
+        #[ink(message, selector = 0xAB92533F)]
+        pub fn settings_of(&self, tg_id: TelegramId) -> Option<UserSettings> {
+            self.record_of(tg_id).map(|record| record.settings)
+        }
+
+        /// Update the preferences of the caller.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x3B402E8E)]
+        pub fn update_settings(
+            &mut self,
+            update: UserSettingsUpdate,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let settings = &mut record.settings;
+            if let Some(auto_withdraw) = update.auto_withdraw {
+                settings.auto_withdraw = auto_withdraw;
+            }
+            if let Some(bot_tipping_enabled) = update.bot_tipping_enabled {
+                settings.bot_tipping_enabled = bot_tipping_enabled;
+            }
+            if let Some(privacy) = update.privacy {
+                settings.privacy = privacy;
+            }
+            if let Some(daily_limit) = update.daily_limit {
+                settings.daily_limit = daily_limit;
+            }
+            if let Some(default_tip) = update.default_tip {
+                settings.default_tip = default_tip;
+            }
+            if let Some(round_up) = update.round_up {
+                settings.round_up = round_up;
+            }
+            if let Some(reserve) = update.reserve {
+                settings.reserve = reserve;
+            }
+            if let Some(auto_top_up) = update.auto_top_up {
+                settings.auto_top_up = auto_top_up;
+            }
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            Ok(())
+        }
+
+        /// Query who the TelegramId accepts tips from, `None` if it accepts
+        /// them from everyone.
+        #[ink(message, selector = 0x4E539023)]
+        pub fn inbox_policy_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<InboxPolicy> {
+            self.address_of(tg_id)
+                .and_then(|account| self.inbox_policies.get(account))
+        }
+
+        /// Block the TelegramId from tipping the caller.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::TooManyMembers` if the caller already blocked
+        ///   `MAX_INBOX_SENDERS` senders.
+        #[ink(message, selector = 0x770BE8E6)]
+        pub fn block_sender(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_inbox_policy(|policy| {
+                add_sender(&mut policy.blocked, tg_id)
+            })
+        }
+
+        /// Unblock the TelegramId, so it could tip the caller again.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x9AD5421E)]
+        pub fn unblock_sender(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_inbox_policy(|policy| {
+                policy.blocked.retain(|blocked| *blocked != tg_id);
+                Ok(())
+            })
+        }
+
+        /// Allow the TelegramId to tip the caller in allowlist-only mode.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::TooManyMembers` if the caller already allowed
+        ///   `MAX_INBOX_SENDERS` senders.
+        #[ink(message, selector = 0x0D600814)]
+        pub fn allow_sender(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_inbox_policy(|policy| {
+                add_sender(&mut policy.allowed, tg_id)
+            })
+        }
+
+        /// Remove the TelegramId from the senders the caller allowed.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0xC4223A94)]
+        pub fn disallow_sender(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_inbox_policy(|policy| {
+                policy.allowed.retain(|allowed| *allowed != tg_id);
+                Ok(())
+            })
+        }
+
+        /// Accept tips only from the senders the caller allowed, or from
+        /// everyone who is not blocked again.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x969A2359)]
+        pub fn set_allowlist_only(
+            &mut self,
+            enabled: bool,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_inbox_policy(|policy| {
+                policy.allowlist_only = enabled;
+                Ok(())
+            })
+        }
+
+        /// Query the tipping statistics of the TelegramId.
+        #[ink(message, selector = 0x4563ACDC)]
+        pub fn stats_of(&self, tg_id: TelegramId) -> Option<UserStats> {
+            self.record_of(tg_id).map(|record| record.stats)
+        }
+
+        /// Query the balance of the TelegramId with the totals of the
+        /// deposits, withdrawals, tips, fees and everything else that moved
+        /// it, see `AuditReport::adds_up`.
+        ///
+        /// Returns `None` if the user hides thier balance from the caller, as
+        /// in `balance_of`.
+        #[ink(message, selector = 0xDAF6D2D2)]
+        pub fn audit_of(&self, tg_id: TelegramId) -> Option<AuditReport> {
+            let address = self.address_of(tg_id)?;
+            let record = self.users.get(address)?;
+            let caller = self.env().caller();
+            let hidden = record.settings.privacy.hide_balance
+                && caller != address
+                && !self.owners.contains(&caller);
+            if hidden {
+                return None;
+            }
+            Some(AuditReport {
+                totals: record.audit,
+                balance: record.balance,
+            })
+        }
+
+        /// Query the loyalty points of the TelegramId.
+        #[ink(message, selector = 0x070989A9)]
+        pub fn points_of(&self, tg_id: TelegramId) -> Balance {
+            self.record_of(tg_id)
+                .map(|record| record.points)
+                .unwrap_or(0)
+        }
+
+        /// Query how much has to be tipped to earn one loyalty point, zero if
+        /// earning points is disabled.
+        #[ink(message, selector = 0x899E7C1B)]
+        pub fn points_rate(&self) -> Balance { self.points_rate }
+
+        /// Query the charity the rounded up tips go to.
+        #[ink(message, selector = 0xE2B880E4)]
+        pub fn charity(&self) -> Option<AccountId> { self.charity }
+
+        /// Query the beneficiary of the TelegramId and thier inactivity
+        /// period.
+        #[ink(message, selector = 0x6149A0D7)]
+        pub fn beneficiary_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<(AccountId, BlockNumber)> {
+            self.record_of(tg_id)?.beneficiary
+        }
+
+        /// Let `account` claim the balance of the caller once they do not
+        /// bind, tip, vote, burn, redeem points or change thier settings for
+        /// `inactivity_period` blocks.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the `inactivity_period` is
+        ///   zero, or shorter than the withdrawal delay of the caller.
+        #[ink(message, selector = 0xC14DF6A8)]
+        pub fn set_beneficiary(
+            &mut self,
+            account: AccountId,
+            inactivity_period: BlockNumber,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            if inactivity_period == 0 {
+                return Err(Error::InvalidParamValue);
+            }
+            self.update_beneficiary(Some((account, inactivity_period)))
+        }
+
+        /// Remove the beneficiary of the caller.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x393FB7A5)]
+        pub fn remove_beneficiary(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.update_beneficiary(None)
+        }
+
+        /// Claim the balance of a dormant user, as thier beneficiary, with
+        /// the tipEDG thier account holds.
+        ///
+        /// The user has to be inactive for thier withdrawal delay too, if it
+        /// is longer than the inactivity period.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the beneficiary
+        ///   of the user.
+        ///
+        /// * Returns `Error::NotDormant` if the user was active within thier
+        ///   inactivity period.
+        ///
+        /// * Returns `Error::WithdrawalPending` if the user has a queued
+        ///   withdrawal.
+        ///
+        /// * Returns `Error::SlashPending` if the user has a pending slash, and
+        ///   `Error::BalanceLocked` if they voted on a parameter change that is
+        ///   still open.
+        ///
+        /// * Returns `Error::Overflow` if the balance and tipEDG of the user
+        ///   would overflow.
+        ///
+        /// * Returns `Error::ZeroAmount` if there is nothing to claim.
+        #[ink(message, selector = 0x342B03B3)]
+        pub fn claim_dormant(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            let period = match record.beneficiary {
+                Some((beneficiary, period)) if beneficiary == caller => period,
+                _ => return Err(Error::NotAllowed),
+            };
+            let period = period.max(blocks_in(record.withdrawal_delay));
+            let until = record.last_active.saturating_add(period);
+            if self.env().block_number() < until {
+                return Err(Error::NotDormant { until });
+            }
+            if record.pending_withdrawal.is_some() {
+                return Err(Error::WithdrawalPending);
+            }
+            if record.pending_slash > 0 {
+                return Err(Error::SlashPending);
+            }
+            if record.locked > 0
+                && self.env().block_number() < record.locked_until
+            {
+                return Err(Error::BalanceLocked {
+                    until: record.locked_until,
+                });
+            }
+            let receipts = self.receipt_balance_of(account);
+            let amount = record
+                .balance
+                .checked_add(receipts)
+                .ok_or(Error::Overflow)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            if receipts > 0 {
+                self.burn_receipts(account, receipts)?;
+            }
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(record.balance);
+            record.balance = 0;
+            self.users.insert(account, &record);
+            self.env().emit_event(DormantClaimed {
+                account,
+                beneficiary: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Query where the tips the TelegramId receives are sent.
+        #[ink(message, selector = 0x1AE5B453)]
+        pub fn payout_address_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<AccountId> {
+            let address = self.address_of(tg_id)?;
+            let record = self.users.get(address)?;
+            Some(record.payout.unwrap_or(address))
+        }
+
+        /// Send the tips the caller receives, and thier balance when they
+        /// unbind, to `address` instead of the bound account, setting it to
+        /// the bound account itself goes back to the default.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0xC8DB9B86)]
+        pub fn set_payout_address(
+            &mut self,
+            address: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            record.payout = (address != caller).then_some(address);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(PayoutAddressChanged {
+                account: caller,
+                payout: address,
+            });
+            Ok(())
+        }
+
+        /// Query where on another parachain the TelegramId receives the tips
+        /// sent with `tip_remote`.
+        #[ink(message, selector = 0xF929A8F5)]
+        pub fn remote_address_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<RemoteAddress> {
+            let address = self.address_of(tg_id)?;
+            self.remote_addresses.get(address)
+        }
+
+        /// Receive the tips sent to the caller with `tip_remote` as the
+        /// `account` on the parachain `para_id`, the runtime sends them there
+        /// with an XCM reserve transfer.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::ExtensionUnavailable` if the contract is built
+        ///   without the `chain-extension` feature, as it could not send tips
+        ///   to other parachains.
+        #[ink(message, selector = 0xE9024CC0)]
+        pub fn set_remote_address(
+            &mut self,
+            para_id: ParaId,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            if !cfg!(feature = "chain-extension") {
+                return Err(Error::ExtensionUnavailable);
+            }
+            let remote = RemoteAddress { para_id, account };
+            self.change_remote_address(Some(remote))
+        }
+
+        /// Remove the remote address of the caller, so the tips sent with
+        /// `tip_remote` are paid locally again.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x7D187D9E)]
+        pub fn clear_remote_address(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.change_remote_address(None)
+        }
+
+        /// Query the total the TelegramId gave to the charity by rounding up
+        /// tips.
+        #[ink(message, selector = 0xCDD6C9B1)]
+        pub fn charity_contributed(&self, tg_id: TelegramId) -> Balance {
+            self.record_of(tg_id)
+                .map(|record| record.charity_contributed)
+                .unwrap_or(0)
+        }
+
+        /// Query for how many consecutive days the TelegramId tipped, zero if
+        /// they did not tip today or yesterday.
+        #[ink(message, selector = 0x7FB9616A)]
+        pub fn streak_of(&self, tg_id: TelegramId) -> u32 {
+            let today = self.env().block_timestamp() / DAY;
+            self.record_of(tg_id)
+                .filter(|record| record.last_tip_day + 1 >= today)
+                .map(|record| record.streak)
+                .unwrap_or(0)
+        }
+
+        /// Query the badges of the TelegramId, as a bitfield of the `BADGE_*`
+        /// flags.
+        #[ink(message, selector = 0x9C6E6EEE)]
+        pub fn badges_of(&self, tg_id: TelegramId) -> u32 {
+            self.record_of(tg_id)
+                .map(|record| record.badges)
+                .unwrap_or(0)
+        }
+
+        /// Query the soulbound achievement tokens of the TelegramId, oldest
+        /// first.
+        #[ink(message, selector = 0xCF9A04B9)]
+        pub fn tokens_of(&self, tg_id: TelegramId) -> Vec<AchievementId> {
+            self.record_of(tg_id)
+                .map(|record| record.achievements)
+                .unwrap_or_default()
+        }
+
+        /// Query a soulbound achievement token.
+        #[ink(message, selector = 0x02253BEB)]
+        pub fn achievement(&self, id: AchievementId) -> Option<Achievement> {
+            self.achievements.get(id)
+        }
+
+        /// Query how many soulbound achievement tokens were minted.
+        #[ink(message, selector = 0xEE21DD89)]
+        pub fn achievements_minted(&self) -> AchievementId {
+            self.next_achievement_id
+        }
+
+        /// Query how much one loyalty point is worth when redeemed, zero if
+        /// redeeming points is disabled.
+        #[ink(message, selector = 0xC432FFD8)]
+        pub fn redemption_rate(&self) -> Balance { self.redemption_rate }
+
+        /// Query the funds left to redeem loyalty points.
+        #[ink(message, selector = 0x974A92E5)]
+        pub fn rewards_pool(&self) -> Balance { self.rewards_pool }
+
+        /// Add the transferred value to the rewards pool.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::Overflow` if the rewards pool would overflow.
+        #[ink(message, payable, selector = 0xC7685520)]
+        pub fn fund_rewards(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            self.rewards_pool = self
+                .rewards_pool
+                .checked_add(self.env().transferred_value())
+                .ok_or(Error::Overflow)?;
+            Ok(())
+        }
+
+        /// Query the address burned funds are sent to.
+        #[ink(message, selector = 0x2A393A21)]
+        pub fn burn_address(&self) -> Option<AccountId> { self.burn_address }
+
+        /// Query the total burned so far.
+        #[ink(message, selector = 0xED9F90C1)]
+        pub fn total_burned(&self) -> Balance { self.total_burned }
+
+        /// Burn part of the balance of the caller, by sending it to the burn
+        /// address.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::BurnDisabled` if there is no burn address.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough spendable balance.
+        #[ink(message, selector = 0xB1EFC17B)]
+        pub fn burn(&mut self, amount: Balance) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let available = self.spendable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            self.send_to_burn_address(amount)?;
+            record.balance -= amount;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(amount);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(Burned {
+                from: Some(caller),
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Query how many blocks a user has to be inactive for before thier
+        /// dust could be swept.
+        #[ink(message, selector = 0x8F954CBA)]
+        pub fn dust_dormancy(&self) -> BlockNumber { self.dust_dormancy }
+
+        /// Announce sweeping the dust of a dormant user, it could be swept
+        /// after `DUST_GRACE_PERIOD` blocks unless the user does anything
+        /// before.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::NotDormant` if the user was active within the dust
+        ///   dormancy.
+        ///
+        /// * Returns `Error::NotDust` if the balance of the user is not below
+        ///   the existential deposit.
+        #[ink(message, selector = 0x355C2253)]
+        pub fn announce_dust_sweep(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let (account, _) = self.dust_of(tg_id)?;
+            let now = self.env().block_number();
+            self.dust_sweeps.insert(account, &now);
+            self.env().emit_event(DustSweepAnnounced {
+                account,
+                at: now + DUST_GRACE_PERIOD,
+            });
+            self.log_admin_action(
+                Some(account),
+                PrivilegedAction::AnnounceDustSweep,
+            );
+            Ok(())
+        }
+
+        /// Burn loyalty points of the caller, adding thier worth from the
+        /// rewards pool to thier balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `points` are zero.
+        ///
+        /// * Returns `Error::RedemptionDisabled` if there is no redemption
+        ///   rate.
+        ///
+        /// * Returns `Error::InsufficientPoints` if the caller does not have
+        ///   enough points.
+        ///
+        /// * Returns `Error::InsufficientRewards` if the rewards pool can not
+        ///   cover the points.
+        #[ink(message, selector = 0x94AEBEC5)]
+        pub fn redeem_points(&mut self, points: Balance) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            if points == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if self.redemption_rate == 0 {
+                return Err(Error::RedemptionDisabled);
+            }
+            if points > record.points {
+                return Err(Error::InsufficientPoints {
+                    available: record.points,
+                });
+            }
+            let amount = points
+                .checked_mul(self.redemption_rate)
+                .filter(|amount| *amount <= self.rewards_pool)
+                .ok_or(Error::InsufficientRewards {
+                    available: self.rewards_pool,
+                })?;
+            record.balance =
+                record.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            record.points -= points;
+            record.audit.other_in =
+                record.audit.other_in.saturating_add(amount);
+            self.rewards_pool -= amount;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(PointsRedeemed {
+                account: caller,
+                points,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Bind the caller address to the provided TelegramId.
+        ///
+        /// A new account has to transfer at least the `bind_deposit`, which is
+        /// held until it unbinds, and the rest is added to its balance.
+        ///
+        /// If the TelegramId is bound to another account whose binding
+        /// expired, see `expires_at`, that binding is removed first and its
+        /// balance is left to be withdrawn by `withdraw_expired`.
+        ///
+        /// Errors:
+        /// * Returns `Error::AccountAlreadyBound` if the AccountId is linked to
+        ///   another account, or already bound to the TelegramId.
+        ///
+        /// * Returns `Error::TelegramIdTaken` if the TelegramId is bound to
+        ///   another account whose binding did not expire.
+        ///
+        /// * Returns `Error::SlashPending`, `Error::TipsPending`,
+        ///   `Error::AccountSelfLocked` or `Error::BalanceLocked` if the
+        ///   expired binding of the TelegramId could not be removed yet.
+        ///
+        /// * Returns `Error::InsufficientFunds` if a new account transferred
+        ///   less than the `bind_deposit`.
+        ///
+        /// * Returns `Error::RateLimited` if the caller, or all accounts, bound
+        ///   too many times in the current window, see `bind_rate_limit`.
+        ///
+        /// * Returns `Error::Overflow` if the deposit would overflow the
+        ///   caller's balance or bind deposit, and `Error::AboveMaxBalance` if
+        ///   it would take it above the `max_balance` of thier verification
+        ///   level.
+        ///
+        /// * Returns `Error::DepositCapExceeded` if the deposit would take the
+        ///   caller, or the contract, above a deposit cap, see `deposit_caps`.
+        #[ink(message, payable, selector = 0xF539C94F)]
+        pub fn bind(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.bind_to(self.env().caller(), tg_id)
+        }
+
+        /// Similar to bind, but with a proof from Telegram that the caller
+        /// controls the `tg_id`, so the bot does not have to be trusted.
+        ///
+        /// The `payload` is the data-check-string of the Telegram login
+        /// widget, and `mac` its hash, verified by the runtime through the
+        /// `TipbotExtension` chain extension. The payload has to have an
+        /// `account` field with the hex of the caller's AccountId, so it could
+        /// not be used by another account.
+        ///
+        /// Errors:
+        /// Same as `bind`, and
+        /// * Returns `Error::InvalidTelegramAuth` if the payload is not signed
+        ///   by Telegram, is for another TelegramId or account, or is older
+        ///   than `TELEGRAM_AUTH_MAX_AGE`.
+        ///
+        /// * Returns `Error::ExtensionUnavailable` if the contract is built
+        ///   without the `chain-extension` feature.
+        #[ink(message, payable, selector = 0x263A22EA)]
+        pub fn bind_verified(
+            &mut self,
+            tg_id: TelegramId,
+            payload: Vec<u8>,
+            mac: [u8; 32],
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            self.ensure_telegram_auth(caller, tg_id, payload, mac)?;
+            self.bind_to(caller, tg_id)
+        }
+
+        /// Similar to `bind_verified`, but called by a relayer on behalf of
+        /// the `account`, which pays the `bind_deposit` for it.
+        ///
+        /// The `signature` is a recoverable ECDSA signature by the key of the
+        /// `account` over the blake2x256 hash of the encoded
+        /// `(contract, b"bind", tg_id)`, and the `account` field of the
+        /// `payload` is the `account`.
+        ///
+        /// Errors:
+        /// Same as `bind_verified`, and
+        /// * Returns `Error::NotAllowed` if the caller is neither the operator
+        ///   nor a relayer allowed to bind users.
+        ///
+        /// * Returns `Error::InvalidSignature` if the signature is not made by
+        ///   the `account`.
+        #[ink(message, payable, selector = 0x87850DE5)]
+        pub fn bind_verified_for(
+            &mut self,
+            account: AccountId,
+            tg_id: TelegramId,
+            payload: Vec<u8>,
+            mac: [u8; 32],
+            signature: [u8; 65],
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.relayer_for(|permissions| permissions.can_bind_verified, 0)?;
+            self.ensure_signed_by(
+                account,
+                &(self.env().account_id(), b"bind", tg_id),
+                &signature,
+            )?;
+            self.ensure_telegram_auth(account, tg_id, payload, mac)?;
+            self.bind_to(account, tg_id)
+        }
+
+        fn bind_to(
+            &mut self,
+            caller: AccountId,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            // if we already know this return an error, to prevent from
+            // account spoofing.
+            if self.linked_accounts.contains(caller) {
+                return Err(Error::AccountAlreadyBound);
+            }
+            let expired = match self.tg_address.get(tg_id) {
+                Some(holder) => {
+                    let record = self
+                        .users
+                        .get(holder)
+                        .expect("every bound telegram id has a record");
+                    if holder == caller {
+                        return Err(Error::AccountAlreadyBound);
+                    }
+                    if !self.is_expired(&record) {
+                        return Err(Error::TelegramIdTaken);
+                    }
+                    self.ensure_removable(holder, &record)?;
+                    Some((holder, record))
+                },
+                None => None,
+            };
+            let bound = self.users.get(caller);
+            let transferred = self.env().transferred_value();
+            let deposit = if bound.is_none() {
+                self.bind_deposit
+            } else {
+                0
+            };
+            if transferred < deposit {
+                return Err(Error::InsufficientFunds {
+                    required: deposit,
+                    available: transferred,
+                });
+            }
+            let bound = bound.unwrap_or_default();
+            // the tipEDG the caller holds counts, as in `mint_receipts`.
+            let held = bound
+                .balance
+                .saturating_add(self.receipt_balance_of(caller));
+            let after = held
+                .checked_add(transferred - deposit)
+                .ok_or(Error::Overflow)?;
+            self.ensure_below_max_balance(&bound, after)?;
+            self.ensure_within_deposit_caps(held, after)?;
+            let counts = self
+                .bind_rate_limit
+                .map(|limit| self.count_binding(limit, caller))
+                .transpose()?;
+            if let Some((window, total, count)) = counts {
+                self.bind_window = (window, total + 1);
+                self.bind_counts.insert(caller, &(window, count + 1));
+            }
+            if let Some((holder, record)) = expired {
+                self.expire_binding(holder, record);
+            }
+
+            let record = self.users.get(caller);
+            let old_tg_id = record.as_ref().map(|record| record.tg_id);
+            let mut record = record.unwrap_or_default();
+            // check if the user added some balance to thier account during the
+            // call.
+            record.balance = record
+                .balance
+                .checked_add(transferred - deposit)
+                .ok_or(Error::Overflow)?;
+            record.audit.deposited =
+                record.audit.deposited.saturating_add(transferred - deposit);
+            record.bind_deposit = record
+                .bind_deposit
+                .checked_add(deposit)
+                .ok_or(Error::Overflow)?;
+            match old_tg_id {
+                // free the old tg_id.
+                //
+                // this ensures that we always have one address for one telegram
+                // id.
+                Some(old_tg_id) => self.tg_address.remove(old_tg_id),
+                None => {
+                    record.index = self.accounts_count;
+                    record.bound_at = self.env().block_number();
+                    self.accounts.insert(record.index, &caller);
+                    self.accounts_count += 1;
+                },
+            }
+            self.award_badges(caller, &mut record, true);
+            // add the new binding.
+            record.tg_id = tg_id;
+            record.last_active = self.env().block_number();
+            self.tg_address.insert(tg_id, &caller);
+            self.users.insert(caller, &record);
+            self.env().emit_event(Bound {
+                schema_version: EVENT_SCHEMA_VERSION,
+                account: caller,
+                tg_id,
+                previous: old_tg_id,
+            });
+            Ok(())
+        }
+
+        /// Renew the binding of the caller, so it expires `binding_ttl`
+        /// blocks from now.
+        ///
+        /// Any other activity renews the binding as well.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0xB5604092)]
+        pub fn renew(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(BindingRenewed {
+                account: caller,
+                expires_at: record.last_active.saturating_add(self.binding_ttl),
+            });
+            Ok(())
+        }
+
+        /// Query the block the binding of the TelegramId expires at, after
+        /// which another account could bind it, or `None` if it is not bound
+        /// or bindings do not expire.
+        #[ink(message, selector = 0xA239F1FC)]
+        pub fn expires_at(&self, tg_id: TelegramId) -> Option<BlockNumber> {
+            if self.binding_ttl == 0 {
+                return None;
+            }
+            self.record_of(tg_id).map(|record| {
+                record.last_active.saturating_add(self.binding_ttl)
+            })
+        }
+
+        /// Query how many bindings could be made per window, `None` if they
+        /// are not limited.
+        #[ink(message, selector = 0xEE26E60F)]
+        pub fn bind_rate_limit(&self) -> Option<BindRateLimit> {
+            self.bind_rate_limit
+        }
+
+        /// Query the maximum amount of gas each hook call is allowed to use.
+        #[ink(message, selector = 0x380644F5)]
+        pub fn hook_gas_limit(&self) -> u64 { self.hook_gas_limit }
+
+        /// Query the maximum amount of gas each call to a token, or the DEX
+        /// router, is allowed to use.
+        #[ink(message, selector = 0x14513F8F)]
+        pub fn token_call_gas_limit(&self) -> u64 { self.token_call_gas_limit }
+
+        /// Query the stricter tip limits of newly bound accounts, `None` if
+        /// they are disabled.
+        #[ink(message, selector = 0x4AA1DF57)]
+        pub fn newcomer_limits(&self) -> Option<NewcomerLimits> {
+            self.newcomer_limits
+        }
+
+        /// Query the amount below which tips are kept in the balance of the
+        /// recipient instead of being transferred, zero if they are always
+        /// transferred.
+        #[ink(message, selector = 0x98F8518F)]
+        pub fn existential_deposit(&self) -> Balance {
+            self.existential_deposit
+        }
+
+        /// Query the refundable deposit required to bind a new account.
+        #[ink(message, selector = 0xD3E31FA7)]
+        pub fn bind_deposit(&self) -> Balance { self.bind_deposit }
+
+        /// Query for how many blocks of no activity a binding lasts, zero if
+        /// bindings do not expire.
+        #[ink(message, selector = 0x2ABD6FE3)]
+        pub fn binding_ttl(&self) -> BlockNumber { self.binding_ttl }
+
+        /// Query the balance left to the account by its expired binding.
+        #[ink(message, selector = 0x5C10854D)]
+        pub fn expired_balance_of(&self, account: AccountId) -> Balance {
+            self.expired_balances.get(account).unwrap_or(0)
+        }
+
+        /// Withdraw the balance left to the caller by thier expired binding.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller has no balance left by an
+        ///   expired binding.
+        ///
+        /// * Returns `Error::BelowSubsistenceThreshold` if the transfer failed.
+        #[ink(message, selector = 0xC8CD5C77)]
+        pub fn withdraw_expired(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let amount =
+                self.expired_balances.get(caller).ok_or(Error::NotFound {
+                    which: Entity::ExpiredBalance,
+                })?;
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            self.take_expired(caller);
+            self.env().emit_event(Withdrawn {
+                account: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Unbind the caller address from thier telegram account.
+        /// and _optionally_ transfer any balance if they have any.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller's `AccountId` is not
+        ///   bounded before.
+        ///
+        /// * Returns `Error::BalanceLocked` if the caller voted on a parameter
+        ///   change that is still open.
+        ///
+        /// * Returns `Error::SlashPending` if the caller has a pending slash.
+        ///
+        /// * Returns `Error::WithdrawalPending` if the caller has a queued
+        ///   withdrawal, and `Error::WithdrawalDelayEnabled` if they have a
+        ///   withdrawal delay and some balance left.
+        ///
+        /// Any held tips to the caller that are out of thier reversal window
+        /// are finalized first, and the waiting jobs of the caller are
+        /// cancelled, thier incentives refunded with the balance.
+        #[ink(message, selector = 0xFDCB6C47)]
+        pub fn unbind(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            if let Some(mut record) = self.users.get(caller) {
+                if record.pending_slash > 0 {
+                    return Err(Error::SlashPending);
+                }
+                if record.pending_withdrawal.is_some() {
+                    return Err(Error::WithdrawalPending);
+                }
+                ensure_not_self_locked(&record)?;
+                self.apply_delay_change(&mut record);
+                if record.withdrawal_delay > 0 && record.balance > 0 {
+                    return Err(Error::WithdrawalDelayEnabled);
+                }
+                if record.locked > 0
+                    && self.env().block_number() < record.locked_until
+                {
+                    return Err(Error::BalanceLocked {
+                        until: record.locked_until,
+                    });
+                }
+            }
+            self.finalize_released_tips(caller)?;
+            self.unbind_account(caller)
+        }
+
+        /// Query the withdrawal delay of the TelegramId, in milliseconds.
+        #[ink(message, selector = 0xF86CEC16)]
+        pub fn withdrawal_delay_of(&self, tg_id: TelegramId) -> Timestamp {
+            self.record_of(tg_id)
+                .map(|mut record| {
+                    self.apply_delay_change(&mut record);
+                    record.withdrawal_delay
+                })
+                .unwrap_or(0)
+        }
+
+        /// Query the queued withdrawal of the TelegramId, as the amount and
+        /// the time it could be executed at.
+        #[ink(message, selector = 0xF53D4B05)]
+        pub fn pending_withdrawal_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<(Balance, Timestamp)> {
+            self.record_of(tg_id)?.pending_withdrawal
+        }
+
+        /// Queue the withdrawals of the caller for `delay_ms` milliseconds, so
+        /// a leaked key could not drain thier balance right away, zero
+        /// disables the delay.
+        ///
+        /// A longer delay takes effect right away, but a shorter one only
+        /// after the current delay.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0x350674EF)]
+        pub fn enable_withdrawal_delay(
+            &mut self,
+            delay_ms: Timestamp,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            self.apply_delay_change(&mut record);
+            let now = self.env().block_timestamp();
+            let effective_at = if delay_ms >= record.withdrawal_delay {
+                record.withdrawal_delay = delay_ms;
+                record.delay_change = None;
+                now
+            } else {
+                let effective_at = now.saturating_add(record.withdrawal_delay);
+                record.delay_change = Some((delay_ms, effective_at));
+                effective_at
+            };
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(WithdrawalDelayChanged {
+                account: caller,
+                delay: delay_ms,
+                effective_at,
+            });
+            Ok(())
+        }
+
+        /// Withdraw `amount` of the caller's balance to thier payout address,
+        /// or queue it if they have a withdrawal delay.
+        ///
+        /// The tipEDG of the caller is burned to cover what the balance is
+        /// short of.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::WithdrawalPending` if the caller already has a
+        ///   queued withdrawal.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough spendable balance, and tipEDG.
+        #[ink(message, selector = 0x7DF79E6B)]
+        pub fn request_withdrawal(
+            &mut self,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            ensure_not_self_locked(&record)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if record.pending_withdrawal.is_some() {
+                return Err(Error::WithdrawalPending);
+            }
+            let shortfall = amount.saturating_sub(self.spendable(&record));
+            let covered =
+                self.cover_from_receipts(caller, &mut record, shortfall);
+            let available = self.spendable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            self.start_withdrawal(caller, record, amount)?;
+            if covered > 0 {
+                self.burn_receipts(caller, covered)
+                    .expect("the tipEDG covered is held");
+            }
+            Ok(())
+        }
+
+        /// Withdraw `amount` of the balance of the TelegramId on behalf of
+        /// the user, so users without gas could withdraw too. Only the
+        /// operator, or a relayer allowed to settle withdrawals, could call
+        /// this.
+        ///
+        /// The `signature` is a recoverable ECDSA signature by the key of the
+        /// user, or of an address linked to them, over the blake2x256 hash of
+        /// the encoded `(contract, b"withdraw", amount, nonce)`, where the
+        /// nonce is thier `relay_nonce_of`, so it could not be replayed.
+        ///
+        /// The relay fee is taken out of the `amount` and set aside for the
+        /// caller, the rest is paid to the user's payout address, or queued
+        /// if they have a withdrawal delay, see `execute_withdrawal_for`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither the operator
+        ///   of the contract nor such a relayer.
+        ///
+        /// * Returns `Error::RelayerCapExceeded` if the withdrawal would take
+        ///   the relayer above its `volume_cap`.
+        ///
+        /// * Returns `Error::NotFound` if the TelegramId is not bound.
+        ///
+        /// * Returns `Error::InvalidSignature` if the signature is not made by
+        ///   the user, nor a linked address, over the current nonce.
+        ///
+        /// * Returns `Error::ZeroAmount` if nothing is left after the relay
+        ///   fee.
+        ///
+        /// * Returns `Error::WithdrawalPending` if the user already has a
+        ///   queued withdrawal.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the user does not have
+        ///   enough spendable balance.
+        #[ink(message, selector = 0x1224BC18)]
+        pub fn request_relayed_withdrawal(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+            signature: [u8; 65],
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let relayer = self.relayer_for(
+                |permissions| permissions.can_settle_batches,
+                amount,
+            )?;
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            let signer = self.recover_signer(
+                &(
+                    self.env().account_id(),
+                    b"withdraw",
+                    amount,
+                    record.relay_nonce,
+                ),
+                &signature,
+            )?;
+            if signer != account && !record.linked.contains(&signer) {
+                return Err(Error::InvalidSignature);
+            }
+            ensure_not_self_locked(&record)?;
+            let fee = self.relay_fee;
+            if amount <= fee {
+                return Err(Error::ZeroAmount);
+            }
+            if record.pending_withdrawal.is_some() {
+                return Err(Error::WithdrawalPending);
+            }
+            let shortfall = amount.saturating_sub(self.spendable(&record));
+            let covered =
+                self.cover_from_receipts(account, &mut record, shortfall);
+            let available = self.spendable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            record.relay_nonce += 1;
+            record.balance -= fee;
+            record.audit.fees_paid = record.audit.fees_paid.saturating_add(fee);
+            self.start_withdrawal(account, record, amount - fee)?;
+            if covered > 0 {
+                self.burn_receipts(account, covered)
+                    .expect("the tipEDG covered is held");
+            }
+            self.reimburse_relayer(relayer, fee);
+            Ok(())
+        }
+
+        /// Query the nonce the next relayed withdrawal of the TelegramId has
+        /// to be signed with, see `request_relayed_withdrawal`.
+        #[ink(message, selector = 0x1D498847)]
+        pub fn relay_nonce_of(&self, tg_id: TelegramId) -> u32 {
+            self.record_of(tg_id)
+                .map(|record| record.relay_nonce)
+                .unwrap_or(0)
+        }
+
+        /// Pay the queued withdrawal of the caller after its delay.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or has no
+        ///   queued withdrawal.
+        ///
+        /// * Returns `Error::WithdrawalTimelocked` if it is too early.
+        #[ink(message, selector = 0xF87F8C3A)]
+        pub fn execute_withdrawal(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.acting_account();
+            self.execute_pending_withdrawal(caller)
+        }
+
+        /// Pay the queued withdrawal of the TelegramId after its delay, on
+        /// behalf of users without gas. Only the operator, or a relayer
+        /// allowed to settle withdrawals, could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither the operator
+        ///   of the contract nor such a relayer.
+        ///
+        /// * Returns `Error::NotFound` if the TelegramId is not bound, or has
+        ///   no queued withdrawal.
+        ///
+        /// * Returns `Error::WithdrawalTimelocked` if it is too early.
+        #[ink(message, selector = 0x06AD7EB9)]
+        pub fn execute_withdrawal_for(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.relayer_for(|permissions| permissions.can_settle_batches, 0)?;
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            self.execute_pending_withdrawal(account)
+        }
+
+        /// Cancel the queued withdrawal of the caller, giving the amount back
+        /// to thier balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or has no
+        ///   queued withdrawal.
+        ///
+        /// * Returns `Error::Overflow` if the amount would overflow the
+        ///   caller's balance.
+        #[ink(message, selector = 0xA693F5EB)]
+        pub fn cancel_withdrawal(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let (mut record, amount, _) = self.pending_withdrawal(caller)?;
+            record.balance =
+                record.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            record.audit.withdrawn =
+                record.audit.withdrawn.saturating_sub(amount);
+            record.pending_withdrawal = None;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(WithdrawalCancelled {
+                account: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Query whether the TelegramId locked thier account.
+        #[ink(message, selector = 0xF9E5D2DA)]
+        pub fn is_self_locked(&self, tg_id: TelegramId) -> bool {
+            self.record_of(tg_id)
+                .is_some_and(|record| record.self_locked)
+        }
+
+        /// Burn `amount` of the tipEDG of the caller back into thier
+        /// balance.
+        ///
+        /// The tipEDG of a user already counts towards thier `max_balance`
+        /// and the deposit caps, so unwrapping it is not limited by them.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough tipEDG.
+        #[ink(message, selector = 0x67A449D0)]
+        pub fn unwrap(&mut self, amount: Balance) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            record.balance =
+                record.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            self.burn_receipts(caller, amount)?;
+            record.audit.other_in =
+                record.audit.other_in.saturating_add(amount);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            Ok(())
+        }
+
+        /// Burn `amount` of the tipEDG of the caller, paying them the EDG
+        /// backing it, bound or not.
+        ///
+        /// A bound caller is paid as by `request_withdrawal`, to thier payout
+        /// address, or queued if they have a withdrawal delay.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough tipEDG.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller is bound and
+        ///   locked themselves, and `Error::WithdrawalPending` if they already
+        ///   have a queued withdrawal.
+        #[ink(message, selector = 0xEC3E9290)]
+        pub fn redeem(&mut self, amount: Balance) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let balance = self.receipt_balances.get(caller).unwrap_or(0);
+            if amount == 0 || balance < amount {
+                return self.burn_receipts(caller, amount);
+            }
+            let mut record = match self.users.get(caller) {
+                Some(record) => record,
+                None => {
+                    self.env()
+                        .transfer(caller, amount)
+                        .map_err(|_| Error::BelowSubsistenceThreshold)?;
+                    return self.burn_receipts(caller, amount);
+                },
+            };
+            ensure_not_self_locked(&record)?;
+            if record.pending_withdrawal.is_some() {
+                return Err(Error::WithdrawalPending);
+            }
+            // the tipEDG is unwrapped into the balance, and withdrawn from it.
+            record.balance =
+                record.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            record.audit.other_in =
+                record.audit.other_in.saturating_add(amount);
+            self.start_withdrawal(caller, record, amount)?;
+            self.burn_receipts(caller, amount)
+        }
+
+        // The tipEDG messages answer to the PSP22, and PSP22Metadata,
+        // selectors, so wallets and dapps see a PSP22 token.
+
+        /// Query the total supply of tipEDG.
+        #[ink(message, selector = 0x162DF8C2)]
+        pub fn receipt_total_supply(&self) -> Balance { self.receipt_supply }
+
+        /// Query the tipEDG balance of the `owner`.
+        #[ink(message, selector = 0x6568382F)]
+        pub fn receipt_balance_of(&self, owner: AccountId) -> Balance {
+            self.receipt_balances.get(owner).unwrap_or(0)
+        }
+
+        /// Query how much tipEDG the `spender` is allowed to spend of the
+        /// `owner`.
+        #[ink(message, selector = 0x4D47D921)]
+        pub fn receipt_allowance(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+        ) -> Balance {
+            self.receipt_allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        /// Transfer `value` tipEDG from the caller to `to`.
+        ///
+        /// A bound `to` could not be taken above the `max_balance` of its
+        /// verification level, or the per-account deposit cap, by it, and a
+        /// bound caller who locked themselves could not transfer.
+        #[ink(message, selector = 0xDB20F9F5)]
+        pub fn receipt_transfer(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            ensure_not_reentered()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentered")))?;
+            self.transfer_receipts(self.env().caller(), to, value)
+        }
+
+        /// Transfer `value` tipEDG from `from` to `to`, spending the
+        /// allowance of the caller, as `receipt_transfer` does.
+        #[ink(message, selector = 0x54B3C76E)]
+        pub fn receipt_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            ensure_not_reentered()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentered")))?;
+            let caller = self.env().caller();
+            let allowance = self.receipt_allowance(from, caller);
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self.transfer_receipts(from, to, value)?;
+            self.set_allowance(from, caller, allowance - value);
+            Ok(())
+        }
+
+        /// Allow the `spender` to spend `value` tipEDG of the caller.
+        #[ink(message, selector = 0xB20F1BBD)]
+        pub fn receipt_approve(
+            &mut self,
+            spender: AccountId,
+            value: Balance,
+        ) -> Result<(), PSP22Error> {
+            ensure_not_reentered()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentered")))?;
+            self.set_allowance(self.env().caller(), spender, value);
+            Ok(())
+        }
+
+        /// Allow the `spender` to spend `delta_value` more tipEDG of the
+        /// caller.
+        #[ink(message, selector = 0x96D6B57A)]
+        pub fn receipt_increase_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: Balance,
+        ) -> Result<(), PSP22Error> {
+            ensure_not_reentered()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentered")))?;
+            let owner = self.env().caller();
+            let allowance = self.receipt_allowance(owner, spender);
+            self.set_allowance(
+                owner,
+                spender,
+                allowance.saturating_add(delta_value),
+            );
+            Ok(())
+        }
+
+        /// Allow the `spender` to spend `delta_value` less tipEDG of the
+        /// caller.
+        #[ink(message, selector = 0xFECB57D5)]
+        pub fn receipt_decrease_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: Balance,
+        ) -> Result<(), PSP22Error> {
+            ensure_not_reentered()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentered")))?;
+            let owner = self.env().caller();
+            let allowance = self.receipt_allowance(owner, spender);
+            if allowance < delta_value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self.set_allowance(owner, spender, allowance - delta_value);
+            Ok(())
+        }
+
+        /// Query the name of tipEDG.
+        #[ink(message, selector = 0x3D261BD4)]
+        pub fn receipt_name(&self) -> Option<String> {
+            Some(String::from("Tipbot EDG"))
+        }
+
+        /// Query the symbol of tipEDG.
+        #[ink(message, selector = 0x34205BE5)]
+        pub fn receipt_symbol(&self) -> Option<String> {
+            Some(String::from("tipEDG"))
+        }
+
+        /// Query the decimals of tipEDG.
+        #[ink(message, selector = 0x7271B782)]
+        pub fn receipt_decimals(&self) -> u8 { EDG_DECIMALS }
+
+        /// Lock the account of the caller right away, blocking tipping,
+        /// burning, withdrawing, unbinding and changing where thier funds
+        /// go, for when they suspect thier key leaked.
+        ///
+        /// Locking an already locked account cancels any request to unlock
+        /// it.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        #[ink(message, selector = 0xA7FD96C6)]
+        pub fn lock_my_account(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            record.self_locked = true;
+            record.unlock_requested_at = None;
+            self.users.insert(caller, &record);
+            self.env().emit_event(AccountLocked { account: caller });
+            Ok(())
+        }
+
+        /// Ask to unlock the account of the caller, it could be unlocked
+        /// after `SELF_LOCK_DELAY` blocks, or right away by an owner.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::NotAllowed` if the account is not locked.
+        #[ink(message, selector = 0x2794EA0E)]
+        pub fn request_unlock(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            if !record.self_locked {
+                return Err(Error::NotAllowed);
+            }
+            let now = self.env().block_number();
+            record.unlock_requested_at = Some(now);
+            self.users.insert(caller, &record);
+            self.env().emit_event(UnlockRequested {
+                account: caller,
+                at: now + SELF_LOCK_DELAY,
+            });
+            Ok(())
+        }
+
+        /// Unlock the account of the caller, `SELF_LOCK_DELAY` blocks after
+        /// they asked to.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::UnlockNotRequested` if the caller did not ask to
+        ///   unlock thier account.
+        ///
+        /// * Returns `Error::UnlockTimelocked` if it is too early.
+        #[ink(message, selector = 0x48DE2BA8)]
+        pub fn unlock_my_account(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let requested_at = record
+                .unlock_requested_at
+                .ok_or(Error::UnlockNotRequested)?;
+            let at = requested_at + SELF_LOCK_DELAY;
+            if self.env().block_number() < at {
+                return Err(Error::UnlockTimelocked { at });
+            }
+            self.unlock_account(caller, record, None);
+            Ok(())
+        }
+
+        /// Query the guardians of the TelegramId, and how many of them have
+        /// to approve a recovery.
+        #[ink(message, selector = 0x9AAD003C)]
+        pub fn guardians_of(&self, tg_id: TelegramId) -> (Vec<AccountId>, u32) {
+            self.record_of(tg_id)
+                .map(|record| (record.guardians, record.guardian_threshold))
+                .unwrap_or_default()
+        }
+
+        /// Query the pending recovery of the TelegramId.
+        #[ink(message, selector = 0xE7FEBCF5)]
+        pub fn recovery_of(&self, tg_id: TelegramId) -> Option<Recovery> {
+            self.address_of(tg_id)
+                .and_then(|account| self.recoveries.get(account))
+        }
+
+        /// Set the guardians who could recover the binding of the caller to
+        /// a new key, together with an owner, no guardians disables
+        /// recovery.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked thier
+        ///   account.
+        ///
+        /// * Returns `Error::TooManyMembers` if there are more than
+        ///   `MAX_GUARDIANS` guardians.
+        ///
+        /// * Returns `Error::InvalidThreshold` if the threshold is zero, or
+        ///   more than the number of guardians, unless there are none.
+        #[ink(message, selector = 0x3BA78629)]
+        pub fn set_guardians(
+            &mut self,
+            guardians: Vec<AccountId>,
+            threshold: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            let mut guardians = guardians;
+            guardians.sort_unstable();
+            guardians.dedup();
+            if guardians.len() > MAX_GUARDIANS {
+                return Err(Error::TooManyMembers);
+            }
+            let threshold = if guardians.is_empty() { 0 } else { threshold };
+            if !guardians.is_empty()
+                && (threshold == 0 || threshold as usize > guardians.len())
+            {
+                return Err(Error::InvalidThreshold);
+            }
+            record.guardians = guardians.clone();
+            record.guardian_threshold = threshold;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(GuardiansChanged {
+                account: caller,
+                guardians,
+                threshold,
+            });
+            Ok(())
+        }
+
+        /// Approve recovering the binding of `tg_id` to `new_account`, as one
+        /// of its guardians or one of the owners.
+        ///
+        /// The recovery starts with the first approval, and could be executed
+        /// by `execute_recovery` once it has enough guardian approvals, an
+        /// owner approval, and `RECOVERY_DELAY` blocks passed.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is neither a guardian of
+        ///   the user nor one of the owners, or the user has no guardians.
+        ///
+        /// * Returns `Error::RecoveryPending` if a recovery to another key is
+        ///   already pending.
+        ///
+        /// * Returns `Error::AlreadyApproved` if the caller already approved
+        ///   it.
+        #[ink(message, selector = 0x07B3BCC7)]
+        pub fn recover_binding(
+            &mut self,
+            tg_id: TelegramId,
+            new_account: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            let is_guardian = record.guardians.contains(&caller);
+            let is_owner = self.owners.contains(&caller);
+            if record.guardians.is_empty() || !(is_guardian || is_owner) {
+                return Err(Error::NotAllowed);
+            }
+            let now = self.env().block_number();
+            let mut recovery =
+                self.recoveries.get(account).unwrap_or(Recovery {
+                    new_account,
+                    approvals: Vec::new(),
+                    owner: None,
+                    started_at: now,
+                });
+            if recovery.new_account != new_account {
+                return Err(Error::RecoveryPending);
+            }
+            let mut approved = false;
+            if is_guardian && !recovery.approvals.contains(&caller) {
+                recovery.approvals.push(caller);
+                approved = true;
+            }
+            if is_owner && recovery.owner.is_none() {
+                recovery.owner = Some(caller);
+                approved = true;
+            }
+            if !approved {
+                return Err(Error::AlreadyApproved);
+            }
+            self.recoveries.insert(account, &recovery);
+            self.env().emit_event(RecoveryApproved {
+                account,
+                new_account,
+                by: caller,
+                at: recovery.started_at + RECOVERY_DELAY,
+            });
+            Ok(())
+        }
+
+        /// Cancel the pending recovery of the caller's binding.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if there is no pending recovery.
+        #[ink(message, selector = 0xB433609C)]
+        pub fn cancel_recovery(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            if !self.recoveries.contains(caller) {
+                return Err(Error::NotFound {
+                    which: Entity::Recovery,
+                });
+            }
+            self.recoveries.remove(caller);
+            self.env().emit_event(RecoveryCancelled { account: caller });
+            Ok(())
+        }
+
+        /// Move the binding of `tg_id`, with its balance, to the new key of
+        /// its approved recovery, anyone could call this.
+        ///
+        /// The tip history stays with the old key.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound, or there is
+        ///   no pending recovery.
+        ///
+        /// * Returns `Error::RecoveryNotApproved` if the recovery does not have
+        ///   enough guardian approvals, or no owner approval.
+        ///
+        /// * Returns `Error::RecoveryTimelocked` if it is too early.
+        ///
+        /// * Returns `Error::AccountAlreadyBound` if the new key is already
+        ///   bound.
+        ///
+        /// * Returns `Error::SlashPending` if the user has a pending slash, and
+        ///   `Error::TipsPending` if there are held tips to them.
+        #[ink(message, selector = 0x5FA742D2)]
+        pub fn execute_recovery(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let recovery =
+                self.recoveries.get(account).ok_or(Error::NotFound {
+                    which: Entity::Recovery,
+                })?;
+            let record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            let approvals = recovery
+                .approvals
+                .iter()
+                .filter(|guardian| record.guardians.contains(guardian))
+                .count();
+            if record.guardian_threshold == 0
+                || approvals < record.guardian_threshold as usize
+                || recovery.owner.is_none()
+            {
+                return Err(Error::RecoveryNotApproved);
+            }
+            let at = recovery.started_at + RECOVERY_DELAY;
+            if self.env().block_number() < at {
+                return Err(Error::RecoveryTimelocked { at });
+            }
+            self.move_binding(account, recovery.new_account, record)?;
+            self.env().emit_event(BindingRecovered {
+                account,
+                new_account: recovery.new_account,
+                tg_id,
+            });
+            Ok(())
+        }
+
+        /// Move the binding of the caller, with thier balance, stats, badges,
+        /// streak, settings and tip history, to `new_account`, freeing the
+        /// caller's key.
+        ///
+        /// The `signature` is a recoverable ECDSA signature by the key of
+        /// `new_account` over the blake2x256 hash of the SCALE encoded
+        /// `(contract, caller, new_account)`, so only ECDSA accounts could be
+        /// rebound to.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::InvalidSignature` if the signature is not made by
+        ///   `new_account`.
+        ///
+        /// * Returns `Error::AccountAlreadyBound` if `new_account` is already
+        ///   bound.
+        ///
+        /// * Returns `Error::BalanceLocked` if the caller voted on a parameter
+        ///   change that is still open, `Error::SlashPending` if they have a
+        ///   pending slash, and `Error::TipsPending` if there are held tips to
+        ///   them.
+        #[ink(message, selector = 0x30704DFD)]
+        pub fn rebind_to(
+            &mut self,
+            new_account: AccountId,
+            signature: [u8; 65],
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            self.ensure_signed_by(
+                new_account,
+                &(self.env().account_id(), caller, new_account),
+                &signature,
+            )?;
+            record.last_active = self.env().block_number();
+            self.move_binding(caller, new_account, record.clone())?;
+            self.env().emit_event(Rebound {
+                account: caller,
+                new_account,
+                tg_id: record.tg_id,
+            });
+            Ok(())
+        }
+
+        /// Link a `secondary` address to the caller's binding, so it could
+        /// deposit, tip and withdraw against the caller's balance.
+        ///
+        /// Withdrawals requested by a secondary address are still paid to the
+        /// caller, or thier payout address, and only the caller manages the
+        /// binding itself.
+        ///
+        /// The `signature` is the ECDSA signature of the `secondary` key over
+        /// the blake2x256 hash of the encoded `(contract, b"link", caller,
+        /// secondary)`, proving it agrees to be linked.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::AccountAlreadyBound` if the `secondary` is bound,
+        ///   or already linked.
+        ///
+        /// * Returns `Error::TooManyMembers` if the caller already has
+        ///   `MAX_LINKED_ADDRESSES` linked addresses.
+        ///
+        /// * Returns `Error::InvalidSignature` if the signature is not made by
+        ///   the `secondary` key.
+        #[ink(message, selector = 0xB1173219)]
+        pub fn link_address(
+            &mut self,
+            secondary: AccountId,
+            signature: [u8; 65],
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            if secondary == caller
+                || self.users.contains(secondary)
+                || self.linked_accounts.contains(secondary)
+            {
+                return Err(Error::AccountAlreadyBound);
+            }
+            if record.linked.len() >= MAX_LINKED_ADDRESSES {
+                return Err(Error::TooManyMembers);
+            }
+            self.ensure_signed_by(
+                secondary,
+                &(self.env().account_id(), b"link", caller, secondary),
+                &signature,
+            )?;
+            record.linked.push(secondary);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.linked_accounts.insert(secondary, &caller);
+            self.env().emit_event(AddressLinked {
+                account: caller,
+                secondary,
+            });
+            Ok(())
+        }
+
+        /// Unlink a secondary address, called by either the bound account or
+        /// the secondary address itself.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the `secondary` is not linked to the
+        /// caller, nor is the caller.
+        #[ink(message, selector = 0x32526D8F)]
+        pub fn unlink_address(
+            &mut self,
+            secondary: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let account = self
+                .linked_accounts
+                .get(secondary)
+                .filter(|account| *account == caller || secondary == caller)
+                .ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            let mut record = self
+                .users
+                .get(account)
+                .expect("every linked address is linked to a bound account");
+            record.linked.retain(|linked| *linked != secondary);
+            self.users.insert(account, &record);
+            self.linked_accounts.remove(secondary);
+            self.env()
+                .emit_event(AddressUnlinked { account, secondary });
+            Ok(())
+        }
+
+        /// Query the most a user could hold by depositing, and the most the
+        /// contract could hold in total, `None` if they are not capped.
+        #[ink(message, selector = 0xEA3EE321)]
+        pub fn deposit_caps(&self) -> (Option<Balance>, Option<Balance>) {
+            (self.deposit_cap, self.global_deposit_cap)
+        }
+
+        /// Query how much more the user could deposit before reaching one of
+        /// the deposit caps, `None` if deposits are not capped.
+        ///
+        /// The tipEDG the user holds counts, the balance of a user who hides
+        /// it counts as zero, as in `balance_of`.
+        #[ink(message, selector = 0x858C5652)]
+        pub fn deposit_headroom_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<Balance> {
+            let receipts = self
+                .address_of(tg_id)
+                .map_or(0, |account| self.receipt_balance_of(account));
+            let balance = self.balance_of(tg_id).saturating_add(receipts);
+            let account =
+                self.deposit_cap.map(|cap| cap.saturating_sub(balance));
+            let global = self
+                .global_deposit_cap
+                .map(|cap| cap.saturating_sub(self.env().balance()));
+            match (account, global) {
+                (Some(account), Some(global)) => Some(account.min(global)),
+                (account, global) => account.or(global),
+            }
+        }
+
+        /// Query the balance of the contract bonded with the staking pallet.
+        #[ink(message, selector = 0x1716404E)]
+        pub fn staking(&self) -> Staking {
+            self.staking.get(()).unwrap_or_default()
+        }
+
+        /// Mint the transferred value as tipEDG, the PSP22 receipt token of
+        /// the contract, 1:1 to the caller, or to the account it is linked to.
+        ///
+        /// The tipEDG could be moved between wallets or used in other dapps,
+        /// and is burned to cover what the balance is short of when its
+        /// holder tips or withdraws, see `unwrap` and `redeem` too.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is neither bound nor
+        ///   linked.
+        ///
+        /// * Returns `Error::ZeroAmount` if nothing is transferred.
+        ///
+        /// * Returns `Error::Overflow` if the balance and tipEDG of the caller
+        ///   would overflow.
+        ///
+        /// * Returns `Error::AboveMaxBalance` if the balance and tipEDG of the
+        ///   caller would be above the `max_balance` of thier verification
+        ///   level.
+        ///
+        /// * Returns `Error::DepositCapExceeded` if the deposit would take the
+        ///   caller, or the contract, above a deposit cap, see `deposit_caps`.
+        #[ink(message, payable, selector = 0x2D10C9BD)]
+        pub fn deposit(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            self.mint_receipts(caller, &record, amount)?;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            Ok(())
+        }
+
+        /// Tip a Telegram user using thier `TelegramId`.
+        ///
+        /// This function should not be called directly by the user.
+        /// Instead, the user should chat with the Telegram bot and the bot
+        /// would call these function for them.
+        ///
+        /// If the caller enabled `auto_top_up`, the transferred value is
+        /// minted as tipEDG before the tip, as `deposit` does, so it covers
+        /// what the balance is short of, and the rest is kept as tipEDG. A
+        /// failed tip reverts the call, returning the transferred value.
+        ///
+        /// The tip is counted in the `category_stats` of the `category`, if
+        /// one is given.
+        /// Errors:
+        /// * Returns `Error::NotFound` with `Entity::Recipient` if the `tg_id`
+        ///   is not bounded to any `AccountId`, or with `Entity::Sender` if the
+        ///   caller is not bounded to any telegram account.
+        ///
+        /// * Returns `Error::InsufficientFunds` when the caller does not have
+        ///   enough balance above thier reserve, and tipEDG to burn for what it
+        ///   is short of.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero, and
+        ///   `Error::SelfTip` if the caller tips themselves.
+        ///
+        /// * Returns `Error::DailyLimitExceeded` if the caller set a daily
+        ///   limit and the tip is above what is left of it.
+        ///
+        /// * Returns `Error::RecipientBlockedSender` if the recipient does not
+        ///   accept tips from the caller, see `inbox_policy_of`.
+        ///
+        /// * Returns `Error::NotAllowed` if a value is transferred but the
+        ///   caller did not enable `auto_top_up`.
+        ///
+        /// * Returns `Error::Overflow` if the deposit would overflow the
+        ///   caller's balance and tipEDG, and `Error::AboveMaxBalance` if it
+        ///   would take them above the `max_balance` of thier verification
+        ///   level.
+        ///
+        /// * Returns `Error::DepositCapExceeded` if the deposit would take the
+        ///   caller, or the contract, above a deposit cap, see `deposit_caps`.
+        ///
+        /// * Returns `Error::NotFound` with `Entity::Category` if the
+        ///   `category` is not registered.
+        #[ink(message, payable, selector = 0xEDD26B46)]
+        pub fn tip(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+            category: Option<u8>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            self.ensure_category(category)?;
+            let transferred = self.env().transferred_value();
+            if transferred == 0 {
+                let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                    which: Entity::Recipient,
+                })?;
+                self.tip_account(
+                    caller, record, target, amount, None, None, None,
+                )?;
+                self.count_category(category, amount);
+                return Ok(());
+            }
+            if !record.settings.auto_top_up {
+                return Err(Error::NotAllowed);
+            }
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            self.mint_receipts(caller, &record, transferred)?;
+            self.tip_account(caller, record, target, amount, None, None, None)?;
+            self.count_category(category, amount);
+            Ok(())
+        }
+
+        /// Tip a Telegram user the same as `tip`, but send the tip to the
+        /// remote address of the recipient on another parachain, see
+        /// `set_remote_address`.
+        ///
+        /// The tip is paid locally instead if the recipient has no remote
+        /// address, if the runtime could not send it, or if it is held, as
+        /// held tips are paid locally once they are released.
+        ///
+        /// Errors:
+        /// Same as `tip`.
+        #[ink(message, selector = 0xF67F8500)]
+        pub fn tip_remote(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let remote = self.remote_addresses.get(target);
+            self.tip_account(caller, record, target, amount, None, None, remote)
+        }
+
+        /// Query the EDG/USD price oracle contract.
+        #[ink(message, selector = 0x18F3E9E9)]
+        pub fn price_oracle(&self) -> Option<AccountId> { self.price_oracle }
+
+        /// Query the last EDG/USD price tipped at, as how much of EDG one US
+        /// cent is worth, and the time it was updated at.
+        #[ink(message, selector = 0x4E29B41F)]
+        pub fn usd_price(&self) -> Option<(Balance, Timestamp)> {
+            self.usd_price
+        }
+
+        /// Similar to tip, but the amount is in US cents, converted to EDG at
+        /// the price of the oracle.
+        ///
+        /// Errors:
+        /// Same as `tip`, and:
+        /// * Returns `Error::OracleDisabled` if there is no price oracle, and
+        ///   `Error::OracleFailed` if it could not be queried.
+        ///
+        /// * Returns `Error::StalePrice` if the price is older than
+        ///   `MAX_PRICE_AGE`.
+        ///
+        /// * Returns `Error::PriceSlippage` if the tip would cost more than
+        ///   `max_amount` of EDG at the price of the oracle.
+        #[ink(message, selector = 0xB01E4FE8)]
+        pub fn tip_usd(
+            &mut self,
+            tg_id: TelegramId,
+            usd_cents: u64,
+            max_amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let oracle = self.price_oracle.ok_or(Error::OracleDisabled)?;
+            let (price, updated_at) = call_out(|| {
+                build_call::<Environment>()
+                    .call_type(
+                        Call::new()
+                            .callee(oracle)
+                            .gas_limit(ORACLE_GAS_LIMIT)
+                            .transferred_value(0),
+                    )
+                    .exec_input(ExecutionInput::new(Selector::new(
+                        EDG_USD_PRICE_SELECTOR,
+                    )))
+                    .returns::<(Balance, Timestamp)>()
+                    .fire()
+            })
+            .map_err(|_| Error::OracleFailed)?;
+            self.tip_usd_at(tg_id, usd_cents, max_amount, price, updated_at)
+        }
+
+        /// Query the DEX tips are swapped through.
+        #[ink(message, selector = 0xF45AE9D0)]
+        pub fn dex(&self) -> Option<Dex> { self.dex.clone() }
+
+        /// Tip `amount` of the PSP22 `pay_token`, swapped through the DEX to
+        /// EDG.
+        ///
+        /// The caller has to approve the contract to spend the `amount` first.
+        /// The EDG it is swapped to is deposited to the caller's balance, and
+        /// tipped from it like any other tip, so it is charged the tip fee and
+        /// counts towards the limits of the caller.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` with `Entity::Sender` or
+        ///   `Entity::Recipient` if the caller or the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero, and
+        ///   `Error::SelfTip` if the caller tips themselves.
+        ///
+        /// * Returns `Error::SwapDisabled` if there is no DEX.
+        ///
+        /// * Returns `Error::SwapFailed` if the tokens could not be pulled or
+        ///   swapped, or the contract would get less than `min_out` of EDG, the
+        ///   pulled tokens are given back then.
+        ///
+        /// * Otherwise the same as `tip`, for the swapped amount.
+        #[ink(message, selector = 0xF69A65AB)]
+        pub fn tip_as(
+            &mut self,
+            tg_id: TelegramId,
+            pay_token: AccountId,
+            amount: Balance,
+            min_out: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            ensure_not_self_locked(&record)?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if caller == target {
+                return Err(Error::SelfTip);
+            }
+            let dex = self.dex.clone().ok_or(Error::SwapDisabled)?;
+            let this = self.env().account_id();
+            let held = self.token_balance(pay_token, this)?;
+            self.invoke(
+                pay_token,
+                ExecutionInput::new(Selector::new(
+                    PSP22_TRANSFER_FROM_SELECTOR,
+                ))
+                .push_arg(caller)
+                .push_arg(this)
+                .push_arg(amount)
+                .push_arg(Vec::<u8>::new()),
+            )?;
+            if self.token_balance(pay_token, this)?.saturating_sub(held)
+                < amount
+            {
+                return Err(Error::SwapFailed);
+            }
+            let received = match self.swap(&dex, pay_token, amount, min_out) {
+                Ok(received) if received >= min_out => received,
+                _ => {
+                    self.give_back(pay_token, caller, held);
+                    return Err(Error::SwapFailed);
+                },
+            };
+            self.tip_swapped(caller, record, target, received)?;
+            self.env().emit_event(SwapTipped {
+                from: caller,
+                to: target,
+                pay_token,
+                amount,
+                received,
+            });
+            Ok(())
+        }
+
+        /// Similar to tip, but also mints a thank-you receipt token to the
+        /// recipient, recording the caller, the amount and the hash of its
+        /// metadata.
+        ///
+        /// If the tip is held, see `reversal_window`, the token is minted only
+        /// once it is paid, and never if it is given back. No token is minted
+        /// to a recipient who hides the tips they receive, see `set_privacy`.
+        ///
+        /// Errors:
+        /// * Returns `Error::FeatureDisabled` if the NFTs are disabled, see
+        ///   `AdminCall::SetFeature`.
+        ///
+        /// * Otherwise the same as `tip`.
+        #[ink(message, selector = 0x00FAC262)]
+        pub fn tip_with_receipt(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+            uri_hash: Hash,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Nfts)?;
+            let tip_id = self.next_tip_id;
+            self.tip(tg_id, amount, None)?;
+            if self.next_tip_id > tip_id {
+                self.tip_nft_requests.insert(tip_id, &uri_hash);
+            } else {
+                let from = self.acting_account();
+                let to = self
+                    .address_of(tg_id)
+                    .expect("the recipient of a paid tip is bound");
+                self.mint_tip_nft(from, to, amount, uri_hash);
+            }
+            Ok(())
+        }
+
+        /// Open a tipping contest until the `ends_at` block, funded with the
+        /// transferred value, returning its Id.
+        ///
+        /// Until then, the volume every bound user tips, anonymous tips left
+        /// out, is tracked, and `finalize_contest` splits the pot among the
+        /// top tippers by the `weights`, the share of each place in basis
+        /// points, first place first. Only one contest runs at a time.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::ZeroAmount` if nothing is transferred.
+        ///
+        /// * Returns `Error::InvalidParamValue` if there are no weights, more
+        ///   than `MAX_CONTEST_WINNERS`, or they do not add up to `MAX_BPS`.
+        ///
+        /// * Returns `Error::ContestEnded` if `ends_at` is not in the future.
+        ///
+        /// * Returns `Error::ContestNotEnded` if the last contest was not
+        ///   finalized yet.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, payable, selector = 0xDAC18F68)]
+        pub fn open_contest(
+            &mut self,
+            ends_at: BlockNumber,
+            weights: Vec<u16>,
+        ) -> Result<ContestId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            self.ensure_owner()?;
+            let pot = self.env().transferred_value();
+            if pot == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let total: u32 =
+                weights.iter().map(|weight| u32::from(*weight)).sum();
+            if weights.is_empty()
+                || weights.len() > MAX_CONTEST_WINNERS
+                || total != u32::from(MAX_BPS)
+            {
+                return Err(Error::InvalidParamValue);
+            }
+            if ends_at <= self.env().block_number() {
+                return Err(Error::ContestEnded);
+            }
+            if let Some(active) = self.active_contest {
+                let ends_at = self.contest_of(active)?.ends_at;
+                return Err(Error::ContestNotEnded { ends_at });
+            }
+            let contest_id = self.next_contest_id;
+            self.next_contest_id += 1;
+            self.contests.insert(
+                contest_id,
+                &Contest {
+                    funder: self.env().caller(),
+                    pot,
+                    ends_at,
+                    weights: weights.clone(),
+                    board: Vec::new(),
+                    finalized: false,
+                },
+            );
+            self.active_contest = Some(contest_id);
+            self.env().emit_event(ContestOpened {
+                contest_id,
+                pot,
+                ends_at,
+                weights,
+            });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::OpenContest { contest_id, pot },
+            );
+            Ok(contest_id)
+        }
+
+        /// Pay the prizes of the running contest after it ends, anyone could
+        /// call this.
+        ///
+        /// Each of the top tippers gets the share of the pot of thier place,
+        /// credited to thier balance, or transferred if they unbound since.
+        /// The shares of the places nobody took, the prizes that could not be
+        /// paid, and the rounding dust, are paid back to the funder, or kept
+        /// in the treasury if that fails too.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if no contest is running.
+        ///
+        /// * Returns `Error::ContestNotEnded` if the contest did not end yet.
+        #[ink(message, selector = 0xDFAAFB5F)]
+        pub fn finalize_contest(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let contest_id = self.active_contest.ok_or(Error::NotFound {
+                which: Entity::Contest,
+            })?;
+            let mut contest = self.contest_of(contest_id)?;
+            if self.env().block_number() < contest.ends_at {
+                return Err(Error::ContestNotEnded {
+                    ends_at: contest.ends_at,
+                });
+            }
+            let mut winners = Vec::new();
+            let mut won: Balance = 0;
+            for ((account, _), weight) in
+                contest.board.iter().zip(&contest.weights)
+            {
+                let prize = fee_of(contest.pot, *weight);
+                // one winner who could not be paid does not hold the others.
+                if self.credit(*account, prize).is_ok() {
+                    won += prize;
+                    winners.push((*account, prize));
+                }
+            }
+            let unclaimed = contest.pot - won;
+            if unclaimed > 0 && self.credit(contest.funder, unclaimed).is_err()
+            {
+                self.treasury = self.treasury.saturating_add(unclaimed);
+            }
+            contest.finalized = true;
+            self.contests.insert(contest_id, &contest);
+            self.active_contest = None;
+            self.env().emit_event(ContestFinalized {
+                contest_id,
+                winners,
+                unclaimed,
+            });
+            Ok(())
+        }
+
+        /// Query a tipping contest.
+        #[ink(message, selector = 0xCD8D230E)]
+        pub fn contest(&self, contest_id: ContestId) -> Option<Contest> {
+            self.contests.get(contest_id)
+        }
+
+        /// Query the contest tips are counted towards, until it is
+        /// finalized.
+        #[ink(message, selector = 0x7DEA0847)]
+        pub fn active_contest(&self) -> Option<ContestId> {
+            self.active_contest
+        }
+
+        /// Query the volume the account tipped during a contest.
+        #[ink(message, selector = 0x01650CE1)]
+        pub fn contest_volume_of(
+            &self,
+            contest_id: ContestId,
+            account: AccountId,
+        ) -> Balance {
+            self.contest_volumes.get((contest_id, account)).unwrap_or(0)
+        }
+
+        /// Create a raffle selling tickets for `ticket_price` each until the
+        /// `ends_at` block, returning its Id.
+        ///
+        /// The `seed_hash` is the blake2x256 hash of the encoded secret seed
+        /// the raffle is drawn with, see `Raffle`. The current
+        /// `raffle_rake_bps` is taken from its pot.
+        ///
+        /// Only the owners, or the operator, could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither one of the
+        ///   owners nor the operator.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `ticket_price` is zero.
+        ///
+        /// * Returns `Error::RaffleEnded` if `ends_at` is not in the future.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0x050ECA35)]
+        pub fn create_raffle(
+            &mut self,
+            ticket_price: Balance,
+            ends_at: BlockNumber,
+            seed_hash: Hash,
+        ) -> Result<RaffleId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let creator = self.env().caller();
+            if creator != self.operator {
+                self.ensure_owner()?;
+            }
+            if ticket_price == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if ends_at <= self.env().block_number() {
+                return Err(Error::RaffleEnded);
+            }
+            let raffle_id = self.next_raffle_id;
+            self.raffles.insert(
+                raffle_id,
+                &Raffle {
+                    creator,
+                    ticket_price,
+                    ends_at,
+                    seed_hash,
+                    entropy: seed_hash,
+                    tickets: 0,
+                    purchases: 0,
+                    rake_bps: self.raffle_rake_bps,
+                },
+            );
+            self.next_raffle_id += 1;
+            self.env().emit_event(RaffleCreated {
+                raffle_id,
+                ticket_price,
+                ends_at,
+            });
+            Ok(raffle_id)
+        }
+
+        /// Buy `count` tickets of the raffle, paid from the caller's
+        /// balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the
+        ///   raffle does not exist.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `count` is zero.
+        ///
+        /// * Returns `Error::RaffleEnded` if the raffle ended.
+        ///
+        /// * Returns `Error::Overflow` if the raffle sold too many tickets.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the tickets.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is a key a buyer of the
+        ///   raffle moved away from.
+        ///
+        /// * Returns `Error::TooManyPositions` if the caller has too many open
+        ///   positions.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0xB1DC989B)]
+        pub fn buy_ticket(
+            &mut self,
+            raffle_id: RaffleId,
+            count: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            let mut raffle =
+                self.raffles.get(raffle_id).ok_or(Error::NotFound {
+                    which: Entity::Raffle,
+                })?;
+            if count == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let now = self.env().block_number();
+            if now >= raffle.ends_at {
+                return Err(Error::RaffleEnded);
+            }
+            let tickets =
+                raffle.tickets.checked_add(count).ok_or(Error::Overflow)?;
+            let cost = raffle
+                .ticket_price
+                .checked_mul(Balance::from(count))
+                .ok_or(Error::Overflow)?;
+            let available = self.spendable(&record);
+            if available < cost {
+                return Err(Error::InsufficientFunds {
+                    required: cost,
+                    available,
+                });
+            }
+            if self.raffle_moves.contains((raffle_id, caller)) {
+                return Err(Error::NotAllowed);
+            }
+            self.add_position(caller, Position::Raffle(raffle_id))?;
+            record.balance -= cost;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(cost);
+            record.last_active = now;
+            self.users.insert(caller, &record);
+            self.raffle_purchases
+                .insert((raffle_id, raffle.purchases), &(caller, tickets));
+            let spent = self.raffle_spent.get((raffle_id, caller)).unwrap_or(0);
+            self.raffle_spent
+                .insert((raffle_id, caller), &spent.saturating_add(cost));
+            raffle.entropy =
+                Hash::from(self.env().hash_encoded::<Blake2x256, _>(&(
+                    raffle.entropy,
+                    caller,
+                    count,
+                    now,
+                    self.env().block_timestamp(),
+                )));
+            raffle.tickets = tickets;
+            raffle.purchases += 1;
+            self.raffles.insert(raffle_id, &raffle);
+            self.env().emit_event(TicketsBought {
+                raffle_id,
+                buyer: caller,
+                count,
+            });
+            Ok(())
+        }
+
+        /// Draw the raffle by revealing its `seed`, paying the pot minus the
+        /// rake to the winner.
+        ///
+        /// Anyone who knows the seed could draw the raffle once it ended,
+        /// until `RAFFLE_REVEAL_WINDOW` blocks later.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the raffle does not exist, or was
+        ///   already drawn.
+        ///
+        /// * Returns `Error::RaffleNotEnded` if the raffle did not end yet.
+        ///
+        /// * Returns `Error::RaffleExpired` if the seed was not revealed in
+        ///   time.
+        ///
+        /// * Returns `Error::InvalidSeed` if the `seed` does not match the
+        ///   commitment of the raffle.
+        #[ink(message, selector = 0x02E46F43)]
+        pub fn draw(
+            &mut self,
+            raffle_id: RaffleId,
+            seed: Hash,
+        ) -> Result<Option<AccountId>, Error> {
+            ensure_not_reentered()?;
+            let raffle =
+                self.raffles.get(raffle_id).ok_or(Error::NotFound {
+                    which: Entity::Raffle,
+                })?;
+            let now = self.env().block_number();
+            if now < raffle.ends_at {
+                return Err(Error::RaffleNotEnded {
+                    ends_at: raffle.ends_at,
+                });
+            }
+            if now >= raffle.ends_at.saturating_add(RAFFLE_REVEAL_WINDOW) {
+                return Err(Error::RaffleExpired);
+            }
+            let seed_hash =
+                Hash::from(self.env().hash_encoded::<Blake2x256, _>(&seed));
+            if seed_hash != raffle.seed_hash {
+                return Err(Error::InvalidSeed);
+            }
+            if raffle.tickets == 0 {
+                self.raffles.remove(raffle_id);
+                self.env().emit_event(RaffleDrawn {
+                    raffle_id,
+                    winner: None,
+                    prize: 0,
+                });
+                return Ok(None);
+            }
+            let random = self
+                .env()
+                .hash_encoded::<Blake2x256, _>(&(seed, raffle.entropy));
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(&random[..16]);
+            let ticket = (u128::from_le_bytes(bytes)
+                % u128::from(raffle.tickets)) as u32;
+            let mut winner =
+                self.raffle_ticket_owner(raffle_id, &raffle, ticket);
+            while let Some(moved) = self.raffle_moves.get((raffle_id, winner)) {
+                winner = moved;
+            }
+            let pot = raffle.ticket_price * Balance::from(raffle.tickets);
+            let rake = fee_of(pot, raffle.rake_bps);
+            let treasury =
+                self.treasury.checked_add(rake).ok_or(Error::Overflow)?;
+            let prize = pot - rake;
+            self.credit(winner, prize)?;
+            self.treasury = treasury;
+            self.raffles.remove(raffle_id);
+            self.env().emit_event(RaffleDrawn {
+                raffle_id,
+                winner: Some(winner),
+                prize,
+            });
+            Ok(Some(winner))
+        }
+
+        /// Refund the tickets the caller bought of a raffle whose seed was
+        /// not revealed in time.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the raffle does not exist, was drawn,
+        ///   or the caller has nothing to refund.
+        ///
+        /// * Returns `Error::RefundNotOpen` if the seed could still be
+        ///   revealed.
+        #[ink(message, selector = 0xC812E577)]
+        pub fn claim_raffle_refund(
+            &mut self,
+            raffle_id: RaffleId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let raffle =
+                self.raffles.get(raffle_id).ok_or(Error::NotFound {
+                    which: Entity::Raffle,
+                })?;
+            let at = raffle.ends_at.saturating_add(RAFFLE_REVEAL_WINDOW);
+            if self.env().block_number() < at {
+                return Err(Error::RefundNotOpen { at });
+            }
+            let amount = self.raffle_spent.get((raffle_id, caller)).ok_or(
+                Error::NotFound {
+                    which: Entity::Raffle,
+                },
+            )?;
+            self.credit(caller, amount)?;
+            self.raffle_spent.remove((raffle_id, caller));
+            self.env().emit_event(RaffleRefunded {
+                raffle_id,
+                account: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Challenge the user of the TelegramId to a coin-flip, staking
+        /// `stake` of the caller's balance, returning the Id of the
+        /// challenge.
+        ///
+        /// The `seed_hash` is the blake2x256 hash of the encoded seed the
+        /// caller reveals to `flip` the coin, so they have to keep the seed.
+        ///
+        /// The challenge could be accepted for `CHALLENGE_TIMEOUT` blocks,
+        /// and cancelled by the caller until then.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller, or the opponent, is not
+        ///   bound.
+        ///
+        /// * Returns `Error::SelfTip` if the caller challenges themselves.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `stake` is zero.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the stake.
+        ///
+        /// * Returns `Error::FeatureLocked` if the verification level of the
+        ///   caller does not unlock `FEATURE_CHALLENGE`.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0xF755A053)]
+        pub fn challenge(
+            &mut self,
+            tg_id: TelegramId,
+            stake: Balance,
+            seed_hash: Hash,
+        ) -> Result<ChallengeId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if record.tg_id == tg_id {
+                return Err(Error::SelfTip);
+            }
+            self.ensure_feature(&record, FEATURE_CHALLENGE)?;
+            self.take_stake(caller, record, stake)?;
+            let challenge_id = self.next_challenge_id;
+            self.challenges.insert(
+                challenge_id,
+                &Challenge {
+                    challenger: caller,
+                    opponent: tg_id,
+                    stake,
+                    created_at: self.env().block_number(),
+                    seed_hash,
+                    accepted: None,
+                },
+            );
+            self.next_challenge_id += 1;
+            self.env().emit_event(ChallengeCreated {
+                challenge_id,
+                challenger: caller,
+                opponent: tg_id,
+                stake,
+            });
+            Ok(challenge_id)
+        }
+
+        /// Accept a coin-flip challenge, staking the same amount of the
+        /// caller's balance, with the `seed` of the caller.
+        ///
+        /// The coin is flipped later, by `flip`. The challenger only commited
+        /// to thier seed, so the caller could not pick a `seed` winning for
+        /// them, and the challenger could not pick one after seeing it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the challenge does not exist, or the
+        ///   caller is not bound.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the opponent.
+        ///
+        /// * Returns `Error::ChallengeAccepted` if it was already accepted.
+        ///
+        /// * Returns `Error::ChallengeExpired` if it was not accepted in time.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the stake.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0x0DDCA1AC)]
+        pub fn accept_challenge(
+            &mut self,
+            challenge_id: ChallengeId,
+            seed: Hash,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let caller = self.env().caller();
+            let mut challenge =
+                self.challenges.get(challenge_id).ok_or(Error::NotFound {
+                    which: Entity::Challenge,
+                })?;
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            if record.tg_id != challenge.opponent {
+                return Err(Error::NotAllowed);
+            }
+            if challenge.accepted.is_some() {
+                return Err(Error::ChallengeAccepted);
+            }
+            let now = self.env().block_number();
+            if now >= challenge.created_at.saturating_add(CHALLENGE_TIMEOUT) {
+                return Err(Error::ChallengeExpired);
+            }
+            self.take_stake(caller, record, challenge.stake)?;
+            challenge.accepted = Some((caller, now, seed));
+            self.challenges.insert(challenge_id, &challenge);
+            self.env().emit_event(ChallengeAccepted {
+                challenge_id,
+                account: caller,
+            });
+            Ok(())
+        }
+
+        /// Flip the coin of an accepted challenge by revealing the `seed` of
+        /// the challenger, paying both stakes to the winner, who is returned.
+        ///
+        /// The coin is the lowest bit of the blake2x256 hash of the encoded
+        /// seeds of the challenger and of the opponent, so it is fixed once
+        /// the challenge is accepted, whoever flips it and whenever they do.
+        /// The runtime randomness is not used, as it changes every block and
+        /// is known ahead, so a side could wait for a block it wins in.
+        ///
+        /// Anyone who knows the seed could flip the coin, until
+        /// `CHALLENGE_TIMEOUT` blocks after the challenge was accepted. A
+        /// challenger who does not reveal thier seed by then, to not lose,
+        /// forfeits both stakes to the opponent, see `cancel_challenge`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the challenge does not exist.
+        ///
+        /// * Returns `Error::ChallengeNotAccepted` if it was not accepted yet.
+        ///
+        /// * Returns `Error::ChallengeExpired` if it was not flipped in time.
+        ///
+        /// * Returns `Error::InvalidSeed` if the `seed` does not match the
+        ///   commitment of the challenger.
+        #[ink(message, selector = 0x633AA551)]
+        pub fn flip(
+            &mut self,
+            challenge_id: ChallengeId,
+            seed: Hash,
+        ) -> Result<AccountId, Error> {
+            ensure_not_reentered()?;
+            let challenge =
+                self.challenges.get(challenge_id).ok_or(Error::NotFound {
+                    which: Entity::Challenge,
+                })?;
+            let (account, accepted_at, opponent_seed) =
+                challenge.accepted.ok_or(Error::ChallengeNotAccepted)?;
+            let now = self.env().block_number();
+            if now >= accepted_at.saturating_add(CHALLENGE_TIMEOUT) {
+                return Err(Error::ChallengeExpired);
+            }
+            let seed_hash =
+                Hash::from(self.env().hash_encoded::<Blake2x256, _>(&seed));
+            if seed_hash != challenge.seed_hash {
+                return Err(Error::InvalidSeed);
+            }
+            let coin = self
+                .env()
+                .hash_encoded::<Blake2x256, _>(&(seed, opponent_seed));
+            let winner = if coin[0] & 1 == 0 {
+                challenge.challenger
+            } else {
+                account
+            };
+            let pot = challenge.stake.saturating_mul(2);
+            self.credit(winner, pot)?;
+            self.challenges.remove(challenge_id);
+            self.env().emit_event(CoinFlipped {
+                challenge_id,
+                winner,
+                pot,
+            });
+            Ok(winner)
+        }
+
+        /// Refund the stakes of a challenge.
+        ///
+        /// The challenger could cancel the challenge until it is accepted,
+        /// and anyone could refund it once it was not accepted for
+        /// `CHALLENGE_TIMEOUT` blocks. Once it was accepted and not flipped
+        /// for `CHALLENGE_TIMEOUT` blocks, the challenger forfeits and both
+        /// stakes are paid to the opponent instead.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the challenge does not exist.
+        ///
+        /// * Returns `Error::RefundNotOpen` if it could not be refunded yet.
+        #[ink(message, selector = 0x31279918)]
+        pub fn cancel_challenge(
+            &mut self,
+            challenge_id: ChallengeId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let challenge =
+                self.challenges.get(challenge_id).ok_or(Error::NotFound {
+                    which: Entity::Challenge,
+                })?;
+            let now = self.env().block_number();
+            let since = challenge
+                .accepted
+                .map_or(challenge.created_at, |(_, accepted_at, _)| {
+                    accepted_at
+                });
+            let at = since.saturating_add(CHALLENGE_TIMEOUT);
+            let cancelled = challenge.accepted.is_none()
+                && self.env().caller() == challenge.challenger;
+            if now < at && !cancelled {
+                return Err(Error::RefundNotOpen { at });
+            }
+            match challenge.accepted {
+                Some((account, _, _)) => {
+                    let pot = challenge.stake.saturating_mul(2);
+                    self.credit(account, pot)?;
+                    self.env().emit_event(CoinFlipped {
+                        challenge_id,
+                        winner: account,
+                        pot,
+                    });
+                },
+                None => {
+                    self.credit(challenge.challenger, challenge.stake)?;
+                    self.env().emit_event(ChallengeRefunded { challenge_id });
+                },
+            }
+            self.challenges.remove(challenge_id);
+            Ok(())
+        }
+
+        /// Create a prediction poll with `options` options, taking stakes
+        /// until the `closes_at` block, returning its Id.
+        ///
+        /// Only the owners, or the operator, could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither one of the
+        ///   owners nor the operator.
+        ///
+        /// * Returns `Error::InvalidOption` if there are less than two options.
+        ///
+        /// * Returns `Error::PollClosed` if `closes_at` is not in the future.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0x40867DAC)]
+        pub fn create_poll(
+            &mut self,
+            options: u8,
+            closes_at: BlockNumber,
+        ) -> Result<PollId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let creator = self.env().caller();
+            if creator != self.operator {
+                self.ensure_owner()?;
+            }
+            if options < 2 {
+                return Err(Error::InvalidOption);
+            }
+            if closes_at <= self.env().block_number() {
+                return Err(Error::PollClosed);
+            }
+            let poll_id = self.next_poll_id;
+            self.polls.insert(
+                poll_id,
+                &Poll {
+                    creator,
+                    options,
+                    closes_at,
+                    total: 0,
+                    winning_option: None,
+                    stakers: 0,
+                },
+            );
+            self.next_poll_id += 1;
+            self.env().emit_event(PollCreated {
+                poll_id,
+                options,
+                closes_at,
+            });
+            Ok(poll_id)
+        }
+
+        /// Stake `amount` of the caller's balance on an option of a poll.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the poll
+        ///   does not exist.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InvalidOption` if the option is not one of the
+        ///   options of the poll.
+        ///
+        /// * Returns `Error::PollClosed` if the poll closed.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the stake.
+        ///
+        /// * Returns `Error::Overflow` if the stakes on the poll would
+        ///   overflow.
+        ///
+        /// * Returns `Error::FeatureDisabled` if games are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0x7A1E7D08)]
+        pub fn stake_on(
+            &mut self,
+            poll_id: PollId,
+            option: u8,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Games)?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            let mut poll = self.polls.get(poll_id).ok_or(Error::NotFound {
+                which: Entity::Poll,
+            })?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if option >= poll.options {
+                return Err(Error::InvalidOption);
+            }
+            let now = self.env().block_number();
+            if now >= poll.closes_at {
+                return Err(Error::PollClosed);
+            }
+            let available = self.spendable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            let option_stake = self
+                .poll_option_stakes
+                .get((poll_id, option))
+                .unwrap_or(0)
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            let stake = self
+                .poll_stakes
+                .get((poll_id, caller, option))
+                .unwrap_or(0)
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            let staked = self
+                .poll_staked
+                .get((poll_id, caller))
+                .unwrap_or(0)
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            poll.total =
+                poll.total.checked_add(amount).ok_or(Error::Overflow)?;
+            self.add_position(caller, Position::Poll(poll_id))?;
+            record.balance -= amount;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(amount);
+            record.last_active = now;
+            self.users.insert(caller, &record);
+            if !self.poll_staked.contains((poll_id, caller)) {
+                self.add_staker(poll_id, &mut poll, caller);
+            }
+            self.polls.insert(poll_id, &poll);
+            self.poll_option_stakes
+                .insert((poll_id, option), &option_stake);
+            self.poll_stakes.insert((poll_id, caller, option), &stake);
+            self.poll_staked.insert((poll_id, caller), &staked);
+            self.env().emit_event(StakedOnPoll {
+                poll_id,
+                account: caller,
+                option,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Resolve a closed poll with its winning option, so the users who
+        /// staked on it could claim thier share by `claim_poll`.
+        ///
+        /// If no one staked on the winning option, every stake could be
+        /// claimed back instead.
+        ///
+        /// Only the owners, or the operator, could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither one of the
+        ///   owners nor the operator.
+        ///
+        /// * Returns `Error::NotFound` if the poll does not exist.
+        ///
+        /// * Returns `Error::InvalidOption` if the option is not one of the
+        ///   options of the poll.
+        ///
+        /// * Returns `Error::PollNotClosed` if the poll did not close yet.
+        ///
+        /// * Returns `Error::PollResolved` if the poll was already resolved.
+        #[ink(message, selector = 0x9E30D65D)]
+        pub fn resolve(
+            &mut self,
+            poll_id: PollId,
+            winning_option: u8,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            if self.env().caller() != self.operator {
+                self.ensure_owner()?;
+            }
+            let mut poll = self.polls.get(poll_id).ok_or(Error::NotFound {
+                which: Entity::Poll,
+            })?;
+            if winning_option >= poll.options {
+                return Err(Error::InvalidOption);
+            }
+            if self.env().block_number() < poll.closes_at {
+                return Err(Error::PollNotClosed {
+                    closes_at: poll.closes_at,
+                });
+            }
+            if poll.winning_option.is_some() {
+                return Err(Error::PollResolved);
+            }
+            poll.winning_option = Some(winning_option);
+            self.polls.insert(poll_id, &poll);
+            self.env().emit_event(PollResolved {
+                poll_id,
+                winning_option,
+            });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::ResolvePoll {
+                    poll_id,
+                    winning_option,
+                },
+            );
+            Ok(())
+        }
+
+        /// Claim the caller's share of a resolved poll into thier balance,
+        /// returning it.
+        ///
+        /// The share is the total staked on the poll, pro-rata to the
+        /// caller's stake on the winning option, rounded down.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the poll does not exist, or the
+        ///   caller has nothing to claim.
+        ///
+        /// * Returns `Error::PollNotResolved` if the poll was not resolved yet.
+        #[ink(message, selector = 0xD138B0AF)]
+        pub fn claim_poll(
+            &mut self,
+            poll_id: PollId,
+        ) -> Result<Balance, Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let poll = self.polls.get(poll_id).ok_or(Error::NotFound {
+                which: Entity::Poll,
+            })?;
+            let option = poll.winning_option.ok_or(Error::PollNotResolved)?;
+            let amount = self.poll_share(poll_id, &poll, caller);
+            if amount == 0 {
+                return Err(Error::NotFound {
+                    which: Entity::Stake,
+                });
+            }
+            self.credit(caller, amount)?;
+            self.poll_stakes.remove((poll_id, caller, option));
+            self.poll_staked.remove((poll_id, caller));
+            self.env().emit_event(PollPaid {
+                poll_id,
+                account: caller,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// Tip a piece of content, by its hash, even before its author is
+        /// known.
+        ///
+        /// The `amount` is taken from the caller's balance into the tip jar
+        /// of the content, which its author later claims by
+        /// `claim_content`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::BelowMinTip`, or `Error::AboveMaxTip`, if the
+        ///   amount is out of the tip limits.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the tip.
+        #[ink(message, selector = 0x7716CDAF)]
+        pub fn tip_content(
+            &mut self,
+            content_hash: Hash,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            ensure_not_self_locked(&record)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if amount < self.params.min_tip {
+                return Err(Error::BelowMinTip {
+                    min: self.params.min_tip,
+                });
+            }
+            if amount > self.params.max_tip {
+                return Err(Error::AboveMaxTip {
+                    max: self.params.max_tip,
+                });
+            }
+            let available = self.tippable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            let mut jar =
+                self.content_jars.get(content_hash).unwrap_or_default();
+            jar.balance =
+                jar.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            record.balance -= amount;
+            record.audit.tips_out =
+                record.audit.tips_out.saturating_add(amount);
+            record.last_active = self.env().block_number();
+            record.stats.tips_sent = record.stats.tips_sent.saturating_add(1);
+            record.stats.total_sent =
+                record.stats.total_sent.saturating_add(amount);
+            self.users.insert(caller, &record);
+            self.put_content_jar(content_hash, &jar);
+            self.env().emit_event(ContentTipped {
+                content_hash,
+                from: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Attest the TelegramId as the author of a piece of content, so it
+        /// could claim the tip jar of the content.
+        ///
+        /// The author does not have to be bound yet, and could be attested
+        /// again to correct it.
+        ///
+        /// Only the owners, or the operator, could call this.
+        ///
+        /// Errors:
+        /// Returns `Error::NotAllowed` if the caller is neither one of the
+        /// owners nor the operator.
+        #[ink(message, selector = 0x0A70E853)]
+        pub fn attest_author(
+            &mut self,
+            content_hash: Hash,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            if self.env().caller() != self.operator {
+                self.ensure_owner()?;
+            }
+            let mut jar =
+                self.content_jars.get(content_hash).unwrap_or_default();
+            jar.author = Some(tg_id);
+            self.put_content_jar(content_hash, &jar);
+            self.env().emit_event(AuthorAttested {
+                content_hash,
+                tg_id,
+            });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::AttestAuthor {
+                    content_hash,
+                    tg_id,
+                },
+            );
+            Ok(())
+        }
+
+        /// Claim the tip jar of a piece of content the caller is the attested
+        /// author of, returning the amount paid after the fee.
+        ///
+        /// The jar is paid like a tip, to the payout address of the caller if
+        /// set. Tips to the content after the claim could be claimed again.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the
+        ///   content has no tip jar.
+        ///
+        /// * Returns `Error::AuthorNotAttested` if the author of the content
+        ///   was not attested yet.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the author.
+        ///
+        /// * Returns `Error::ZeroAmount` if the jar is empty.
+        ///
+        /// * Returns `Error::BelowSubsistenceThreshold` if the transfer failed.
+        #[ink(message, selector = 0x66C5909F)]
+        pub fn claim_content(
+            &mut self,
+            content_hash: Hash,
+        ) -> Result<Balance, Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut jar =
+                self.content_jars.get(content_hash).ok_or(Error::NotFound {
+                    which: Entity::Content,
+                })?;
+            let author = jar.author.ok_or(Error::AuthorNotAttested)?;
+            if author != record.tg_id {
+                return Err(Error::NotAllowed);
+            }
+            if jar.balance == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let fee = fee_of(jar.balance, self.params.fee_bps);
+            let treasury =
+                self.treasury.checked_add(fee).ok_or(Error::Overflow)?;
+            let received = jar.balance - fee;
+            self.env()
+                .transfer(record.payout.unwrap_or(caller), received)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            self.treasury = treasury;
+            self.record_fee(jar.balance, fee);
+            jar.balance = 0;
+            self.content_jars.insert(content_hash, &jar);
+            let stats = &mut record.stats;
+            stats.tips_received = stats.tips_received.saturating_add(1);
+            stats.total_received =
+                stats.total_received.saturating_add(received);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(ContentClaimed {
+                content_hash,
+                account: caller,
+                received,
+            });
+            Ok(received)
+        }
+
+        /// Register an external charity cause the users could donate to, or
+        /// change the account and the description of a registered one,
+        /// keeping its total.
+        ///
+        /// Errors:
+        /// Returns `Error::NotAllowed` if the caller is not one of the owners
+        /// of the contract.
+        #[ink(message, selector = 0xEB86F15A)]
+        pub fn register_cause(
+            &mut self,
+            cause_id: CauseId,
+            account: AccountId,
+            metadata_hash: Hash,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let total = self
+                .causes
+                .get(cause_id)
+                .map(|cause| cause.total)
+                .unwrap_or(0);
+            self.causes.insert(
+                cause_id,
+                &Cause {
+                    account,
+                    metadata_hash,
+                    total,
+                },
+            );
+            self.env().emit_event(CauseRegistered {
+                cause_id,
+                account,
+                metadata_hash,
+            });
+            self.log_admin_action(
+                Some(account),
+                PrivilegedAction::RegisterCause {
+                    cause_id,
+                    metadata_hash,
+                },
+            );
+            Ok(())
+        }
+
+        /// Query a charity cause.
+        #[ink(message, selector = 0x6660E870)]
+        pub fn cause(&self, cause_id: CauseId) -> Option<Cause> {
+            self.causes.get(cause_id)
+        }
+
+        /// Query how the bot displays the amounts of an asset, by its token
+        /// contract, `None` for the native EDG.
+        ///
+        /// The native EDG falls back to its own symbol and decimals until the
+        /// owners register others.
+        #[ink(message, selector = 0x0F8B4091)]
+        pub fn asset_metadata(
+            &self,
+            asset: Option<AccountId>,
+        ) -> Option<AssetMeta> {
+            self.asset_metadata.get(asset).or_else(|| {
+                asset.is_none().then(|| AssetMeta {
+                    symbol: String::from("EDG"),
+                    decimals: EDG_DECIMALS,
+                })
+            })
+        }
+
+        /// Set the display metadata of an asset, or remove it with `None`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the symbol is empty, or
+        ///   longer than `MAX_SYMBOL_LEN` bytes.
+        #[ink(message, selector = 0xCD397F95)]
+        pub fn set_asset_metadata(
+            &mut self,
+            asset: Option<AccountId>,
+            meta: Option<AssetMeta>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            match &meta {
+                Some(meta)
+                    if meta.symbol.is_empty()
+                        || meta.symbol.len() > MAX_SYMBOL_LEN =>
+                {
+                    return Err(Error::InvalidParamValue)
+                },
+                Some(meta) => self.asset_metadata.insert(asset, meta),
+                None => self.asset_metadata.remove(asset),
+            }
+            self.env().emit_event(AssetMetadataSet {
+                asset,
+                meta: meta.clone(),
+            });
+            self.log_admin_action(
+                asset,
+                PrivilegedAction::SetAssetMetadata { asset, meta },
+            );
+            Ok(())
+        }
+
+        /// Register a tip category, rename it, or remove it with `None`, its
+        /// totals are kept.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the name is empty, or longer
+        ///   than `MAX_CATEGORY_NAME_LEN` bytes.
+        ///
+        /// * Returns `Error::TooManyMembers` if there are already
+        ///   `MAX_CATEGORIES` categories.
+        #[ink(message, selector = 0x87A01F61)]
+        pub fn set_category(
+            &mut self,
+            category: u8,
+            name: Option<String>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let index =
+                self.categories.iter().position(|(id, _)| *id == category);
+            match (&name, index) {
+                (Some(name), _)
+                    if name.is_empty()
+                        || name.len() > MAX_CATEGORY_NAME_LEN =>
+                {
+                    return Err(Error::InvalidParamValue)
+                },
+                (Some(name), Some(index)) => {
+                    self.categories[index].1 = name.clone()
+                },
+                (Some(_), None) if self.categories.len() >= MAX_CATEGORIES => {
+                    return Err(Error::TooManyMembers)
+                },
+                (Some(name), None) => {
+                    self.categories.push((category, name.clone()))
+                },
+                (None, Some(index)) => {
+                    self.categories.remove(index);
+                },
+                (None, None) => {},
+            }
+            self.env().emit_event(CategorySet {
+                category,
+                name: name.clone(),
+            });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::SetCategory { category, name },
+            );
+            Ok(())
+        }
+
+        /// Query the registered tip categories, with thier names and the
+        /// totals tipped in them.
+        #[ink(message, selector = 0x27A45A6F)]
+        pub fn category_stats(&self) -> Vec<(u8, String, CategoryStats)> {
+            self.categories
+                .iter()
+                .map(|(id, name)| {
+                    let stats = self.category_stats.get(id).unwrap_or_default();
+                    (*id, name.clone(), stats)
+                })
+                .collect()
+        }
+
+        /// Query the moderator note of an account, if any.
+        #[ink(message, selector = 0xB6175E5C)]
+        pub fn note_of(&self, account: AccountId) -> Option<Note> {
+            self.notes.get(account)
+        }
+
+        /// Set the moderator note of an account, as the hash of its full text
+        /// and a short label, or remove it with `None`.
+        ///
+        /// Only the owners, or the operator, could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither one of the
+        ///   owners nor the operator.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the label is longer than
+        ///   `MAX_NOTE_LEN` bytes.
+        #[ink(message, selector = 0x79899930)]
+        pub fn set_note(
+            &mut self,
+            account: AccountId,
+            note: Option<(Hash, Vec<u8>)>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            if caller != self.operator {
+                self.ensure_owner()?;
+            }
+            let note = match note {
+                Some((_, label)) if label.len() > MAX_NOTE_LEN => {
+                    return Err(Error::InvalidParamValue)
+                },
+                Some((hash, label)) => {
+                    let note = Note {
+                        hash,
+                        label,
+                        set_by: caller,
+                        at: self.env().block_number(),
+                    };
+                    self.notes.insert(account, &note);
+                    Some(note)
+                },
+                None => {
+                    self.notes.remove(account);
+                    None
+                },
+            };
+            let hash = note.as_ref().map(|note| note.hash);
+            self.env().emit_event(NoteSet { account, note });
+            self.log_admin_action(
+                Some(account),
+                PrivilegedAction::SetNote { hash },
+            );
+            Ok(())
+        }
+
+        /// Query the verification level of the user of the TelegramId.
+        #[ink(message, selector = 0x2971588C)]
+        pub fn verification_level_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Option<VerificationLevel> {
+            self.record_of(tg_id).map(|record| record.verification)
+        }
+
+        /// Query the limits of a verification level, `None` if it is not
+        /// limited.
+        #[ink(message, selector = 0xB6813FC5)]
+        pub fn level_limits(
+            &self,
+            level: VerificationLevel,
+        ) -> Option<LevelLimits> {
+            self.level_limits[level as usize]
+        }
+
+        /// Query whether a feature is turned on, all of them are by default.
+        #[ink(message, selector = 0x1B0976F7)]
+        pub fn is_enabled(&self, feature: Feature) -> bool {
+            self.disabled_features & feature.flag() == 0
+        }
+
+        /// Donate `amount` of the caller's balance to a charity cause, it is
+        /// transferred to the account of the cause right away, without a
+        /// fee.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the cause
+        ///   is not registered.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough balance above thier reserve.
+        ///
+        /// * Returns `Error::BelowSubsistenceThreshold` if the transfer failed.
+        #[ink(message, selector = 0x3A139BDC)]
+        pub fn donate(
+            &mut self,
+            cause_id: CauseId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            ensure_not_self_locked(&record)?;
+            let mut cause =
+                self.causes.get(cause_id).ok_or(Error::NotFound {
+                    which: Entity::Cause,
+                })?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let available = self.tippable(&record);
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            self.env()
+                .transfer(cause.account, amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            record.balance -= amount;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(amount);
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            cause.total = cause.total.saturating_add(amount);
+            self.causes.insert(cause_id, &cause);
+            self.env().emit_event(Donated {
+                cause_id,
+                from: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Escrow a budget for some work of the `worker`, split into the
+        /// `milestones` amounts, taken from the caller's balance, returning
+        /// the Id of the escrow.
+        ///
+        /// The `arbiter`, if any, could release a delivered milestone if the
+        /// caller did not within `arbiter_timeout` blocks of its delivery.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller, or the worker, is not
+        ///   bound.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::SelfTip` if the caller is the worker.
+        ///
+        /// * Returns `Error::ZeroAmount` if there are no milestones, or one of
+        ///   them is zero, and `Error::TooManyMilestones` if there are more
+        ///   than `MAX_MILESTONES`.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller does not have
+        ///   enough balance above thier reserve.
+        ///
+        /// * Returns `Error::FeatureLocked` if the verification level of the
+        ///   caller does not unlock `FEATURE_ESCROW`.
+        ///
+        /// * Returns `Error::FeatureDisabled` if escrows are disabled, see
+        ///   `AdminCall::SetFeature`.
+        #[ink(message, selector = 0x547B21C4)]
+        pub fn create_escrow(
+            &mut self,
+            worker: TelegramId,
+            milestones: Vec<Balance>,
+            arbiter: Option<AccountId>,
+            arbiter_timeout: BlockNumber,
+        ) -> Result<EscrowId, Error> {
+            ensure_not_reentered()?;
+            self.ensure_enabled(Feature::Escrow)?;
+            let (funder, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            ensure_not_self_locked(&record)?;
+            self.ensure_feature(&record, FEATURE_ESCROW)?;
+            let worker = self.address_of(worker).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if worker == funder {
+                return Err(Error::SelfTip);
+            }
+            if milestones.len() > MAX_MILESTONES {
+                return Err(Error::TooManyMilestones);
+            }
+            if milestones.is_empty() || milestones.contains(&0) {
+                return Err(Error::ZeroAmount);
+            }
+            let total = milestones
+                .iter()
+                .try_fold(0 as Balance, |total, amount| {
+                    total.checked_add(*amount)
+                })
+                .ok_or(Error::Overflow)?;
+            let available = self.tippable(&record);
+            if available < total {
+                return Err(Error::InsufficientFunds {
+                    required: total,
+                    available,
+                });
+            }
+            record.balance -= total;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(total);
+            record.last_active = self.env().block_number();
+            self.users.insert(funder, &record);
+            let escrow_id = self.next_escrow_id;
+            self.escrows.insert(
+                escrow_id,
+                &Escrow {
+                    funder,
+                    worker,
+                    arbiter,
+                    arbiter_timeout,
+                    milestones: milestones
+                        .into_iter()
+                        .map(|amount| Milestone {
+                            amount,
+                            state: MilestoneState::Pending,
+                        })
+                        .collect(),
+                },
+            );
+            self.next_escrow_id += 1;
+            self.env().emit_event(EscrowCreated {
+                escrow_id,
+                funder,
+                worker,
+                total,
+            });
+            Ok(escrow_id)
+        }
+
+        /// Query a milestone escrow.
+        #[ink(message, selector = 0x7CF75B78)]
+        pub fn escrow(&self, escrow_id: EscrowId) -> Option<Escrow> {
+            self.escrows.get(escrow_id)
+        }
+
+        /// Mark a milestone of the escrow as delivered, only the worker could
+        /// call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the escrow, or the milestone, does
+        ///   not exist.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the worker.
+        ///
+        /// * Returns `Error::AlreadyDelivered` if the milestone was already
+        ///   delivered, and `Error::Disputed` if it is disputed.
+        #[ink(message, selector = 0xC3017A4D)]
+        pub fn deliver_milestone(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut escrow = self.escrow_of(escrow_id)?;
+            if self.env().caller() != escrow.worker {
+                return Err(Error::NotAllowed);
+            }
+            let milestone = milestone_of(&mut escrow, index)?;
+            match milestone.state {
+                MilestoneState::Pending => {},
+                MilestoneState::Disputed => return Err(Error::Disputed),
+                _ => return Err(Error::AlreadyDelivered),
+            }
+            milestone.state =
+                MilestoneState::Delivered(self.env().block_number());
+            self.escrows.insert(escrow_id, &escrow);
+            self.env()
+                .emit_event(MilestoneDelivered { escrow_id, index });
+            Ok(())
+        }
+
+        /// Pay a delivered milestone of the escrow to the worker.
+        ///
+        /// The funder could release it right away, the arbiter only
+        /// `arbiter_timeout` blocks after it was delivered.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the escrow, or the milestone, does
+        ///   not exist.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is neither the funder
+        ///   nor the arbiter.
+        ///
+        /// * Returns `Error::MilestoneNotDelivered` if the milestone was not
+        ///   delivered yet, and `Error::Disputed` if it is disputed.
+        ///
+        /// * Returns `Error::ReleaseNotOpen` if the arbiter calls this before
+        ///   the timeout.
+        #[ink(message, selector = 0xCE781E95)]
+        pub fn release_milestone(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let now = self.env().block_number();
+            let mut escrow = self.escrow_of(escrow_id)?;
+            let (funder, arbiter, worker) =
+                (escrow.funder, escrow.arbiter, escrow.worker);
+            let timeout = escrow.arbiter_timeout;
+            let milestone = milestone_of(&mut escrow, index)?;
+            let delivered_at = match milestone.state {
+                MilestoneState::Delivered(at) => at,
+                MilestoneState::Disputed => return Err(Error::Disputed),
+                _ => return Err(Error::MilestoneNotDelivered),
+            };
+            if caller != funder {
+                if Some(caller) != arbiter {
+                    return Err(Error::NotAllowed);
+                }
+                let at = delivered_at.saturating_add(timeout);
+                if now < at {
+                    return Err(Error::ReleaseNotOpen { at });
+                }
+            }
+            let amount = milestone.amount;
+            milestone.state = MilestoneState::Released;
+            self.credit(worker, amount)?;
+            self.escrows.insert(escrow_id, &escrow);
+            self.env().emit_event(MilestoneReleased {
+                escrow_id,
+                index,
+                by: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Refund the milestones of the escrow that were not delivered yet
+        /// to the funder, the delivered ones could still be released.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the escrow does not exist, or none of
+        ///   its milestones is pending.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the funder.
+        #[ink(message, selector = 0x04B3469B)]
+        pub fn refund_escrow(
+            &mut self,
+            escrow_id: EscrowId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut escrow = self.escrow_of(escrow_id)?;
+            if self.env().caller() != escrow.funder {
+                return Err(Error::NotAllowed);
+            }
+            let mut amount: Balance = 0;
+            for milestone in &mut escrow.milestones {
+                if milestone.state == MilestoneState::Pending {
+                    milestone.state = MilestoneState::Refunded;
+                    amount += milestone.amount;
+                }
+            }
+            if amount == 0 {
+                return Err(Error::NotFound {
+                    which: Entity::Milestone,
+                });
+            }
+            self.credit(escrow.funder, amount)?;
+            self.escrows.insert(escrow_id, &escrow);
+            self.env().emit_event(EscrowRefunded { escrow_id, amount });
+            Ok(())
+        }
+
+        /// Query the panel of arbiters, and the fee paid to open a dispute.
+        #[ink(message, selector = 0x18E785C4)]
+        pub fn arbiters(&self) -> (Vec<AccountId>, Balance) {
+            (self.arbiters.clone(), self.dispute_fee)
+        }
+
+        /// Query the dispute over a milestone of an escrow.
+        #[ink(message, selector = 0x8A718822)]
+        pub fn dispute(
+            &self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Option<Dispute> {
+            self.disputes.get((escrow_id, index))
+        }
+
+        /// Dispute a milestone of an escrow that is not released, nor
+        /// refunded, yet, paying the `dispute_fee` from the caller's balance.
+        ///
+        /// The arbiters vote on it for `DISPUTE_VOTING_PERIOD` blocks, and the
+        /// milestone is released to the worker, or refunded to the funder, as
+        /// soon as a majority of them agree, or by `resolve_dispute` after
+        /// the voting ends.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the
+        ///   escrow, or the milestone, does not exist.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is neither the funder
+        ///   nor the worker.
+        ///
+        /// * Returns `Error::Disputed` if the milestone is already disputed.
+        ///
+        /// * Returns `Error::ArbitrationDisabled` if there are no arbiters.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the fee.
+        #[ink(message, selector = 0x2506C6D0)]
+        pub fn open_dispute(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            let mut escrow = self.escrow_of(escrow_id)?;
+            if caller != escrow.funder && caller != escrow.worker {
+                return Err(Error::NotAllowed);
+            }
+            let milestone = milestone_of(&mut escrow, index)?;
+            if milestone.state == MilestoneState::Disputed {
+                return Err(Error::Disputed);
+            }
+            if self.arbiters.is_empty() {
+                return Err(Error::ArbitrationDisabled);
+            }
+            let fee = self.dispute_fee;
+            let available = self.tippable(&record);
+            if available < fee {
+                return Err(Error::InsufficientFunds {
+                    required: fee,
+                    available,
+                });
+            }
+            let previous = milestone.state;
+            milestone.state = MilestoneState::Disputed;
+            let now = self.env().block_number();
+            let ends_at = now.saturating_add(DISPUTE_VOTING_PERIOD);
+            record.balance -= fee;
+            record.audit.fees_paid = record.audit.fees_paid.saturating_add(fee);
+            record.last_active = now;
+            self.users.insert(caller, &record);
+            self.escrows.insert(escrow_id, &escrow);
+            self.disputes.insert(
+                (escrow_id, index),
+                &Dispute {
+                    opened_by: caller,
+                    fee,
+                    previous,
+                    ends_at,
+                    arbiters: self.arbiters.clone(),
+                    release_votes: Vec::new(),
+                    refund_votes: Vec::new(),
+                },
+            );
+            self.env().emit_event(DisputeOpened {
+                escrow_id,
+                index,
+                by: caller,
+                ends_at,
+            });
+            Ok(())
+        }
+
+        /// Vote on a dispute as one of its arbiters, to `release` the
+        /// milestone to the worker, or to refund it to the funder, deciding
+        /// it once a majority of the arbiters agree.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the milestone is not disputed.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not one of the
+        ///   arbiters of the dispute.
+        ///
+        /// * Returns `Error::VotingClosed` if the voting already ended.
+        ///
+        /// * Returns `Error::AlreadyVoted` if the caller already voted.
+        #[ink(message, selector = 0x56B010DA)]
+        pub fn vote_on_dispute(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+            release: bool,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let mut dispute = self.dispute_of(escrow_id, index)?;
+            if !dispute.arbiters.contains(&caller) {
+                return Err(Error::NotAllowed);
+            }
+            if self.env().block_number() >= dispute.ends_at {
+                return Err(Error::VotingClosed);
+            }
+            if dispute.release_votes.contains(&caller)
+                || dispute.refund_votes.contains(&caller)
+            {
+                return Err(Error::AlreadyVoted);
+            }
+            let votes = if release {
+                &mut dispute.release_votes
+            } else {
+                &mut dispute.refund_votes
+            };
+            votes.push(caller);
+            if votes.len() > dispute.arbiters.len() / 2 {
+                return self.decide_dispute(
+                    escrow_id,
+                    index,
+                    dispute,
+                    Some(release),
+                );
+            }
+            self.disputes.insert((escrow_id, index), &dispute);
+            Ok(())
+        }
+
+        /// Decide a dispute after its voting ended, by the majority of the
+        /// votes cast.
+        ///
+        /// If the vote is tied, the milestone goes back to where it was
+        /// before the dispute, and the fee is refunded.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the milestone is not disputed.
+        ///
+        /// * Returns `Error::VotingOpen` if the voting did not end yet.
+        #[ink(message, selector = 0x539B8B08)]
+        pub fn resolve_dispute(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let dispute = self.dispute_of(escrow_id, index)?;
+            if self.env().block_number() < dispute.ends_at {
+                return Err(Error::VotingOpen {
+                    ends_at: dispute.ends_at,
+                });
+            }
+            let (release, refund) =
+                (dispute.release_votes.len(), dispute.refund_votes.len());
+            let outcome = match release.cmp(&refund) {
+                core::cmp::Ordering::Greater => Some(true),
+                core::cmp::Ordering::Less => Some(false),
+                core::cmp::Ordering::Equal => None,
+            };
+            self.decide_dispute(escrow_id, index, dispute, outcome)
+        }
+
+        /// Claim an alias for the TelegramId of the caller, replacing its
+        /// previous alias if any.
+        ///
+        /// Aliases resolve to the TelegramId, so they survive changes of the
+        /// Telegram username and rebinding to other accounts. The alias is
+        /// normalized first, see `resolve_alias`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::InvalidAlias` if the alias is not valid.
+        ///
+        /// * Returns `Error::AliasReserved` if the alias is reserved.
+        ///
+        /// * Returns `Error::AliasTaken` if another TelegramId holds the alias.
+        #[ink(message, selector = 0x19D28C89)]
+        pub fn claim_alias(&mut self, name: String) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let name = normalize_alias(&name).ok_or(Error::InvalidAlias)?;
+            if self.reserved_aliases.get(&name).unwrap_or(false) {
+                return Err(Error::AliasReserved);
+            }
+            match self.alias_holders.get(&name) {
+                Some(holder) if holder == record.tg_id => return Ok(()),
+                Some(_) => return Err(Error::AliasTaken),
+                None => (),
+            }
+            self.assign_alias(name, record.tg_id);
+            Ok(())
+        }
+
+        /// Release the alias held by the TelegramId of the caller.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or the alias
+        ///   is not held by the caller.
+        ///
+        /// * Returns `Error::InvalidAlias` if the alias is not valid.
+        #[ink(message, selector = 0xD452CC64)]
+        pub fn release_alias(&mut self, name: String) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let name = normalize_alias(&name).ok_or(Error::InvalidAlias)?;
+            if self.alias_holders.get(&name) != Some(record.tg_id) {
+                return Err(Error::NotFound {
+                    which: Entity::Alias,
+                });
+            }
+            self.unassign_alias(name, record.tg_id);
+            Ok(())
+        }
+
+        /// Reserve an alias so it could not be claimed, optionally assigning
+        /// it to the TelegramId of the `holder`, or lift its reservation.
+        ///
+        /// Reserving an alias takes it from its current holder, and lifting
+        /// the reservation releases it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is not one of the owners
+        ///   of the contract.
+        ///
+        /// * Returns `Error::InvalidAlias` if the alias is not valid.
+        #[ink(message, selector = 0xD2A85B3F)]
+        pub fn reserve_alias(
+            &mut self,
+            name: String,
+            reserved: bool,
+            holder: Option<TelegramId>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_owner()?;
+            let name = normalize_alias(&name).ok_or(Error::InvalidAlias)?;
+            if let Some(tg_id) = self.alias_holders.get(&name) {
+                self.unassign_alias(name.clone(), tg_id);
+            }
+            if reserved {
+                self.reserved_aliases.insert(&name, &true);
+                if let Some(tg_id) = holder {
+                    self.assign_alias(name.clone(), tg_id);
+                }
+            } else {
+                self.reserved_aliases.remove(&name);
+            }
+            self.env().emit_event(AliasReservationChanged {
+                name: name.clone(),
+                reserved,
+            });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::ReserveAlias {
+                    name,
+                    reserved,
+                    holder,
+                },
+            );
+            Ok(())
+        }
+
+        /// Similar to tip, but the emitted events do not reveal the caller.
+        ///
+        /// Only a commitment to the caller, the blake2x256 hash of the encoded
+        /// `(caller, salt)`, is published, so the caller could later prove
+        /// the tip by revealing the `salt`. The hooks are not notified about
+        /// anonymous tips.
+        ///
+        /// Errors:
+        /// Same as `tip`.
+        #[ink(message, selector = 0x204ED430)]
+        pub fn tip_anonymous(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+            salt: Hash,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let commitment = self.commitment_of(caller, salt);
+            self.tip_account(
+                caller,
+                record,
+                target,
+                amount,
+                Some(commitment),
+                None,
+                None,
+            )
+        }
+
+        /// Similar to tip, but only the operator, or a relayer allowed to tip
+        /// on behalf of users, can call this function.
+        ///
+        /// Called in behalf of the `from` TelegramId owner using the bot. if
+        /// an `anonymous` salt is given, the tip is anonymous as in
+        /// `tip_anonymous`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither the operator
+        ///   of the contract nor such a relayer, or the `from` user disabled
+        ///   tipping by the bot.
+        ///
+        /// * Returns `Error::RelayerCapExceeded` if the tip would take the
+        ///   relayer above its `volume_cap`.
+        ///
+        /// * Returns `Error::NotFound` with `Entity::Sender` or
+        ///   `Entity::Recipient` if the `from` or `to` is not bounded to any
+        ///   telegram account.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the `from` user can not
+        ///   cover the relay fee, on top of the tip.
+        ///
+        /// * Returns `Error::NotFound` with `Entity::Category` if the
+        ///   `category` is not registered.
+        ///
+        /// The relay fee is charged to the `from` user, and set aside for the
+        /// caller. The tip is counted in the `category_stats` of the
+        /// `category`, if one is given.
+        #[ink(message, selector = 0x9BD7E6B7)]
+        pub fn tip_from(
+            &mut self,
+            from: TelegramId,
+            to: TelegramId,
+            amount: Balance,
+            anonymous: Option<Hash>,
+            category: Option<u8>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let relayer = self
+                .relayer_for(|permissions| permissions.can_tip_from, amount)?;
+            self.ensure_category(category)?;
+            let from = self.address_of(from).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            let to = self.address_of(to).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let mut record = self
+                .users
+                .get(from)
+                .expect("every bound telegram id has a record");
+            if !record.settings.bot_tipping_enabled {
+                return Err(Error::NotAllowed);
+            }
+            let fee = self.relay_fee;
+            let available = self.spendable(&record);
+            if available < fee {
+                return Err(Error::InsufficientFunds {
+                    required: fee,
+                    available,
+                });
+            }
+            record.balance -= fee;
+            record.audit.fees_paid = record.audit.fees_paid.saturating_add(fee);
+            let commitment =
+                anonymous.map(|salt| self.commitment_of(from, salt));
+            self.tip_account(from, record, to, amount, commitment, None, None)?;
+            self.count_category(category, amount);
+            self.reimburse_relayer(relayer, fee);
+            Ok(())
+        }
+
+        /// Query the flat fee charged for every tip relayed by the operator.
+        #[ink(message, selector = 0xE614CBFA)]
+        pub fn relay_fee(&self) -> Balance { self.relay_fee }
+
+        /// Query the relay fees the operator did not claim yet.
+        #[ink(message, selector = 0x9D7BAB79)]
+        pub fn relay_reimbursement(&self) -> Balance {
+            self.relay_reimbursement
+        }
+
+        /// Query the total relay fees charged so far.
+        #[ink(message, selector = 0x53955E11)]
+        pub fn total_relay_fees(&self) -> Balance { self.total_relay_fees }
+
+        /// Pay the unclaimed relay fees to the operator, or to the relayer
+        /// calling this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither the operator
+        ///   of the contract nor a relayer.
+        ///
+        /// * Returns `Error::ZeroAmount` if there is nothing to claim.
+        #[ink(message, selector = 0x2045D0AF)]
+        pub fn claim_relay_reimbursement(&mut self) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let operator = self.env().caller();
+            let relayer = if operator == self.operator {
+                None
+            } else {
+                Some(self.relayers.get(operator).ok_or(Error::NotAllowed)?)
+            };
+            let amount = relayer
+                .as_ref()
+                .map_or(self.relay_reimbursement, |relayer| {
+                    relayer.reimbursement
+                });
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            self.env()
+                .transfer(operator, amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            match relayer {
+                Some(mut relayer) => {
+                    relayer.reimbursement = 0;
+                    self.relayers.insert(operator, &relayer);
+                },
+                None => self.relay_reimbursement = 0,
+            }
+            self.env().emit_event(RelayReimbursed { operator, amount });
+            self.log_admin_action(
+                None,
+                PrivilegedAction::ClaimRelayReimbursement(amount),
+            );
+            Ok(())
+        }
+
+        /// Query a relayer other than the operator.
+        #[ink(message, selector = 0x36BCD880)]
+        pub fn relayer(&self, relayer: AccountId) -> Option<Relayer> {
+            self.relayers.get(relayer)
+        }
+
+        /// Query a registered group.
+        #[ink(message, selector = 0xDB0E26F0)]
+        pub fn group(&self, group_id: GroupId) -> Option<Group> {
+            self.groups.get(group_id)
+        }
+
+        /// Query the lowest fee the groups could take from thier tips, in
+        /// basis points.
+        #[ink(message, selector = 0x9255580F)]
+        pub fn group_fee_floor(&self) -> u16 { self.group_fee_floor }
+
+        /// Query the tipping statistics of the TelegramId in a group.
+        #[ink(message, selector = 0x61E70A58)]
+        pub fn group_stats_of(
+            &self,
+            group_id: GroupId,
+            tg_id: TelegramId,
+        ) -> Option<UserStats> {
+            self.address_of(tg_id)
+                .and_then(|address| self.group_stats.get((group_id, address)))
+        }
+
+        /// Query a leaderboard of a group, as the TelegramIds and thier
+        /// totals, most first.
+        ///
+        /// Users who unbound since are left out.
+        #[ink(message, selector = 0x36028A2B)]
+        pub fn group_leaderboard(
+            &self,
+            group_id: GroupId,
+            ranking: Ranking,
+        ) -> Vec<(TelegramId, Balance)> {
+            let group = match self.groups.get(group_id) {
+                Some(group) => group,
+                None => return Vec::new(),
+            };
+            let board = match ranking {
+                Ranking::Senders => group.top_senders,
+                Ranking::Receivers => group.top_receivers,
+            };
+            board
+                .into_iter()
+                .filter_map(|(account, total)| {
+                    let record = self.users.get(account)?;
+                    Some((record.tg_id, total))
+                })
+                .collect()
+        }
+
+        /// Register a group, the caller becomes its registrant.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound.
+        ///
+        /// * Returns `Error::AlreadyRegistered` if the group is already
+        ///   registered.
+        ///
+        /// * Returns `Error::TooManyPositions` if the caller has too many open
+        ///   positions.
+        #[ink(message, selector = 0x70DBC85F)]
+        pub fn register_group(
+            &mut self,
+            group_id: GroupId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let caller = self.env().caller();
+            if !self.users.contains(caller) {
+                return Err(Error::NotFound {
+                    which: Entity::Account,
+                });
+            }
+            if self.groups.contains(group_id) {
+                return Err(Error::AlreadyRegistered);
+            }
+            self.add_position(caller, Position::Group(group_id))?;
+            self.groups.insert(
+                group_id,
+                &Group {
+                    registrant: caller,
+                    moderators: Vec::new(),
+                    config: GroupConfig::default(),
+                    treasury: 0,
+                    tips: 0,
+                    volume: 0,
+                    top_senders: Vec::new(),
+                    top_receivers: Vec::new(),
+                },
+            );
+            self.group_ids.insert(self.groups_count, &group_id);
+            self.groups_count += 1;
+            self.env().emit_event(GroupRegistered {
+                group_id,
+                registrant: caller,
+            });
+            Ok(())
+        }
+
+        /// Donate the transferred value to the pot of a group.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered.
+        ///
+        /// * Returns `Error::ZeroAmount` if nothing is transferred.
+        ///
+        /// * Returns `Error::Overflow` if the group pot would overflow.
+        #[ink(message, payable, selector = 0x9251B726)]
+        pub fn donate_to_group(
+            &mut self,
+            group_id: GroupId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut group = self.group_of(group_id)?;
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            group.treasury =
+                group.treasury.checked_add(amount).ok_or(Error::Overflow)?;
+            self.groups.insert(group_id, &group);
+            self.env().emit_event(GroupDonation {
+                group_id,
+                from: self.env().caller(),
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Tip `bps` basis points of the caller's spendable balance, as it
+        /// is when the tip is executed, to the `tg_id`.
+        ///
+        /// If the caller rounds up thier tips, the tip is lowered to a whole
+        /// EDG when rounding it up would not fit in the balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::InvalidParamValue` if `bps` is above `MAX_BPS`.
+        ///
+        /// * Otherwise, same as `tip`.
+        #[ink(message, selector = 0x08E58AA9)]
+        pub fn tip_percent(
+            &mut self,
+            tg_id: TelegramId,
+            bps: u16,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.tip_share(tg_id, bps)
+        }
+
+        /// Tip all of the caller's spendable balance, as it is when the tip
+        /// is executed, to the `tg_id`, the same as `tip_percent` with
+        /// `MAX_BPS`.
+        ///
+        /// Errors:
+        /// Same as `tip_percent`.
+        #[ink(message, selector = 0x720D4CCB)]
+        pub fn tip_all(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.tip_share(tg_id, MAX_BPS)
+        }
+
+        /// Tip `total` to the `recipients` in proportion to thier weights,
+        /// as one tip to each of them.
+        ///
+        /// A recipient listed twice gets the sum of its weights. The shares
+        /// are rounded down, and the rounding dust is not charged.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the `total` is zero, or there are
+        ///   no recipients, and `Error::TooManyRecipients` if there are more
+        ///   than `MAX_WEIGHTED_RECIPIENTS`.
+        ///
+        /// * Returns `Error::ZeroWeight` if one of the weights is zero.
+        ///
+        /// * Otherwise, same as `tip`, for any of the shares, in which case
+        ///   none of them is paid.
+        #[ink(message, selector = 0x30F29908)]
+        pub fn tip_weighted(
+            &mut self,
+            recipients: Vec<(TelegramId, u32)>,
+            total: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            if total == 0 || recipients.is_empty() {
+                return Err(Error::ZeroAmount);
+            }
+            if recipients.iter().any(|(_, weight)| *weight == 0) {
+                return Err(Error::ZeroWeight);
+            }
+            let mut weights: Vec<(TelegramId, Balance)> = Vec::new();
+            for (tg_id, weight) in recipients {
+                match weights.iter_mut().find(|(listed, _)| *listed == tg_id) {
+                    Some((_, sum)) => *sum += Balance::from(weight),
+                    None => weights.push((tg_id, Balance::from(weight))),
+                }
+            }
+            if weights.len() > MAX_WEIGHTED_RECIPIENTS {
+                return Err(Error::TooManyRecipients);
+            }
+            let sum: Balance = weights.iter().map(|(_, weight)| weight).sum();
+            let mut shares = Vec::with_capacity(weights.len());
+            for (tg_id, weight) in weights {
+                let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                    which: Entity::Recipient,
+                })?;
+                shares.push((target, pro_rata(total, weight, sum)));
+            }
+            // check every share as if the ones before it were paid, so none
+            // is paid unless all of them could be.
+            let era = self.current_era();
+            let mut preview = record;
+            for &(target, share) in shares.iter() {
+                let (_, round_up, _) =
+                    self.check_tip(caller, &preview, target, share, None)?;
+                let charged = share + round_up;
+                preview.spent_today = ledger::spent_in(
+                    preview.spent_today,
+                    preview.spent_era,
+                    era,
+                ) + charged;
+                preview.spent_era = era;
+                preview.balance -= charged;
+            }
+            for (target, share) in shares {
+                let record =
+                    self.users.get(caller).expect("the sender is still bound");
+                self.tip_account(
+                    caller, record, target, share, None, None, None,
+                )?;
+            }
+            Ok(())
+        }
+
+        /// Similar to tip, but the tip is sent in a group, and counted
+        /// towards its statistics and leaderboards.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` with `Entity::Group` if the group is not
+        ///   registered.
+        ///
+        /// * Returns `Error::BannedFromGroup` if the caller or the recipient is
+        ///   banned from the group.
+        ///
+        /// * Returns `Error::TipCooldown` if the caller tipped in the group
+        ///   less than its cooldown ago.
+        ///
+        /// * Otherwise, same as `tip`, with the policy of the group.
+        #[ink(message, selector = 0x40AD9D04)]
+        pub fn tip_in_group(
+            &mut self,
+            group_id: GroupId,
+            tg_id: TelegramId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.group_of(group_id)?;
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            self.ensure_not_banned(group_id, record.tg_id)?;
+            self.ensure_not_banned(group_id, tg_id)?;
+            self.tip_account(
+                caller,
+                record,
+                target,
+                amount,
+                None,
+                Some(group_id),
+                None,
+            )
+        }
+
+        /// Check whether `from_tg` could tip `amount` to `to_tg`, optionally
+        /// in a group, without sending anything, so the bot could tell why a
+        /// tip would fail before submitting it.
+        ///
+        /// Errors:
+        /// Returns the error the same `tip`, or `tip_in_group` if a group is
+        /// given, would fail with, except the ones only the transfer itself
+        /// could tell.
+        #[ink(message, selector = 0x08A7754F)]
+        pub fn can_tip(
+            &self,
+            from_tg: TelegramId,
+            to_tg: TelegramId,
+            amount: Balance,
+            group: Option<GroupId>,
+        ) -> Result<(), Error> {
+            if let Some(group_id) = group {
+                self.group_of(group_id)?;
+            }
+            let caller = self.address_of(from_tg).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            let record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Sender,
+            })?;
+            let target = self.address_of(to_tg).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if let Some(group_id) = group {
+                self.ensure_not_banned(group_id, from_tg)?;
+                self.ensure_not_banned(group_id, to_tg)?;
+            }
+            self.check_tip(caller, &record, target, amount, group)
+                .map(|_| ())
+        }
+
+        /// Query whether the TelegramId is banned from the group.
+        #[ink(message, selector = 0x93122A52)]
+        pub fn is_banned_from_group(
+            &self,
+            group_id: GroupId,
+            tg_id: TelegramId,
+        ) -> bool {
+            self.group_bans.contains((group_id, tg_id))
+        }
+
+        /// Add a moderator to a group, only the registrant of the group can
+        /// call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the registrant.
+        ///
+        /// * Returns `Error::AlreadyRegistered` if the account is already a
+        ///   moderator.
+        ///
+        /// * Returns `Error::TooManyModerators` if there is no room for more
+        ///   moderators.
+        ///
+        /// * Returns `Error::TooManyPositions` if the account has too many open
+        ///   positions, it could leave groups by `remove_group_moderator`.
+        #[ink(message, selector = 0x0D82E206)]
+        pub fn add_group_moderator(
+            &mut self,
+            group_id: GroupId,
+            moderator: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut group = self.group_of(group_id)?;
+            if self.env().caller() != group.registrant {
+                return Err(Error::NotAllowed);
+            }
+            if group.moderators.contains(&moderator) {
+                return Err(Error::AlreadyRegistered);
+            }
+            if group.moderators.len() >= MAX_GROUP_MODERATORS {
+                return Err(Error::TooManyModerators);
+            }
+            self.add_position(moderator, Position::Group(group_id))?;
+            group.moderators.push(moderator);
+            self.groups.insert(group_id, &group);
+            Ok(())
+        }
+
+        /// Remove a moderator from a group, only the registrant of the group,
+        /// or the moderator leaving it, can call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered, or the
+        ///   account is not a moderator of it.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is neither the
+        ///   registrant nor the moderator.
+        #[ink(message, selector = 0xB0A6207C)]
+        pub fn remove_group_moderator(
+            &mut self,
+            group_id: GroupId,
+            moderator: AccountId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut group = self.group_of(group_id)?;
+            let caller = self.env().caller();
+            if caller != group.registrant && caller != moderator {
+                return Err(Error::NotAllowed);
+            }
+            let index = group
+                .moderators
+                .iter()
+                .position(|account| account == &moderator)
+                .ok_or(Error::NotFound {
+                    which: Entity::Moderator,
+                })?;
+            group.moderators.swap_remove(index);
+            self.groups.insert(group_id, &group);
+            Ok(())
+        }
+
+        /// Set the tipping policy of a group.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not a moderator of
+        ///   the group.
+        ///
+        /// * Returns `Error::InvalidParamValue` if the fee is more than
+        ///   `MAX_BPS`.
+        #[ink(message, selector = 0x0CD46950)]
+        pub fn set_group_config(
+            &mut self,
+            group_id: GroupId,
+            config: GroupConfig,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut group = self.ensure_moderator(group_id)?;
+            if config.fee_bps.is_some_and(|fee_bps| fee_bps > MAX_BPS) {
+                return Err(Error::InvalidParamValue);
+            }
+            group.config = config;
+            self.groups.insert(group_id, &group);
+            Ok(())
+        }
+
+        /// Ban, or lift the ban of, a user from tipping and raining in a
+        /// group.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not a moderator of
+        ///   the group.
+        #[ink(message, selector = 0x8CED1439)]
+        pub fn ban_from_group(
+            &mut self,
+            group_id: GroupId,
+            tg_id: TelegramId,
+            banned: bool,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            self.ensure_moderator(group_id)?;
+            if banned {
+                self.group_bans.insert((group_id, tg_id), &());
+            } else {
+                self.group_bans.remove((group_id, tg_id));
+            }
+            self.env().emit_event(GroupBan {
+                group_id,
+                tg_id,
+                banned,
+            });
+            self.log_admin_action(
+                self.address_of(tg_id),
+                PrivilegedAction::BanFromGroup {
+                    group_id,
+                    tg_id,
+                    banned,
+                },
+            );
+            Ok(())
+        }
+
+        /// Pay `amount` from the group pot into the balance of every one of
+        /// the `recipients`, once each.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the group is not registered, or one
+        ///   of the recipients is not bound.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not a moderator of
+        ///   the group.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero, or there are
+        ///   no recipients, and `Error::TooManyRecipients` if there are more
+        ///   than `MAX_RAIN_RECIPIENTS`.
+        ///
+        /// * Returns `Error::BannedFromGroup` if one of the recipients is
+        ///   banned from the group.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the group pot can not cover
+        ///   the rain.
+        #[ink(message, selector = 0xB7C7B331)]
+        pub fn rain_in_group(
+            &mut self,
+            group_id: GroupId,
+            recipients: Vec<TelegramId>,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut group = self.ensure_moderator(group_id)?;
+            // a recipient listed twice is still paid once.
+            let mut recipients = recipients;
+            recipients.sort_unstable();
+            recipients.dedup();
+            if amount == 0 || recipients.is_empty() {
+                return Err(Error::ZeroAmount);
+            }
+            if recipients.len() > MAX_RAIN_RECIPIENTS {
+                return Err(Error::TooManyRecipients);
+            }
+            let total = amount
+                .checked_mul(recipients.len() as Balance)
+                .filter(|total| *total <= group.treasury)
+                .ok_or(Error::InsufficientFunds {
+                    required: amount
+                        .saturating_mul(recipients.len() as Balance),
+                    available: group.treasury,
+                })?;
+            let mut records = Vec::with_capacity(recipients.len());
+            for &tg_id in recipients.iter() {
+                self.ensure_not_banned(group_id, tg_id)?;
+                let address =
+                    self.address_of(tg_id).ok_or(Error::NotFound {
+                        which: Entity::Recipient,
+                    })?;
+                let mut record = self
+                    .users
+                    .get(address)
+                    .expect("every bound telegram id has a record");
+                record.balance = record
+                    .balance
+                    .checked_add(amount)
+                    .ok_or(Error::Overflow)?;
+                record.audit.tips_in =
+                    record.audit.tips_in.saturating_add(amount);
+                records.push((address, record));
+            }
+            for (address, record) in records {
+                self.users.insert(address, &record);
+            }
+            group.treasury -= total;
+            self.groups.insert(group_id, &group);
+            self.env().emit_event(GroupRain {
+                group_id,
+                moderator: self.env().caller(),
+                recipients: recipients.len() as u32,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Query a team wallet.
+        #[ink(message, selector = 0x9F526839)]
+        pub fn team(&self, team_id: TeamId) -> Option<Team> {
+            self.teams.get(team_id)
+        }
+
+        /// Query a tip from a team wallet waiting for approvals.
+        #[ink(message, selector = 0x347CD100)]
+        pub fn team_tip(&self, team_tip_id: TeamTipId) -> Option<TeamTip> {
+            self.team_tips.get(team_tip_id)
+        }
+
+        /// Create a team wallet, spent by `threshold` of the `members`.
+        ///
+        /// Errors:
+        /// * Returns `Error::TooManyMembers` if there are more than
+        ///   `MAX_TEAM_MEMBERS` members.
+        ///
+        /// * Returns `Error::InvalidThreshold` if the threshold is zero or more
+        ///   than the number of members.
+        ///
+        /// * Returns `Error::Overflow` if the idle period of the team would
+        ///   overflow the block number.
+        #[ink(message, selector = 0x27F48408)]
+        pub fn create_team(
+            &mut self,
+            name_hash: Hash,
+            members: Vec<TelegramId>,
+            threshold: u32,
+        ) -> Result<TeamId, Error> {
+            ensure_not_reentered()?;
+            let mut members = members;
+            members.sort_unstable();
+            members.dedup();
+            if members.len() > MAX_TEAM_MEMBERS {
+                return Err(Error::TooManyMembers);
+            }
+            if threshold == 0 || threshold as usize > members.len() {
+                return Err(Error::InvalidThreshold);
+            }
+            let idle_until = self
+                .env()
+                .block_number()
+                .checked_add(TEAM_IDLE_PERIOD)
+                .ok_or(Error::Overflow)?;
+            let team_id = self.next_team_id;
+            self.next_team_id += 1;
+            self.teams.insert(
+                team_id,
+                &Team {
+                    name_hash,
+                    members,
+                    threshold,
+                    balance: 0,
+                    contributors: Vec::new(),
+                    contributed: 0,
+                    idle_until,
+                },
+            );
+            self.env().emit_event(TeamCreated { team_id, name_hash });
+            Ok(team_id)
+        }
+
+        /// Add the transferred value to the balance of a team.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the team does not exist.
+        ///
+        /// * Returns `Error::ZeroAmount` if nothing is transferred.
+        ///
+        /// * Returns `Error::TooManyContributors` if the caller would be more
+        ///   than `MAX_TEAM_CONTRIBUTORS` contributors.
+        ///
+        /// * Returns `Error::Overflow` if the team balance would overflow.
+        #[ink(message, payable, selector = 0x6503E086)]
+        pub fn fund_team(&mut self, team_id: TeamId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut team = self.team_of(team_id)?;
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let caller = self.env().caller();
+            team.balance =
+                team.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            team.contributed = team
+                .contributed
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            let funded = team
+                .contributors
+                .iter_mut()
+                .find(|(contributor, _)| *contributor == caller);
+            match funded {
+                Some((_, funded)) => *funded += amount,
+                None => {
+                    if team.contributors.len() >= MAX_TEAM_CONTRIBUTORS {
+                        return Err(Error::TooManyContributors);
+                    }
+                    team.contributors.push((caller, amount));
+                },
+            }
+            self.teams.insert(team_id, &team);
+            Ok(())
+        }
+
+        /// Cancel a team that paid no tip for `TEAM_IDLE_PERIOD` blocks,
+        /// refunding its balance to the contributors, each in proportion to
+        /// what they funded.
+        ///
+        /// Anyone could call this. A share that could not be refunded, and
+        /// the rounding, go to the treasury.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the team does not exist.
+        ///
+        /// * Returns `Error::NotDormant` if the team paid a tip too recently.
+        #[ink(message, selector = 0x002BA0F3)]
+        pub fn cancel_team(&mut self, team_id: TeamId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let team = self.team_of(team_id)?;
+            if self.env().block_number() < team.idle_until {
+                return Err(Error::NotDormant {
+                    until: team.idle_until,
+                });
+            }
+            self.refund_team(team_id, team);
+            Ok(())
+        }
+
+        /// Propose a tip from a team wallet, approved by the caller, it is
+        /// paid once `threshold` members approve it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the team does not exist.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not a member of the
+        ///   team.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns any error of paying the tip, if paid.
+        #[ink(message, selector = 0xCC066E8E)]
+        pub fn propose_team_tip(
+            &mut self,
+            team_id: TeamId,
+            tg_id: TelegramId,
+            amount: Balance,
+        ) -> Result<TeamTipId, Error> {
+            ensure_not_reentered()?;
+            let team = self.team_of(team_id)?;
+            let member = self.ensure_member(&team)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let team_tip_id = self.next_team_tip_id;
+            self.next_team_tip_id += 1;
+            self.env().emit_event(TeamTipProposed {
+                team_tip_id,
+                team_id,
+                to: tg_id,
+                amount,
+            });
+            let tip = TeamTip {
+                team_id,
+                to: tg_id,
+                amount,
+                approvals: ink_prelude::vec![member],
+            };
+            self.approve_team_tip_with(team_tip_id, team, tip)?;
+            Ok(team_tip_id)
+        }
+
+        /// Approve a tip from a team wallet, paying it once it has
+        /// `threshold` approvals.
+        ///
+        /// If paying the tip fails the approval is not counted, so it could
+        /// be retried later.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the team tip does not exist.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not a member of the
+        ///   team.
+        ///
+        /// * Returns `Error::AlreadyApproved` if the caller already approved
+        ///   it.
+        ///
+        /// * Returns any error of paying the tip, if paid.
+        #[ink(message, selector = 0xF0C2263D)]
+        pub fn approve_team_tip(
+            &mut self,
+            team_tip_id: TeamTipId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut tip =
+                self.team_tips.get(team_tip_id).ok_or(Error::NotFound {
+                    which: Entity::TeamTip,
+                })?;
+            let team = self.team_of(tip.team_id)?;
+            let member = self.ensure_member(&team)?;
+            if tip.approvals.contains(&member) {
+                return Err(Error::AlreadyApproved);
+            }
+            tip.approvals.push(member);
+            self.approve_team_tip_with(team_tip_id, team, tip)
+        }
+
+        /// Query the scheduled termination, as the beneficiary and the block
+        /// it could be executed at.
+        #[ink(message, selector = 0x70029E5B)]
+        pub fn termination(&self) -> Option<(AccountId, BlockNumber)> {
+            self.termination
+        }
+
+        /// Refund up to `limit` items once the termination is due, returning
+        /// how many were refunded, so the termination could be spread over
+        /// as many calls as it takes.
+        ///
+        /// Anyone could call this. The funds held for the users are refunded
+        /// first, stage by stage as `WindDownStage` lists them, then each
+        /// user is unbound and refunded thier balance and bind deposit, the
+        /// balances left by removed bindings are paid, and the tipEDG of
+        /// every holder is redeemed. A refund that could not be transferred,
+        /// like one below the existential deposit, or a tip jar whose author
+        /// is not bound, is left to the beneficiary.
+        ///
+        /// At most `MAX_WIND_DOWN` items are refunded at once.
+        ///
+        /// Errors:
+        /// * Returns `Error::TerminationNotScheduled` if the termination was
+        ///   not scheduled.
+        ///
+        /// * Returns `Error::TerminationTimelocked` if it is too early.
+        #[ink(message, selector = 0x7308AC39)]
+        pub fn wind_down(&mut self, limit: u32) -> Result<u32, Error> {
+            ensure_not_reentered()?;
+            self.ensure_termination_due()?;
+            let (mut stage, mut cursor) =
+                self.wind_down_cursor.unwrap_or((WindDownStage::Groups, 0));
+            let mut refunded = 0;
+            while refunded < limit.min(MAX_WIND_DOWN) {
+                match stage {
+                    WindDownStage::Groups if cursor < self.groups_count => {
+                        self.refund_group_pot(cursor);
+                        cursor += 1;
+                    },
+                    WindDownStage::Groups => {
+                        stage = WindDownStage::Teams;
+                        cursor = 0;
+                        continue;
+                    },
+                    WindDownStage::Teams if cursor < self.next_team_id => {
+                        if let Some(team) = self.teams.get(cursor) {
+                            self.refund_team(cursor, team);
+                        }
+                        cursor += 1;
+                    },
+                    WindDownStage::Teams => {
+                        stage = WindDownStage::Escrows;
+                        cursor = 0;
+                        continue;
+                    },
+                    WindDownStage::Escrows if cursor < self.next_escrow_id => {
+                        self.refund_escrow_of(cursor);
+                        cursor += 1;
+                    },
+                    WindDownStage::Escrows => {
+                        stage = WindDownStage::Challenges;
+                        cursor = 0;
+                        continue;
+                    },
+                    WindDownStage::Challenges
+                        if cursor < self.next_challenge_id =>
+                    {
+                        self.refund_challenge(cursor);
+                        cursor += 1;
+                    },
+                    WindDownStage::Challenges => {
+                        stage = WindDownStage::Raffles;
+                        cursor = 0;
+                        continue;
+                    },
+                    // a raffle is refunded a purchase at a time, and removed
+                    // once none is left.
+                    WindDownStage::Raffles if cursor < self.next_raffle_id => {
+                        if !self.refund_last_purchase(cursor) {
+                            cursor += 1;
+                        }
+                    },
+                    WindDownStage::Raffles => {
+                        stage = WindDownStage::Polls;
+                        cursor = 0;
+                        continue;
+                    },
+                    // a poll is refunded a staker at a time, and removed once
+                    // none is left.
+                    WindDownStage::Polls if cursor < self.next_poll_id => {
+                        if !self.refund_last_staker(cursor) {
+                            cursor += 1;
+                        }
+                    },
+                    WindDownStage::Polls => {
+                        stage = WindDownStage::ContentJars;
+                        cursor = 0;
+                        continue;
+                    },
+                    WindDownStage::ContentJars
+                        if cursor < self.content_jars_count =>
+                    {
+                        self.pay_content_jar(cursor);
+                        cursor += 1;
+                    },
+                    WindDownStage::ContentJars => {
+                        stage = WindDownStage::Relayers;
+                        cursor = 0;
+                        continue;
+                    },
+                    WindDownStage::Relayers if cursor < self.relayers_count => {
+                        self.pay_relayer(cursor);
+                        cursor += 1;
+                    },
+                    WindDownStage::Relayers if self.relay_reimbursement > 0 => {
+                        let amount = self.relay_reimbursement;
+                        let _ = self.env().transfer(self.operator, amount);
+                        self.relay_reimbursement = 0;
+                    },
+                    WindDownStage::Relayers => {
+                        stage = WindDownStage::Jobs;
+                        continue;
+                    },
+                    WindDownStage::Jobs if self.job_queue().len > 0 => {
+                        self.refund_first_job();
+                    },
+                    WindDownStage::Jobs => {
+                        stage = WindDownStage::Accounts;
+                        continue;
+                    },
+                    WindDownStage::Accounts if self.accounts_count > 0 => {
+                        self.refund_last_account();
+                    },
+                    WindDownStage::Accounts => {
+                        stage = WindDownStage::Expired;
+                        continue;
+                    },
+                    WindDownStage::Expired if self.expired_count > 0 => {
+                        self.pay_last_expired();
+                    },
+                    WindDownStage::Expired => {
+                        stage = WindDownStage::Receipts;
+                        continue;
+                    },
+                    WindDownStage::Receipts
+                        if self.receipt_holders_count > 0 =>
+                    {
+                        self.redeem_last_holder();
+                    },
+                    // a user who bound since is refunded too.
+                    WindDownStage::Receipts if self.accounts_count > 0 => {
+                        stage = WindDownStage::Accounts;
+                        continue;
+                    },
+                    WindDownStage::Receipts if self.expired_count > 0 => {
+                        stage = WindDownStage::Expired;
+                        continue;
+                    },
+                    WindDownStage::Receipts => break,
+                }
+                refunded += 1;
+            }
+            self.wind_down_cursor = Some((stage, cursor));
+            Ok(refunded)
+        }
+
+        /// Query the registered hook contracts.
+        #[ink(message, selector = 0x6EC490FE)]
+        pub fn hooks(&self) -> Vec<AccountId> {
+            (0..self.hooks_count)
+                .filter_map(|index| self.hooks.get(index))
+                .collect()
+        }
+
+        /// Query the receipt of a tip, if it is still in the tip log.
+        #[ink(message, selector = 0x28C1EFDA)]
+        pub fn tip_receipt(&self, tip_id: TipId) -> Option<TipRecord> {
+            let next = self.next_receipt_id;
+            if tip_id >= next || next - tip_id > TIP_LOG_SIZE {
+                return None;
+            }
+            self.tip_log.get(tip_id % TIP_LOG_SIZE)
+        }
+
+        /// Query the latest `limit` tips, newest first.
+        ///
+        /// At most `MAX_RECENT_TIPS` tips are returned.
+        #[ink(message, selector = 0xFC20CD55)]
+        pub fn recent_tips(&self, limit: u32) -> Vec<(TipId, TipRecord)> {
+            let next = self.next_receipt_id;
+            let limit = limit.min(MAX_RECENT_TIPS).min(TIP_LOG_SIZE).min(next);
+            (next - limit..next)
+                .rev()
+                .filter_map(|tip_id| {
+                    self.tip_log
+                        .get(tip_id % TIP_LOG_SIZE)
+                        .map(|record| (tip_id, record))
+                })
+                .collect()
+        }
+
+        /// Query the Ids of the tips sent by the TelegramId, newest first,
+        /// skipping the `start` newest ones.
+        ///
+        /// Only the latest `history_limit` tips are kept, and at most
+        /// `MAX_RECENT_TIPS` are returned.
+        #[ink(message, selector = 0x28757687)]
+        pub fn tips_sent_by(
+            &self,
+            tg_id: TelegramId,
+            start: u32,
+            limit: u32,
+        ) -> Vec<TipId> {
+            self.address_of(tg_id)
+                .zip(self.record_of(tg_id))
+                .map(|(account, record)| {
+                    self.history_page(
+                        &self.sent_history,
+                        account,
+                        record.sent_seq,
+                        start,
+                        limit,
+                    )
+                })
+                .unwrap_or_default()
+        }
+
+        /// Query the Ids of the tips received by the TelegramId, newest
+        /// first, skipping the `start` newest ones.
+        ///
+        /// Only the latest `history_limit` tips are kept, and at most
+        /// `MAX_RECENT_TIPS` are returned.
+        #[ink(message, selector = 0x62869ED8)]
+        pub fn tips_received_by(
+            &self,
+            tg_id: TelegramId,
+            start: u32,
+            limit: u32,
+        ) -> Vec<TipId> {
+            self.address_of(tg_id)
+                .zip(self.record_of(tg_id))
+                .map(|(account, record)| {
+                    self.history_page(
+                        &self.received_history,
+                        account,
+                        record.received_seq,
+                        start,
+                        limit,
+                    )
+                })
+                .unwrap_or_default()
+        }
+
+        /// Query a thank-you receipt token.
+        #[ink(message, selector = 0x61E8C508)]
+        pub fn tip_nft(&self, id: TipNftId) -> Option<TipNft> {
+            self.tip_nfts.get(id)
+        }
+
+        /// Query the thank-you receipt tokens of the TelegramId, newest first,
+        /// skipping the `start` newest ones.
+        ///
+        /// At most `MAX_RECENT_TIPS` are returned.
+        #[ink(message, selector = 0x93895FAA)]
+        pub fn tip_nfts_of(
+            &self,
+            tg_id: TelegramId,
+            start: u32,
+            limit: u32,
+        ) -> Vec<TipNftId> {
+            let count = self.tip_nft_counts.get(tg_id).unwrap_or(0);
+            (0..count.saturating_sub(start))
+                .rev()
+                .take(limit.min(MAX_RECENT_TIPS) as usize)
+                .filter_map(|index| self.tip_nfts_by_owner.get((tg_id, index)))
+                .collect()
+        }
+
+        /// Query a raffle that was not drawn yet.
+        #[ink(message, selector = 0x24A8A781)]
+        pub fn raffle(&self, raffle_id: RaffleId) -> Option<Raffle> {
+            self.raffles.get(raffle_id)
+        }
+
+        /// Query how much the account spent on tickets of the raffle.
+        #[ink(message, selector = 0x48461867)]
+        pub fn raffle_spent(
+            &self,
+            raffle_id: RaffleId,
+            account: AccountId,
+        ) -> Balance {
+            self.raffle_spent.get((raffle_id, account)).unwrap_or(0)
+        }
+
+        /// Query the bound account the secondary address is linked to.
+        #[ink(message, selector = 0xEE79EBFA)]
+        pub fn linked_to(&self, secondary: AccountId) -> Option<AccountId> {
+            self.linked_accounts.get(secondary)
+        }
+
+        /// Query the secondary addresses linked to the TelegramId.
+        #[ink(message, selector = 0x59C1F6D5)]
+        pub fn linked_addresses_of(&self, tg_id: TelegramId) -> Vec<AccountId> {
+            self.record_of(tg_id)
+                .map(|record| record.linked)
+                .unwrap_or_default()
+        }
+
+        /// Query a coin-flip challenge that was not flipped nor refunded yet.
+        #[ink(message, selector = 0xBDC714A6)]
+        pub fn challenge_of(
+            &self,
+            challenge_id: ChallengeId,
+        ) -> Option<Challenge> {
+            self.challenges.get(challenge_id)
+        }
+
+        /// Query a prediction poll.
+        #[ink(message, selector = 0x1E44DFC6)]
+        pub fn poll(&self, poll_id: PollId) -> Option<Poll> {
+            self.polls.get(poll_id)
+        }
+
+        /// Query the total staked on an option of a poll.
+        #[ink(message, selector = 0xF49158FF)]
+        pub fn poll_option_stake(
+            &self,
+            poll_id: PollId,
+            option: u8,
+        ) -> Balance {
+            self.poll_option_stakes.get((poll_id, option)).unwrap_or(0)
+        }
+
+        /// Query the stake of the account on an option of a poll, that was
+        /// not claimed yet.
+        #[ink(message, selector = 0xC6F6C860)]
+        pub fn poll_stake_of(
+            &self,
+            poll_id: PollId,
+            account: AccountId,
+            option: u8,
+        ) -> Balance {
+            self.poll_stakes
+                .get((poll_id, account, option))
+                .unwrap_or(0)
+        }
+
+        /// Query the tip jar of a piece of content.
+        #[ink(message, selector = 0xD78F5A2A)]
+        pub fn content_jar(&self, content_hash: Hash) -> Option<ContentJar> {
+            self.content_jars.get(content_hash)
+        }
+
+        /// Query the TelegramId an alias resolves to.
+        ///
+        /// Aliases are normalized by dropping a leading `@` and lower casing
+        /// them, so `@Alice` resolves the same as `alice`.
+        #[ink(message, selector = 0x00376CCD)]
+        pub fn resolve_alias(&self, name: String) -> Option<TelegramId> {
+            normalize_alias(&name).and_then(|name| self.alias_holders.get(name))
+        }
+
+        /// Query the alias of the TelegramId.
+        #[ink(message, selector = 0xD9A1EE24)]
+        pub fn alias_of(&self, tg_id: TelegramId) -> Option<String> {
+            self.aliases.get(tg_id)
+        }
+
+        /// Query whether the alias is reserved by the owners.
+        #[ink(message, selector = 0x73B3073A)]
+        pub fn is_alias_reserved(&self, name: String) -> bool {
+            normalize_alias(&name)
+                .and_then(|name| self.reserved_aliases.get(name))
+                .unwrap_or(false)
+        }
+
+        /// Query the rake taken from the pot of new raffles, in basis points.
+        #[ink(message, selector = 0x746681CB)]
+        pub fn raffle_rake_bps(&self) -> u16 { self.raffle_rake_bps }
+
+        /// Query how many of the latest sent, and received, tips are kept per
+        /// user.
+        #[ink(message, selector = 0x565BE109)]
+        pub fn history_limit(&self) -> u32 { self.history_limit }
+
+        /// Query for how many blocks tips are held, zero if they are final
+        /// right away.
+        #[ink(message, selector = 0xDEA3A6EC)]
+        pub fn reversal_window(&self) -> BlockNumber { self.reversal_window }
+
+        /// Query a tip held in the reversal window.
+        #[ink(message, selector = 0x7A328EBB)]
+        pub fn pending_tip(&self, tip_id: HeldTipId) -> Option<PendingTip> {
+            self.pending_tips.get(tip_id)
+        }
+
+        /// Query the amount above which the recipient has to accept a tip.
+        #[ink(message, selector = 0x9E0E6539)]
+        pub fn acceptance_threshold(&self) -> Option<Balance> {
+            self.acceptance_threshold
+        }
+
+        /// Revoke a held tip within its reversal window, or at any time
+        /// before the recipient accepts it, giving the amount back to the
+        /// sender.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such held tip.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the sender.
+        ///
+        /// * Returns `Error::ReversalWindowClosed` if the reversal window
+        ///   already ended.
+        #[ink(message, selector = 0xA3FF5D44)]
+        pub fn revoke_tip(&mut self, tip_id: HeldTipId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let tip = self.pending_tip_of(tip_id)?;
+            if tip.from != self.env().caller() {
+                return Err(Error::NotAllowed);
+            }
+            if !tip.needs_acceptance
+                && self.env().block_number() >= tip.release_at
+            {
+                return Err(Error::ReversalWindowClosed);
+            }
+            self.refund_tip(tip_id, &tip)?;
+            self.env().emit_event(TipRevoked { tip_id });
+            Ok(())
+        }
+
+        /// Accept a held tip that is above the acceptance threshold.
+        ///
+        /// It is paid right away, unless it is still in its reversal window.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such held tip.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the recipient, or
+        ///   the tip does not need to be accepted.
+        ///
+        /// * Returns `Error::TipExpired` if the tip expired, see
+        ///   `set_tip_expiry`.
+        #[ink(message, selector = 0xF6D389A6)]
+        pub fn accept_tip(&mut self, tip_id: HeldTipId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut tip = self.pending_tip_of(tip_id)?;
+            if tip.to != self.env().caller() || !tip.needs_acceptance {
+                return Err(Error::NotAllowed);
+            }
+            if is_expired(&tip, self.env().block_number()) {
+                return Err(Error::TipExpired);
+            }
+            tip.needs_acceptance = false;
+            self.env().emit_event(TipAccepted { tip_id });
+            if self.env().block_number() >= tip.release_at {
+                self.release_tip(tip_id, tip)
+            } else {
+                self.pending_tips.insert(tip_id, &tip);
+                Ok(())
+            }
+        }
+
+        /// Decline a held tip, giving the amount back to the sender.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such held tip.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the recipient.
+        #[ink(message, selector = 0x7DCB3326)]
+        pub fn decline_tip(&mut self, tip_id: HeldTipId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let tip = self.pending_tip_of(tip_id)?;
+            if tip.to != self.env().caller() {
+                return Err(Error::NotAllowed);
+            }
+            self.refund_tip(tip_id, &tip)?;
+            self.env().emit_event(TipDeclined { tip_id });
+            Ok(())
+        }
+
+        /// Set the block after which a held tip that the recipient did not
+        /// accept by then expires, `None` to never expire it.
+        ///
+        /// Expired tips could be given back to the sender by anyone with
+        /// `expire_pending`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such held tip.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the sender, or
+        ///   the tip does not need to be accepted.
+        #[ink(message, selector = 0xAD22022C)]
+        pub fn set_tip_expiry(
+            &mut self,
+            tip_id: HeldTipId,
+            expires_at: Option<BlockNumber>,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut tip = self.pending_tip_of(tip_id)?;
+            if tip.from != self.env().caller() || !tip.needs_acceptance {
+                return Err(Error::NotAllowed);
+            }
+            tip.expires_at = expires_at;
+            self.pending_tips.insert(tip_id, &tip);
+            self.env().emit_event(TipExpirySet { tip_id, expires_at });
+            Ok(())
+        }
+
+        /// Give every expired tip held for the TelegramId back to its
+        /// sender, returning how many expired.
+        ///
+        /// Anyone could call this, and is paid `EXPIRY_REWARD_BPS` of each
+        /// expired tip for it, credited to thier balance if they are bound.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the TelegramId is not bound.
+        ///
+        /// * Returns `Error::BelowSubsistenceThreshold` if the reward, or a
+        ///   refund to a sender that unbound since, could not be transferred.
+        #[ink(message, selector = 0x864E8D8E)]
+        pub fn expire_pending(
+            &mut self,
+            tg_id: TelegramId,
+        ) -> Result<u32, Error> {
+            ensure_not_reentered()?;
+            let recipient = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let now = self.env().block_number();
+            let mut expired = Vec::new();
+            for tip_id in self.incoming_tips.get(recipient).unwrap_or_default()
+            {
+                let tip = self.pending_tip_of(tip_id)?;
+                if is_expired(&tip, now) {
+                    expired.push((tip_id, tip));
+                }
+            }
+            let rewards: Vec<Balance> = expired
+                .iter()
+                .map(|(_, tip)| fee_of(tip.amount, EXPIRY_REWARD_BPS))
+                .collect();
+            let total = rewards.iter().sum();
+            let caller = self.env().caller();
+            if total > 0 {
+                self.credit(caller, total)?;
+            }
+            for ((tip_id, tip), reward) in expired.iter().zip(rewards) {
+                let refund = PendingTip {
+                    amount: tip.amount - reward,
+                    ..tip.clone()
+                };
+                self.refund_tip(*tip_id, &refund)?;
+                self.env().emit_event(TipExpired {
+                    tip_id: *tip_id,
+                    by: caller,
+                    reward,
+                });
+            }
+            Ok(expired.len() as u32)
+        }
+
+        /// Schedule a tip of `amount` to the TelegramId, sent once the
+        /// `execute_at` time comes, returning the Id of the job.
+        ///
+        /// The caller deposits `KEEPER_INCENTIVE` from thier balance for the
+        /// keeper who sends it, see `execute_due`. The tip itself is taken
+        /// from the balance when it is sent, under the limits of that time.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller, or the recipient, is not
+        ///   bound.
+        ///
+        /// * Returns `Error::AccountSelfLocked` if the caller locked
+        ///   themselves.
+        ///
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InvalidParamValue` if `execute_at` is not in the
+        ///   future.
+        ///
+        /// * Returns `Error::TooManyJobs` if the queue, or the jobs of the
+        ///   caller, are full.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the caller can not afford
+        ///   the incentive.
+        #[ink(message, selector = 0xD26F402D)]
+        pub fn schedule_tip(
+            &mut self,
+            tg_id: TelegramId,
+            amount: Balance,
+            execute_at: Timestamp,
+        ) -> Result<JobId, Error> {
+            ensure_not_reentered()?;
+            let (from, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            ensure_not_self_locked(&record)?;
+            self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if execute_at <= self.env().block_timestamp() {
+                return Err(Error::InvalidParamValue);
+            }
+            let mut user_jobs = self.user_jobs.get(from).unwrap_or_default();
+            let mut queue = self.job_queue();
+            if queue.len as usize >= MAX_JOBS
+                || user_jobs.len() >= MAX_JOBS_PER_USER
+            {
+                return Err(Error::TooManyJobs);
+            }
+            let incentive = KEEPER_INCENTIVE;
+            let available = self.spendable(&record);
+            if available < incentive {
+                return Err(Error::InsufficientFunds {
+                    required: incentive,
+                    available,
+                });
+            }
+            record.balance -= incentive;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(incentive);
+            self.users.insert(from, &record);
+            let job_id = queue.tail;
+            queue.tail += 1;
+            queue.len += 1;
+            self.job_queue.insert((), &queue);
+            self.jobs.insert(
+                job_id,
+                &Job {
+                    owner: from,
+                    kind: JobKind::Tip { to: tg_id, amount },
+                    execute_at,
+                    incentive,
+                },
+            );
+            user_jobs.push(job_id);
+            self.user_jobs.insert(from, &user_jobs);
+            self.env().emit_event(TipScheduled {
+                job_id,
+                from,
+                to: tg_id,
+                amount,
+                execute_at,
+            });
+            Ok(job_id)
+        }
+
+        /// Query a job that was not executed yet.
+        #[ink(message, selector = 0x267DD617)]
+        pub fn job(&self, job_id: JobId) -> Option<Job> {
+            self.jobs.get(job_id)
+        }
+
+        /// Query the waiting jobs of the TelegramId, oldest first.
+        #[ink(message, selector = 0x174DA251)]
+        pub fn jobs_of(&self, tg_id: TelegramId) -> Vec<(JobId, Job)> {
+            self.address_of(tg_id)
+                .and_then(|account| self.user_jobs.get(account))
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|job_id| Some((job_id, self.jobs.get(job_id)?)))
+                .collect()
+        }
+
+        /// Query up to `limit` waiting jobs, starting from the `start`th one,
+        /// oldest first.
+        ///
+        /// At most `MAX_EXPORT_PAGE` jobs are returned, and fewer once the
+        /// end of the queue is reached.
+        #[ink(message, selector = 0x3D905903)]
+        pub fn pending_jobs(
+            &self,
+            start: u32,
+            limit: u32,
+        ) -> Vec<(JobId, Job)> {
+            self.waiting_jobs()
+                .skip(start as usize)
+                .take(limit.min(MAX_EXPORT_PAGE) as usize)
+                .collect()
+        }
+
+        /// Cancel a waiting job of the caller, giving the incentive back to
+        /// thier balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the caller is not bound, or there is
+        ///   no such job.
+        ///
+        /// * Returns `Error::NotAllowed` if the job is not of the caller.
+        #[ink(message, selector = 0xA23D9011)]
+        pub fn cancel_job(&mut self, job_id: JobId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let (account, mut record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            let job = self
+                .jobs
+                .get(job_id)
+                .ok_or(Error::NotFound { which: Entity::Job })?;
+            if job.owner != account {
+                return Err(Error::NotAllowed);
+            }
+            record.balance = record.balance.saturating_add(job.incentive);
+            record.audit.other_in =
+                record.audit.other_in.saturating_add(job.incentive);
+            self.users.insert(account, &record);
+            self.remove_job(job_id, &job);
+            self.env().emit_event(JobCancelled { job_id });
+            Ok(())
+        }
+
+        /// Execute up to `limit` of the jobs whose time came, oldest first,
+        /// returning how many were executed.
+        ///
+        /// Anyone could call this, and is paid the incentive of each executed
+        /// job, credited to thier balance if they are bound. A job that
+        /// failed, like a tip the sender can no longer afford, is dropped,
+        /// and its incentive is still paid.
+        ///
+        /// Errors:
+        /// Returns `Error::BelowSubsistenceThreshold` if the incentives could
+        /// not be transferred.
+        #[ink(message, selector = 0x8AA7379D)]
+        pub fn execute_due(&mut self, limit: u32) -> Result<u32, Error> {
+            ensure_not_reentered()?;
+            let now = self.env().block_timestamp();
+            let due = self
+                .waiting_jobs()
+                .filter(|(_, job)| job.execute_at <= now)
+                .take(limit as usize)
+                .collect::<Vec<_>>();
+            let total = due.iter().map(|(_, job)| job.incentive).sum();
+            if total > 0 {
+                self.credit(self.env().caller(), total)?;
+            }
+            for (job_id, job) in &due {
+                self.remove_job(*job_id, job);
+                let error = self.run_job(job).err();
+                self.env().emit_event(JobExecuted {
+                    job_id: *job_id,
+                    error,
+                });
+            }
+            Ok(due.len() as u32)
+        }
+
+        /// Finalize a held tip after its reversal window, paying it to the
+        /// recipient.
+        ///
+        /// Anyone could call this, and the recipient's held tips are also
+        /// finalized when they `unbind`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such held tip.
+        ///
+        /// * Returns `Error::TipNotAccepted` if the recipient has to accept it
+        ///   first.
+        ///
+        /// * Returns `Error::TipHeld` if the reversal window did not end yet.
+        #[ink(message, selector = 0x71F199CE)]
+        pub fn finalize_tip(&mut self, tip_id: HeldTipId) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let tip = self.pending_tip_of(tip_id)?;
+            if tip.needs_acceptance {
+                return Err(Error::TipNotAccepted);
+            }
+            if self.env().block_number() < tip.release_at {
+                return Err(Error::TipHeld {
+                    release_at: tip.release_at,
+                });
+            }
+            self.release_tip(tip_id, tip)
+        }
+
+        /// Query a slash waiting for its appeal window to end.
+        #[ink(message, selector = 0x2BA6449F)]
+        pub fn slash(&self, slash_id: SlashId) -> Option<Slash> {
+            self.slashes.get(slash_id)
+        }
+
+        /// Propose slashing an account into the treasury, a shorthand for
+        /// `propose_admin_call(AdminAction::ProposeSlash { .. })`.
+        ///
+        /// Once executed the amount is held until the `APPEAL_WINDOW` ends,
+        /// then anyone could `finalize_slash` it unless the user contests it.
+        ///
+        /// Errors:
+        /// Same as `propose_admin_call`.
+        #[ink(message, selector = 0xE6908748)]
+        pub fn propose_slash(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+            reason_hash: Hash,
+        ) -> Result<ProposalId, Error> {
+            ensure_not_reentered()?;
+            self.propose_admin_call(AdminAction::ProposeSlash {
+                account,
+                amount,
+                reason_hash,
+            })
+        }
+
+        /// Contest a slash of the caller, leaving it to the owners to either
+        /// cancel or uphold it.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such slash.
+        ///
+        /// * Returns `Error::NotAllowed` if the caller is not the slashed
+        ///   account.
+        ///
+        /// * Returns `Error::AppealClosed` if the appeal window already ended.
+        #[ink(message, selector = 0xBF6F297B)]
+        pub fn contest_slash(
+            &mut self,
+            slash_id: SlashId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let mut slash = self.slash_of(slash_id)?;
+            if slash.account != self.env().caller() {
+                return Err(Error::NotAllowed);
+            }
+            if self.env().block_number() >= slash.appeal_ends_at {
+                return Err(Error::AppealClosed);
+            }
+            slash.contested = true;
+            self.slashes.insert(slash_id, &slash);
+            self.env().emit_event(SlashContested { slash_id });
+            Ok(())
+        }
+
+        /// Finalize an uncontested slash after its appeal window, moving the
+        /// amount to the treasury.
+        ///
+        /// Anyone could call this.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if there is no such slash.
+        ///
+        /// * Returns `Error::AppealOpen` if the appeal window did not end yet.
+        ///
+        /// * Returns `Error::SlashContested` if the user contested it.
+        #[ink(message, selector = 0x990F5BD9)]
+        pub fn finalize_slash(
+            &mut self,
+            slash_id: SlashId,
+        ) -> Result<(), Error> {
+            ensure_not_reentered()?;
+            let slash = self.slash_of(slash_id)?;
+            if self.env().block_number() < slash.appeal_ends_at {
+                return Err(Error::AppealOpen {
+                    ends_at: slash.appeal_ends_at,
+                });
+            }
+            if slash.contested {
+                return Err(Error::SlashContested);
+            }
+            self.apply_slash(slash_id, slash)
+        }
+
+        /// Count the approval of the proposal, executing it if it has enough
+        /// approvals, or storing it for later otherwise.
+        fn approve_proposal(
+            &mut self,
+            proposal_id: ProposalId,
+            proposal: Proposal,
+        ) -> Result<(), Error> {
+            let approvals = proposal
+                .approvals
+                .iter()
+                .filter(|owner| self.owners.contains(owner))
+                .count();
+            if approvals < self.threshold as usize {
+                self.proposals.insert(proposal_id, &proposal);
+                self.env().emit_event(ProposalApproved {
+                    proposal_id,
+                    owner: self.env().caller(),
+                });
+                return Ok(());
+            }
+            let target = self.admin_target(&proposal.action);
+            self.execute_admin_action(proposal.action.clone())?;
+            self.log_admin_action(
+                target,
+                PrivilegedAction::Admin(proposal.action),
+            );
+            self.proposals.remove(proposal_id);
+            self.env().emit_event(ProposalExecuted { proposal_id });
+            Ok(())
+        }
+
+        /// The account an admin action targets, if any.
+        fn admin_target(&self, action: &AdminAction) -> Option<AccountId> {
+            match action {
+                AdminAction::ForceUnbind(account)
+                | AdminAction::RegisterHook(account)
+                | AdminAction::UnregisterHook(account)
+                | AdminAction::ScheduleTermination(account)
+                | AdminAction::SetOperator(account)
+                | AdminAction::AddOwner(account)
+                | AdminAction::RemoveOwner(account)
+                | AdminAction::ProposeSlash { account, .. } => Some(*account),
+                AdminAction::CancelSlash(slash_id)
+                | AdminAction::UpholdSlash(slash_id) => {
+                    self.slashes.get(slash_id).map(|slash| slash.account)
+                },
+                AdminAction::CancelTermination
+                | AdminAction::Terminate
+                | AdminAction::SetThreshold(_)
+                | AdminAction::RenounceOwnership
+                | AdminAction::Configure(_) => None,
+            }
+        }
+
+        /// Emit the `AdminActionLogged` event of a privileged call of the
+        /// caller, with the next action Id.
+        fn log_admin_action(
+            &mut self,
+            target: Option<AccountId>,
+            action: PrivilegedAction,
+        ) {
+            let action_id = self.next_admin_action_id;
+            self.next_admin_action_id += 1;
+            self.env().emit_event(AdminActionLogged {
+                action_id,
+                by: self.env().caller(),
+                target,
+                action,
+            });
+        }
+
+        /// Execute an approved admin action.
+        fn execute_admin_action(
+            &mut self,
+            action: AdminAction,
+        ) -> Result<(), Error> {
+            match action {
+                AdminAction::ForceUnbind(account) => self.force_unbind(account),
+                AdminAction::RegisterHook(contract) => {
+                    self.register_hook(contract)
+                },
+                AdminAction::UnregisterHook(contract) => {
+                    self.unregister_hook(contract)
+                },
+                AdminAction::ScheduleTermination(beneficiary) => {
+                    self.schedule_termination(beneficiary);
+                    Ok(())
+                },
+                AdminAction::CancelTermination => self.cancel_termination(),
+                AdminAction::Terminate => self.terminate(),
+                AdminAction::SetOperator(operator) => {
+                    self.operator = operator;
+                    Ok(())
+                },
+                AdminAction::AddOwner(owner) => {
+                    if self.owners.contains(&owner) {
+                        return Err(Error::AlreadyRegistered);
+                    }
+                    self.owners.push(owner);
+                    Ok(())
+                },
+                AdminAction::RemoveOwner(owner) => {
+                    let index =
+                        self.owners.iter().position(|o| o == &owner).ok_or(
+                            Error::NotFound {
+                                which: Entity::Owner,
+                            },
+                        )?;
+                    if self.owners.len() <= self.threshold as usize {
+                        return Err(Error::InvalidThreshold);
+                    }
+                    self.owners.swap_remove(index);
+                    Ok(())
+                },
+                AdminAction::SetThreshold(threshold) => {
+                    if threshold == 0 || threshold as usize > self.owners.len()
+                    {
+                        return Err(Error::InvalidThreshold);
+                    }
+                    self.threshold = threshold;
+                    Ok(())
+                },
+                AdminAction::RenounceOwnership => {
+                    self.owners.clear();
+                    self.threshold = 0;
+                    self.env().emit_event(OwnershipRenounced {});
+                    Ok(())
+                },
+                AdminAction::ProposeSlash {
+                    account,
+                    amount,
+                    reason_hash,
+                } => self.open_slash(account, amount, reason_hash),
+                AdminAction::CancelSlash(slash_id) => {
+                    let slash = self.slash_of(slash_id)?;
+                    if let Some(mut record) = self.slashed_record(&slash) {
+                        record.pending_slash -= slash.amount;
+                        self.users.insert(slash.account, &record);
+                    }
+                    self.slashes.remove(slash_id);
+                    self.env().emit_event(SlashCancelled { slash_id });
+                    Ok(())
+                },
+                AdminAction::UpholdSlash(slash_id) => {
+                    let slash = self.slash_of(slash_id)?;
+                    // an uncontested slash is final only once the user had
+                    // the whole appeal window to contest it.
+                    if !slash.contested
+                        && self.env().block_number() < slash.appeal_ends_at
+                    {
+                        return Err(Error::AppealOpen {
+                            ends_at: slash.appeal_ends_at,
+                        });
+                    }
+                    self.apply_slash(slash_id, slash)
+                },
+                AdminAction::Configure(setting) => self.configure(setting),
+            }
+        }
+
+        /// Change a setting of the contract.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if the value is not valid for
+        /// the setting.
+        fn configure(&mut self, setting: Setting) -> Result<(), Error> {
+            match setting {
+                Setting::ReversalWindow(window) => {
+                    self.reversal_window = window;
+                },
+                Setting::AcceptanceThreshold(threshold) => {
+                    self.acceptance_threshold = threshold;
+                },
+                Setting::HistoryLimit(limit) => self.history_limit = limit,
+                Setting::PointsRate(rate) => self.points_rate = rate,
+                Setting::RedemptionRate(rate) => self.redemption_rate = rate,
+                Setting::StreakRebate(Some(rebate))
+                    if rebate.rebate_bps > MAX_BPS =>
+                {
+                    return Err(Error::InvalidParamValue)
+                },
+                Setting::StreakRebate(rebate) => self.streak_rebate = rebate,
+                Setting::Charity(charity) => self.charity = charity,
+                Setting::BurnAddress(address) => self.burn_address = address,
+                Setting::DustDormancy(0) => {
+                    return Err(Error::InvalidParamValue)
+                },
+                Setting::DustDormancy(dormancy) => {
+                    self.dust_dormancy = dormancy
+                },
+                Setting::RelayFee(fee) => self.relay_fee = fee,
+                Setting::PriceOracle(oracle) => self.price_oracle = oracle,
+                Setting::Dex(dex) => self.dex = dex,
+                Setting::RaffleRake(rake_bps) if rake_bps > MAX_BPS => {
+                    return Err(Error::InvalidParamValue)
+                },
+                Setting::RaffleRake(rake_bps) => {
+                    self.raffle_rake_bps = rake_bps
+                },
+                Setting::Binding(setting) => self.configure_binding(setting)?,
+                Setting::Transfer(setting) => {
+                    self.configure_transfer(setting)?
+                },
+                Setting::Admin(setting) => self.configure_admin(setting)?,
+            }
+            Ok(())
+        }
+
+        /// Change a setting of the governance.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if the group fee floor is more
+        /// than `MAX_BPS`.
+        fn configure_admin(
+            &mut self,
+            setting: AdminSetting,
+        ) -> Result<(), Error> {
+            match setting {
+                AdminSetting::ParamQuorum(quorum) => self.param_quorum = quorum,
+                AdminSetting::GroupFeeFloor(floor) if floor <= MAX_BPS => {
+                    self.group_fee_floor = floor
+                },
+                AdminSetting::GroupFeeFloor(_) => {
+                    return Err(Error::InvalidParamValue)
+                },
+                AdminSetting::Call(call) => self.execute_admin_call(call)?,
+            }
+            Ok(())
+        }
+
+        /// Execute an approved privileged call.
+        fn execute_admin_call(&mut self, call: AdminCall) -> Result<(), Error> {
+            match call {
+                AdminCall::BurnTreasury(amount) => self.burn_treasury(amount),
+                AdminCall::SweepDust(tg_id) => self.sweep_dust(tg_id),
+                AdminCall::CosignUnlock(tg_id) => self.cosign_unlock(tg_id),
+                AdminCall::ForceUnbindMany(accounts) => {
+                    self.force_unbind_many(&accounts);
+                    Ok(())
+                },
+                AdminCall::AdminRebind { account, new_tg_id } => {
+                    self.change_tg_id(account, new_tg_id)
+                },
+                AdminCall::SetRelayer { relayer, config } => {
+                    self.set_relayer(relayer, config)
+                },
+                AdminCall::StakeIdle(amount) => self.stake_idle(amount),
+                AdminCall::Unstake(amount) => self.unstake(amount),
+                AdminCall::WithdrawUnbonded => self.withdraw_unbonded(),
+                AdminCall::SetArbiters {
+                    arbiters,
+                    dispute_fee,
+                } => self.set_arbiters(arbiters, dispute_fee),
+                AdminCall::SetVerificationLevel { tg_id, level } => {
+                    self.set_verification_level(tg_id, level)
+                },
+                AdminCall::SetLevelLimits { level, limits } => {
+                    self.level_limits[level as usize] = limits;
+                    self.env().emit_event(LevelLimitsSet { level, limits });
+                    Ok(())
+                },
+                AdminCall::SetFeature { feature, enabled } => {
+                    if enabled {
+                        self.disabled_features &= !feature.flag();
+                    } else {
+                        self.disabled_features |= feature.flag();
+                    }
+                    self.env().emit_event(FeatureSet { feature, enabled });
+                    Ok(())
+                },
+                AdminCall::SetDepositCaps {
+                    per_account,
+                    global,
+                } => {
+                    self.deposit_cap = per_account;
+                    self.global_deposit_cap = global;
+                    self.env().emit_event(DepositCapsSet {
+                        per_account,
+                        global,
+                    });
+                    Ok(())
+                },
+            }
+        }
+
+        /// Configure a relayer, or remove it with `None`.
+        ///
+        /// Reconfiguring a relayer keeps its volume and unclaimed relay fees,
+        /// and removing it leaves the fees to the operator.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if the name is empty, or longer
+        /// than `MAX_RELAYER_NAME_LEN` bytes, or the relayer is the operator.
+        fn set_relayer(
+            &mut self,
+            relayer: AccountId,
+            config: Option<RelayerConfig>,
+        ) -> Result<(), Error> {
+            if relayer == self.operator {
+                return Err(Error::InvalidParamValue);
+            }
+            let current = self.relayers.get(relayer);
+            match &config {
+                Some(config)
+                    if config.name.is_empty()
+                        || config.name.len() > MAX_RELAYER_NAME_LEN =>
+                {
+                    return Err(Error::InvalidParamValue)
+                },
+                Some(config) => {
+                    let updated = match current {
+                        Some(current) => Relayer {
+                            config: config.clone(),
+                            ..current
+                        },
+                        None => Relayer {
+                            config: config.clone(),
+                            volume: 0,
+                            volume_era: self.current_era(),
+                            reimbursement: 0,
+                        },
+                    };
+                    self.relayers.insert(relayer, &updated);
+                    push_indexed(
+                        &mut self.relayer_accounts,
+                        &mut self.relayer_index,
+                        &mut self.relayers_count,
+                        relayer,
+                    );
+                },
+                None => {
+                    if let Some(current) = current {
+                        self.relay_reimbursement = self
+                            .relay_reimbursement
+                            .saturating_add(current.reimbursement);
+                    }
+                    self.relayers.remove(relayer);
+                    remove_indexed(
+                        &mut self.relayer_accounts,
+                        &mut self.relayer_index,
+                        &mut self.relayers_count,
+                        relayer,
+                    );
+                },
+            }
+            self.env().emit_event(RelayerSet { relayer, config });
+            Ok(())
+        }
+
+        /// Bond `amount` of the idle balance of the contract with the staking
+        /// pallet, once the owners approve an `AdminCall::StakeIdle`.
+        ///
+        /// What is bonded, or still unbonding, never goes above the treasury
+        /// and the `StakeShare` of the deposits, the rest of the balance of
+        /// the contract, so the users could always withdraw the rest of the
+        /// deposits.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::InsufficientFunds` if it would bond more than
+        ///   allowed.
+        ///
+        /// * Returns `Error::ExtensionUnavailable` if the contract is built
+        ///   without the `chain-extension` feature, and `Error::StakingFailed`
+        ///   if the runtime did not bond it.
+        fn stake_idle(&mut self, amount: Balance) -> Result<(), Error> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if !cfg!(feature = "chain-extension") {
+                return Err(Error::ExtensionUnavailable);
+            }
+            let mut staking = self.staking();
+            let held = self.env().balance();
+            let deposits = held.saturating_sub(self.treasury);
+            let limit = self
+                .treasury
+                .min(held)
+                .saturating_add(fee_of(deposits, staking.deposit_share_bps));
+            let locked = staking.bonded.saturating_add(staking.unbonding);
+            let available = limit.saturating_sub(locked);
+            if amount > available {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            if !self.bond(amount) {
+                return Err(Error::StakingFailed);
+            }
+            staking.bonded += amount;
+            self.staking.insert((), &staking);
+            self.env().emit_event(Staked { amount });
+            Ok(())
+        }
+
+        /// Start unbonding `amount` of the bonded balance of the contract, it
+        /// stays locked until it is withdrawn by `withdraw_unbonded`.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero, and
+        ///   `Error::InsufficientFunds` if it is more than what is bonded.
+        ///
+        /// * Returns `Error::ExtensionUnavailable` if the contract is built
+        ///   without the `chain-extension` feature, and `Error::StakingFailed`
+        ///   if the runtime did not unbond it.
+        fn unstake(&mut self, amount: Balance) -> Result<(), Error> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if !cfg!(feature = "chain-extension") {
+                return Err(Error::ExtensionUnavailable);
+            }
+            let mut staking = self.staking();
+            if amount > staking.bonded {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available: staking.bonded,
+                });
+            }
+            if !self.unbond(amount) {
+                return Err(Error::StakingFailed);
+            }
+            staking.bonded -= amount;
+            staking.unbonding = staking.unbonding.saturating_add(amount);
+            self.staking.insert((), &staking);
+            self.env().emit_event(Unstaked { amount });
+            Ok(())
+        }
+
+        /// Unlock the balance of the contract that finished unbonding.
+        ///
+        /// Errors:
+        /// Returns `Error::ExtensionUnavailable` if the contract is built
+        /// without the `chain-extension` feature.
+        fn withdraw_unbonded(&mut self) -> Result<(), Error> {
+            if !cfg!(feature = "chain-extension") {
+                return Err(Error::ExtensionUnavailable);
+            }
+            let mut staking = self.staking();
+            // the runtime could not unlock more than was unbonding.
+            let amount = self.unlock_unbonded().min(staking.unbonding);
+            staking.unbonding -= amount;
+            self.staking.insert((), &staking);
+            self.env().emit_event(UnbondedWithdrawn { amount });
+            Ok(())
+        }
+
+        /// Appoint the panel of arbiters deciding the disputes over escrows,
+        /// and the fee paid to open a dispute.
+        ///
+        /// The disputes already open keep the panel they were opened with.
+        ///
+        /// Errors:
+        /// Returns `Error::TooManyMembers` if there are more than
+        /// `MAX_ARBITERS` arbiters.
+        fn set_arbiters(
+            &mut self,
+            mut arbiters: Vec<AccountId>,
+            dispute_fee: Balance,
+        ) -> Result<(), Error> {
+            arbiters.sort_unstable();
+            arbiters.dedup();
+            if arbiters.len() > MAX_ARBITERS {
+                return Err(Error::TooManyMembers);
+            }
+            self.arbiters = arbiters;
+            self.dispute_fee = dispute_fee;
+            Ok(())
+        }
+
+        /// Set the verification level of the user of the TelegramId.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the TelegramId is not bound.
+        fn set_verification_level(
+            &mut self,
+            tg_id: TelegramId,
+            level: VerificationLevel,
+        ) -> Result<(), Error> {
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            record.verification = level;
+            self.users.insert(account, &record);
+            self.env()
+                .emit_event(VerificationLevelSet { account, level });
+            Ok(())
+        }
+
+        /// Unbind many accounts at once, refunding thier balances.
+        ///
+        /// Accounts that are not bound, or whose refund fails, are skipped.
+        fn force_unbind_many(&mut self, accounts: &[AccountId]) {
+            for account in accounts {
+                // `force_unbind` undoes its writes when it fails.
+                let _ = self.force_unbind(*account);
+            }
+        }
+
+        /// Co-sign the request of a user to unlock thier account, unlocking
+        /// it right away.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::UnlockNotRequested` if the user did not ask to
+        ///   unlock thier account.
+        fn cosign_unlock(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            if record.unlock_requested_at.is_none() {
+                return Err(Error::UnlockNotRequested);
+            }
+            self.unlock_account(account, record, Some(self.env().caller()));
+            Ok(())
+        }
+
+        /// Sweep the dust of a dormant user into the treasury after its
+        /// announcement, unbinding them so thier storage is freed.
+        ///
+        /// The bind deposit, and any queued withdrawal, of the user are not
+        /// swept, but left to be withdrawn by `withdraw_expired`.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound, or the
+        ///   sweep was not announced.
+        ///
+        /// * Returns `Error::NotDormant` if the grace period did not end yet,
+        ///   or the user was active since the announcement.
+        ///
+        /// * Returns `Error::NotDust` if the balance of the user is not below
+        ///   the existential deposit.
+        ///
+        /// * Returns any error of `ensure_removable`.
+        fn sweep_dust(&mut self, tg_id: TelegramId) -> Result<(), Error> {
+            let (account, record) = self.dust_of(tg_id)?;
+            let announced_at =
+                self.dust_sweeps.get(account).ok_or(Error::NotFound {
+                    which: Entity::DustSweep,
+                })?;
+            let until = announced_at + DUST_GRACE_PERIOD;
+            if record.last_active > announced_at {
+                return Err(Error::NotDormant {
+                    until: record.last_active + self.dust_dormancy,
+                });
+            }
+            if self.env().block_number() < until {
+                return Err(Error::NotDormant { until });
+            }
+            self.ensure_removable(account, &record)?;
+            let treasury = self
+                .treasury
+                .checked_add(record.balance)
+                .ok_or(Error::Overflow)?;
+            self.treasury = treasury;
+            self.dust_sweeps.remove(account);
+            let pending =
+                record.pending_withdrawal.map_or(0, |(amount, _)| amount);
+            let incentives = self.cancel_jobs_of(account);
+            self.leave_balance(
+                account,
+                record
+                    .bind_deposit
+                    .saturating_add(pending)
+                    .saturating_add(incentives),
+            );
+            self.remove_binding(account, &record);
+            self.env().emit_event(DustSwept {
+                account,
+                amount: record.balance,
+            });
+            Ok(())
+        }
+
+        /// Burn part of the treasury, by sending it to the burn address.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the `amount` is zero.
+        ///
+        /// * Returns `Error::BurnDisabled` if there is no burn address.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the treasury does not have
+        ///   enough.
+        fn burn_treasury(&mut self, amount: Balance) -> Result<(), Error> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if self.treasury < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available: self.treasury,
+                });
+            }
+            self.send_to_burn_address(amount)?;
+            self.treasury -= amount;
+            self.env().emit_event(Burned { from: None, amount });
+            Ok(())
+        }
+
+        /// Change a setting of the transfers, and of the calls to other
+        /// contracts.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if a gas limit is zero, or the
+        /// stake share is above `MAX_BPS`.
+        fn configure_transfer(
+            &mut self,
+            setting: TransferSetting,
+        ) -> Result<(), Error> {
+            match setting {
+                TransferSetting::ExistentialDeposit(ed) => {
+                    self.existential_deposit =
+                        ed.unwrap_or_else(|| self.env().minimum_balance())
+                },
+                TransferSetting::HookGasLimit(0)
+                | TransferSetting::TokenCallGasLimit(0) => {
+                    return Err(Error::InvalidParamValue)
+                },
+                TransferSetting::HookGasLimit(limit) => {
+                    self.hook_gas_limit = limit
+                },
+                TransferSetting::TokenCallGasLimit(limit) => {
+                    self.token_call_gas_limit = limit
+                },
+                TransferSetting::StakeShare(bps) if bps > MAX_BPS => {
+                    return Err(Error::InvalidParamValue)
+                },
+                TransferSetting::StakeShare(bps) => {
+                    let mut staking = self.staking.get(()).unwrap_or_default();
+                    staking.deposit_share_bps = bps;
+                    self.staking.insert((), &staking);
+                },
+            }
+            Ok(())
+        }
+
+        /// Change a setting of the bindings.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if the rate limit window is
+        /// zero.
+        fn configure_binding(
+            &mut self,
+            setting: BindingSetting,
+        ) -> Result<(), Error> {
+            match setting {
+                BindingSetting::Ttl(ttl) => self.binding_ttl = ttl,
+                BindingSetting::Deposit(deposit) => self.bind_deposit = deposit,
+                BindingSetting::RateLimit(Some(limit)) if limit.window == 0 => {
+                    return Err(Error::InvalidParamValue)
+                },
+                BindingSetting::RateLimit(limit) => {
+                    self.bind_rate_limit = limit;
+                    self.bind_window = (0, 0);
+                },
+                BindingSetting::NewcomerLimits(limits) => {
+                    self.newcomer_limits = limits
+                },
+            }
+            Ok(())
+        }
+
+        /// Hold `amount` of the account's balance for a new slash.
+        ///
+        /// Errors:
+        /// * Returns `Error::ZeroAmount` if the amount is zero.
+        ///
+        /// * Returns `Error::NotFound` if the account is not bound.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the account does not have
+        ///   enough balance that is not already held by other slashes.
+        fn open_slash(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+            reason_hash: Hash,
+        ) -> Result<(), Error> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let mut record =
+                self.users.get(account).ok_or(Error::NotFound {
+                    which: Entity::Account,
+                })?;
+            let available = record.balance - record.pending_slash;
+            if available < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available,
+                });
+            }
+            record.pending_slash += amount;
+            self.users.insert(account, &record);
+            let slash_id = self.next_slash_id;
+            self.next_slash_id += 1;
+            let appeal_ends_at = self.env().block_number() + APPEAL_WINDOW;
+            self.slashes.insert(
+                slash_id,
+                &Slash {
+                    account,
+                    amount,
+                    reason_hash,
+                    appeal_ends_at,
+                    contested: false,
+                },
+            );
+            self.env().emit_event(SlashProposed {
+                slash_id,
+                account,
+                amount,
+                reason_hash,
+                appeal_ends_at,
+            });
+            Ok(())
+        }
+
+        /// Move the slashed amount from the account to the treasury.
+        ///
+        /// A slash of an account that was force unbound since is only
+        /// removed, the unbinding already moved what it held to the treasury.
+        ///
+        /// Errors:
+        /// Returns `Error::Overflow` if the treasury would overflow.
+        fn apply_slash(
+            &mut self,
+            slash_id: SlashId,
+            slash: Slash,
+        ) -> Result<(), Error> {
+            let mut record = match self.slashed_record(&slash) {
+                Some(record) => record,
+                None => {
+                    self.slashes.remove(slash_id);
+                    self.env().emit_event(SlashFinalized {
+                        slash_id,
+                        amount: slash.amount,
+                    });
+                    return Ok(());
+                },
+            };
+            self.treasury = self
+                .treasury
+                .checked_add(slash.amount)
+                .ok_or(Error::Overflow)?;
+            record.balance -= slash.amount;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(slash.amount);
+            record.pending_slash -= slash.amount;
+            self.users.insert(slash.account, &record);
+            self.slashes.remove(slash_id);
+            self.env().emit_event(SlashFinalized {
+                slash_id,
+                amount: slash.amount,
+            });
+            Ok(())
+        }
+
+        /// The record of the binding the slash was proposed against, `None`
+        /// if the account is no longer bound, or was bound again since.
+        fn slashed_record(&self, slash: &Slash) -> Option<UserRecord> {
+            let proposed_at =
+                slash.appeal_ends_at.saturating_sub(APPEAL_WINDOW);
+            self.users
+                .get(slash.account)
+                .filter(|record| record.bound_at <= proposed_at)
+        }
+
+        fn slash_of(&self, slash_id: SlashId) -> Result<Slash, Error> {
+            self.slashes.get(slash_id).ok_or(Error::NotFound {
+                which: Entity::Slash,
+            })
+        }
+
+        /// Schedule the termination of the contract.
+        ///
+        /// After `TERMINATION_DELAY` blocks the owners could `terminate` it to
+        /// refund all the users and send whatever is left to the
+        /// `beneficiary`, giving everyone time to notice.
+        fn schedule_termination(&mut self, beneficiary: AccountId) {
+            let at = self.env().block_number() + TERMINATION_DELAY;
+            self.termination = Some((beneficiary, at));
+            // whatever was held since the last wind down is refunded too.
+            self.wind_down_cursor = None;
+            self.env()
+                .emit_event(TerminationScheduled { beneficiary, at });
+        }
+
+        /// Cancel the scheduled termination of the contract.
+        ///
+        /// Errors:
+        /// Returns `Error::TerminationNotScheduled` if there is nothing to
+        /// cancel.
+        fn cancel_termination(&mut self) -> Result<(), Error> {
+            if self.termination.take().is_none() {
+                return Err(Error::TerminationNotScheduled);
+            }
+            self.env().emit_event(TerminationCancelled {});
+            Ok(())
+        }
+
+        /// Terminate the contract after the timelock.
+        ///
+        /// The contract is removed and whatever is left goes to the
+        /// beneficiary, every bound user has to be refunded by `wind_down`
+        /// first.
+        ///
+        /// Errors:
+        /// * Returns `Error::TerminationNotScheduled` if the termination was
+        ///   not scheduled.
+        ///
+        /// * Returns `Error::TerminationTimelocked` if it is too early.
+        ///
+        /// * Returns `Error::WindDownPending` if there are still users, or
+        ///   balances left by removed bindings, to refund, and
+        ///   `Error::ReceiptsOutstanding` if there is still tipEDG to redeem.
+        ///
+        /// * Returns `Error::TipsPending` if there are held tips, they have to
+        ///   be finalized or revoked first.
+        ///
+        /// * Returns `Error::StakeLocked` if part of the balance is still
+        ///   bonded, or unbonding, it has to be unstaked and withdrawn first.
+        fn terminate(&mut self) -> Result<(), Error> {
+            let beneficiary = self.ensure_termination_due()?;
+            if self.pending_tips_count > 0 {
+                return Err(Error::TipsPending);
+            }
+            if self.wind_down_cursor.map(|(stage, _)| stage)
+                != Some(WindDownStage::Receipts)
+                || self.accounts_count > 0
+                || self.expired_count > 0
+            {
+                return Err(Error::WindDownPending);
+            }
+            if self.receipt_supply > 0 {
+                return Err(Error::ReceiptsOutstanding {
+                    supply: self.receipt_supply,
+                });
+            }
+            let staking = self.staking();
+            if staking.bonded > 0 || staking.unbonding > 0 {
+                return Err(Error::StakeLocked);
+            }
+            // the contract is gone after this, so it is logged first.
+            self.log_admin_action(
+                Some(beneficiary),
+                PrivilegedAction::Admin(AdminAction::Terminate),
+            );
+            self.env().terminate_contract(beneficiary)
+        }
+
+        /// Register a contract to be notified on every successful tip.
+        ///
+        /// The contract must implement an `on_tip(from: AccountId, to:
+        /// AccountId, amount: Balance)` message, it is called with a bounded
+        /// gas limit and any failure is ignored.
+        ///
+        /// Errors:
+        /// * Returns `Error::AlreadyRegistered` if the contract is already
+        ///   registered.
+        ///
+        /// * Returns `Error::TooManyHooks` if there is no room for more hooks.
+        fn register_hook(&mut self, contract: AccountId) -> Result<(), Error> {
+            if self.hooks().contains(&contract) {
+                return Err(Error::AlreadyRegistered);
+            }
+            if self.hooks_count as usize >= MAX_HOOKS {
+                return Err(Error::TooManyHooks);
+            }
+            self.hooks.insert(self.hooks_count, &contract);
+            self.hooks_count += 1;
+            Ok(())
+        }
+
+        /// Remove a previously registered hook contract.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the contract is not registered.
+        fn unregister_hook(
+            &mut self,
+            contract: AccountId,
+        ) -> Result<(), Error> {
+            let index = self.hooks().iter().position(|hook| hook == &contract);
+            match index {
+                Some(index) => {
+                    // move the last hook into the freed index.
+                    let last = self.hooks_count - 1;
+                    if let Some(moved) = self.hooks.get(last) {
+                        self.hooks.insert(index as u32, &moved);
+                    }
+                    self.hooks.remove(last);
+                    self.hooks_count = last;
+                    Ok(())
+                },
+                None => Err(Error::NotFound {
+                    which: Entity::Hook,
+                }),
+            }
+        }
+
+        /// Pay the pot of the `index`th registered group to its registrant,
+        /// a pot that could not be paid is left to the beneficiary.
+        fn refund_group_pot(&mut self, index: u32) {
+            let group_id = self
+                .group_ids
+                .get(index)
+                .expect("every registered group has an index");
+            let mut group =
+                self.groups.get(group_id).expect("the group is registered");
+            if group.treasury > 0
+                && self.credit(group.registrant, group.treasury).is_ok()
+            {
+                group.treasury = 0;
+                self.groups.insert(group_id, &group);
+            }
+        }
+
+        /// Refund the balance of the team to its contributors and remove it,
+        /// see `cancel_team`.
+        fn refund_team(&mut self, team_id: TeamId, team: Team) {
+            let mut refunded: Balance = 0;
+            for (contributor, funded) in &team.contributors {
+                let share = pro_rata(team.balance, *funded, team.contributed);
+                if share > 0 && self.credit(*contributor, share).is_ok() {
+                    refunded += share;
+                }
+            }
+            self.treasury =
+                self.treasury.saturating_add(team.balance - refunded);
+            self.teams.remove(team_id);
+            self.env().emit_event(TeamCancelled { team_id, refunded });
+        }
+
+        /// Refund the milestones of the escrow that were not released to its
+        /// funder, and the fees of its open disputes to who opened them.
+        fn refund_escrow_of(&mut self, escrow_id: EscrowId) {
+            let mut escrow = match self.escrows.get(escrow_id) {
+                Some(escrow) => escrow,
+                None => return,
+            };
+            let mut amount: Balance = 0;
+            for (index, milestone) in escrow.milestones.iter_mut().enumerate() {
+                match milestone.state {
+                    MilestoneState::Released | MilestoneState::Refunded => {
+                        continue
+                    },
+                    MilestoneState::Disputed => {
+                        let key = (escrow_id, index as u32);
+                        if let Some(dispute) = self.disputes.get(key) {
+                            let _ = self.credit(dispute.opened_by, dispute.fee);
+                            self.disputes.remove(key);
+                        }
+                    },
+                    MilestoneState::Pending | MilestoneState::Delivered(_) => {
+                    },
+                }
+                milestone.state = MilestoneState::Refunded;
+                amount = amount.saturating_add(milestone.amount);
+            }
+            if amount > 0 && self.credit(escrow.funder, amount).is_ok() {
+                self.env().emit_event(EscrowRefunded { escrow_id, amount });
+            }
+            self.escrows.insert(escrow_id, &escrow);
+        }
+
+        /// Refund the stake of each side of the challenge, if it was not
+        /// flipped yet.
+        fn refund_challenge(&mut self, challenge_id: ChallengeId) {
+            if let Some(challenge) = self.challenges.get(challenge_id) {
+                let _ = self.credit(challenge.challenger, challenge.stake);
+                if let Some((account, _, _)) = challenge.accepted {
+                    let _ = self.credit(account, challenge.stake);
+                }
+                self.challenges.remove(challenge_id);
+                self.env().emit_event(ChallengeRefunded { challenge_id });
+            }
+        }
+
+        /// Refund the last purchase of the raffle, to the key its buyer moved
+        /// to if they did, or remove the raffle if none is left, returning
+        /// whether there was a purchase to refund.
+        fn refund_last_purchase(&mut self, raffle_id: RaffleId) -> bool {
+            let mut raffle = match self.raffles.get(raffle_id) {
+                Some(raffle) => raffle,
+                None => return false,
+            };
+            if raffle.purchases == 0 {
+                self.raffles.remove(raffle_id);
+                return false;
+            }
+            raffle.purchases -= 1;
+            let (mut buyer, _) = self
+                .raffle_purchases
+                .get((raffle_id, raffle.purchases))
+                .expect("every purchase has an index");
+            while let Some(moved) = self.raffle_moves.get((raffle_id, buyer)) {
+                buyer = moved;
+            }
+            // the buyer is refunded all thier tickets at thier last purchase.
+            if let Some(amount) = self.raffle_spent.get((raffle_id, buyer)) {
+                let _ = self.credit(buyer, amount);
+                self.raffle_spent.remove((raffle_id, buyer));
+                self.env().emit_event(RaffleRefunded {
+                    raffle_id,
+                    account: buyer,
+                    amount,
+                });
+            }
+            self.raffles.insert(raffle_id, &raffle);
+            true
+        }
+
+        /// Pay the last staker of the poll thier share, see `poll_share`, or
+        /// remove the poll if none is left, returning whether there was a
+        /// staker to pay.
+        fn refund_last_staker(&mut self, poll_id: PollId) -> bool {
+            let mut poll = match self.polls.get(poll_id) {
+                Some(poll) => poll,
+                None => return false,
+            };
+            if poll.stakers == 0 {
+                self.polls.remove(poll_id);
+                return false;
+            }
+            poll.stakers -= 1;
+            let account = self
+                .poll_stakers
+                .get((poll_id, poll.stakers))
+                .expect("every staker has an index");
+            self.poll_stakers.remove((poll_id, poll.stakers));
+            // a staker listed twice, or who claimed, has nothing left.
+            if self.poll_staked.contains((poll_id, account)) {
+                let amount = self.poll_share(poll_id, &poll, account);
+                if amount > 0 && self.credit(account, amount).is_ok() {
+                    self.env().emit_event(PollPaid {
+                        poll_id,
+                        account,
+                        amount,
+                    });
+                }
+                self.poll_staked.remove((poll_id, account));
+                if let Some(option) = poll.winning_option {
+                    self.poll_stakes.remove((poll_id, account, option));
+                }
+            }
+            self.polls.insert(poll_id, &poll);
+            true
+        }
+
+        /// Pay the tip jar of the `index`th piece of content with one to its
+        /// attested author, a jar whose author is not bound is left to the
+        /// beneficiary.
+        fn pay_content_jar(&mut self, index: u32) {
+            let content_hash = self
+                .content_hashes
+                .get(index)
+                .expect("every tip jar has an index");
+            let mut jar = self
+                .content_jars
+                .get(content_hash)
+                .expect("the tip jar exists");
+            let author =
+                jar.author.and_then(|tg_id| self.tg_address.get(tg_id));
+            if let Some(account) = author {
+                if jar.balance > 0 && self.credit(account, jar.balance).is_ok()
+                {
+                    self.env().emit_event(ContentClaimed {
+                        content_hash,
+                        account,
+                        received: jar.balance,
+                    });
+                    jar.balance = 0;
+                    self.content_jars.insert(content_hash, &jar);
+                }
+            }
+        }
+
+        /// Pay the unclaimed relay fees of the `index`th relayer, fees that
+        /// could not be transferred are left to the beneficiary.
+        fn pay_relayer(&mut self, index: u32) {
+            let operator = self
+                .relayer_accounts
+                .get(index)
+                .expect("every relayer has an index");
+            let mut relayer = self
+                .relayers
+                .get(operator)
+                .expect("every indexed relayer is configured");
+            let amount = relayer.reimbursement;
+            if amount > 0 && self.env().transfer(operator, amount).is_ok() {
+                self.env().emit_event(RelayReimbursed { operator, amount });
+            }
+            relayer.reimbursement = 0;
+            self.relayers.insert(operator, &relayer);
+        }
+
+        /// Cancel the oldest waiting job, giving its incentive back to the
+        /// balance of its owner, or leaving it to them if they are not bound.
+        fn refund_first_job(&mut self) {
+            let job_id = self.job_queue().head;
+            let job = self
+                .jobs
+                .get(job_id)
+                .expect("the head of the queue is waiting");
+            self.remove_job(job_id, &job);
+            match self.users.get(job.owner) {
+                Some(mut record) => {
+                    record.balance =
+                        record.balance.saturating_add(job.incentive);
+                    record.audit.other_in =
+                        record.audit.other_in.saturating_add(job.incentive);
+                    self.users.insert(job.owner, &record);
+                },
+                None => self.leave_balance(job.owner, job.incentive),
+            }
+            self.env().emit_event(JobCancelled { job_id });
+        }
+
+        /// Pay the balance left to the last account with one, removing it
+        /// even if it could not be transferred.
+        fn pay_last_expired(&mut self) {
+            let account = self
+                .expired_accounts
+                .get(self.expired_count - 1)
+                .expect("the last account exists");
+            let amount = self.take_expired(account);
+            let _ = self.env().transfer(account, amount);
+        }
+
+        /// Redeem all the tipEDG of the last holder, burning it even if the
+        /// EDG backing it could not be transferred.
+        fn redeem_last_holder(&mut self) {
+            let holder = self
+                .receipt_holders
+                .get(self.receipt_holders_count - 1)
+                .expect("the last holder exists");
+            let amount = self.receipt_balance_of(holder);
+            let _ = self.env().transfer(holder, amount);
+            self.burn_receipts(holder, amount)
+                .expect("every holder has tipEDG");
+        }
+
+        /// Unbind and refund the last bound account, dropping its binding
+        /// even if the refund could not be transferred.
+        fn refund_last_account(&mut self) {
+            let account = self
+                .accounts
+                .get(self.accounts_count - 1)
+                .expect("the last account exists");
+            if self.unbind_account(account).is_err() {
+                let record = self
+                    .users
+                    .get(account)
+                    .expect("every bound account has a record");
+                self.remove_binding(account, &record);
+            }
+        }
+
+        /// Unbind the account, refunding it but for what its pending slashes
+        /// hold, which goes to the treasury.
+        fn force_unbind(&mut self, account: AccountId) -> Result<(), Error> {
+            let record = self.users.get(account).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let treasury = self.treasury;
+            if record.pending_slash > 0 {
+                self.treasury = treasury
+                    .checked_add(record.pending_slash)
+                    .ok_or(Error::Overflow)?;
+                let mut slashed = record.clone();
+                slashed.balance -= record.pending_slash;
+                slashed.audit.other_out = slashed
+                    .audit
+                    .other_out
+                    .saturating_add(record.pending_slash);
+                slashed.pending_slash = 0;
+                self.users.insert(account, &slashed);
+            }
+            // `force_unbind_many` goes on after a failure, so undo the slash.
+            if let Err(error) = self.unbind_account(account) {
+                self.treasury = treasury;
+                self.users.insert(account, &record);
+                return Err(error);
+            }
+            self.env().emit_event(ForceUnbound {
+                account,
+                tg_id: record.tg_id,
+            });
+            Ok(())
+        }
+
+        fn unbind_account(&mut self, account: AccountId) -> Result<(), Error> {
+            let record = match self.users.get(account) {
+                Some(record) => record,
+                None => {
+                    return Err(Error::NotFound {
+                        which: Entity::Account,
+                    })
+                },
+            };
+            // if the caller have some balance, a queued withdrawal, a bind
+            // deposit, or waiting jobs, transfer it back to them. this
+            // happens first, so a failed transfer keeps the binding, it could
+            // not call back into the contract as transfers to accounts run no
+            // code.
+            let pending =
+                record.pending_withdrawal.map_or(0, |(amount, _)| amount);
+            let refund = record
+                .balance
+                .saturating_add(pending)
+                .saturating_add(record.bind_deposit)
+                .saturating_add(self.job_incentives_of(account));
+            if refund > 0 {
+                self.env()
+                    .transfer(record.payout.unwrap_or(account), refund)
+                    .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            }
+            self.cancel_jobs_of(account);
+            self.remove_binding(account, &record);
+            Ok(())
+        }
+
+        /// The current window of the binding rate limit, how many bindings
+        /// were made in it, and how many times the account bound in it.
+        ///
+        /// Errors:
+        /// Returns `Error::RateLimited` if the account, or all accounts,
+        /// bound too many times in the window.
+        fn count_binding(
+            &self,
+            limit: BindRateLimit,
+            account: AccountId,
+        ) -> Result<(BlockNumber, u32, u32), Error> {
+            let (window, until) =
+                ledger::rate_window(self.env().block_number(), limit.window);
+            let total = match self.bind_window {
+                (current, total) if current == window => total,
+                _ => 0,
+            };
+            let count = match self.bind_counts.get(account) {
+                Some((current, count)) if current == window => count,
+                _ => 0,
+            };
+            if (limit.total > 0 && total >= limit.total)
+                || (limit.per_account > 0 && count >= limit.per_account)
+            {
+                return Err(Error::RateLimited { until });
+            }
+            Ok((window, total, count))
+        }
+
+        /// Whether the binding of the record went without activity for
+        /// `binding_ttl` blocks.
+        fn is_expired(&self, record: &UserRecord) -> bool {
+            self.binding_ttl > 0
+                && self.env().block_number()
+                    >= record.last_active.saturating_add(self.binding_ttl)
+        }
+
+        /// Ensure the termination is scheduled and its timelock passed,
+        /// returning the beneficiary.
+        ///
+        /// Errors:
+        /// * Returns `Error::TerminationNotScheduled` if it is not scheduled.
+        ///
+        /// * Returns `Error::TerminationTimelocked` if it is too early.
+        fn ensure_termination_due(&self) -> Result<AccountId, Error> {
+            let (beneficiary, at) =
+                self.termination.ok_or(Error::TerminationNotScheduled)?;
+            if self.env().block_number() < at {
+                return Err(Error::TerminationTimelocked { at });
+            }
+            Ok(beneficiary)
+        }
+
+        /// Ensures that the binding of the account could be removed with its
+        /// balance, as it has no pending slash, held tips, self-lock nor
+        /// votes locking it.
+        fn ensure_removable(
+            &self,
+            account: AccountId,
+            record: &UserRecord,
+        ) -> Result<(), Error> {
+            if record.pending_slash > 0 {
+                return Err(Error::SlashPending);
+            }
+            if self.incoming_tips.contains(account) {
+                return Err(Error::TipsPending);
+            }
+            ensure_not_self_locked(record)?;
+            if record.locked > 0
+                && self.env().block_number() < record.locked_until
+            {
+                return Err(Error::BalanceLocked {
+                    until: record.locked_until,
+                });
+            }
+            Ok(())
+        }
+
+        /// Remove the expired binding of the account, leaving its balance,
+        /// with any queued withdrawal, to be withdrawn by `withdraw_expired`.
+        fn expire_binding(&mut self, account: AccountId, record: UserRecord) {
+            let pending =
+                record.pending_withdrawal.map_or(0, |(amount, _)| amount);
+            let balance = record
+                .balance
+                .saturating_add(pending)
+                .saturating_add(record.bind_deposit)
+                .saturating_add(self.cancel_jobs_of(account));
+            self.leave_balance(account, balance);
+            self.remove_binding(account, &record);
+            self.env().emit_event(BindingExpired {
+                account,
+                tg_id: record.tg_id,
+                balance,
+            });
+        }
+
+        /// Leave `amount` to the account of a removed binding, to be
+        /// withdrawn by `withdraw_expired`.
+        fn leave_balance(&mut self, account: AccountId, amount: Balance) {
+            if amount > 0 {
+                let left = self.expired_balances.get(account).unwrap_or(0);
+                self.expired_balances
+                    .insert(account, &left.saturating_add(amount));
+                push_indexed(
+                    &mut self.expired_accounts,
+                    &mut self.expired_index,
+                    &mut self.expired_count,
+                    account,
+                );
+            }
+        }
+
+        /// Remove the balance left to the account by a removed binding,
+        /// returning it.
+        fn take_expired(&mut self, account: AccountId) -> Balance {
+            let amount = self.expired_balances.get(account).unwrap_or(0);
+            self.expired_balances.remove(account);
+            remove_indexed(
+                &mut self.expired_accounts,
+                &mut self.expired_index,
+                &mut self.expired_count,
+                account,
+            );
+            amount
+        }
+
+        /// Remove the binding of the account, and its record, whatever its
+        /// balance is.
+        fn remove_binding(&mut self, account: AccountId, record: &UserRecord) {
+            for secondary in &record.linked {
+                self.linked_accounts.remove(secondary);
+            }
+            self.users.remove(account);
+            self.tg_address.remove(record.tg_id);
+            self.remote_addresses.remove(account);
+            // fill the gap in the list with the last account.
+            let last = self.accounts_count - 1;
+            if record.index != last {
+                let moved =
+                    self.accounts.get(last).expect("the last account exists");
+                let mut moved_record = self
+                    .users
+                    .get(moved)
+                    .expect("every listed account has a record");
+                moved_record.index = record.index;
+                self.users.insert(moved, &moved_record);
+                self.accounts.insert(record.index, &moved);
+            }
+            self.accounts.remove(last);
+            self.accounts_count = last;
+            self.env().emit_event(Unbound {
+                schema_version: EVENT_SCHEMA_VERSION,
+                account,
+                tg_id: record.tg_id,
+            });
+        }
+
+        /// Query a user whose balance could be swept as dust, as the account
+        /// and its record.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the `tg_id` is not bound.
+        ///
+        /// * Returns `Error::NotDormant` if the user was active within the dust
+        ///   dormancy.
+        ///
+        /// * Returns `Error::NotDust` if the balance of the user is not below
+        ///   the existential deposit.
+        fn dust_of(
+            &self,
+            tg_id: TelegramId,
+        ) -> Result<(AccountId, UserRecord), Error> {
+            let account = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let record = self
+                .users
+                .get(account)
+                .expect("every bound telegram id has a record");
+            let until = record.last_active.saturating_add(self.dust_dormancy);
+            if self.env().block_number() < until {
+                return Err(Error::NotDormant { until });
+            }
+            if record.balance >= self.env().minimum_balance() {
+                return Err(Error::NotDust);
+            }
+            Ok((account, record))
+        }
+
+        /// Query the record of the TelegramId.
+        fn record_of(&self, tg_id: TelegramId) -> Option<UserRecord> {
+            self.address_of(tg_id)
+                .and_then(|address| self.users.get(address))
+        }
+
+        /// The balance of the `address` with the given `record` as seen by
+        /// the caller, zero if the user hides it from them.
+        fn visible_balance(
+            &self,
+            address: AccountId,
+            record: &UserRecord,
+        ) -> Balance {
+            let caller = self.env().caller();
+            let hidden = record.settings.privacy.hide_balance
+                && caller != address
+                && !self.owners.contains(&caller);
+            if hidden {
+                0
+            } else {
+                record.balance
+            }
+        }
+
+        /// Tip `target` from the `caller` account, given the caller's
+        /// already loaded `record`.
+        ///
+        /// The tip is held if there is a reversal window, or if it is above
+        /// the acceptance threshold, otherwise it is paid right away. The
+        /// tipEDG of the caller is burned to cover what thier balance is
+        /// short of.
+        ///
+        /// All the checks, and the transfer itself, happen before we write
+        /// anything, and the tipEDG is burned once the tip is held or paid,
+        /// so a failed tip leaves the storage as it was. `execute_due` relies
+        /// on this, as it goes on after a failed tip.
+        ///
+        /// A tip paid right away is sent to the `remote` address if the
+        /// runtime could send it there, see `settle_tip`.
+        #[allow(clippy::too_many_arguments)]
+        fn tip_account(
+            &mut self,
+            caller: AccountId,
+            mut record: UserRecord,
+            target: AccountId,
+            amount: Balance,
+            commitment: Option<Hash>,
+            group: Option<GroupId>,
+            remote: Option<RemoteAddress>,
+        ) -> Result<(), Error> {
+            let round_up = match self.charity {
+                Some(_) if record.settings.round_up => {
+                    ledger::round_up(amount, EDG)
+                },
+                _ => 0,
+            };
+            let shortfall = amount
+                .saturating_add(round_up)
+                .saturating_sub(self.tippable(&record));
+            let covered =
+                self.cover_from_receipts(caller, &mut record, shortfall);
+            let (charity, round_up, cooldown) =
+                self.check_tip(caller, &record, target, amount, group)?;
+            let cooldown = group.zip(cooldown);
+            let charged = amount + round_up;
+            let now = self.env().block_number();
+            let era = self.current_era();
+            if record.spent_era != era {
+                record.spent_era = era;
+                record.spent_today = 0;
+            }
+            record.spent_today = record.spent_today.saturating_add(charged);
+            record.last_active = now;
+            record.last_tip_at = Some(now);
+            record.balance -= charged;
+            record.audit.tips_out =
+                record.audit.tips_out.saturating_add(charged);
+            record.charity_contributed =
+                record.charity_contributed.saturating_add(round_up);
+            let needs_acceptance = self
+                .acceptance_threshold
+                .is_some_and(|threshold| amount > threshold);
+            let held = self.is_enabled(Feature::PendingTips)
+                && (needs_acceptance || self.reversal_window > 0);
+            if held {
+                self.hold_tip(
+                    record,
+                    PendingTip {
+                        from: caller,
+                        to: target,
+                        amount,
+                        release_at: now + self.reversal_window,
+                        needs_acceptance,
+                        commitment,
+                        group,
+                        expires_at: None,
+                    },
+                )?;
+            } else {
+                self.settle_tip(
+                    caller,
+                    Some(record),
+                    target,
+                    amount,
+                    commitment,
+                    group,
+                    remote,
+                )?;
+            }
+            if covered > 0 {
+                self.burn_receipts(caller, covered)
+                    .expect("the tipEDG covered is held");
+            }
+            // only groups with a cooldown need to know when users tipped.
+            if let Some((group_id, _)) = cooldown {
+                self.group_last_tip.insert((group_id, caller), &now);
+            }
+            if let Some(charity) = charity.filter(|_| round_up > 0) {
+                self.donate_round_up(caller, charity, round_up, commitment);
+            }
+            // calling other contracts comes last, once the state is updated.
+            if !held && commitment.is_none() {
+                self.notify_hooks(caller, target, amount);
+            }
+            Ok(())
+        }
+
+        /// Send `amount` to the burn address, counting it as burned.
+        ///
+        /// Errors:
+        /// * Returns `Error::BurnDisabled` if there is no burn address.
+        ///
+        /// * Returns `Error::BelowSubsistenceThreshold` if the transfer failed.
+        fn send_to_burn_address(
+            &mut self,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let address = self.burn_address.ok_or(Error::BurnDisabled)?;
+            self.env()
+                .transfer(address, amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            self.total_burned = self.total_burned.saturating_add(amount);
+            Ok(())
+        }
+
+        /// Send the rounded up part of a tip to the charity, the sender is
+        /// already charged for it.
+        ///
+        /// The tip itself is already paid, or held, at this point, so if the
+        /// charity could not receive the donation it is given back to the
+        /// sender instead of failing the tip.
+        fn donate_round_up(
+            &mut self,
+            caller: AccountId,
+            charity: AccountId,
+            amount: Balance,
+            commitment: Option<Hash>,
+        ) {
+            if self.env().transfer(charity, amount).is_err() {
+                if let Some(mut record) = self.users.get(caller) {
+                    record.balance = record.balance.saturating_add(amount);
+                    record.audit.other_in =
+                        record.audit.other_in.saturating_add(amount);
+                    record.spent_today =
+                        record.spent_today.saturating_sub(amount);
+                    record.charity_contributed =
+                        record.charity_contributed.saturating_sub(amount);
+                    self.users.insert(caller, &record);
+                }
+                return;
+            }
+            self.env().emit_event(CharityDonated {
+                account: commitment.is_none().then_some(caller),
+                charity,
+                amount,
+            });
+        }
+
+        /// Hold a tip in the reversal window, or until the recipient accepts
+        /// it, the `record` of the sender is already charged.
+        ///
+        /// Errors:
+        /// * Returns `Error::TooManyPendingTips` if the recipient is already
+        ///   receiving too many held tips, or too many from the sender.
+        ///
+        /// * Returns `Error::TooManyPositions` if the sender has too many open
+        ///   positions.
+        fn hold_tip(
+            &mut self,
+            record: UserRecord,
+            tip: PendingTip,
+        ) -> Result<(), Error> {
+            let mut incoming =
+                self.incoming_tips.get(tip.to).unwrap_or_default();
+            if incoming.len() >= MAX_PENDING_TIPS {
+                return Err(Error::TooManyPendingTips);
+            }
+            let from_sender = incoming
+                .iter()
+                .filter_map(|tip_id| self.pending_tips.get(tip_id))
+                .filter(|held| held.from == tip.from)
+                .count();
+            if from_sender >= MAX_PENDING_TIPS_PER_SENDER {
+                return Err(Error::TooManyPendingTips);
+            }
+            let tip_id = self.next_tip_id;
+            self.add_position(tip.from, Position::Tip(tip_id))?;
+            self.next_tip_id += 1;
+            incoming.push(tip_id);
+            self.incoming_tips.insert(tip.to, &incoming);
+            self.pending_tips.insert(tip_id, &tip);
+            self.pending_tips_count += 1;
+            self.users.insert(tip.from, &record);
+            let hide_to = self
+                .users
+                .get(tip.to)
+                .is_some_and(|record| record.settings.privacy.hide_received);
+            self.env().emit_event(TipHeld {
+                tip_id,
+                from: tip.commitment.is_none().then_some(tip.from),
+                to: (!hide_to).then_some(tip.to),
+                amount: tip.amount,
+                release_at: tip.release_at,
+                needs_acceptance: tip.needs_acceptance,
+            });
+            Ok(())
+        }
+
+        /// Pay a held tip to the recipient.
+        fn release_tip(
+            &mut self,
+            tip_id: HeldTipId,
+            tip: PendingTip,
+        ) -> Result<(), Error> {
+            let record = self.users.get(tip.from);
+            self.settle_tip(
+                tip.from,
+                record,
+                tip.to,
+                tip.amount,
+                tip.commitment,
+                tip.group,
+                None,
+            )?;
+            let uri_hash = self.tip_nft_requests.get(tip_id);
+            self.remove_pending_tip(tip_id, &tip);
+            self.env().emit_event(TipFinalized { tip_id });
+            if tip.commitment.is_none() {
+                if let Some(uri_hash) = uri_hash {
+                    self.mint_tip_nft(tip.from, tip.to, tip.amount, uri_hash);
+                }
+                self.notify_hooks(tip.from, tip.to, tip.amount);
+            }
+            Ok(())
+        }
+
+        /// Mint a thank-you receipt token of a paid tip to its recipient,
+        /// unless they hide the tips they receive.
+        fn mint_tip_nft(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+            uri_hash: Hash,
+        ) {
+            let tg_id = match self.users.get(to) {
+                Some(record) if !record.settings.privacy.hide_received => {
+                    record.tg_id
+                },
+                _ => return,
+            };
+            let id = self.next_tip_nft_id;
+            self.tip_nfts.insert(
+                id,
+                &TipNft {
+                    from,
+                    to: tg_id,
+                    amount,
+                    uri_hash,
+                    minted_at: self.env().block_number(),
+                },
+            );
+            self.next_tip_nft_id += 1;
+            let count = self.tip_nft_counts.get(tg_id).unwrap_or(0);
+            self.tip_nfts_by_owner.insert((tg_id, count), &id);
+            self.tip_nft_counts.insert(tg_id, &(count + 1));
+            self.env().emit_event(TipNftMinted {
+                from,
+                to: tg_id,
+                id,
+            });
+        }
+
+        /// Give a held tip back to its sender.
+        fn refund_tip(
+            &mut self,
+            tip_id: HeldTipId,
+            tip: &PendingTip,
+        ) -> Result<(), Error> {
+            match self.users.get(tip.from) {
+                Some(mut record) => {
+                    record.balance = record
+                        .balance
+                        .checked_add(tip.amount)
+                        .ok_or(Error::Overflow)?;
+                    record.audit.other_in =
+                        record.audit.other_in.saturating_add(tip.amount);
+                    self.users.insert(tip.from, &record);
+                },
+                // the sender unbound since, so refund them directly.
+                None => {
+                    self.env()
+                        .transfer(tip.from, tip.amount)
+                        .map_err(|_| Error::BelowSubsistenceThreshold)?;
+                },
+            }
+            self.remove_pending_tip(tip_id, tip);
+            Ok(())
+        }
+
+        /// Finalize every held tip to `account` that is out of its reversal
+        /// window, and does not wait to be accepted.
+        fn finalize_released_tips(
+            &mut self,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            let now = self.env().block_number();
+            for tip_id in self.incoming_tips.get(account).unwrap_or_default() {
+                let tip = self.pending_tip_of(tip_id)?;
+                if !tip.needs_acceptance && now >= tip.release_at {
+                    self.release_tip(tip_id, tip)?;
+                }
+            }
+            Ok(())
+        }
+
+        fn remove_pending_tip(&mut self, tip_id: HeldTipId, tip: &PendingTip) {
+            let mut incoming =
+                self.incoming_tips.get(tip.to).unwrap_or_default();
+            incoming.retain(|id| *id != tip_id);
+            if incoming.is_empty() {
+                self.incoming_tips.remove(tip.to);
+            } else {
+                self.incoming_tips.insert(tip.to, &incoming);
+            }
+            self.pending_tips.remove(tip_id);
+            self.tip_nft_requests.remove(tip_id);
+            self.pending_tips_count -= 1;
+        }
+
+        fn pending_tip_of(
+            &self,
+            tip_id: HeldTipId,
+        ) -> Result<PendingTip, Error> {
+            self.pending_tips
+                .get(tip_id)
+                .ok_or(Error::NotFound { which: Entity::Tip })
+        }
+
+        /// Tip `bps` basis points of the caller's spendable balance to the
+        /// `tg_id`, see `tip_percent`.
+        fn tip_share(
+            &mut self,
+            tg_id: TelegramId,
+            bps: u16,
+        ) -> Result<(), Error> {
+            if bps > MAX_BPS {
+                return Err(Error::InvalidParamValue);
+            }
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let available = self.tippable(&record);
+            let mut amount = fee_of(available, bps);
+            let rounds_up = self.charity.is_some() && record.settings.round_up;
+            if rounds_up
+                && amount.saturating_add(ledger::round_up(amount, EDG))
+                    > available
+            {
+                amount = available - available % EDG;
+            }
+            self.tip_account(caller, record, target, amount, None, None, None)
+        }
+
+        /// Check whether `caller`, with the given `record`, could tip `amount`
+        /// to `target`, optionally in a group, without changing anything.
+        ///
+        /// Returns the charity the tip is rounded up for, how much it is
+        /// rounded up by, and the cooldown of the group if it has one.
+        fn check_tip(
+            &self,
+            caller: AccountId,
+            record: &UserRecord,
+            target: AccountId,
+            amount: Balance,
+            group: Option<GroupId>,
+        ) -> Result<(Option<AccountId>, Balance, Option<BlockNumber>), Error>
+        {
+            ensure_not_self_locked(record)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if caller == target {
+                return Err(Error::SelfTip);
+            }
+            if let Some(policy) = self.inbox_policies.get(target) {
+                let sender = record.tg_id;
+                if policy.blocked.contains(&sender)
+                    || (policy.allowlist_only
+                        && !policy.allowed.contains(&sender))
+                {
+                    return Err(Error::RecipientBlockedSender);
+                }
+            }
+            let params = &self.params;
+            let config = group
+                .and_then(|group_id| self.groups.get(group_id))
+                .map(|group| group.config)
+                .unwrap_or_default();
+            let min_tip = config.min_tip.unwrap_or(params.min_tip);
+            if amount < min_tip {
+                return Err(Error::BelowMinTip { min: min_tip });
+            }
+            let now = self.env().block_number();
+            let newcomer = self.newcomer_limits.filter(|limits| {
+                record.stats.tips_sent < limits.tips
+                    || now < record.bound_at.saturating_add(limits.blocks)
+            });
+            let max_tip = newcomer.map_or(params.max_tip, |limits| {
+                limits.max_tip.min(params.max_tip)
+            });
+            let max_tip = self
+                .level_limits(record.verification)
+                .map_or(max_tip, |limits| limits.max_tip.min(max_tip));
+            if amount > max_tip {
+                return Err(Error::AboveMaxTip { max: max_tip });
+            }
+            if let Some((limits, last)) = newcomer.zip(record.last_tip_at) {
+                let until = last.saturating_add(limits.cooldown);
+                if now < until {
+                    return Err(Error::TipCooldown { until });
+                }
+            }
+            let charity = self.charity.filter(|_| record.settings.round_up);
+            let round_up = match charity {
+                Some(_) => ledger::round_up(amount, EDG),
+                None => 0,
+            };
+            let charged =
+                amount.checked_add(round_up).ok_or(Error::Overflow)?;
+            let available = self.tippable(record);
+            if available < charged {
+                return Err(Error::InsufficientFunds {
+                    required: charged,
+                    available,
+                });
+            }
+            let cooldown = group.zip(config.cooldown);
+            if let Some((group_id, cooldown)) = cooldown {
+                if let Some(last) = self.group_last_tip.get((group_id, caller))
+                {
+                    let until = last.saturating_add(cooldown);
+                    if now < until {
+                        return Err(Error::TipCooldown { until });
+                    }
+                }
+            }
+            let spent_today = ledger::spent_in(
+                record.spent_today,
+                record.spent_era,
+                self.current_era(),
+            );
+            ledger::check_daily_limit(
+                spent_today,
+                charged,
+                record.settings.daily_limit,
+            )
+            .map_err(|remaining| Error::DailyLimitExceeded { remaining })?;
+            Ok((charity, round_up, config.cooldown))
+        }
+
+        /// Pay a tip to `target`, taking the fee into the treasury, given the
+        /// sender's `record`, if they are still bound, already charged.
+        ///
+        /// The tip is sent to the `remote` address if the runtime could send
+        /// it there, and to the payout address of the recipient otherwise.
+        ///
+        /// The hooks are not notified, the callers do it once they are done
+        /// with the state.
+        #[allow(clippy::too_many_arguments)]
+        fn settle_tip(
+            &mut self,
+            caller: AccountId,
+            record: Option<UserRecord>,
+            target: AccountId,
+            amount: Balance,
+            commitment: Option<Hash>,
+            group: Option<GroupId>,
+            remote: Option<RemoteAddress>,
+        ) -> Result<(), Error> {
+            // the group could not be unregistered, it is checked before the
+            // tip.
+            let group_record =
+                group.and_then(|group_id| self.groups.get(group_id));
+            // a group could lower the fee, but not below the floor.
+            let fee_bps = group_record
+                .as_ref()
+                .and_then(|group| group.config.fee_bps)
+                .map(|fee_bps| fee_bps.max(self.group_fee_floor))
+                .unwrap_or(self.params.fee_bps);
+            let fee = fee_of(amount, fee_bps);
+            let treasury =
+                self.treasury.checked_add(fee).ok_or(Error::Overflow)?;
+            let received = amount - fee;
+            let target_record = self.users.get(target);
+            // the tip could be reaped if the payee's account is empty, so it
+            // is kept in the recipient's balance instead.
+            let keep = received < self.existential_deposit;
+            if keep && target_record.is_none() {
+                return Err(Error::BelowExistentialDeposit {
+                    min: self.existential_deposit,
+                });
+            }
+            let payee = target_record
+                .as_ref()
+                .and_then(|record| record.payout)
+                .unwrap_or(target);
+            let remote = remote.filter(|remote| {
+                !keep && self.transfer_to_parachain(*remote, received)
+            });
+            if !keep && remote.is_none() {
+                self.env()
+                    .transfer(payee, received)
+                    .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            }
+            self.treasury = treasury;
+            let mut fee = fee;
+            let tip_id = self.next_receipt_id;
+            self.next_receipt_id += 1;
+            let limit = self.history_limit;
+            let mut from_tg = None;
+            if let Some(mut record) = record {
+                self.extend_streak(&mut record);
+                let rebate = match &self.streak_rebate {
+                    Some(rebate) if record.streak >= rebate.min_streak => {
+                        fee_of(fee, rebate.rebate_bps)
+                    },
+                    _ => 0,
+                };
+                self.treasury -= rebate;
+                fee -= rebate;
+                record.balance = record.balance.saturating_add(rebate);
+                record.audit.other_in =
+                    record.audit.other_in.saturating_add(rebate);
+                record.stats.tips_sent =
+                    record.stats.tips_sent.saturating_add(1);
+                record.stats.total_sent =
+                    record.stats.total_sent.saturating_add(amount);
+                if let Some(points) = amount.checked_div(self.points_rate) {
+                    record.points = record.points.saturating_add(points);
+                }
+                // badges earned by anonymous tips are not announced.
+                self.award_badges(caller, &mut record, commitment.is_none());
+                // anonymous tips are left out of the sender's history.
+                if commitment.is_none() {
+                    from_tg = Some(record.tg_id);
+                    push_history(
+                        &mut self.sent_history,
+                        limit,
+                        caller,
+                        &mut record.sent_seq,
+                        tip_id,
+                    );
+                }
+                self.users.insert(caller, &record);
+            }
+            let mut hide_to = false;
+            let mut to_tg = None;
+            if let Some(mut target_record) = target_record {
+                if keep {
+                    target_record.balance =
+                        target_record.balance.saturating_add(received);
+                    target_record.audit.tips_in =
+                        target_record.audit.tips_in.saturating_add(received);
+                }
+                let stats = &mut target_record.stats;
+                stats.tips_received = stats.tips_received.saturating_add(1);
+                stats.total_received =
+                    stats.total_received.saturating_add(received);
+                hide_to = target_record.settings.privacy.hide_received;
+                if !hide_to {
+                    to_tg = Some(target_record.tg_id);
+                    push_history(
+                        &mut self.received_history,
+                        limit,
+                        target,
+                        &mut target_record.received_seq,
+                        tip_id,
+                    );
+                }
+                self.users.insert(target, &target_record);
+            }
+            if let Some((group_id, group_record)) = group.zip(group_record) {
+                self.record_group_tip(
+                    group_id,
+                    group_record,
+                    commitment.is_none().then_some(caller),
+                    (!hide_to).then_some(target),
+                    amount,
+                    received,
+                );
+            }
+            let mut cache = self.stats_cache.get(()).unwrap_or_default();
+            self.add_fee(&mut cache, amount, fee);
+            self.record_epoch_tip(&mut cache, from_tg, to_tg, amount, received);
+            self.stats_cache.insert((), &cache);
+            if from_tg.is_some() {
+                self.record_contest_tip(caller, amount);
+            }
+            self.tip_log.insert(
+                tip_id % TIP_LOG_SIZE,
+                &TipRecord {
+                    from_tg,
+                    to_tg,
+                    amount,
+                    at: self.env().block_number(),
+                },
+            );
+            self.env().emit_event(Tipped {
+                schema_version: EVENT_SCHEMA_VERSION,
+                tip_id,
+                from: commitment.is_none().then_some(caller),
+                to: (!hide_to).then_some(target),
+                from_tg,
+                to_tg,
+                amount,
+                fee,
+                commitment,
+                group,
+            });
+            if let Some(remote) = remote {
+                self.env().emit_event(TipSentRemotely {
+                    tip_id,
+                    para_id: remote.para_id,
+                });
+            }
+            Ok(())
+        }
+
+        /// Count a tip towards the statistics and leaderboards of a group,
+        /// leaving out the parties who do not want to be known.
+        fn record_group_tip(
+            &mut self,
+            group_id: GroupId,
+            mut group: Group,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+            received: Balance,
+        ) {
+            group.tips = group.tips.saturating_add(1);
+            group.volume = group.volume.saturating_add(amount);
+            if let Some(from) = from {
+                let mut stats =
+                    self.group_stats.get((group_id, from)).unwrap_or_default();
+                stats.tips_sent = stats.tips_sent.saturating_add(1);
+                stats.total_sent = stats.total_sent.saturating_add(amount);
+                ledger::rank(
+                    &mut group.top_senders,
+                    from,
+                    stats.total_sent,
+                    GROUP_LEADERBOARD_SIZE,
+                );
+                self.group_stats.insert((group_id, from), &stats);
+            }
+            if let Some(to) = to {
+                let mut stats =
+                    self.group_stats.get((group_id, to)).unwrap_or_default();
+                stats.tips_received = stats.tips_received.saturating_add(1);
+                stats.total_received =
+                    stats.total_received.saturating_add(received);
+                ledger::rank(
+                    &mut group.top_receivers,
+                    to,
+                    stats.total_received,
+                    GROUP_LEADERBOARD_SIZE,
+                );
+                self.group_stats.insert((group_id, to), &stats);
+            }
+            self.groups.insert(group_id, &group);
+        }
+
+        fn group_of(&self, group_id: GroupId) -> Result<Group, Error> {
+            self.groups.get(group_id).ok_or(Error::NotFound {
+                which: Entity::Group,
+            })
+        }
+
+        /// Ensures that the caller is the registrant, or a moderator, of the
+        /// group, returning the group.
+        fn ensure_moderator(&self, group_id: GroupId) -> Result<Group, Error> {
+            let group = self.group_of(group_id)?;
+            let caller = self.env().caller();
+            if caller != group.registrant && !group.moderators.contains(&caller)
+            {
+                return Err(Error::NotAllowed);
+            }
+            Ok(group)
+        }
+
+        /// Move the binding, the record, the tip history, the open positions,
+        /// the waiting jobs, the inbox policy and the remote address of
+        /// `from` to the key `to`.
+        ///
+        /// The per-group statistics stay with the old key.
+        ///
+        /// Errors:
+        /// * Returns `Error::AccountAlreadyBound` if `to` is already bound.
+        ///
+        /// * Returns `Error::BalanceLocked` if the balance is locked by votes,
+        ///   so it could not vote again from the new key.
+        ///
+        /// * Returns `Error::SlashPending` if the user has a pending slash.
+        ///
+        /// * Returns `Error::TipsPending` if there are held tips to the user.
+        fn move_binding(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            record: UserRecord,
+        ) -> Result<(), Error> {
+            if self.users.contains(to) || self.linked_accounts.contains(to) {
+                return Err(Error::AccountAlreadyBound);
+            }
+            if record.locked > 0
+                && self.env().block_number() < record.locked_until
+            {
+                return Err(Error::BalanceLocked {
+                    until: record.locked_until,
+                });
+            }
+            if record.pending_slash > 0 {
+                return Err(Error::SlashPending);
+            }
+            if self.incoming_tips.contains(from) {
+                return Err(Error::TipsPending);
+            }
+            let limit = self.history_limit;
+            move_history(
+                &mut self.sent_history,
+                limit,
+                from,
+                to,
+                record.sent_seq,
+            );
+            move_history(
+                &mut self.received_history,
+                limit,
+                from,
+                to,
+                record.received_seq,
+            );
+            let mut positions = self.open_positions(to);
+            for position in self.open_positions(from) {
+                self.move_position(from, to, position);
+                if !positions.contains(&position) {
+                    positions.push(position);
+                }
+            }
+            self.positions.remove(from);
+            self.positions.insert(to, &positions);
+            if let Some(user_jobs) = self.user_jobs.get(from) {
+                for job_id in &user_jobs {
+                    let mut job = self
+                        .jobs
+                        .get(job_id)
+                        .expect("every job of a user is stored");
+                    job.owner = to;
+                    self.jobs.insert(job_id, &job);
+                }
+                self.user_jobs.remove(from);
+                self.user_jobs.insert(to, &user_jobs);
+            }
+            if let Some(policy) = self.inbox_policies.get(from) {
+                self.inbox_policies.remove(from);
+                self.inbox_policies.insert(to, &policy);
+            }
+            if let Some(remote) = self.remote_addresses.get(from) {
+                self.remote_addresses.remove(from);
+                self.remote_addresses.insert(to, &remote);
+            }
+            self.recoveries.remove(from);
+            self.dust_sweeps.remove(from);
+            for secondary in &record.linked {
+                self.linked_accounts.insert(secondary, &to);
+            }
+            self.users.remove(from);
+            self.users.insert(to, &record);
+            self.tg_address.insert(record.tg_id, &to);
+            self.accounts.insert(record.index, &to);
+            Ok(())
+        }
+
+        /// The share of the account of a resolved poll, the total staked on
+        /// it pro-rata to the account's stake on the winning option, rounded
+        /// down, or its stakes back if the poll was not resolved, or no one
+        /// staked on the winning option.
+        fn poll_share(
+            &self,
+            poll_id: PollId,
+            poll: &Poll,
+            account: AccountId,
+        ) -> Balance {
+            let staked = self.poll_staked.get((poll_id, account)).unwrap_or(0);
+            let option = match poll.winning_option {
+                Some(option) => option,
+                None => return staked,
+            };
+            let winning_total =
+                self.poll_option_stakes.get((poll_id, option)).unwrap_or(0);
+            if winning_total == 0 {
+                return staked;
+            }
+            let stake = self
+                .poll_stakes
+                .get((poll_id, account, option))
+                .unwrap_or(0);
+            pro_rata(poll.total, stake, winning_total)
+        }
+
+        /// The positions of the account that are still open.
+        fn open_positions(&self, account: AccountId) -> Vec<Position> {
+            let mut positions = self.positions.get(account).unwrap_or_default();
+            positions.retain(|position| match *position {
+                Position::Tip(tip_id) => self
+                    .pending_tips
+                    .get(tip_id)
+                    .is_some_and(|tip| tip.from == account),
+                Position::Raffle(raffle_id) => {
+                    self.raffles.contains(raffle_id)
+                        && self.raffle_spent.contains((raffle_id, account))
+                },
+                Position::Poll(poll_id) => {
+                    self.poll_staked.contains((poll_id, account))
+                        && self.polls.get(poll_id).is_some_and(|poll| {
+                            // a losing stake could not be claimed.
+                            poll.winning_option.is_none_or(|option| {
+                                self.poll_option_stakes
+                                    .get((poll_id, option))
+                                    .unwrap_or(0)
+                                    == 0
+                                    || self
+                                        .poll_stakes
+                                        .contains((poll_id, account, option))
+                            })
+                        })
+                },
+                Position::Group(group_id) => {
+                    self.groups.get(group_id).is_some_and(|group| {
+                        group.registrant == account
+                            || group.moderators.contains(&account)
+                    })
+                },
+            });
+            positions
+        }
+
+        /// Record a position of the account, dropping those that closed.
+        ///
+        /// Errors:
+        /// Returns `Error::TooManyPositions` if the account already has
+        /// `MAX_POSITIONS` open positions.
+        fn add_position(
+            &mut self,
+            account: AccountId,
+            position: Position,
+        ) -> Result<(), Error> {
+            let mut positions = self.open_positions(account);
+            if positions.contains(&position) {
+                return Ok(());
+            }
+            if positions.len() >= MAX_POSITIONS {
+                return Err(Error::TooManyPositions);
+            }
+            positions.push(position);
+            self.positions.insert(account, &positions);
+            Ok(())
+        }
+
+        /// Store the tip jar of the content, listing it if it is new.
+        fn put_content_jar(&mut self, content_hash: Hash, jar: &ContentJar) {
+            if !self.content_jars.contains(content_hash) {
+                self.content_hashes
+                    .insert(self.content_jars_count, &content_hash);
+                self.content_jars_count += 1;
+            }
+            self.content_jars.insert(content_hash, jar);
+        }
+
+        /// List the account as a staker of the poll.
+        fn add_staker(
+            &mut self,
+            poll_id: PollId,
+            poll: &mut Poll,
+            account: AccountId,
+        ) {
+            self.poll_stakers.insert((poll_id, poll.stakers), &account);
+            poll.stakers += 1;
+        }
+
+        /// Move an open position of `from` to the key `to`.
+        fn move_position(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            position: Position,
+        ) {
+            match position {
+                Position::Tip(tip_id) => {
+                    if let Some(mut tip) = self.pending_tips.get(tip_id) {
+                        tip.from = to;
+                        self.pending_tips.insert(tip_id, &tip);
+                    }
+                },
+                Position::Raffle(raffle_id) => {
+                    if let Some(spent) =
+                        self.raffle_spent.get((raffle_id, from))
+                    {
+                        let kept =
+                            self.raffle_spent.get((raffle_id, to)).unwrap_or(0);
+                        self.raffle_spent.remove((raffle_id, from));
+                        self.raffle_spent.insert(
+                            (raffle_id, to),
+                            &kept.saturating_add(spent),
+                        );
+                    }
+                    // the tickets stay bought by the old key, `to` is the
+                    // last key of the buyer so it never moved away.
+                    self.raffle_moves.remove((raffle_id, to));
+                    self.raffle_moves.insert((raffle_id, from), &to);
+                },
+                Position::Poll(poll_id) => {
+                    if let Some(staked) = self.poll_staked.get((poll_id, from))
+                    {
+                        let kept = self.poll_staked.get((poll_id, to));
+                        if kept.is_none() {
+                            if let Some(mut poll) = self.polls.get(poll_id) {
+                                self.add_staker(poll_id, &mut poll, to);
+                                self.polls.insert(poll_id, &poll);
+                            }
+                        }
+                        let kept = kept.unwrap_or(0);
+                        self.poll_staked.remove((poll_id, from));
+                        self.poll_staked.insert(
+                            (poll_id, to),
+                            &kept.saturating_add(staked),
+                        );
+                    }
+                    let options =
+                        self.polls.get(poll_id).map_or(0, |poll| poll.options);
+                    for option in 0..options {
+                        if let Some(stake) =
+                            self.poll_stakes.get((poll_id, from, option))
+                        {
+                            let kept = self
+                                .poll_stakes
+                                .get((poll_id, to, option))
+                                .unwrap_or(0);
+                            self.poll_stakes.remove((poll_id, from, option));
+                            self.poll_stakes.insert(
+                                (poll_id, to, option),
+                                &kept.saturating_add(stake),
+                            );
+                        }
+                    }
+                },
+                Position::Group(group_id) => {
+                    if let Some(mut group) = self.groups.get(group_id) {
+                        if group.registrant == from {
+                            group.registrant = to;
+                        }
+                        if let Some(index) = group
+                            .moderators
+                            .iter()
+                            .position(|account| account == &from)
+                        {
+                            if group.moderators.contains(&to) {
+                                group.moderators.swap_remove(index);
+                            } else {
+                                group.moderators[index] = to;
+                            }
+                        }
+                        self.groups.insert(group_id, &group);
+                    }
+                },
+            }
+        }
+
+        fn unlock_account(
+            &mut self,
+            account: AccountId,
+            mut record: UserRecord,
+            owner: Option<AccountId>,
+        ) {
+            record.self_locked = false;
+            record.unlock_requested_at = None;
+            self.users.insert(account, &record);
+            self.env().emit_event(AccountUnlocked { account, owner });
+        }
+
+        /// Pay `amount` of the balance in the `record` to the payout address
+        /// of the account.
+        fn withdraw(
+            &mut self,
+            account: AccountId,
+            mut record: UserRecord,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            self.env()
+                .transfer(record.payout.unwrap_or(account), amount)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            record.balance -= amount;
+            record.audit.withdrawn =
+                record.audit.withdrawn.saturating_add(amount);
+            self.users.insert(account, &record);
+            self.env().emit_event(Withdrawn { account, amount });
+            Ok(())
+        }
+
+        /// Withdraw `amount` of the `record` of the account to its payout
+        /// address, or queue it if it has a withdrawal delay, the spendable
+        /// balance is already checked.
+        fn start_withdrawal(
+            &mut self,
+            account: AccountId,
+            mut record: UserRecord,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            self.apply_delay_change(&mut record);
+            record.last_active = self.env().block_number();
+            if record.withdrawal_delay == 0 {
+                return self.withdraw(account, record, amount);
+            }
+            let at = self
+                .env()
+                .block_timestamp()
+                .saturating_add(record.withdrawal_delay);
+            record.balance -= amount;
+            record.pending_withdrawal = Some((amount, at));
+            record.audit.withdrawn =
+                record.audit.withdrawn.saturating_add(amount);
+            self.users.insert(account, &record);
+            self.env().emit_event(WithdrawalRequested {
+                account,
+                amount,
+                at,
+            });
+            Ok(())
+        }
+
+        /// Pay the queued withdrawal of the account after its delay.
+        fn execute_pending_withdrawal(
+            &mut self,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            let (mut record, amount, at) = self.pending_withdrawal(account)?;
+            ensure_not_self_locked(&record)?;
+            if self.env().block_timestamp() < at {
+                return Err(Error::WithdrawalTimelocked { at });
+            }
+            // the amount is already taken out of the balance, and counted as
+            // withdrawn again by `withdraw`.
+            record.balance =
+                record.balance.checked_add(amount).ok_or(Error::Overflow)?;
+            record.audit.withdrawn =
+                record.audit.withdrawn.saturating_sub(amount);
+            record.pending_withdrawal = None;
+            record.last_active = self.env().block_number();
+            self.withdraw(account, record, amount)
+        }
+
+        /// Query the queued withdrawal of the account, with its record.
+        fn pending_withdrawal(
+            &self,
+            account: AccountId,
+        ) -> Result<(UserRecord, Balance, Timestamp), Error> {
+            let record = self.users.get(account).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let (amount, at) =
+                record.pending_withdrawal.ok_or(Error::NotFound {
+                    which: Entity::Withdrawal,
+                })?;
+            Ok((record, amount, at))
+        }
+
+        /// Apply the shorter withdrawal delay of the user once it takes
+        /// effect.
+        fn apply_delay_change(&self, record: &mut UserRecord) {
+            if let Some((delay, effective_at)) = record.delay_change {
+                if self.env().block_timestamp() >= effective_at {
+                    record.withdrawal_delay = delay;
+                    record.delay_change = None;
+                }
+            }
+        }
+
+        fn update_beneficiary(
+            &mut self,
+            beneficiary: Option<(AccountId, BlockNumber)>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            // the beneficiary could not get the balance out any faster than
+            // a withdrawal.
+            if beneficiary.is_some_and(|(_, period)| {
+                period < blocks_in(record.withdrawal_delay)
+            }) {
+                return Err(Error::InvalidParamValue);
+            }
+            record.beneficiary = beneficiary;
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(BeneficiarySet {
+                account: caller,
+                beneficiary,
+            });
+            Ok(())
+        }
+
+        fn team_of(&self, team_id: TeamId) -> Result<Team, Error> {
+            self.teams.get(team_id).ok_or(Error::NotFound {
+                which: Entity::Team,
+            })
+        }
+
+        /// Ensures that the caller is a member of the team, returning thier
+        /// TelegramId.
+        fn ensure_member(&self, team: &Team) -> Result<TelegramId, Error> {
+            let record = self
+                .users
+                .get(self.env().caller())
+                .filter(|record| team.members.contains(&record.tg_id))
+                .ok_or(Error::NotAllowed)?;
+            ensure_not_self_locked(&record)?;
+            Ok(record.tg_id)
+        }
+
+        /// Store a team tip with its approvals, or pay it once it has enough.
+        fn approve_team_tip_with(
+            &mut self,
+            team_tip_id: TeamTipId,
+            team: Team,
+            tip: TeamTip,
+        ) -> Result<(), Error> {
+            if tip.approvals.len() < team.threshold as usize {
+                self.team_tips.insert(team_tip_id, &tip);
+                return Ok(());
+            }
+            self.pay_team_tip(team_tip_id, team, tip)
+        }
+
+        /// Pay an approved team tip from the team balance.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotFound` if the recipient is not bound.
+        ///
+        /// * Returns `Error::InsufficientFunds` if the team balance can not
+        ///   cover the tip.
+        ///
+        /// * Returns `Error::Overflow` if the treasury would overflow.
+        fn pay_team_tip(
+            &mut self,
+            team_tip_id: TeamTipId,
+            mut team: Team,
+            tip: TeamTip,
+        ) -> Result<(), Error> {
+            let target = self.address_of(tip.to).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            if team.balance < tip.amount {
+                return Err(Error::InsufficientFunds {
+                    required: tip.amount,
+                    available: team.balance,
+                });
+            }
+            let fee = fee_of(tip.amount, self.params.fee_bps);
+            let treasury =
+                self.treasury.checked_add(fee).ok_or(Error::Overflow)?;
+            let received = tip.amount - fee;
+            let mut record = self
+                .users
+                .get(target)
+                .expect("every bound telegram id has a record");
+            self.env()
+                .transfer(record.payout.unwrap_or(target), received)
+                .map_err(|_| Error::BelowSubsistenceThreshold)?;
+            self.treasury = treasury;
+            self.record_fee(tip.amount, fee);
+            team.balance -= tip.amount;
+            team.idle_until =
+                self.env().block_number().saturating_add(TEAM_IDLE_PERIOD);
+            self.teams.insert(tip.team_id, &team);
+            let stats = &mut record.stats;
+            stats.tips_received = stats.tips_received.saturating_add(1);
+            stats.total_received =
+                stats.total_received.saturating_add(received);
+            self.users.insert(target, &record);
+            self.team_tips.remove(team_tip_id);
+            self.env().emit_event(TeamTipped {
+                team_tip_id,
+                team_id: tip.team_id,
+                to: target,
+                amount: tip.amount,
+                fee,
+            });
+            Ok(())
+        }
+
+        fn ensure_not_banned(
+            &self,
+            group_id: GroupId,
+            tg_id: TelegramId,
+        ) -> Result<(), Error> {
+            if self.group_bans.contains((group_id, tg_id)) {
+                return Err(Error::BannedFromGroup { tg_id });
+            }
+            Ok(())
+        }
+
+        /// Count a tip sent today towards the streak of the user.
+        ///
+        /// A tip on the day after the last one extends the streak, more tips
+        /// on the same day do not, and a missed day starts it over.
+        fn extend_streak(&self, record: &mut UserRecord) {
+            let today = self.env().block_timestamp() / DAY;
+            if record.streak > 0 && record.last_tip_day == today {
+                return;
+            }
+            if record.streak > 0 && record.last_tip_day + 1 == today {
+                record.streak = record.streak.saturating_add(1);
+            } else {
+                record.streak = 1;
+            }
+            record.last_tip_day = today;
+        }
+
+        /// Award the badges the user has earned since, minting an achievement
+        /// token for each unless the NFTs are disabled, and announcing them if
+        /// `announce` is set.
+        fn award_badges(
+            &mut self,
+            account: AccountId,
+            record: &mut UserRecord,
+            announce: bool,
+        ) {
+            let stats = &record.stats;
+            let earned = [
+                (BADGE_FIRST_TIP, stats.tips_sent >= 1),
+                (BADGE_TEN_TIPS, stats.tips_sent >= 10),
+                (BADGE_HUNDRED_EDG, stats.total_sent >= 100 * EDG),
+                (BADGE_THOUSAND_EDG, stats.total_sent >= 1000 * EDG),
+                (
+                    BADGE_ONE_YEAR,
+                    self.env().block_number() >= record.bound_at + YEAR,
+                ),
+            ];
+            for (badge, condition) in earned {
+                if condition && record.badges & badge == 0 {
+                    record.badges |= badge;
+                    if announce {
+                        self.env().emit_event(BadgeEarned { account, badge });
+                    }
+                    if !self.is_enabled(Feature::Nfts) {
+                        continue;
+                    }
+                    let id = self.next_achievement_id;
+                    self.achievements.insert(
+                        id,
+                        &Achievement {
+                            tg_id: record.tg_id,
+                            badge,
+                            minted_at: self.env().block_number(),
+                        },
+                    );
+                    self.next_achievement_id += 1;
+                    record.achievements.push(id);
+                    if announce {
+                        self.env().emit_event(AchievementMinted {
+                            account,
+                            id,
+                            badge,
+                        });
+                    }
+                }
+            }
+        }
+
+        /// A page of a user's tip history, newest first.
+        fn history_page(
+            &self,
+            history: &Mapping<(AccountId, u32), TipId>,
+            account: AccountId,
+            seq: u32,
+            start: u32,
+            limit: u32,
+        ) -> Vec<TipId> {
+            let oldest = seq.saturating_sub(self.history_limit);
+            let newest = seq.saturating_sub(start);
+            (oldest..newest)
+                .rev()
+                .take(limit.min(MAX_RECENT_TIPS) as usize)
+                .filter_map(|position| history.get((account, position)))
+                .collect()
+        }
+
+        /// Convert `usd_cents` to EDG at the `price` of the oracle, checking
+        /// its age and that it comes to at most `max_amount`.
+        fn usd_to_edg(
+            &self,
+            usd_cents: u64,
+            price: Balance,
+            updated_at: Timestamp,
+            max_amount: Balance,
+        ) -> Result<Balance, Error> {
+            if self.env().block_timestamp().saturating_sub(updated_at)
+                > MAX_PRICE_AGE
+            {
+                return Err(Error::StalePrice { updated_at });
+            }
+            let amount = Balance::from(usd_cents)
+                .checked_mul(price)
+                .ok_or(Error::Overflow)?;
+            if amount > max_amount {
+                return Err(Error::PriceSlippage { price });
+            }
+            Ok(amount)
+        }
+
+        /// Tip `usd_cents` to the TelegramId at the `price` the oracle
+        /// answered `tip_usd` with.
+        fn tip_usd_at(
+            &mut self,
+            tg_id: TelegramId,
+            usd_cents: u64,
+            max_amount: Balance,
+            price: Balance,
+            updated_at: Timestamp,
+        ) -> Result<(), Error> {
+            let (caller, record) =
+                self.acting_record().ok_or(Error::NotFound {
+                    which: Entity::Sender,
+                })?;
+            let target = self.address_of(tg_id).ok_or(Error::NotFound {
+                which: Entity::Recipient,
+            })?;
+            let amount =
+                self.usd_to_edg(usd_cents, price, updated_at, max_amount)?;
+            self.tip_account(caller, record, target, amount, None, None, None)?;
+            self.usd_price = Some((price, updated_at));
+            Ok(())
+        }
+
+        /// Swap `amount` of the `pay_token` held by the contract through the
+        /// DEX to EDG, for at least `min_out`, returning how much the contract
+        /// got.
+        fn swap(
+            &self,
+            dex: &Dex,
+            pay_token: AccountId,
+            amount: Balance,
+            min_out: Balance,
+        ) -> Result<Balance, Error> {
+            self.invoke(
+                pay_token,
+                ExecutionInput::new(Selector::new(PSP22_APPROVE_SELECTOR))
+                    .push_arg(dex.router)
+                    .push_arg(amount),
+            )?;
+            let before = self.env().balance();
+            self.invoke(
+                dex.router,
+                ExecutionInput::new(Selector::new(SWAP_FOR_NATIVE_SELECTOR))
+                    .push_arg(amount)
+                    .push_arg(min_out)
+                    .push_arg(vec![pay_token, dex.wrapped_edg])
+                    .push_arg(self.env().account_id())
+                    .push_arg(self.env().block_timestamp()),
+            )?;
+            Ok(self.env().balance().saturating_sub(before))
+        }
+
+        /// Deposit the `received` EDG of a swap to the caller's `record`,
+        /// and tip it to the `target`.
+        fn tip_swapped(
+            &mut self,
+            caller: AccountId,
+            mut record: UserRecord,
+            target: AccountId,
+            received: Balance,
+        ) -> Result<(), Error> {
+            record.balance = record
+                .balance
+                .checked_add(received)
+                .ok_or(Error::Overflow)?;
+            record.audit.deposited =
+                record.audit.deposited.saturating_add(received);
+            self.tip_account(caller, record, target, received, None, None, None)
+        }
+
+        /// Give the `pay_token` pulled from the caller back, that is anything
+        /// above what the contract `held` before.
+        fn give_back(
+            &self,
+            pay_token: AccountId,
+            to: AccountId,
+            held: Balance,
+        ) {
+            let this = self.env().account_id();
+            if let Ok(balance) = self.token_balance(pay_token, this) {
+                let _ = self.invoke(
+                    pay_token,
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                        .push_arg(to)
+                        .push_arg(balance.saturating_sub(held))
+                        .push_arg(Vec::<u8>::new()),
+                );
+            }
+        }
+
+        /// Query the balance of `owner` in the PSP22 `token`.
+        fn token_balance(
+            &self,
+            token: AccountId,
+            owner: AccountId,
+        ) -> Result<Balance, Error> {
+            call_out(|| {
+                build_call::<Environment>()
+                    .call_type(
+                        Call::new()
+                            .callee(token)
+                            .gas_limit(self.token_call_gas_limit)
+                            .transferred_value(0),
+                    )
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(
+                            PSP22_BALANCE_OF_SELECTOR,
+                        ))
+                        .push_arg(owner),
+                    )
+                    .returns::<Balance>()
+                    .fire()
+            })
+            .map_err(|_| Error::SwapFailed)
+        }
+
+        /// Call a token, or the DEX router, ignoring its output, the balances
+        /// are checked instead.
+        fn invoke<Args: scale::Encode>(
+            &self,
+            callee: AccountId,
+            input: ExecutionInput<Args>,
+        ) -> Result<(), Error> {
+            call_out(|| {
+                build_call::<Environment>()
+                    .call_type(
+                        Call::new()
+                            .callee(callee)
+                            .gas_limit(self.token_call_gas_limit)
+                            .transferred_value(0),
+                    )
+                    .exec_input(input)
+                    .returns::<()>()
+                    .fire()
+            })
+            .map_err(|_| Error::SwapFailed)
+        }
+
+        /// The commitment to the sender of an anonymous tip.
+        fn commitment_of(&self, sender: AccountId, salt: Hash) -> Hash {
+            Hash::from(
+                self.env().hash_encoded::<Blake2x256, _>(&(sender, salt)),
+            )
+        }
+
+        /// The era of the current block.
+        fn current_era(&self) -> EraIndex {
+            self.env().block_number() / ERA_LENGTH
+        }
+
+        /// Count a tip towards the statistics of the current epoch, and of
+        /// the parties who do not mind being known, a new epoch starts from
+        /// zero on its first tip.
+        fn record_epoch_tip(
+            &mut self,
+            cache: &mut StatsCache,
+            from_tg: Option<TelegramId>,
+            to_tg: Option<TelegramId>,
+            amount: Balance,
+            received: Balance,
+        ) {
+            let epoch = self.current_epoch();
+            if cache.epoch_totals.epoch != epoch {
+                self.write_stats(cache);
+                cache.epoch_totals = EpochStats {
+                    epoch,
+                    ..Default::default()
+                };
+            }
+            let totals = &mut cache.epoch_totals;
+            totals.tips = totals.tips.saturating_add(1);
+            totals.volume = totals.volume.saturating_add(amount);
+            if let Some(tg_id) = from_tg {
+                let mut stats = self.epoch_stats_of(tg_id, epoch);
+                stats.tips_sent = stats.tips_sent.saturating_add(1);
+                stats.total_sent = stats.total_sent.saturating_add(amount);
+                if totals
+                    .top_tipper
+                    .is_none_or(|(_, top)| stats.total_sent > top)
+                {
+                    totals.top_tipper = Some((tg_id, stats.total_sent));
+                }
+                self.user_epoch_stats.insert((tg_id, epoch), &stats);
+            }
+            if let Some(tg_id) = to_tg {
+                let mut stats = self.epoch_stats_of(tg_id, epoch);
+                stats.tips_received = stats.tips_received.saturating_add(1);
+                stats.total_received =
+                    stats.total_received.saturating_add(received);
+                self.user_epoch_stats.insert((tg_id, epoch), &stats);
+            }
+        }
+
+        /// Count a tip of the `sender` towards the running contest, if it
+        /// did not end yet.
+        fn record_contest_tip(&mut self, sender: AccountId, amount: Balance) {
+            let now = self.env().block_number();
+            let running = self.active_contest.and_then(|contest_id| {
+                self.contests
+                    .get(contest_id)
+                    .filter(|contest| now < contest.ends_at)
+                    .map(|contest| (contest_id, contest))
+            });
+            let (contest_id, mut contest) = match running {
+                Some(running) => running,
+                None => return,
+            };
+            let volume = self
+                .contest_volume_of(contest_id, sender)
+                .saturating_add(amount);
+            self.contest_volumes.insert((contest_id, sender), &volume);
+            let places = contest.weights.len();
+            ledger::rank(&mut contest.board, sender, volume, places);
+            self.contests.insert(contest_id, &contest);
+        }
+
+        /// Add a tip and its fee to the history of the current era.
+        fn record_fee(&mut self, amount: Balance, fee: Balance) {
+            let mut cache = self.stats_cache.get(()).unwrap_or_default();
+            self.add_fee(&mut cache, amount, fee);
+            self.stats_cache.insert((), &cache);
+        }
+
+        /// Add a tip and its fee to the totals of the current era in the
+        /// `cache`.
+        ///
+        /// The totals of the current era, and epoch, are cached together and
+        /// written to thier mappings every `STATS_FLUSH_INTERVAL` tips, or
+        /// once a new era, or epoch, starts, so most tips pay for one read
+        /// and write of the cache instead of two of each.
+        fn add_fee(
+            &mut self,
+            cache: &mut StatsCache,
+            amount: Balance,
+            fee: Balance,
+        ) {
+            let era = self.current_era();
+            // the epoch totals of the last tip are counted after its fee, so
+            // they are written by the next one.
+            if cache.unflushed_tips >= STATS_FLUSH_INTERVAL {
+                self.write_stats(cache);
+            }
+            if cache.era_fees.era != era {
+                self.write_stats(cache);
+                cache.era_fees = EraFees {
+                    era,
+                    ..Default::default()
+                };
+            }
+            let fees = &mut cache.era_fees;
+            fees.volume = fees.volume.saturating_add(amount);
+            fees.fees = fees.fees.saturating_add(fee);
+            cache.unflushed_tips += 1;
+        }
+
+        /// Write the totals of the `cache` to thier mappings, if any tip was
+        /// counted since they were last written.
+        fn write_stats(&mut self, cache: &mut StatsCache) {
+            if cache.unflushed_tips == 0 {
+                return;
+            }
+            self.fee_history.insert(cache.era_fees.era, &cache.era_fees);
+            self.epoch_stats
+                .insert(cache.epoch_totals.epoch, &cache.epoch_totals);
+            cache.unflushed_tips = 0;
+        }
+
+        /// Credit `amount` to the balance of the account, or transfer it
+        /// directly if the account is not bound.
+        fn credit(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            match self.users.get(account) {
+                Some(mut record) => {
+                    record.balance = record
+                        .balance
+                        .checked_add(amount)
+                        .ok_or(Error::Overflow)?;
+                    record.audit.other_in =
+                        record.audit.other_in.saturating_add(amount);
+                    self.users.insert(account, &record);
+                },
+                None => {
+                    self.env()
+                        .transfer(account, amount)
+                        .map_err(|_| Error::BelowSubsistenceThreshold)?;
+                },
+            }
+            Ok(())
+        }
+
+        /// Make the normalized alias resolve to the TelegramId, releasing
+        /// the previous alias of the TelegramId.
+        fn assign_alias(&mut self, name: String, tg_id: TelegramId) {
+            if let Some(previous) = self.aliases.get(tg_id) {
+                self.unassign_alias(previous, tg_id);
+            }
+            self.alias_holders.insert(&name, &tg_id);
+            self.aliases.insert(tg_id, &name);
+            self.env().emit_event(AliasClaimed { tg_id, name });
+        }
+
+        /// Release the normalized alias held by the TelegramId.
+        fn unassign_alias(&mut self, name: String, tg_id: TelegramId) {
+            self.alias_holders.remove(&name);
+            self.aliases.remove(tg_id);
+            self.env().emit_event(AliasReleased { tg_id, name });
+        }
+
+        /// Take the `stake` of a coin-flip challenge from the balance of the
+        /// account.
+        fn take_stake(
+            &mut self,
+            account: AccountId,
+            mut record: UserRecord,
+            stake: Balance,
+        ) -> Result<(), Error> {
+            ensure_not_self_locked(&record)?;
+            if stake == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let available = self.spendable(&record);
+            if available < stake {
+                return Err(Error::InsufficientFunds {
+                    required: stake,
+                    available,
+                });
+            }
+            record.balance -= stake;
+            record.audit.other_out =
+                record.audit.other_out.saturating_add(stake);
+            record.last_active = self.env().block_number();
+            self.users.insert(account, &record);
+            Ok(())
+        }
+
+        /// The buyer of the `ticket` of the raffle, found by a binary search
+        /// over its purchases.
+        fn raffle_ticket_owner(
+            &self,
+            raffle_id: RaffleId,
+            raffle: &Raffle,
+            ticket: u32,
+        ) -> AccountId {
+            let (mut low, mut high) = (0, raffle.purchases - 1);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let (_, end) = self
+                    .raffle_purchases
+                    .get((raffle_id, mid))
+                    .expect("every purchase of a raffle is stored");
+                if end > ticket {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            self.raffle_purchases
+                .get((raffle_id, low))
+                .expect("every purchase of a raffle is stored")
+                .0
+        }
+
+        /// The part of the balance that is not locked by votes nor held by
+        /// pending slashes.
+        fn spendable(&self, record: &UserRecord) -> Balance {
+            ledger::spendable(
+                record.balance,
+                record.pending_slash,
+                record.locked,
+                record.locked_until,
+                self.env().block_number(),
+            )
+        }
+
+        /// The part of the spendable balance that could be tipped, above the
+        /// reserve of the user.
+        fn tippable(&self, record: &UserRecord) -> Balance {
+            self.spendable(record)
+                .saturating_sub(record.settings.reserve)
+        }
+
+        /// Apply `update` to the inbox policy of the caller, removing it once
+        /// it restricts nothing.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound, or what the
+        /// `update` returns.
+        fn update_inbox_policy(
+            &mut self,
+            update: impl FnOnce(&mut InboxPolicy) -> Result<(), Error>,
+        ) -> Result<(), Error> {
+            let (account, _) = self.acting_record().ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            let mut policy =
+                self.inbox_policies.get(account).unwrap_or_default();
+            update(&mut policy)?;
+            if policy == InboxPolicy::default() {
+                self.inbox_policies.remove(account);
+            } else {
+                self.inbox_policies.insert(account, &policy);
+            }
+            Ok(())
+        }
+
+        /// Run a job whose time came, as if its owner acted then.
+        ///
+        /// Errors:
+        /// Returns the error the same action would fail with, like `tip` for
+        /// a `JobKind::Tip`.
+        fn run_job(&mut self, job: &Job) -> Result<(), Error> {
+            match job.kind {
+                JobKind::Tip { to, amount } => {
+                    let record =
+                        self.users.get(job.owner).ok_or(Error::NotFound {
+                            which: Entity::Sender,
+                        })?;
+                    let target =
+                        self.address_of(to).ok_or(Error::NotFound {
+                            which: Entity::Recipient,
+                        })?;
+                    self.tip_account(
+                        job.owner, record, target, amount, None, None, None,
+                    )
+                },
+            }
+        }
+
+        /// The bounds of the queue of the waiting jobs.
+        fn job_queue(&self) -> JobQueue {
+            self.job_queue.get(()).unwrap_or_default()
+        }
+
+        /// The waiting jobs, oldest first.
+        fn waiting_jobs(&self) -> impl Iterator<Item = (JobId, Job)> + '_ {
+            let queue = self.job_queue();
+            (queue.head..queue.tail).filter_map(move |job_id| {
+                Some((job_id, self.jobs.get(job_id)?))
+            })
+        }
+
+        /// Take a job out of the queue and of the jobs of its owner.
+        fn remove_job(&mut self, job_id: JobId, job: &Job) {
+            self.jobs.remove(job_id);
+            let mut queue = self.job_queue();
+            queue.len -= 1;
+            // the head skips the jobs already removed, so it is always a
+            // waiting job unless the queue is empty.
+            while queue.head < queue.tail && !self.jobs.contains(queue.head) {
+                queue.head += 1;
+            }
+            self.job_queue.insert((), &queue);
+            let mut user_jobs =
+                self.user_jobs.get(job.owner).unwrap_or_default();
+            user_jobs.retain(|id| *id != job_id);
+            if user_jobs.is_empty() {
+                self.user_jobs.remove(job.owner);
+            } else {
+                self.user_jobs.insert(job.owner, &user_jobs);
+            }
+        }
+
+        /// The incentives the waiting jobs of the account hold.
+        fn job_incentives_of(&self, account: AccountId) -> Balance {
+            self.user_jobs
+                .get(account)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|job_id| self.jobs.get(job_id))
+                .fold(0, |total: Balance, job| {
+                    total.saturating_add(job.incentive)
+                })
+        }
+
+        /// Cancel the waiting jobs of an account whose binding is removed,
+        /// returning the incentives they held.
+        fn cancel_jobs_of(&mut self, account: AccountId) -> Balance {
+            let mut incentives: Balance = 0;
+            for job_id in self.user_jobs.get(account).unwrap_or_default() {
+                let job = self
+                    .jobs
+                    .get(job_id)
+                    .expect("every job of a user is stored");
+                incentives = incentives.saturating_add(job.incentive);
+                self.remove_job(job_id, &job);
+                self.env().emit_event(JobCancelled { job_id });
+            }
+            incentives
+        }
+
+        /// Ensure a deposit taking the balance of the user to `balance` is
+        /// within the limits of thier verification level.
+        ///
+        /// Errors:
+        /// Returns `Error::AboveMaxBalance` if it is not.
+        fn ensure_below_max_balance(
+            &self,
+            record: &UserRecord,
+            balance: Balance,
+        ) -> Result<(), Error> {
+            match self.level_limits(record.verification) {
+                Some(limits) if balance > limits.max_balance => {
+                    Err(Error::AboveMaxBalance {
+                        max: limits.max_balance,
+                    })
+                },
+                _ => Ok(()),
+            }
+        }
+
+        /// Ensure a deposit of the transferred value, taking the balance of
+        /// the user from `before` to `after`, is within the deposit caps.
+        ///
+        /// The contract balance already includes the transferred value.
+        ///
+        /// Errors:
+        /// Returns `Error::DepositCapExceeded` if it is not.
+        fn ensure_within_deposit_caps(
+            &self,
+            before: Balance,
+            after: Balance,
+        ) -> Result<(), Error> {
+            if let Some(cap) = self.deposit_cap.filter(|cap| after > *cap) {
+                return Err(Error::DepositCapExceeded {
+                    headroom: cap.saturating_sub(before),
+                });
+            }
+            let held = self.env().balance();
+            if let Some(cap) = self.global_deposit_cap.filter(|cap| held > *cap)
+            {
+                let transferred = self.env().transferred_value();
+                return Err(Error::DepositCapExceeded {
+                    headroom: cap
+                        .saturating_sub(held.saturating_sub(transferred)),
+                });
+            }
+            Ok(())
+        }
+
+        /// Ensure the verification level of the user unlocks the `FEATURE_*`
+        /// flag.
+        ///
+        /// Errors:
+        /// Returns `Error::FeatureLocked` if it does not.
+        fn ensure_feature(
+            &self,
+            record: &UserRecord,
+            feature: u32,
+        ) -> Result<(), Error> {
+            match self.level_limits(record.verification) {
+                Some(limits) if limits.features & feature == 0 => {
+                    Err(Error::FeatureLocked)
+                },
+                _ => Ok(()),
+            }
+        }
+
+        /// Ensure the owners did not turn the feature off.
+        ///
+        /// Errors:
+        /// Returns `Error::FeatureDisabled` if they did.
+        fn ensure_enabled(&self, feature: Feature) -> Result<(), Error> {
+            if !self.is_enabled(feature) {
+                return Err(Error::FeatureDisabled);
+            }
+            Ok(())
+        }
+
+        /// Query a tipping contest.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the contest does not exist.
+        fn contest_of(&self, contest_id: ContestId) -> Result<Contest, Error> {
+            self.contests.get(contest_id).ok_or(Error::NotFound {
+                which: Entity::Contest,
+            })
+        }
+
+        /// Query a milestone escrow.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the escrow does not exist.
+        fn escrow_of(&self, escrow_id: EscrowId) -> Result<Escrow, Error> {
+            self.escrows.get(escrow_id).ok_or(Error::NotFound {
+                which: Entity::Escrow,
+            })
+        }
+
+        /// Query the dispute over a milestone of an escrow.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the milestone is not disputed.
+        fn dispute_of(
+            &self,
+            escrow_id: EscrowId,
+            index: u32,
+        ) -> Result<Dispute, Error> {
+            self.disputes
+                .get((escrow_id, index))
+                .ok_or(Error::NotFound {
+                    which: Entity::Dispute,
+                })
+        }
+
+        /// Release the disputed milestone to the worker, or refund it to the
+        /// funder, as `released` says, sharing the fee among the arbiters
+        /// who voted so. A tie restores the milestone, and refunds the fee.
+        fn decide_dispute(
+            &mut self,
+            escrow_id: EscrowId,
+            index: u32,
+            dispute: Dispute,
+            released: Option<bool>,
+        ) -> Result<(), Error> {
+            let mut escrow = self.escrow_of(escrow_id)?;
+            let (worker, funder) = (escrow.worker, escrow.funder);
+            let milestone = milestone_of(&mut escrow, index)?;
+            let amount = milestone.amount;
+            let voters = match released {
+                Some(true) => {
+                    milestone.state = MilestoneState::Released;
+                    self.credit(worker, amount)?;
+                    dispute.release_votes
+                },
+                Some(false) => {
+                    milestone.state = MilestoneState::Refunded;
+                    self.credit(funder, amount)?;
+                    dispute.refund_votes
+                },
+                None => {
+                    milestone.state = dispute.previous;
+                    self.credit(dispute.opened_by, dispute.fee)?;
+                    Vec::new()
+                },
+            };
+            if !voters.is_empty() {
+                let share = dispute.fee / voters.len() as Balance;
+                for voter in &voters {
+                    self.credit(*voter, share)?;
+                }
+                let dust = dispute.fee - share * voters.len() as Balance;
+                self.treasury = self.treasury.saturating_add(dust);
+            }
+            self.escrows.insert(escrow_id, &escrow);
+            self.disputes.remove((escrow_id, index));
+            self.env().emit_event(DisputeResolved {
+                escrow_id,
+                index,
+                released,
+            });
+            Ok(())
+        }
+
+        /// The current params with the `param` changed to `value`.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidParamValue` if the value is not valid for
+        /// the parameter.
+        fn with_param(
+            &self,
+            param: Param,
+            value: Balance,
+        ) -> Result<Params, Error> {
+            let mut params = self.params.clone();
+            match param {
+                Param::FeeBps if value <= Balance::from(MAX_BPS) => {
+                    params.fee_bps = value as u16;
+                },
+                Param::FeeBps => return Err(Error::InvalidParamValue),
+                Param::MinTip => params.min_tip = value,
+                Param::MaxTip => params.max_tip = value,
+            }
+            if params.min_tip > params.max_tip {
+                return Err(Error::InvalidParamValue);
+            }
+            Ok(params)
+        }
+
+        /// Notify every registered hook about a successful tip.
+        ///
+        /// This is best-effort, a failing (or out of gas) hook does not revert
+        /// the tip, a `HookFailed` event is emitted instead.
+        fn notify_hooks(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) {
+            for hook in self.hooks() {
+                let result = call_out(|| {
+                    build_call::<Environment>()
+                        .call_type(
+                            Call::new()
+                                .callee(hook)
+                                .gas_limit(self.hook_gas_limit)
+                                .transferred_value(0),
+                        )
+                        .exec_input(
+                            ExecutionInput::new(Selector::new(ON_TIP_SELECTOR))
+                                .push_arg(from)
+                                .push_arg(to)
+                                .push_arg(amount),
+                        )
+                        .returns::<()>()
+                        .fire()
+                });
+                if result.is_err() {
+                    self.env().emit_event(HookFailed { hook });
+                }
+            }
+        }
+
+        /// The account the caller acts for, the bound account it is linked
+        /// to, or the caller itself.
+        fn acting_account(&self) -> AccountId {
+            let caller = self.env().caller();
+            self.linked_accounts.get(caller).unwrap_or(caller)
+        }
+
+        /// The account the caller acts for with its record, if it is bound.
+        ///
+        /// The links are only looked up if the caller is not bound, so
+        /// bound callers do not pay for them.
+        fn acting_record(&self) -> Option<(AccountId, UserRecord)> {
+            let caller = self.env().caller();
+            match self.users.get(caller) {
+                Some(record) => Some((caller, record)),
+                None => {
+                    let account = self.linked_accounts.get(caller)?;
+                    self.users.get(account).map(|record| (account, record))
+                },
+            }
+        }
+
+        /// Ensures that the `signature` over the blake2x256 hash of the
+        /// encoded `message` is made by the key of the `signer`.
+        /// otherwise, returns `Error::InvalidSignature`.
+        fn ensure_signed_by<T: scale::Encode>(
+            &self,
+            signer: AccountId,
+            message: &T,
+            signature: &[u8; 65],
+        ) -> Result<(), Error> {
+            if self.recover_signer(message, signature)? != signer {
+                return Err(Error::InvalidSignature);
+            }
+            Ok(())
+        }
+
+        /// Ensures that the `category`, if any, is registered.
+        /// otherwise, returns `Error::NotFound`.
+        fn ensure_category(&self, category: Option<u8>) -> Result<(), Error> {
+            match category {
+                Some(category)
+                    if !self
+                        .categories
+                        .iter()
+                        .any(|(id, _)| *id == category) =>
+                {
+                    Err(Error::NotFound {
+                        which: Entity::Category,
+                    })
+                },
+                _ => Ok(()),
+            }
+        }
+
+        /// Count a tip of `amount` in the totals of the `category`, if any.
+        fn count_category(&mut self, category: Option<u8>, amount: Balance) {
+            if let Some(category) = category {
+                let mut stats =
+                    self.category_stats.get(category).unwrap_or_default();
+                stats.tips = stats.tips.saturating_add(1);
+                stats.volume = stats.volume.saturating_add(amount);
+                self.category_stats.insert(category, &stats);
+            }
+        }
+
+        /// The account of the key that made the `signature` over the
+        /// blake2x256 hash of the encoded `message`.
+        ///
+        /// Errors:
+        /// Returns `Error::InvalidSignature` if no key could be recovered.
+        fn recover_signer<T: scale::Encode>(
+            &self,
+            message: &T,
+            signature: &[u8; 65],
+        ) -> Result<AccountId, Error> {
+            let message = self.env().hash_encoded::<Blake2x256, _>(message);
+            let public_key = self
+                .env()
+                .ecdsa_recover(signature, &message)
+                .map_err(|_| Error::InvalidSignature)?;
+            let key = self.env().hash_bytes::<Blake2x256>(&public_key);
+            Ok(AccountId::from(key))
+        }
+
+        /// Ensures that the caller is one of the owners of the contract.
+        /// otherwise, returns `Error::NotAllowed`.
+        #[inline(always)]
+        fn ensure_owner(&self) -> Result<(), Error> {
+            self.owners
+                .contains(&self.env().caller())
+                .then_some(())
+                .ok_or(Error::NotAllowed)
+        }
+
+        /// Ensures that the caller is the operator, or a relayer with the
+        /// permission, counting `volume` towards the cap of the relayer.
+        ///
+        /// Returns the relayer with the volume counted, to be stored by
+        /// `reimburse_relayer` once the relayed call succeeds, `None` for the
+        /// operator.
+        ///
+        /// Errors:
+        /// * Returns `Error::NotAllowed` if the caller is neither.
+        ///
+        /// * Returns `Error::RelayerCapExceeded` if the volume is above what is
+        ///   left of the cap of the relayer.
+        fn relayer_for(
+            &self,
+            permission: impl FnOnce(&RelayerPermissions) -> bool,
+            volume: Balance,
+        ) -> Result<Option<(AccountId, Relayer)>, Error> {
+            let caller = self.env().caller();
+            if caller == self.operator {
+                return Ok(None);
+            }
+            let mut relayer = self
+                .relayers
+                .get(caller)
+                .filter(|relayer| permission(&relayer.config.permissions))
+                .ok_or(Error::NotAllowed)?;
+            let era = self.current_era();
+            if relayer.volume_era != era {
+                relayer.volume_era = era;
+                relayer.volume = 0;
+            }
+            let total = relayer.volume.saturating_add(volume);
+            if let Some(cap) = relayer.config.volume_cap {
+                if total > cap {
+                    return Err(Error::RelayerCapExceeded {
+                        remaining: cap.saturating_sub(relayer.volume),
+                    });
+                }
+            }
+            relayer.volume = total;
+            Ok(Some((caller, relayer)))
+        }
+
+        /// Set the relay fee aside for the relayer, or the operator, storing
+        /// the volume `relayer_for` counted.
+        fn reimburse_relayer(
+            &mut self,
+            relayer: Option<(AccountId, Relayer)>,
+            fee: Balance,
+        ) {
+            match relayer {
+                Some((account, mut relayer)) => {
+                    relayer.reimbursement =
+                        relayer.reimbursement.saturating_add(fee);
+                    self.relayers.insert(account, &relayer);
+                },
+                None => {
+                    self.relay_reimbursement =
+                        self.relay_reimbursement.saturating_add(fee)
+                },
+            }
+            self.total_relay_fees = self.total_relay_fees.saturating_add(fee);
+        }
+
+        /// Ensures that the `payload` is a recent login of the `tg_id`, signed
+        /// by Telegram with the `mac`.
+        /// otherwise, returns `Error::InvalidTelegramAuth`.
+        #[cfg(feature = "chain-extension")]
+        fn ensure_telegram_auth(
+            &self,
+            account: AccountId,
+            tg_id: TelegramId,
+            payload: Vec<u8>,
+            mac: [u8; 32],
+        ) -> Result<(), Error> {
+            let (id, auth_date, signed_for) = parse_telegram_auth(&payload)
+                .ok_or(Error::InvalidTelegramAuth)?;
+            let age = self
+                .env()
+                .block_timestamp()
+                .saturating_sub(auth_date.saturating_mul(1000));
+            if id != tg_id
+                || signed_for != account
+                || age > TELEGRAM_AUTH_MAX_AGE
+            {
+                return Err(Error::InvalidTelegramAuth);
+            }
+            if !self.env().extension().verify_telegram_auth(payload, mac) {
+                return Err(Error::InvalidTelegramAuth);
+            }
+            Ok(())
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn ensure_telegram_auth(
+            &self,
+            _account: AccountId,
+            _tg_id: TelegramId,
+            _payload: Vec<u8>,
+            _mac: [u8; 32],
+        ) -> Result<(), Error> {
+            Err(Error::ExtensionUnavailable)
+        }
+
+        #[cfg(feature = "chain-extension")]
+        fn identity_display_name(&self, account: AccountId) -> Option<Vec<u8>> {
+            self.env().extension().identity_display_name(account)
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn identity_display_name(
+            &self,
+            _account: AccountId,
+        ) -> Option<Vec<u8>> {
+            None
+        }
+
+        /// Send `amount` to the `remote` address with an XCM reserve
+        /// transfer, returning whether the runtime sent it.
+        #[cfg(feature = "chain-extension")]
+        fn transfer_to_parachain(
+            &self,
+            remote: RemoteAddress,
+            amount: Balance,
+        ) -> bool {
+            self.env().extension().transfer_to_parachain(
+                remote.para_id,
+                remote.account,
+                amount,
+            )
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn transfer_to_parachain(
+            &self,
+            _remote: RemoteAddress,
+            _amount: Balance,
+        ) -> bool {
+            false
+        }
+
+        #[cfg(feature = "chain-extension")]
+        fn bond(&self, amount: Balance) -> bool {
+            self.env().extension().bond(amount)
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn bond(&self, _amount: Balance) -> bool { false }
+
+        #[cfg(feature = "chain-extension")]
+        fn unbond(&self, amount: Balance) -> bool {
+            self.env().extension().unbond(amount)
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn unbond(&self, _amount: Balance) -> bool { false }
+
+        #[cfg(feature = "chain-extension")]
+        fn unlock_unbonded(&self) -> Balance {
+            self.env().extension().withdraw_unbonded()
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        fn unlock_unbonded(&self) -> Balance { 0 }
+
+        /// Set, or remove, the remote address of the caller.
+        ///
+        /// Errors:
+        /// Returns `Error::NotFound` if the caller is not bound.
+        fn change_remote_address(
+            &mut self,
+            remote: Option<RemoteAddress>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let mut record = self.users.get(caller).ok_or(Error::NotFound {
+                which: Entity::Account,
+            })?;
+            ensure_not_self_locked(&record)?;
+            match remote {
+                Some(remote) => self.remote_addresses.insert(caller, &remote),
+                None => self.remote_addresses.remove(caller),
+            }
+            record.last_active = self.env().block_number();
+            self.users.insert(caller, &record);
+            self.env().emit_event(RemoteAddressChanged {
+                account: caller,
+                remote,
+            });
+            Ok(())
+        }
+
+        /// Move up to `shortfall` of the tipEDG of the account into its
+        /// `record`, so it could spend what it holds as tipEDG, returning how
+        /// much was moved.
+        ///
+        /// Nothing is burned here, the caller burns it with `burn_receipts`
+        /// once the action succeeded, so a failed action leaves the tipEDG as
+        /// it was.
+        fn cover_from_receipts(
+            &self,
+            account: AccountId,
+            record: &mut UserRecord,
+            shortfall: Balance,
+        ) -> Balance {
+            if shortfall == 0 {
+                return 0;
+            }
+            let receipts = self.receipt_balances.get(account).unwrap_or(0);
+            let amount =
+                shortfall.min(receipts).min(Balance::MAX - record.balance);
+            record.balance += amount;
+            record.audit.other_in =
+                record.audit.other_in.saturating_add(amount);
+            amount
+        }
+
+        /// Mint `amount` of tipEDG to the bound account, for the EDG it
+        /// deposited, given its `record`.
+        ///
+        /// Errors:
+        /// * Returns `Error::Overflow` if the balance and tipEDG of the
+        ///   account, or the tipEDG supply, would overflow.
+        ///
+        /// * Returns `Error::AboveMaxBalance` if they would be above the
+        ///   `max_balance` of its verification level.
+        ///
+        /// * Returns `Error::DepositCapExceeded` if the deposit would take the
+        ///   account, or the contract, above a deposit cap.
+        fn mint_receipts(
+            &mut self,
+            account: AccountId,
+            record: &UserRecord,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let receipts = self.receipt_balance_of(account);
+            let held = record.balance.saturating_add(receipts);
+            let after = held.checked_add(amount).ok_or(Error::Overflow)?;
+            self.ensure_below_max_balance(record, after)?;
+            self.ensure_within_deposit_caps(held, after)?;
+            self.receipt_supply = self
+                .receipt_supply
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            self.set_receipt_balance(account, receipts + amount);
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(account),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Burn `amount` of the tipEDG of the account.
+        fn burn_receipts(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let balance = self.receipt_balances.get(account).unwrap_or(0);
+            if balance < amount {
+                return Err(Error::InsufficientFunds {
+                    required: amount,
+                    available: balance,
+                });
+            }
+            self.set_receipt_balance(account, balance - amount);
+            self.receipt_supply -= amount;
+            self.env().emit_event(Transfer {
+                from: Some(account),
+                to: None,
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Move `value` tipEDG from `from` to `to`.
+        fn transfer_receipts(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<(), PSP22Error> {
+            if to == AccountId::from([0; 32]) {
+                return Err(PSP22Error::ZeroRecipientAddress);
+            }
+            let from_balance = self.receipt_balances.get(from).unwrap_or(0);
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            if self
+                .users
+                .get(from)
+                .is_some_and(|record| record.self_locked)
+            {
+                return Err(PSP22Error::Custom(String::from(
+                    "AccountSelfLocked",
+                )));
+            }
+            // the total supply bounds every balance, so this never overflows.
+            let to_balance = self.receipt_balances.get(to).unwrap_or(0);
+            // tipEDG sent to a bound user counts towards thier limits, as a
+            // deposit would.
+            if let Some(record) = self.users.get(to).filter(|_| from != to) {
+                let after = record.balance.saturating_add(to_balance + value);
+                if self.ensure_below_max_balance(&record, after).is_err() {
+                    return Err(PSP22Error::Custom(String::from(
+                        "AboveMaxBalance",
+                    )));
+                }
+                if self.deposit_cap.is_some_and(|cap| after > cap) {
+                    return Err(PSP22Error::Custom(String::from(
+                        "DepositCapExceeded",
+                    )));
+                }
+            }
+            self.set_receipt_balance(from, from_balance - value);
+            let to_balance = self.receipt_balances.get(to).unwrap_or(0);
+            self.set_receipt_balance(to, to_balance + value);
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value,
+            });
+            Ok(())
+        }
+
+        /// Set the tipEDG balance of the account, listing it among the
+        /// holders while it is not zero.
+        fn set_receipt_balance(
+            &mut self,
+            account: AccountId,
+            balance: Balance,
+        ) {
+            if balance > 0 {
+                self.receipt_balances.insert(account, &balance);
+                push_indexed(
+                    &mut self.receipt_holders,
+                    &mut self.receipt_holder_index,
+                    &mut self.receipt_holders_count,
+                    account,
+                );
+                return;
+            }
+            self.receipt_balances.remove(account);
+            remove_indexed(
+                &mut self.receipt_holders,
+                &mut self.receipt_holder_index,
+                &mut self.receipt_holders_count,
+                account,
+            );
+        }
+
+        /// Set the tipEDG allowance of the `spender` over the `owner`.
+        fn set_allowance(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+        ) {
+            self.receipt_allowances.insert((owner, spender), &value);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
+        }
+    }
+
+    /// Make `call`, a call to another contract, with the reentrancy lock set,
+    /// so it could not call back into the messages that change the state.
+    fn call_out<R>(call: impl FnOnce() -> R) -> R {
+        ink_env::set_contract_storage_return_size(&REENTRANCY_LOCK, &());
+        let result = call();
+        ink_env::clear_contract_storage(&REENTRANCY_LOCK);
+        result
+    }
+
+    /// Ensures that no call to another contract is in progress.
+    /// otherwise, returns `Error::Reentered`.
+    fn ensure_not_reentered() -> Result<(), Error> {
+        match ink_env::get_contract_storage::<()>(&REENTRANCY_LOCK) {
+            Ok(None) => Ok(()),
+            _ => Err(Error::Reentered),
+        }
+    }
+
+    /// Ensures that the user did not lock thier account.
+    /// otherwise, returns `Error::AccountSelfLocked`.
+    fn ensure_not_self_locked(record: &UserRecord) -> Result<(), Error> {
+        if record.self_locked {
+            return Err(Error::AccountSelfLocked);
+        }
+        Ok(())
+    }
+
+    /// The milestone of the escrow at the `index`.
+    ///
+    /// Errors:
+    /// Returns `Error::NotFound` if there is no such milestone, or it was
+    /// already released or refunded.
+    fn milestone_of(
+        escrow: &mut Escrow,
+        index: u32,
+    ) -> Result<&mut Milestone, Error> {
+        escrow
+            .milestones
+            .get_mut(index as usize)
+            .filter(|milestone| {
+                !matches!(
+                    milestone.state,
+                    MilestoneState::Released | MilestoneState::Refunded
+                )
+            })
+            .ok_or(Error::NotFound {
+                which: Entity::Milestone,
+            })
+    }
+
+    /// Whether the held tip expired unaccepted by the block `now`.
+    fn is_expired(tip: &PendingTip, now: BlockNumber) -> bool {
+        tip.needs_acceptance
+            && tip.expires_at.is_some_and(|expires_at| now > expires_at)
+    }
+
+    /// Add the sender to a list of an inbox policy, unless it is already
+    /// there.
+    ///
+    /// Errors:
+    /// Returns `Error::TooManyMembers` if the list is full.
+    fn add_sender(
+        senders: &mut Vec<TelegramId>,
+        tg_id: TelegramId,
+    ) -> Result<(), Error> {
+        if senders.contains(&tg_id) {
+            return Ok(());
+        }
+        if senders.len() >= MAX_INBOX_SENDERS {
+            return Err(Error::TooManyMembers);
+        }
+        senders.push(tg_id);
+        Ok(())
+    }
+
+    /// The number of blocks expected in the `duration` in milliseconds,
+    /// rounded up.
+    fn blocks_in(duration: Timestamp) -> BlockNumber {
+        let blocks = duration / BLOCK_TIME
+            + u64::from(!duration.is_multiple_of(BLOCK_TIME));
+        blocks.min(BlockNumber::MAX.into()) as BlockNumber
+    }
+
+    /// Append a tip to a user's history, pruning the tip that falls out of
+    /// the latest `limit` ones.
+    fn push_history(
+        history: &mut Mapping<(AccountId, u32), TipId>,
+        limit: u32,
+        account: AccountId,
+        seq: &mut u32,
+        tip_id: TipId,
+    ) {
+        history.insert((account, *seq), &tip_id);
+        *seq += 1;
+        // the pruned tips are the ones below the kept window, down to the
+        // first already pruned one, more than one only after the limit got
+        // lowered.
+        let kept = seq.saturating_sub(limit);
+        for position in (0..kept).rev().take(MAX_HISTORY_PRUNE as usize) {
+            if !history.contains((account, position)) {
+                break;
+            }
+            history.remove((account, position));
+        }
+    }
+
+    /// Move the latest `limit` tips of a user's history from `from` to `to`.
+    fn move_history(
+        history: &mut Mapping<(AccountId, u32), TipId>,
+        limit: u32,
+        from: AccountId,
+        to: AccountId,
+        seq: u32,
+    ) {
+        for position in seq.saturating_sub(limit)..seq {
+            if let Some(tip_id) = history.get((from, position)) {
+                history.remove((from, position));
+                history.insert((to, position), &tip_id);
+            }
+        }
+    }
+
+    /// Add the account to the end of an indexed list, unless it is already
+    /// in it.
+    fn push_indexed(
+        list: &mut Mapping<u32, AccountId>,
+        index: &mut Mapping<AccountId, u32>,
+        count: &mut u32,
+        account: AccountId,
+    ) {
+        if !index.contains(account) {
+            list.insert(*count, &account);
+            index.insert(account, &*count);
+            *count += 1;
+        }
+    }
+
+    /// Remove the account from an indexed list, if it is in it, filling its
+    /// gap with the last account of the list.
+    fn remove_indexed(
+        list: &mut Mapping<u32, AccountId>,
+        index: &mut Mapping<AccountId, u32>,
+        count: &mut u32,
+        account: AccountId,
+    ) {
+        if let Some(position) = index.get(account) {
+            let last = *count - 1;
+            if position != last {
+                let moved = list.get(last).expect("the last account exists");
+                list.insert(position, &moved);
+                index.insert(moved, &position);
+            }
+            list.remove(last);
+            index.remove(account);
+            *count = last;
+        }
+    }
+
+    /// Read the TelegramId, the login time in seconds, and the hex encoded
+    /// `account` it is signed for, of a Telegram login data-check-string, the
+    /// `key=value` fields separated by new lines.
+    #[cfg(feature = "chain-extension")]
+    fn parse_telegram_auth(
+        payload: &[u8],
+    ) -> Option<(TelegramId, u64, AccountId)> {
+        let payload = core::str::from_utf8(payload).ok()?;
+        let field = |key: &str| {
+            payload.split('\n').find_map(|line| {
+                line.strip_prefix(key)
+                    .and_then(|line| line.strip_prefix('='))
+            })
+        };
+        let id = field("id")?.parse().ok()?;
+        let auth_date = field("auth_date")?.parse().ok()?;
+        let hex = field("account")?;
+        if hex.len() != 64 {
+            return None;
+        }
+        let mut account = [0; 32];
+        for (byte, digits) in account.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let digits = core::str::from_utf8(digits).ok()?;
+            *byte = u8::from_str_radix(digits, 16).ok()?;
+        }
+        Some((id, auth_date, AccountId::from(account)))
+    }
+
+    /// The normalized alias, without a leading `@` and lower cased, or
+    /// `None` if it is empty, longer than `MAX_ALIAS_LEN` bytes, or has
+    /// characters other than letters, digits and underscores.
+    fn normalize_alias(name: &str) -> Option<String> {
+        let name = name.strip_prefix('@').unwrap_or(name).to_lowercase();
+        let valid = !name.is_empty()
+            && name.len() <= MAX_ALIAS_LEN
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        valid.then_some(name)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_env::test;
+        use ink_lang as ink;
+        use secp256k1::{Message, PublicKey, SecretKey, SECP256K1};
+
+        type Accounts = test::DefaultAccounts<Environment>;
+        type Event = <Tipbot as ink::reflect::ContractEventBase>::Type;
+
+        #[ink::test]
+        fn happy_path() {
+            set_from_owner();
+            let mut bot = Tipbot::new();
+            let accounts = default_accounts();
+
+            // ensures everything is clean.
+            assert_eq!(bot.telegram_id_of(Some(accounts.bob)), None);
+            assert_eq!(bot.balance_of(42), 0);
+            // bind bob.
+            set_sender(accounts.bob, 6969);
+            assert!(bot.bind(42).is_ok());
+
+            assert_eq!(bot.telegram_id_of(Some(accounts.bob)), Some(42));
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            assert_eq!(bot.balance_of(42), 6969);
+        }
+
+        #[ink::test]
+        fn already_bounded() {
+            set_from_owner();
+            let mut bot = Tipbot::new();
+            let accounts = default_accounts();
+            // bind bob.
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.eve, 1);
+            // 42 is already bounded.
+            assert_eq!(bot.bind(42), Err(Error::TelegramIdTaken));
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            // and bob is already bound to it.
+            set_sender(accounts.bob, 0);
+            assert_eq!(bot.bind(42), Err(Error::AccountAlreadyBound));
+        }
+
+        #[ink::test]
+        fn bind_twice() {
+            set_from_owner();
+            let mut bot = Tipbot::new();
+            let accounts = default_accounts();
+            // bind bob.
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            // rebind to 4242.
+            assert!(bot.bind(4242).is_ok());
+            assert_eq!(bot.address_of(4242), Some(accounts.bob));
+            // now 42 is gone.
+            assert_eq!(bot.address_of(42), None);
+        }
+
+        #[ink::test]
+        fn bind_twice_balance() {
+            set_from_owner();
+            let mut bot = Tipbot::new();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            set_sender(accounts.bob, 200);
+            assert!(bot.bind(4242).is_ok());
+            assert_eq!(bot.balance_of(4242), 100 + 200);
+        }
+
+        #[ink::test]
+        fn unbind_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            assert!(bot.unbind().is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+        }
+
+        #[ink::test]
+        fn unbind_not_found() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+
+            assert_eq!(
+                bot.unbind(),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            assert_eq!(bot.balance_of(42), 0);
+        }
+
+        #[ink::test]
+        fn force_unbind_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            assert!(
+                admin(&mut bot, AdminAction::ForceUnbind(accounts.bob)).is_ok()
+            );
+            assert_eq!(bot.balance_of(42), 0);
+        }
+
+        #[ink::test]
+        fn admin_rebind_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+
+            assert!(admin(&mut bot, AdminAction::SetOperator(accounts.django))
+                .is_ok());
+            // the owners have to approve it.
+            set_from_owner();
+            assert_eq!(
+                bot.admin_rebind(accounts.bob, 24),
+                Err(Error::NotAllowed)
+            );
+            let rebind = |account, new_tg_id| AdminCall::AdminRebind {
+                account,
+                new_tg_id,
+            };
+            assert_eq!(
+                admin_call(&mut bot, rebind(accounts.bob, 43)),
+                Err(Error::TelegramIdTaken)
+            );
+            assert_eq!(
+                admin_call(&mut bot, rebind(accounts.frank, 24)),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            assert!(admin_call(&mut bot, rebind(accounts.bob, 24)).is_ok());
+            assert_eq!(bot.address_of(42), None);
+            assert_eq!(bot.address_of(24), Some(accounts.bob));
+            assert_eq!(bot.balance_of(24), 100);
+            // the operator could correct it too.
+            set_caller(bot.operator());
+            assert!(bot.admin_rebind(accounts.bob, 42).is_ok());
+            assert!(matches!(
+                nth_last_event(1),
+                Event::TelegramIdChanged(TelegramIdChanged {
+                    old_tg_id: 24,
+                    new_tg_id: 42,
+                    ..
+                })
+            ));
+            assert_eq!(bot.telegram_id_of(Some(accounts.bob)), Some(42));
+        }
+
+        #[cfg(feature = "chain-extension")]
+        #[ink::test]
+        fn bind_verified_works() {
+            struct MockTelegramAuth;
+
+            impl test::ChainExtension for MockTelegramAuth {
+                fn func_id(&self) -> u32 { 0x5447_0001 }
+
+                fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                    // the off-chain engine passes the encoded arguments as a
+                    // byte vector.
+                    let input: Vec<u8> =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    let (_, mac): (Vec<u8>, [u8; 32]) =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    scale::Encode::encode_to(&(mac == [7; 32]), output);
+                    0
+                }
+            }
+
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            test::register_chain_extension(MockTelegramAuth);
+            let hex = |account: AccountId| {
+                AsRef::<[u8]>::as_ref(&account)
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>()
+            };
+            let payload = format!(
+                "account={}\nauth_date=0\nfirst_name=Bob\nid=42\nusername=bob",
+                hex(accounts.bob)
+            )
+            .into_bytes();
+
+            // the payload is signed for bob only.
+            set_sender(accounts.charlie, 100);
+            assert_eq!(
+                bot.bind_verified(42, payload.clone(), [7; 32]),
+                Err(Error::InvalidTelegramAuth)
+            );
+            set_sender(accounts.bob, 100);
+            assert_eq!(bot.bind_verified(42, payload.clone(), [7; 32]), Ok(()));
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            assert_eq!(bot.balance_of(42), 100);
+
+            set_sender(accounts.charlie, 0);
+            // a wrong hash, another TelegramId, and a stale payload.
+            assert_eq!(
+                bot.bind_verified(43, payload.clone(), [7; 32]),
+                Err(Error::InvalidTelegramAuth)
+            );
+            let payload = format!(
+                "account={}\nauth_date=0\nid=43",
+                hex(accounts.charlie)
+            )
+            .into_bytes();
+            assert_eq!(
+                bot.bind_verified(43, payload.clone(), [1; 32]),
+                Err(Error::InvalidTelegramAuth)
+            );
+            advance_to(TELEGRAM_AUTH_MAX_AGE + 6);
+            assert_eq!(
+                bot.bind_verified(43, payload, [7; 32]),
+                Err(Error::InvalidTelegramAuth)
+            );
+            assert_eq!(
+                bot.bind_verified(43, b"id=43".to_vec(), [7; 32]),
+                Err(Error::InvalidTelegramAuth)
+            );
+            assert_eq!(bot.address_of(43), None);
+        }
+
+        #[cfg(not(feature = "chain-extension"))]
+        #[ink::test]
+        fn chain_extension_is_unavailable() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+            assert_eq!(
+                bot.bind_verified(42, b"auth_date=0\nid=42".to_vec(), [7; 32]),
+                Err(Error::ExtensionUnavailable)
+            );
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.identity_of(42), None);
+            assert_eq!(
+                bot.set_remote_address(2000, accounts.frank),
+                Err(Error::ExtensionUnavailable)
+            );
+
+            // remote tips are paid locally.
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+            let before = get_balance(accounts.charlie);
+            set_caller(accounts.bob);
+            assert!(bot.tip_remote(43, 30).is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 30);
+
+            set_from_owner();
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::StakeIdle(100)),
+                Err(Error::ExtensionUnavailable)
+            );
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::Unstake(100)),
+                Err(Error::ExtensionUnavailable)
+            );
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::WithdrawUnbonded),
+                Err(Error::ExtensionUnavailable)
+            );
+        }
+
+        #[cfg(feature = "chain-extension")]
+        #[ink::test]
+        fn identity_of_works() {
+            struct MockIdentity(AccountId);
+
+            impl test::ChainExtension for MockIdentity {
+                fn func_id(&self) -> u32 { 0x5447_0002 }
+
+                fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                    let input: Vec<u8> =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    let account: AccountId =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    let name = (account == self.0).then(|| b"Bob".to_vec());
+                    scale::Encode::encode_to(&name, output);
+                    0
+                }
+            }
+
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            test::register_chain_extension(MockIdentity(accounts.bob));
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+
+            assert_eq!(bot.identity_of(42), Some(b"Bob".to_vec()));
+            assert_eq!(bot.identity_of(43), None);
+            assert_eq!(bot.identity_of(44), None);
+        }
+
+        #[cfg(feature = "chain-extension")]
+        #[ink::test]
+        fn tip_remote_works() {
+            struct MockXcm;
+
+            impl test::ChainExtension for MockXcm {
+                fn func_id(&self) -> u32 { 0x5447_0003 }
+
+                fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                    let input: Vec<u8> =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    let (para_id, _, _): (ParaId, AccountId, Balance) =
+                        scale::Decode::decode(&mut &input[..]).unwrap();
+                    // only parachain 2000 is reachable.
+                    scale::Encode::encode_to(&(para_id == 2000), output);
+                    0
+                }
+            }
+
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            test::register_chain_extension(MockXcm);
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+            assert!(bot.set_remote_address(2000, accounts.frank).is_ok());
+            let remote = RemoteAddress {
+                para_id: 2000,
+                account: accounts.frank,
+            };
+            assert!(matches!(
+                last_event(),
+                Event::RemoteAddressChanged(RemoteAddressChanged {
+                    account,
+                    remote: Some(changed),
+                }) if account == accounts.charlie && changed == remote
+            ));
+            assert_eq!(bot.remote_address_of(43), Some(remote));
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(44).is_ok());
+            assert!(bot.set_remote_address(3000, accounts.frank).is_ok());
+
+            // sent to parachain 2000, so nothing is paid locally.
+            let charlie = get_balance(accounts.charlie);
+            set_caller(accounts.bob);
+            assert!(bot.tip_remote(43, 30).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::TipSentRemotely(TipSentRemotely { para_id: 2000, .. })
+            ));
+            assert_eq!(get_balance(accounts.charlie), charlie);
+            assert_eq!(bot.stats_of(43).unwrap().total_received, 30);
+            assert_eq!(bot.balance_of(42), 70);
+            // `tip` pays locally.
+            assert!(bot.tip(43, 10, None).is_ok());
+            assert_eq!(get_balance(accounts.charlie), charlie + 10);
+
+            // parachain 3000 is not reachable, so it is paid locally.
+            let eve = get_balance(accounts.eve);
+            assert!(bot.tip_remote(44, 20).is_ok());
+            assert!(matches!(last_event(), Event::Tipped(_)));
+            assert_eq!(get_balance(accounts.eve), eve + 20);
+
+            set_caller(accounts.charlie);
+            assert!(bot.clear_remote_address().is_ok());
+            assert_eq!(bot.remote_address_of(43), None);
+            set_caller(accounts.bob);
+            assert!(bot.tip_remote(43, 5).is_ok());
+            assert_eq!(get_balance(accounts.charlie), charlie + 15);
+
+            // unbinding forgets the remote address.
+            set_caller(accounts.eve);
+            assert!(bot.unbind().is_ok());
+            assert!(bot.bind(44).is_ok());
+            assert_eq!(bot.remote_address_of(44), None);
+        }
+
+        #[cfg(feature = "chain-extension")]
+        #[ink::test]
+        fn rebind_moves_remote_address() {
+            struct MockXcm;
+
+            impl test::ChainExtension for MockXcm {
+                fn func_id(&self) -> u32 { 0x5447_0003 }
+
+                fn call(&mut self, _: &[u8], output: &mut Vec<u8>) -> u32 {
+                    scale::Encode::encode_to(&true, output);
+                    0
+                }
+            }
+
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            test::register_chain_extension(MockXcm);
+            let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+            let (new_key, signature) = sign_rebind(&secret, accounts.charlie);
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+            assert!(bot.set_remote_address(2000, accounts.frank).is_ok());
+            let remote = RemoteAddress {
+                para_id: 2000,
+                account: accounts.frank,
+            };
+            assert!(bot.rebind_to(new_key, signature).is_ok());
+
+            assert_eq!(bot.remote_address_of(43), Some(remote));
+            set_caller(accounts.bob);
+            assert!(bot.tip_remote(43, 30).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::TipSentRemotely(TipSentRemotely { para_id: 2000, .. })
+            ));
+            // the new key manages it.
+            set_caller(new_key);
+            assert!(bot.clear_remote_address().is_ok());
+            assert_eq!(bot.remote_address_of(43), None);
+        }
+
+        #[cfg(feature = "chain-extension")]
+        #[ink::test]
+        fn stake_idle_works() {
+            struct MockStaking(u32);
+
+            impl test::ChainExtension for MockStaking {
+                fn func_id(&self) -> u32 { self.0 }
+
+                fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                    match self.0 {
+                        // everything unbonding is unlocked at once.
+                        0x5447_0006 => {
+                            scale::Encode::encode_to(&Balance::MAX, output)
+                        },
+                        _ => scale::Encode::encode_to(&true, output),
+                    }
+                    0
+                }
+            }
+
+            let mut bot = create_contract(1000);
+            for func_id in [0x5447_0004, 0x5447_0005, 0x5447_0006] {
+                test::register_chain_extension(MockStaking(func_id));
+            }
+            set_from_noowner();
+            let setting = AdminSetting::Call(AdminCall::StakeIdle(100));
+            assert_eq!(
+                bot.propose_admin_call(AdminAction::Configure(Setting::Admin(
+                    setting
+                ))),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::StakeIdle(0)),
+                Err(Error::ZeroAmount)
+            );
+            // only the treasury could be bonded by default, and it is empty.
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::StakeIdle(100)),
+                Err(Error::InsufficientFunds {
+                    required: 100,
+                    available: 0,
+                })
+            );
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    AdminAction::Configure(Setting::Transfer(
+                        TransferSetting::StakeShare(MAX_BPS + 1)
+                    ))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Transfer(
+                    TransferSetting::StakeShare(2_000)
+                ))
+            )
+            .is_ok());
+
+            // a fifth of the 1000 deposited.
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::StakeIdle(300)),
+                Err(Error::InsufficientFunds {
+                    required: 300,
+                    available: 200,
+                })
+            );
+            assert!(admin_call(&mut bot, AdminCall::StakeIdle(150)).is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::Staked(Staked { amount: 150 })
+            ));
+            assert_eq!(
+                bot.staking(),
+                Staking {
+                    bonded: 150,
+                    unbonding: 0,
+                    deposit_share_bps: 2_000,
+                }
+            );
+
+            // what is unbonding is still locked, so it still counts.
+            assert!(admin_call(&mut bot, AdminCall::Unstake(100)).is_ok());
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::Unstake(100)),
+                Err(Error::InsufficientFunds {
+                    required: 100,
+                    available: 50,
+                })
+            );
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::StakeIdle(100)),
+                Err(Error::InsufficientFunds {
+                    required: 100,
+                    available: 50,
+                })
+            );
+            assert!(admin_call(&mut bot, AdminCall::WithdrawUnbonded).is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::UnbondedWithdrawn(UnbondedWithdrawn { amount: 100 })
+            ));
+            assert!(admin_call(&mut bot, AdminCall::StakeIdle(100)).is_ok());
+            assert_eq!(bot.staking().bonded, 150);
+            assert_eq!(bot.staking().unbonding, 0);
+        }
+
+        #[ink::test]
+        fn force_unbind_many_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(43).is_ok());
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(44).is_ok());
+
+            let before = get_balance(accounts.bob);
+            assert!(admin_call(
+                &mut bot,
+                AdminCall::ForceUnbindMany(vec![
+                    accounts.bob,
+                    accounts.frank,
+                    accounts.charlie,
+                    accounts.bob,
+                ])
+            )
+            .is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::ForceUnbound(ForceUnbound { account, tg_id: 43 })
+                    if account == accounts.charlie
+            ));
+            assert_eq!(get_balance(accounts.bob), before + 100);
+            assert_eq!(bot.address_of(42), None);
+            assert_eq!(bot.address_of(43), None);
+            assert_eq!(bot.address_of(44), Some(accounts.eve));
+        }
+
+        #[ink::test]
+        fn force_unbind_noowner() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            set_from_noowner();
+            assert_eq!(
+                bot.propose_admin_call(AdminAction::ForceUnbind(accounts.bob)),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(bot.balance_of(42), 100);
+        }
+
+        #[ink::test]
+        fn tipping_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            // bind alice account.
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            // now bind bob account.
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.balance_of(142), 0);
+            set_balance(accounts.bob, 1); // set that they are have only 1 token.
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 50, None).is_ok()); // tip bob with 50.
+            assert_eq!(bot.balance_of(42), 50); // now we have 50.
+            assert_eq!(bot.balance_of(142), 0); // bob is still zero.
+            assert_eq!(get_balance(accounts.bob), 51); // they have balance now.
+        }
+
+        #[ink::test]
+        fn events_are_indexed_by_telegram_id() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Bound(Bound {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    tg_id: 42,
+                    previous: None,
+                    ..
+                })
+            ));
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 50, None).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(Tipped {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    from_tg: Some(42),
+                    to_tg: Some(142),
+                    ..
+                })
+            ));
+            // indexed by the accounts and the TelegramIds, with no signature
+            // topic.
+            let event = test::recorded_events().last().unwrap();
+            assert_eq!(event.topics.len(), 4);
+
+            assert!(bot.bind(43).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Bound(Bound {
+                    tg_id: 43,
+                    previous: Some(42),
+                    ..
+                })
+            ));
+            assert!(bot.unbind().is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Unbound(Unbound { tg_id: 43, account, .. })
+                    if account == accounts.alice
+            ));
+        }
+
+        #[ink::test]
+        fn export_users_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            assert!(bot.export_users(0, 10).is_empty());
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(242).is_ok());
+
+            let page = bot.export_users(0, 2);
+            assert_eq!(page.len(), 2);
+            assert_eq!(page[0].0, accounts.alice);
+            assert_eq!(page[0].1.balance, 100);
+            assert_eq!(page[1].1.tg_id, 142);
+            let page = bot.export_users(2, 2);
+            assert_eq!(page.len(), 1);
+            assert_eq!(page[0].0, accounts.charlie);
+            assert!(bot.export_users(3, 2).is_empty());
+            assert!(bot.export_users(u32::MAX, u32::MAX).is_empty());
+        }
+
+        #[ink::test]
+        fn tipping_not_found() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            // bind alice account.
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+
+            set_caller(accounts.alice);
+            // tip `142` with 50.
+            assert_eq!(
+                bot.tip(142, 50, None),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert_eq!(bot.balance_of(42), 100); // still 100.
+        }
+
+        #[ink::test]
+        fn tipping_no_balance() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            // bind alice account.
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+            // now bind bob account.
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            // tip bob with 150.
+            assert_eq!(
+                bot.tip(142, 150, None),
+                Err(Error::InsufficientFunds {
+                    required: 150,
+                    available: 100,
+                })
+            );
+            assert_eq!(bot.balance_of(42), 100); // still 100.
+        }
+
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            assert_eq!(bot.owners(), vec![accounts.alice]);
+
+            assert!(admin(&mut bot, AdminAction::RenounceOwnership).is_ok());
+            assert!(bot.owners().is_empty());
+            // created, renounced, logged and executed.
+            assert_eq!(test::recorded_events().count(), 4);
+            // the old owner has no special powers anymore.
+            assert_eq!(
+                admin(&mut bot, AdminAction::RegisterHook(accounts.frank)),
+                Err(Error::NotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn multisig_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.eve);
+            let mut bot = Tipbot::with_owners(
+                vec![accounts.alice, accounts.bob, accounts.charlie],
+                2,
+            );
+            assert_eq!(bot.operator(), accounts.eve);
+            assert_eq!(bot.threshold(), 2);
+
+            set_caller(accounts.alice);
+            let action = AdminAction::SetOperator(accounts.frank);
+            let id = bot.propose_admin_call(action.clone()).unwrap();
+            // one approval is not enough.
+            assert_eq!(bot.operator(), accounts.eve);
+            assert_eq!(
+                bot.proposal(id),
+                Some(Proposal {
+                    action,
+                    approvals: vec![accounts.alice]
+                })
+            );
+            assert_eq!(bot.approve(id), Err(Error::AlreadyApproved));
+            set_caller(accounts.django);
+            assert_eq!(bot.approve(id), Err(Error::NotAllowed));
+
+            set_caller(accounts.bob);
+            assert!(bot.approve(id).is_ok());
+            assert_eq!(bot.operator(), accounts.frank);
+            assert_eq!(bot.proposal(id), None);
+            assert_eq!(
+                bot.approve(id),
+                Err(Error::NotFound {
+                    which: Entity::Proposal
+                })
+            );
+        }
+
+        #[ink::test]
+        fn multisig_failed_action_is_retried() {
+            let accounts = default_accounts();
+            let mut bot =
+                Tipbot::with_owners(vec![accounts.alice, accounts.bob], 2);
+
+            set_caller(accounts.alice);
+            let action = AdminAction::ForceUnbind(accounts.frank);
+            let id = bot.propose_admin_call(action).unwrap();
+            set_caller(accounts.bob);
+            // frank is not bound yet.
+            assert_eq!(
+                bot.approve(id),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            set_sender(accounts.frank, 0);
+            assert!(bot.bind(42).is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.approve(id).is_ok());
+            assert_eq!(bot.address_of(42), None);
+        }
+
+        #[ink::test]
+        fn owners_management() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            assert!(
+                admin(&mut bot, AdminAction::AddOwner(accounts.bob)).is_ok()
+            );
+            assert_eq!(
+                admin(&mut bot, AdminAction::AddOwner(accounts.bob)),
+                Err(Error::AlreadyRegistered)
+            );
+            assert_eq!(
+                admin(&mut bot, AdminAction::SetThreshold(3)),
+                Err(Error::InvalidThreshold)
+            );
+            assert!(admin(&mut bot, AdminAction::SetThreshold(2)).is_ok());
+            // can't go below the threshold.
+            set_from_owner();
+            let id = bot
+                .propose_admin_call(AdminAction::RemoveOwner(accounts.bob))
+                .unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(bot.approve(id), Err(Error::InvalidThreshold));
+            assert_eq!(bot.owners(), vec![accounts.alice, accounts.bob]);
+        }
+
+        #[ink::test]
+        fn unbind_keeps_accounts_list() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 0);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(2).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(3).is_ok());
+            assert_eq!(bot.accounts_count, 3);
+
+            // unbinding the first one moves the last into its place.
+            set_caller(accounts.alice);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(bot.accounts_count, 2);
+            assert_eq!(bot.accounts.get(0), Some(accounts.charlie));
+            assert_eq!(
+                bot.users.get(accounts.charlie).map(|r| r.index),
+                Some(0)
+            );
+            assert_eq!(bot.accounts.get(1), Some(accounts.bob));
+            assert_eq!(bot.accounts.get(2), None);
+        }
+
+        #[ink::test]
+        fn terminate_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 0);
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 200);
+            assert!(bot.bind(142).is_ok());
+
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::TerminationNotScheduled)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::TerminationTimelocked {
+                    at: TERMINATION_DELAY
+                })
+            );
+            assert_eq!(
+                bot.wind_down(10),
+                Err(Error::TerminationTimelocked {
+                    at: TERMINATION_DELAY
+                })
+            );
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::WindDownPending)
+            );
+            // anyone could wind it down, a page at a time.
+            set_caller(accounts.django);
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(get_balance(accounts.charlie), 200);
+            assert_eq!(get_balance(accounts.bob), 0);
+            assert_eq!(bot.wind_down(10), Ok(1));
+            assert_eq!(bot.wind_down(10), Ok(0));
+            let should_terminate = move || {
+                let _ = admin(&mut bot, AdminAction::Terminate);
+            };
+            test::assert_contract_termination::<Environment, _>(
+                should_terminate,
+                accounts.eve,
+                1000 - 300,
+            );
+            assert_eq!(get_balance(accounts.bob), 100);
+            assert_eq!(get_balance(accounts.charlie), 200);
+        }
+
+        #[ink::test]
+        fn wind_down_refunds_group_pots() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            assert!(bot.register_group(-7).is_ok());
+            set_sender(accounts.charlie, 50);
+            assert!(bot.donate_to_group(-7).is_ok());
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            // the pot is refunded first, then the registrant with it.
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(bot.balance_of(42), 150);
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::WindDownPending)
+            );
+            assert_eq!(bot.wind_down(10), Ok(1));
+            assert_eq!(get_balance(accounts.bob), 150);
+        }
+
+        #[ink::test]
+        fn wind_down_redeems_receipts() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.frank, 0);
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 70);
+            assert!(bot.deposit().is_ok());
+            assert!(bot
+                .receipt_transfer(accounts.frank, 30, Vec::new())
+                .is_ok());
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            // the holders are paid after the bound users, bound or not.
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(get_balance(accounts.bob), 100);
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::WindDownPending)
+            );
+            assert_eq!(bot.wind_down(10), Ok(2));
+            assert_eq!(get_balance(accounts.bob), 140);
+            assert_eq!(get_balance(accounts.frank), 30);
+            assert_eq!(bot.receipt_total_supply(), 0);
+            assert_eq!(bot.wind_down(10), Ok(0));
+            let should_terminate = move || {
+                let _ = admin(&mut bot, AdminAction::Terminate);
+            };
+            test::assert_contract_termination::<Environment, _>(
+                should_terminate,
+                accounts.eve,
+                1000 - 170,
+            );
+        }
+
+        #[ink::test]
+        fn wind_down_refunds_escrows_and_games() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 0);
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(142).is_ok());
+            let seed = Hash::from([1; 32]);
+
+            set_caller(accounts.bob);
+            assert_eq!(bot.create_escrow(142, vec![30, 20], None, 5), Ok(0));
+            set_caller(accounts.charlie);
+            assert!(bot.deliver_milestone(0, 0).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(bot.challenge(142, 10, seed_hash_of(seed)), Ok(0));
+            set_caller(accounts.charlie);
+            assert!(bot.accept_challenge(0, Hash::from([2; 32])).is_ok());
+            set_from_owner();
+            assert_eq!(bot.create_raffle(10, 5, seed_hash_of(seed)), Ok(0));
+            assert_eq!(bot.create_poll(2, 5), Ok(0));
+            set_caller(accounts.bob);
+            assert!(bot.buy_ticket(0, 2).is_ok());
+            assert!(bot.stake_on(0, 0, 5).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.buy_ticket(0, 1).is_ok());
+            assert!(bot.stake_on(0, 1, 10).is_ok());
+            assert_eq!(bot.balance_of(42), 15);
+            assert_eq!(bot.balance_of(142), 70);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            // the escrow is refunded to the funder, delivered or not.
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(bot.balance_of(42), 65);
+            // each side of the challenge gets thier stake back.
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(bot.balance_of(42), 75);
+            assert_eq!(bot.balance_of(142), 80);
+            // the raffle, and the poll, are refunded a buyer, or a staker, at
+            // a time and then removed.
+            assert_eq!(bot.wind_down(6), Ok(6));
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(bot.balance_of(142), 100);
+            assert_eq!(bot.raffle(0), None);
+            assert_eq!(bot.poll(0), None);
+            assert_eq!(bot.challenge_of(0), None);
+            assert!(bot
+                .escrow(0)
+                .unwrap()
+                .milestones
+                .iter()
+                .all(|milestone| milestone.state == MilestoneState::Refunded));
+            assert_eq!(bot.wind_down(10), Ok(2));
+            assert_eq!(get_balance(accounts.bob), 100);
+            assert_eq!(get_balance(accounts.charlie), 100);
+            let should_terminate = move || {
+                let _ = admin(&mut bot, AdminAction::Terminate);
+            };
+            test::assert_contract_termination::<Environment, _>(
+                should_terminate,
+                accounts.eve,
+                1000 - 200,
+            );
+        }
+
+        #[ink::test]
+        fn wind_down_pays_jars_relayers_and_left_balances() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 0);
+            set_balance(accounts.django, 0);
+            set_balance(accounts.frank, 0);
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(142).is_ok());
+            let (attested, unattested) =
+                (Hash::from([1; 32]), Hash::from([2; 32]));
+
+            set_caller(accounts.bob);
+            assert!(bot.tip_content(attested, 20).is_ok());
+            assert!(bot.tip_content(unattested, 10).is_ok());
+            set_from_owner();
+            assert!(bot.attest_author(attested, 142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RelayFee(5))
+            )
+            .is_ok());
+            let config = RelayerConfig {
+                name: String::from("discord-bridge"),
+                permissions: RelayerPermissions {
+                    can_tip_from: true,
+                    ..Default::default()
+                },
+                volume_cap: None,
+            };
+            let call = AdminCall::SetRelayer {
+                relayer: accounts.frank,
+                config: Some(config),
+            };
+            assert!(admin_call(&mut bot, call).is_ok());
+            set_caller(accounts.frank);
+            assert!(bot.tip_from(42, 142, 20, None, None).is_ok());
+            // as an expired binding would.
+            bot.leave_balance(accounts.django, 30);
+            // the tip itself was paid out to charlie right away.
+            let (bob, charlie) =
+                (bot.balance_of(42), get_balance(accounts.charlie));
+            let left = get_balance(contract_id());
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            // the jar of a known author is paid to them, the other one is
+            // left to the beneficiary.
+            assert_eq!(bot.wind_down(2), Ok(2));
+            assert_eq!(bot.balance_of(142), 20);
+            assert_eq!(bot.content_jar(unattested).unwrap().balance, 10);
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(get_balance(accounts.frank), 5);
+            assert_eq!(bot.relayer(accounts.frank).unwrap().reimbursement, 0);
+            // the balances left by removed bindings are paid after the
+            // bound users.
+            assert_eq!(bot.wind_down(10), Ok(3));
+            assert_eq!(get_balance(accounts.bob), bob);
+            assert_eq!(get_balance(accounts.charlie), charlie + 20);
+            assert_eq!(get_balance(accounts.django), 30);
+            assert_eq!(bot.expired_balance_of(accounts.django), 0);
+            assert_eq!(bot.wind_down(10), Ok(0));
+            let should_terminate = move || {
+                let _ = admin(&mut bot, AdminAction::Terminate);
+            };
+            test::assert_contract_termination::<Environment, _>(
+                should_terminate,
+                accounts.eve,
+                left - bob - 20 - 5 - 30,
+            );
+        }
+
+        #[ink::test]
+        fn wind_down_refunds_jobs() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.eve, 0);
+            endow(accounts.eve, 100 + KEEPER_INCENTIVE);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(2).is_ok());
+            let now = ink_env::block_timestamp::<Environment>();
+            set_caller(accounts.eve);
+            let job_id = bot.schedule_tip(2, 10, now + 1).unwrap();
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.frank)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            // the incentive goes back to the balance first, and is refunded
+            // with it.
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(bot.job(job_id), None);
+            assert_eq!(bot.balance_of(1), 100 + KEEPER_INCENTIVE);
+            assert_eq!(bot.wind_down(10), Ok(2));
+            assert_eq!(get_balance(accounts.eve), 100 + KEEPER_INCENTIVE);
+            assert_eq!(bot.execute_due(10), Ok(0));
+        }
+
+        #[ink::test]
+        fn cancel_termination_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_from_noowner();
+            assert_eq!(
+                bot.propose_admin_call(AdminAction::ScheduleTermination(
+                    accounts.django
+                )),
+                Err(Error::NotAllowed)
+            );
+
+            assert_eq!(
+                admin(&mut bot, AdminAction::CancelTermination),
+                Err(Error::TerminationNotScheduled)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            assert_eq!(
+                bot.termination(),
+                Some((accounts.eve, TERMINATION_DELAY))
+            );
+            assert!(admin(&mut bot, AdminAction::CancelTermination).is_ok());
+            assert_eq!(bot.termination(), None);
+        }
+
+        #[test]
+        fn spec_version_tracks_the_messages() {
+            // split, so this test is not found as a message itself.
+            let attribute = concat!("#[ink(", "message");
+            let source = include_str!("lib.rs");
+            let mut signatures = String::new();
+            for (start, _) in source.match_indices(attribute) {
+                let message = &source[start..];
+                let end = message.find('{').expect("every message has a body");
+                let signature =
+                    message[..end].split_whitespace().collect::<Vec<_>>();
+                // ignore the trailing commas rustfmt adds, or removes.
+                signatures.push_str(&signature.join(" ").replace(", )", ")"));
+                signatures.push('\n');
+            }
+            let mut fingerprint = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(
+                signatures.as_bytes(),
+                &mut fingerprint,
+            );
+            let fingerprint: String = fingerprint[..8]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            assert_eq!(
+                (SPEC_VERSION, fingerprint.as_str()),
+                (52, "59fd4909999b0534"),
+                "a message changed, bump `SPEC_VERSION` and pin the new \
+                 fingerprint"
+            );
+        }
+
+        #[ink::test]
+        fn info_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            let info = bot.info();
+            assert_eq!(info.spec_version, bot.spec_version());
+            assert_eq!(info.storage_version, STORAGE_VERSION);
+            assert_eq!(info.event_schema_version, EVENT_SCHEMA_VERSION);
+            assert_eq!(info.owners, bot.owners());
+            assert_eq!(info.threshold, bot.threshold());
+            assert_eq!(info.operator, bot.operator());
+            assert_eq!(info.params, bot.params());
+            assert_eq!(info.newcomer_limits, None);
+            assert_eq!(info.terminates_at, None);
+            assert_eq!(info.features, SUPPORTED_FEATURES);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            assert_eq!(bot.info().terminates_at, Some(TERMINATION_DELAY));
+        }
+
+        #[ink::test]
+        fn register_hook_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            assert!(admin(&mut bot, AdminAction::RegisterHook(accounts.frank))
+                .is_ok());
+            assert_eq!(bot.hooks(), vec![accounts.frank]);
+            assert!(admin(&mut bot, AdminAction::RegisterHook(accounts.eve))
+                .is_ok());
+            assert_eq!(
+                admin(&mut bot, AdminAction::RegisterHook(accounts.eve)),
+                Err(Error::AlreadyRegistered)
+            );
+
+            // the last hook takes the index of the removed one.
+            assert!(admin(
+                &mut bot,
+                AdminAction::UnregisterHook(accounts.frank)
+            )
+            .is_ok());
+            assert_eq!(bot.hooks(), vec![accounts.eve]);
+            assert!(admin(&mut bot, AdminAction::UnregisterHook(accounts.eve))
+                .is_ok());
+            assert!(bot.hooks().is_empty());
+        }
+
+        #[ink::test]
+        fn gas_limits_works() {
+            let mut bot = create_contract(1000);
+            assert_eq!(bot.hook_gas_limit(), DEFAULT_HOOK_GAS_LIMIT);
+            assert_eq!(
+                bot.token_call_gas_limit(),
+                DEFAULT_TOKEN_CALL_GAS_LIMIT
+            );
+
+            for setting in [
+                TransferSetting::HookGasLimit(0),
+                TransferSetting::TokenCallGasLimit(0),
+            ] {
+                assert_eq!(
+                    admin(
+                        &mut bot,
+                        AdminAction::Configure(Setting::Transfer(setting))
+                    ),
+                    Err(Error::InvalidParamValue)
+                );
+            }
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Transfer(
+                    TransferSetting::HookGasLimit(1_000)
+                ))
+            )
+            .is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Transfer(
+                    TransferSetting::TokenCallGasLimit(2_000)
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.hook_gas_limit(), 1_000);
+            assert_eq!(bot.token_call_gas_limit(), 2_000);
+        }
+
+        #[ink::test]
+        fn admin_action_log_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            let action = AdminAction::RegisterHook(accounts.frank);
+            assert!(admin(&mut bot, action.clone()).is_ok());
+            assert!(matches!(
+                nth_last_event(1),
+                Event::AdminActionLogged(AdminActionLogged {
+                    action_id: 0,
+                    by,
+                    target: Some(target),
+                    action: PrivilegedAction::Admin(logged),
+                }) if by == accounts.alice
+                    && target == accounts.frank
+                    && logged == action
+            ));
+            assert!(bot.set_category(7, Some(String::from("art"))).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::AdminActionLogged(AdminActionLogged {
+                    action_id: 1,
+                    target: None,
+                    action: PrivilegedAction::SetCategory {
+                        category: 7,
+                        name: Some(name),
+                    },
+                    ..
+                }) if name == "art"
+            ));
+            // failed calls are not logged.
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.ban_from_group(1, 42, true),
+                Err(Error::NotFound {
+                    which: Entity::Group
+                })
+            );
+            assert_eq!(test::recorded_events().count(), 5);
+        }
+
+        #[ink::test]
+        fn register_hook_twice() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            let action = AdminAction::RegisterHook(accounts.frank);
+            assert!(admin(&mut bot, action.clone()).is_ok());
+            assert_eq!(admin(&mut bot, action), Err(Error::AlreadyRegistered));
+        }
+
+        #[ink::test]
+        fn register_hook_noowner() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_from_noowner();
+            assert_eq!(
+                bot.propose_admin_call(AdminAction::RegisterHook(
+                    accounts.frank
+                )),
+                Err(Error::NotAllowed)
+            );
+            assert!(bot.hooks().is_empty());
+        }
+
+        #[ink::test]
+        fn tip_from_noowner() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_from_noowner();
+            assert_eq!(
+                bot.tip_from(42, 142, 50, None, None),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(bot.balance_of(42), 100);
+        }
+
+        #[ink::test]
+        fn tip_usd_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert_eq!(bot.tip_usd(142, 500, 1000), Err(Error::OracleDisabled));
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::PriceOracle(Some(
+                    accounts.frank
+                )))
+            )
+            .is_ok());
+            assert_eq!(bot.price_oracle(), Some(accounts.frank));
+
+            advance_to(MAX_PRICE_AGE + 6);
+            assert_eq!(
+                bot.usd_to_edg(500, 2, 0, 1000),
+                Err(Error::StalePrice { updated_at: 0 })
+            );
+            assert_eq!(bot.usd_to_edg(500, 2, 6, 1000), Ok(1000));
+            // the amount the price gives is capped by the caller.
+            assert_eq!(
+                bot.usd_to_edg(500, 3, 6, 1000),
+                Err(Error::PriceSlippage { price: 3 })
+            );
+            assert_eq!(
+                bot.usd_to_edg(u64::MAX, Balance::MAX, 6, Balance::MAX),
+                Err(Error::Overflow)
+            );
+
+            // the answer of the oracle is tipped like any other tip.
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_usd_at(142, 30, 60, 3, 6),
+                Err(Error::PriceSlippage { price: 3 })
+            );
+            let before = get_balance(accounts.bob);
+            assert!(bot.tip_usd_at(142, 30, 100, 2, 6).is_ok());
+            assert_eq!(bot.balance_of(42), 40);
+            assert_eq!(get_balance(accounts.bob), before + 60);
+            assert_eq!(bot.usd_price(), Some((2, 6)));
+        }
+
+        #[ink::test]
+        fn tip_as_checks() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let token = accounts.frank;
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_as(142, token, 100, 90),
+                Err(Error::SwapDisabled)
+            );
+            let dex = Dex {
+                router: accounts.eve,
+                wrapped_edg: accounts.django,
+            };
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Dex(Some(dex.clone())))
+            )
+            .is_ok());
+            assert_eq!(bot.dex(), Some(dex));
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_as(43, token, 100, 90),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert_eq!(bot.tip_as(42, token, 100, 90), Err(Error::SelfTip));
+            assert_eq!(bot.tip_as(142, token, 0, 0), Err(Error::ZeroAmount));
+            set_caller(accounts.django);
+            assert_eq!(
+                bot.tip_as(142, token, 100, 90),
+                Err(Error::NotFound {
+                    which: Entity::Sender
+                })
+            );
+
+            // the swapped EDG is tipped from the sender's balance like any
+            // other tip.
+            bot.params.fee_bps = 1_000;
+            let before = get_balance(accounts.bob);
+            let treasury = bot.treasury();
+            let record = bot.users.get(accounts.charlie).unwrap();
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_swapped(
+                    accounts.charlie,
+                    record.clone(),
+                    accounts.bob,
+                    0
+                ),
+                Err(Error::ZeroAmount)
+            );
+            assert!(bot
+                .tip_swapped(accounts.charlie, record, accounts.bob, 50)
+                .is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(Tipped {
+                    amount: 50,
+                    fee: 5,
+                    ..
+                })
+            ));
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(get_balance(accounts.bob), before + 45);
+            assert_eq!(bot.treasury(), treasury + 5);
+            assert_eq!(bot.stats_of(42).unwrap().tips_sent, 1);
+        }
+
+        #[ink::test]
+        fn receipt_token_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            // deposits are minted as tipEDG, the balance is left as it was.
+            set_sender(accounts.charlie, 60);
+            assert!(bot.deposit().is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Transfer(Transfer {
+                    from: None,
+                    value: 60,
+                    ..
+                })
+            ));
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 60);
+            assert_eq!(bot.receipt_total_supply(), 60);
+            assert_eq!(bot.receipt_symbol(), Some(String::from("tipEDG")));
+
+            // the receipts move between wallets, bound or not.
+            assert_eq!(
+                bot.receipt_transfer(accounts.frank, 61, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+            assert!(bot
+                .receipt_transfer(accounts.frank, 30, Vec::new())
+                .is_ok());
+            assert!(bot.receipt_approve(accounts.eve, 20).is_ok());
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.receipt_transfer_from(
+                    accounts.charlie,
+                    accounts.eve,
+                    21,
+                    Vec::new()
+                ),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert!(bot
+                .receipt_transfer_from(
+                    accounts.charlie,
+                    accounts.eve,
+                    20,
+                    Vec::new()
+                )
+                .is_ok());
+            assert_eq!(
+                bot.receipt_allowance(accounts.charlie, accounts.eve),
+                0
+            );
+
+            // frank redeems for EDG, eve is not bound to unwrap.
+            set_caller(accounts.frank);
+            let before = get_balance(accounts.frank);
+            assert!(bot.redeem(30).is_ok());
+            assert_eq!(get_balance(accounts.frank), before + 30);
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.unwrap(20),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            assert!(bot
+                .receipt_transfer(accounts.charlie, 20, Vec::new())
+                .is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.unwrap(10).is_ok());
+            assert_eq!(bot.balance_of(42), 110);
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 20);
+
+            // tips, and withdrawals, burn what the balance is short of.
+            assert!(bot.tip(142, 120, None).is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 10);
+            assert_eq!(
+                bot.request_withdrawal(11),
+                Err(Error::InsufficientFunds {
+                    required: 11,
+                    available: 10,
+                })
+            );
+            let before = get_balance(accounts.charlie);
+            assert_eq!(bot.request_withdrawal(10), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), before + 10);
+            assert_eq!(bot.receipt_total_supply(), 0);
+        }
+
+        #[ink::test]
+        fn relay_fee_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RelayFee(5))
+            )
+            .is_ok());
+            assert_eq!(bot.relay_fee(), 5);
+
+            let operator = bot.operator();
+            set_caller(operator);
+            assert_eq!(bot.claim_relay_reimbursement(), Err(Error::ZeroAmount));
+            assert!(bot.tip_from(42, 142, 50, None, None).is_ok());
+            assert_eq!(bot.balance_of(42), 45);
+            // the fee comes on top of the tip.
+            assert_eq!(
+                bot.tip_from(42, 142, 41, None, None),
+                Err(Error::InsufficientFunds {
+                    required: 41,
+                    available: 40,
+                })
+            );
+            assert_eq!(bot.balance_of(42), 45);
+            assert_eq!(bot.relay_reimbursement(), 5);
+            // tips by the users themselves are not charged.
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert_eq!(bot.balance_of(42), 35);
+            assert_eq!(bot.claim_relay_reimbursement(), Err(Error::NotAllowed));
+
+            set_caller(operator);
+            assert!(bot.tip_from(42, 142, 10, None, None).is_ok());
+            let before = get_balance(operator);
+            assert!(bot.claim_relay_reimbursement().is_ok());
+            assert_eq!(get_balance(operator), before + 10);
+            assert_eq!(bot.relay_reimbursement(), 0);
+            assert_eq!(bot.total_relay_fees(), 10);
+        }
+
+        #[cfg(feature = "simulator")]
+        #[ink::test]
+        fn simulator_matches_tip_from() {
+            use crate::simulator::{
+                SimError, Simulator, Snapshot, UserSnapshot,
+            };
+
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 100);
+            assert!(bot.bind(242).is_ok());
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    bot_tipping_enabled: Some(false),
+                    ..Default::default()
+                })
+                .is_ok());
+            set_sender(accounts.frank, 100);
+            assert!(bot.bind(342).is_ok());
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    daily_limit: Some(Some(50)),
+                    ..Default::default()
+                })
+                .is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RelayFee(5))
+            )
+            .is_ok());
+
+            let params = bot.params();
+            let users = bot
+                .export_users(0, MAX_EXPORT_PAGE)
+                .into_iter()
+                .map(|(account, record)| UserSnapshot {
+                    account: *AsRef::<[u8; 32]>::as_ref(&account),
+                    tg_id: record.tg_id,
+                    balance: record.balance,
+                    pending_slash: record.pending_slash,
+                    locked: record.locked,
+                    locked_until: record.locked_until,
+                    spent_today: record.spent_today,
+                    spent_era: record.spent_era,
+                    daily_limit: record.settings.daily_limit,
+                    self_locked: record.self_locked,
+                    bot_tipping_enabled: record.settings.bot_tipping_enabled,
+                })
+                .collect();
+            let mut simulator = Simulator::new(Snapshot {
+                block: ink_env::block_number::<Environment>(),
+                era_length: ERA_LENGTH,
+                fee_bps: params.fee_bps,
+                min_tip: params.min_tip,
+                max_tip: params.max_tip,
+                relay_fee: bot.relay_fee(),
+                users,
+                group_pots: Vec::new(),
+            });
+            let to_sim = |error| match error {
+                Error::NotFound { .. } => SimError::NotFound,
+                Error::NotAllowed => SimError::NotAllowed,
+                Error::SelfTip => SimError::SelfTip,
+                Error::ZeroAmount => SimError::ZeroAmount,
+                Error::AccountSelfLocked => SimError::AccountSelfLocked,
+                Error::BelowMinTip { min } => SimError::BelowMinTip { min },
+                Error::AboveMaxTip { max } => SimError::AboveMaxTip { max },
+                Error::InsufficientFunds {
+                    required,
+                    available,
+                } => SimError::InsufficientFunds {
+                    required,
+                    available,
+                },
+                Error::DailyLimitExceeded { remaining } => {
+                    SimError::DailyLimitExceeded { remaining }
+                },
+                Error::Overflow => SimError::Overflow,
+                error => panic!("unexpected error {:?}", error),
+            };
+
+            set_caller(bot.operator());
+            // the relay fee, the bot tipping setting and the daily limit all
+            // reject a tip the same way on both sides.
+            for (from, to, amount) in [
+                (42, 142, 60),
+                (42, 142, 40),
+                (242, 142, 10),
+                (342, 142, 30),
+                (342, 142, 30),
+                (42, 42, 1),
+                (42, 7, 1),
+                (42, 142, 0),
+                (42, 142, 30),
+            ] {
+                assert_eq!(
+                    simulator.tip(from, to, amount).map(|_| ()),
+                    bot.tip_from(from, to, amount, None, None).map_err(to_sim),
+                    "tip of {} from {} to {}",
+                    amount,
+                    from,
+                    to,
+                );
+                assert_eq!(simulator.balance_of(from), bot.balance_of(from));
+            }
+            assert_eq!(simulator.relay_fees(), bot.total_relay_fees());
+        }
+
+        #[ink::test]
+        fn relayed_withdrawal_works() {
+            let mut bot = create_contract(1000);
+            let secret = SecretKey::from_slice(&[3; 32]).unwrap();
+            let public_key =
+                PublicKey::from_secret_key(SECP256K1, &secret).serialize();
+            let mut user = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public_key, &mut user);
+            let user = AccountId::from(user);
+            set_balance(user, 1000);
+            let sign = |amount: Balance, nonce: u32| {
+                let mut message = [0; 32];
+                ink_env::hash_encoded::<Blake2x256, _>(
+                    &(
+                        test::callee::<Environment>(),
+                        b"withdraw",
+                        amount,
+                        nonce,
+                    ),
+                    &mut message,
+                );
+                let (recovery_id, compact) = SECP256K1
+                    .sign_ecdsa_recoverable(
+                        &Message::from_slice(&message).unwrap(),
+                        &secret,
+                    )
+                    .serialize_compact();
+                let mut signature = [0; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = recovery_id.to_i32() as u8;
+                signature
+            };
+
+            set_sender(user, 100);
+            assert!(bot.bind(42).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RelayFee(5))
+            )
+            .is_ok());
+            set_caller(user);
+            assert_eq!(
+                bot.request_relayed_withdrawal(42, 30, sign(30, 0)),
+                Err(Error::NotAllowed)
+            );
+
+            let operator = bot.operator();
+            set_caller(operator);
+            assert_eq!(
+                bot.request_relayed_withdrawal(42, 30, sign(30, 1)),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                bot.request_relayed_withdrawal(42, 31, sign(30, 0)),
+                Err(Error::InvalidSignature)
+            );
+            let before = get_balance(user);
+            assert!(bot
+                .request_relayed_withdrawal(42, 30, sign(30, 0))
+                .is_ok());
+            assert_eq!(get_balance(user), before + 25);
+            assert_eq!(bot.balance_of(42), 70);
+            assert_eq!(bot.relay_reimbursement(), 5);
+            assert_eq!(bot.relay_nonce_of(42), 1);
+            // the signature could not be replayed.
+            assert_eq!(
+                bot.request_relayed_withdrawal(42, 30, sign(30, 0)),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                bot.request_relayed_withdrawal(42, 5, sign(5, 1)),
+                Err(Error::ZeroAmount)
+            );
+
+            // withdrawals of users with a delay are queued, and executed by
+            // the operator too.
+            set_caller(user);
+            assert!(bot.enable_withdrawal_delay(60).is_ok());
+            set_caller(operator);
+            assert!(bot
+                .request_relayed_withdrawal(42, 20, sign(20, 1))
+                .is_ok());
+            let at = ink_env::block_timestamp::<Environment>() + 60;
+            assert_eq!(bot.pending_withdrawal_of(42), Some((15, at)));
+            assert_eq!(
+                bot.execute_withdrawal_for(42),
+                Err(Error::WithdrawalTimelocked { at })
+            );
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            set_caller(user);
+            assert_eq!(bot.execute_withdrawal_for(42), Err(Error::NotAllowed));
+            set_caller(operator);
+            assert!(bot.execute_withdrawal_for(42).is_ok());
+            assert_eq!(get_balance(user), before + 40);
+            assert_eq!(bot.balance_of(42), 50);
+            assert_eq!(bot.total_relay_fees(), 10);
+        }
+
+        #[ink::test]
+        fn deposit_overflow() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob, Balance::MAX);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), Balance::MAX);
+            // any deposit on top of that overflows.
+            set_sender(accounts.bob, 1);
+            assert_eq!(bot.bind(4242), Err(Error::Overflow));
+            // and nothing changed.
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            assert_eq!(bot.address_of(4242), None);
+            assert_eq!(bot.balance_of(42), Balance::MAX);
+        }
+
+        #[test]
+        fn balance_math_extreme_values() {
+            let extremes =
+                [0, 1, 2, Balance::MAX / 2, Balance::MAX - 1, Balance::MAX];
+            for &deposit in extremes.iter() {
+                for &top_up in extremes.iter() {
+                    for &amount in extremes.iter() {
+                        test::run_test::<Environment, _>(|accounts| {
+                            deposit_then_tip(accounts, deposit, top_up, amount);
+                            Ok(())
+                        })
+                        .expect("test environment works");
+                    }
+                }
+            }
+        }
+
+        /// Deposit twice then tip, checking the balances after every step.
+        fn deposit_then_tip(
+            accounts: Accounts,
+            deposit: Balance,
+            top_up: Balance,
+            amount: Balance,
+        ) {
+            let mut bot = create_contract(Balance::MAX);
+            set_sender(accounts.alice, deposit);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.alice, top_up);
+            let (tg_id, balance) = match deposit.checked_add(top_up) {
+                Some(balance) => {
+                    assert!(bot.bind(43).is_ok());
+                    (43, balance)
+                },
+                None => {
+                    assert_eq!(bot.bind(43), Err(Error::Overflow));
+                    (42, deposit)
+                },
+            };
+            assert_eq!(bot.balance_of(tg_id), balance);
+
+            set_caller(accounts.alice);
+            if amount == 0 {
+                assert_eq!(bot.tip(142, amount, None), Err(Error::ZeroAmount));
+                assert_eq!(bot.balance_of(tg_id), balance);
+            } else if amount <= balance {
+                assert!(bot.tip(142, amount, None).is_ok());
+                assert_eq!(bot.balance_of(tg_id), balance - amount);
+                assert_eq!(get_balance(accounts.bob), amount);
+            } else {
+                assert_eq!(
+                    bot.tip(142, amount, None),
+                    Err(Error::InsufficientFunds {
+                        required: amount,
+                        available: balance,
+                    })
+                );
+                assert_eq!(bot.balance_of(tg_id), balance);
+                assert_eq!(get_balance(accounts.bob), 0);
+            }
+        }
+
+        #[ink::test]
+        fn tipping_zero_amount() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert_eq!(bot.tip(142, 0, None), Err(Error::ZeroAmount));
+            assert_eq!(bot.stats_of(42).map(|s| s.tips_sent), Some(0));
+        }
+
+        #[ink::test]
+        fn tipping_self() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+
+            set_caller(accounts.alice);
+            assert_eq!(bot.tip(42, 50, None), Err(Error::SelfTip));
+            set_from_owner();
+            assert_eq!(
+                bot.tip_from(42, 42, 50, None, None),
+                Err(Error::SelfTip)
+            );
+            assert_eq!(bot.balance_of(42), 100);
+        }
+
+        #[ink::test]
+        fn tip_from_not_found() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_from_owner();
+            assert_eq!(
+                bot.tip_from(42, 142, 50, None, None),
+                Err(Error::NotFound {
+                    which: Entity::Sender
+                })
+            );
+            assert_eq!(
+                bot.tip_from(142, 42, 50, None, None),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+        }
+
+        #[ink::test]
+        fn tipping_updates_stats() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 30, None).is_ok());
+            assert!(bot.tip(142, 20, None).is_ok());
+
+            let alice = bot.stats_of(42).expect("alice is bound");
+            assert_eq!(alice.tips_sent, 2);
+            assert_eq!(alice.total_sent, 50);
+            let bob = bot.stats_of(142).expect("bob is bound");
+            assert_eq!(bob.tips_received, 2);
+            assert_eq!(bob.total_received, 50);
+            assert_eq!(bot.stats_of(7), None);
+        }
+
+        #[ink::test]
+        fn param_change_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.charlie, 0);
+
+            set_sender(accounts.alice, 300);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 200);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(242).is_ok());
+
+            set_from_noowner();
+            assert_eq!(
+                bot.propose_param_change(Param::FeeBps, 100),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_param_change(Param::FeeBps, 10_001),
+                Err(Error::InvalidParamValue)
+            );
+            let id = bot.propose_param_change(Param::FeeBps, 100).unwrap();
+
+            assert!(bot.vote(id, true).is_ok());
+            assert_eq!(bot.vote(id, true), Err(Error::AlreadyVoted));
+            set_caller(accounts.charlie);
+            assert_eq!(bot.vote(id, true), Err(Error::NoVotingPower));
+            set_caller(accounts.alice);
+            assert!(bot.vote(id, false).is_ok());
+            assert_eq!(
+                bot.enact(id),
+                Err(Error::VotingOpen {
+                    ends_at: VOTING_PERIOD
+                })
+            );
+            // alice has more balance, so the change is rejected.
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.vote(id, false), Err(Error::VotingClosed));
+            assert!(bot.enact(id).is_ok());
+            assert_eq!(bot.params(), Params::default());
+            assert_eq!(bot.param_proposal(id), None);
+
+            // too little balance voted for the change to pass.
+            set_caller(accounts.bob);
+            let id = bot.propose_param_change(Param::FeeBps, 100).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.param_quorum(), DEFAULT_PARAM_QUORUM);
+            assert!(bot.enact(id).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::ParamChangeConcluded(ParamChangeConcluded {
+                    passed: false,
+                    ..
+                })
+            ));
+            assert_eq!(bot.params(), Params::default());
+
+            set_param_quorum(&mut bot, 200);
+            set_caller(accounts.bob);
+            let id = bot.propose_param_change(Param::FeeBps, 100).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.enact(id).is_ok());
+            assert_eq!(bot.params().fee_bps, 100);
+
+            // a 1% fee goes to the treasury.
+            set_caller(accounts.alice);
+            assert!(bot.tip(242, 200, None).is_ok());
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(get_balance(accounts.charlie), 198);
+            assert_eq!(bot.treasury(), 2);
+            assert_eq!(bot.stats_of(242).unwrap().total_received, 198);
+        }
+
+        #[ink::test]
+        fn tip_limits_works() {
+            let mut bot = create_contract(1000);
+            set_param_quorum(&mut bot, 0);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 300);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            let id = bot.propose_param_change(Param::MinTip, 10).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            let id = bot.propose_param_change(Param::MaxTip, 100).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.enact(0).is_ok());
+            assert!(bot.enact(1).is_ok());
+            assert_eq!(
+                bot.propose_param_change(Param::MinTip, 101),
+                Err(Error::InvalidParamValue)
+            );
+
+            assert_eq!(
+                bot.tip(142, 9, None),
+                Err(Error::BelowMinTip { min: 10 })
+            );
+            assert_eq!(
+                bot.tip(142, 101, None),
+                Err(Error::AboveMaxTip { max: 100 })
+            );
+            assert!(bot.tip(142, 100, None).is_ok());
+        }
+
+        #[ink::test]
+        fn vote_locks_balance() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 300);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            let id = bot.propose_param_change(Param::FeeBps, 100).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            assert_eq!(
+                bot.tip(142, 1, None),
+                Err(Error::InsufficientFunds {
+                    required: 1,
+                    available: 0
+                })
+            );
+            assert_eq!(
+                bot.unbind(),
+                Err(Error::BalanceLocked {
+                    until: VOTING_PERIOD
+                })
+            );
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert!(bot.unbind().is_ok());
+        }
+
+        #[ink::test]
+        fn treasury_report_works() {
+            let mut bot = create_contract(1000);
+            set_param_quorum(&mut bot, 0);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            let id = bot.propose_param_change(Param::FeeBps, 500).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            // the voting period spans 7 eras.
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.enact(id).is_ok());
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert!(bot.tip(142, 20, None).is_ok());
+            for _ in 0..ERA_LENGTH * 2 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip(142, 40, None).is_ok());
+
+            let report = bot.treasury_report(3);
+            assert_eq!(
+                report,
+                vec![
+                    EraFees {
+                        era: 7,
+                        volume: 120,
+                        fees: 6
+                    },
+                    EraFees {
+                        era: 8,
+                        volume: 0,
+                        fees: 0
+                    },
+                    EraFees {
+                        era: 9,
+                        volume: 40,
+                        fees: 2
+                    },
+                ]
+            );
+            assert_eq!(bot.treasury(), 8);
+            assert_eq!(bot.treasury_report(0), vec![]);
+            assert_eq!(bot.treasury_report(u32::MAX).len(), 10);
+        }
+
+        #[ink::test]
+        fn slash_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let reason = Hash::from([0x42; 32]);
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_from_noowner();
+            assert_eq!(
+                bot.propose_slash(accounts.bob, 60, reason),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert_eq!(
+                bot.propose_slash(accounts.bob, 101, reason),
+                Err(Error::InsufficientFunds {
+                    required: 101,
+                    available: 100
+                })
+            );
+            assert!(bot.propose_slash(accounts.bob, 60, reason).is_ok());
+            assert_eq!(
+                bot.slash(0),
+                Some(Slash {
+                    account: accounts.bob,
+                    amount: 60,
+                    reason_hash: reason,
+                    appeal_ends_at: APPEAL_WINDOW,
+                    contested: false,
+                })
+            );
+
+            // the slashed amount is held.
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.tip(142, 41, None),
+                Err(Error::InsufficientFunds {
+                    required: 41,
+                    available: 40
+                })
+            );
+            assert_eq!(bot.unbind(), Err(Error::SlashPending));
+            assert_eq!(
+                bot.finalize_slash(0),
+                Err(Error::AppealOpen {
+                    ends_at: APPEAL_WINDOW
+                })
+            );
+            for _ in 0..APPEAL_WINDOW {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.contest_slash(0), Err(Error::AppealClosed));
+            set_from_noowner();
+            assert!(bot.finalize_slash(0).is_ok());
+            assert_eq!(bot.slash(0), None);
+            assert_eq!(bot.balance_of(42), 40);
+            assert_eq!(bot.treasury(), 60);
+            assert_eq!(
+                bot.finalize_slash(0),
+                Err(Error::NotFound {
+                    which: Entity::Slash
+                })
+            );
+        }
+
+        #[ink::test]
+        fn contested_slash_goes_to_owners() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let reason = Hash::from([0x42; 32]);
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+
+            set_from_owner();
+            assert!(bot.propose_slash(accounts.bob, 60, reason).is_ok());
+            assert!(bot.propose_slash(accounts.bob, 40, reason).is_ok());
+
+            set_from_noowner();
+            assert_eq!(bot.contest_slash(0), Err(Error::NotAllowed));
+            set_caller(accounts.bob);
+            assert!(bot.contest_slash(0).is_ok());
+            assert!(bot.contest_slash(1).is_ok());
+            for _ in 0..APPEAL_WINDOW {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.finalize_slash(0), Err(Error::SlashContested));
+
+            assert!(admin(&mut bot, AdminAction::UpholdSlash(0)).is_ok());
+            assert!(admin(&mut bot, AdminAction::CancelSlash(1)).is_ok());
+            assert_eq!(bot.balance_of(42), 40);
+            assert_eq!(bot.treasury(), 60);
+
+            set_caller(accounts.bob);
+            assert!(bot.unbind().is_ok());
+        }
+
+        #[ink::test]
+        fn uncontested_slash_waits_for_appeal() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_from_owner();
+            assert!(bot
+                .propose_slash(accounts.bob, 60, Hash::from([0x42; 32]))
+                .is_ok());
+
+            assert_eq!(
+                admin(&mut bot, AdminAction::UpholdSlash(0)),
+                Err(Error::AppealOpen {
+                    ends_at: APPEAL_WINDOW
+                })
+            );
+            assert_eq!(bot.balance_of(42), 100);
+            for _ in 0..APPEAL_WINDOW {
+                test::advance_block::<Environment>();
+            }
+            assert!(admin(&mut bot, AdminAction::UpholdSlash(0)).is_ok());
+            assert_eq!(bot.balance_of(42), 40);
+            assert_eq!(bot.treasury(), 60);
+        }
+
+        #[ink::test]
+        fn force_unbind_keeps_pending_slash() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            set_from_owner();
+            assert!(bot
+                .propose_slash(accounts.bob, 60, Hash::from([0x42; 32]))
+                .is_ok());
+
+            let before = get_balance(accounts.bob);
+            assert!(
+                admin(&mut bot, AdminAction::ForceUnbind(accounts.bob)).is_ok()
+            );
+            assert_eq!(get_balance(accounts.bob), before + 40);
+            assert_eq!(bot.treasury(), 60);
+
+            // binding again does not revive the old slash.
+            test::advance_block::<Environment>();
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(42).is_ok());
+            for _ in 0..APPEAL_WINDOW {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.finalize_slash(0).is_ok());
+            assert_eq!(bot.slash(0), None);
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(bot.treasury(), 60);
+        }
+
+        #[ink::test]
+        fn held_tips_per_sender_are_capped() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.charlie, 1000);
+            assert!(bot.bind(242).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+
+            set_caller(accounts.alice);
+            for _ in 0..MAX_PENDING_TIPS_PER_SENDER {
+                assert!(bot.tip(142, 10, None).is_ok());
+            }
+            assert_eq!(bot.tip(142, 10, None), Err(Error::TooManyPendingTips));
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 10, None).is_ok());
+        }
+
+        #[ink::test]
+        fn held_tips_could_be_revoked() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 1000, None).is_ok());
+            assert!(bot.pending_tip(0).is_some());
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(get_balance(accounts.bob), 0);
+            assert_eq!(
+                bot.finalize_tip(0),
+                Err(Error::TipHeld { release_at: 10 })
+            );
+
+            set_caller(accounts.bob);
+            assert_eq!(bot.revoke_tip(0), Err(Error::NotAllowed));
+            set_caller(accounts.alice);
+            assert!(bot.revoke_tip(0).is_ok());
+            assert_eq!(bot.balance_of(42), 1000);
+            assert_eq!(bot.pending_tip(0), None);
+            assert_eq!(
+                bot.revoke_tip(0),
+                Err(Error::NotFound { which: Entity::Tip })
+            );
+            assert_eq!(bot.stats_of(42).unwrap().tips_sent, 0);
+        }
+
+        #[ink::test]
+        fn feature_flags_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+
+            let set_feature = |feature, enabled| {
+                AdminAction::Configure(Setting::Admin(AdminSetting::Call(
+                    AdminCall::SetFeature { feature, enabled },
+                )))
+            };
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_admin_call(set_feature(
+                    Feature::PendingTips,
+                    false
+                )),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.alice);
+            assert!(bot.is_enabled(Feature::PendingTips));
+            assert!(admin(&mut bot, set_feature(Feature::PendingTips, false))
+                .is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::FeatureSet(FeatureSet {
+                    feature: Feature::PendingTips,
+                    enabled: false,
+                })
+            ));
+            assert!(!bot.is_enabled(Feature::PendingTips));
+            assert_eq!(
+                bot.info().features,
+                SUPPORTED_FEATURES & !FEATURE_PENDING_TIPS
+            );
+            // the tip is paid right away, instead of being held.
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(bot.pending_tip(0), None);
+            assert_eq!(get_balance(accounts.bob), 100);
+
+            assert!(admin(&mut bot, set_feature(Feature::Games, false)).is_ok());
+            assert_eq!(
+                bot.create_raffle(10, 5, Hash::default()),
+                Err(Error::FeatureDisabled)
+            );
+            assert!(admin(&mut bot, set_feature(Feature::Nfts, false)).is_ok());
+            assert_eq!(
+                bot.tip_with_receipt(142, 10, Hash::default()),
+                Err(Error::FeatureDisabled)
+            );
+            assert_eq!(bot.balance_of(42), 900);
+
+            assert!(admin(&mut bot, set_feature(Feature::Games, true)).is_ok());
+            assert!(bot.create_raffle(10, 5, Hash::default()).is_ok());
+            assert!(admin(&mut bot, set_feature(Feature::PendingTips, true))
+                .is_ok());
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert!(bot.pending_tip(0).is_some());
+        }
+
+        #[ink::test]
+        fn held_tips_get_finalized() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(bot.tip(142, 20, None).is_ok());
+            test::advance_block::<Environment>();
+            assert!(bot.tip(142, 30, None).is_ok());
+            for _ in 0..9 {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.revoke_tip(0), Err(Error::ReversalWindowClosed));
+
+            set_from_noowner();
+            assert!(bot.finalize_tip(0).is_ok());
+            assert_eq!(get_balance(accounts.bob), 10);
+
+            // unbinding finalizes the rest that is out of the window.
+            set_caller(accounts.bob);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(get_balance(accounts.bob), 30);
+            assert_eq!(bot.pending_tip(1), None);
+            assert!(bot.pending_tip(2).is_some());
+            assert_eq!(bot.stats_of(42).unwrap().total_sent, 30);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                admin(&mut bot, AdminAction::Terminate),
+                Err(Error::TipsPending)
+            );
+            assert!(bot.finalize_tip(2).is_ok());
+            assert_eq!(get_balance(accounts.bob), 60);
+        }
+
+        #[ink::test]
+        fn large_tips_need_acceptance() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::AcceptanceThreshold(Some(100)))
+            )
+            .is_ok());
+
+            set_caller(accounts.alice);
+            // small tips are paid right away.
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(get_balance(accounts.bob), 100);
+            assert!(bot.tip(142, 300, None).is_ok());
+            assert!(bot.tip(142, 200, None).is_ok());
+            assert_eq!(
+                bot.pending_tip(0).map(|tip| tip.needs_acceptance),
+                Some(true)
+            );
+            assert_eq!(bot.balance_of(42), 400);
+            assert_eq!(bot.finalize_tip(0), Err(Error::TipNotAccepted));
+            assert_eq!(bot.accept_tip(0), Err(Error::NotAllowed));
+
+            set_caller(accounts.bob);
+            assert!(bot.accept_tip(0).is_ok());
+            assert_eq!(get_balance(accounts.bob), 400);
+            assert!(bot.decline_tip(1).is_ok());
+            assert_eq!(bot.balance_of(42), 600);
+            assert_eq!(bot.pending_tip(1), None);
+            assert_eq!(bot.stats_of(142).unwrap().total_received, 400);
+        }
+
+        #[ink::test]
+        fn unaccepted_tips_expire() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.bob, 0);
+
+            endow(accounts.alice, 20_000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::AcceptanceThreshold(Some(100)))
+            )
+            .is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 10_000, None).is_ok());
+            assert!(bot.tip(142, 5_000, None).is_ok());
+            assert!(bot.tip(142, 1_000, None).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(bot.set_tip_expiry(0, Some(2)), Err(Error::NotAllowed));
+            set_caller(accounts.alice);
+            assert!(bot.set_tip_expiry(0, Some(2)).is_ok());
+            assert!(bot.set_tip_expiry(1, Some(2)).is_ok());
+            assert!(bot.set_tip_expiry(2, Some(5)).is_ok());
+            assert_eq!(bot.pending_tip(0).unwrap().expires_at, Some(2));
+
+            // nothing expires before the deadline.
+            set_caller(accounts.eve);
+            assert_eq!(bot.expire_pending(142), Ok(0));
+            assert_eq!(
+                bot.expire_pending(7),
+                Err(Error::NotFound {
+                    which: Entity::Recipient,
+                })
+            );
+            set_caller(accounts.bob);
+            assert!(bot.accept_tip(1).is_ok());
+            for _ in 0..3 {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.accept_tip(0), Err(Error::TipExpired));
+
+            set_caller(accounts.eve);
+            let eve = get_balance(accounts.eve);
+            assert_eq!(bot.expire_pending(142), Ok(1));
+            assert_eq!(get_balance(accounts.eve), eve + 10);
+            assert_eq!(bot.balance_of(42), 4_000 + 9_990);
+            assert_eq!(bot.pending_tip(0), None);
+            assert!(bot.pending_tip(2).is_some());
+            assert!(matches!(
+                last_event(),
+                Event::TipExpired(TipExpired {
+                    tip_id: 0,
+                    reward: 10,
+                    ..
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn anonymous_tip_hides_sender() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let salt = Hash::from([0x07; 32]);
+
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip_anonymous(142, 10, salt).is_ok());
+            match last_event() {
+                Event::Tipped(tipped) => {
+                    assert_eq!(tipped.from, None);
+                    assert_eq!(tipped.to, Some(accounts.bob));
+                    assert_eq!(
+                        tipped.commitment,
+                        Some(bot.commitment_of(accounts.alice, salt))
+                    );
+                },
+                _ => panic!("expected a Tipped event"),
+            }
+
+            set_from_owner();
+            assert!(bot.tip_from(42, 142, 10, Some(salt), None).is_ok());
+            assert!(
+                matches!(last_event(), Event::Tipped(tipped) if tipped.from.is_none())
+            );
+            assert!(bot.tip_from(42, 142, 10, None, None).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(tipped) if tipped.from == Some(accounts.alice)
+            ));
+            assert_eq!(bot.balance_of(42), 70);
+        }
+
+        #[ink::test]
+        fn privacy_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 50);
+            assert!(bot.bind(142).is_ok());
+
+            set_from_noowner();
+            assert_eq!(
+                bot.set_privacy(true, true),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            set_caller(accounts.bob);
+            assert!(bot.set_privacy(true, true).is_ok());
+            assert_eq!(bot.balance_of(142), 50);
+            set_from_noowner();
+            assert_eq!(bot.balance_of(142), 0);
+            set_from_owner();
+            assert_eq!(bot.balance_of(142), 50);
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.balance_of(142), 0);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(tipped) if tipped.to.is_none()
+            ));
+            set_caller(accounts.bob);
+            assert!(bot.set_privacy(false, false).is_ok());
+            set_caller(accounts.charlie);
+            assert_eq!(bot.balance_of(142), 50);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(tipped) if tipped.to == Some(accounts.bob)
+            ));
+        }
+
+        #[ink::test]
+        fn settings_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.settings_of(42), Some(UserSettings::default()));
+            assert_eq!(bot.settings_of(7), None);
+
+            set_caller(accounts.charlie);
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    auto_withdraw: Some(true),
+                    daily_limit: Some(Some(30)),
+                    default_tip: Some(5),
+                    ..Default::default()
+                })
+                .is_ok());
+            assert!(bot.set_privacy(false, true).is_ok());
+            assert_eq!(
+                bot.settings_of(42),
+                Some(UserSettings {
+                    auto_withdraw: true,
+                    bot_tipping_enabled: true,
+                    privacy: Privacy {
+                        hide_received: false,
+                        hide_balance: true,
+                    },
+                    daily_limit: Some(30),
+                    default_tip: 5,
+                    round_up: false,
+                    reserve: 0,
+                    auto_top_up: false,
+                })
+            );
+
+            assert!(bot.tip(142, 20, None).is_ok());
+            assert_eq!(
+                bot.tip(142, 20, None),
+                Err(Error::DailyLimitExceeded { remaining: 10 })
+            );
+            for _ in 0..ERA_LENGTH {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip(142, 20, None).is_ok());
+
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    bot_tipping_enabled: Some(false),
+                    ..Default::default()
+                })
+                .is_ok());
+            set_from_owner();
+            assert_eq!(
+                bot.tip_from(42, 142, 1, None, None),
+                Err(Error::NotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn tip_receipts_works() {
+            let mut bot = create_contract(10_000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 2000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.recent_tips(10), vec![]);
+            assert_eq!(bot.tip_receipt(0), None);
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 1, None).is_ok());
+            test::advance_block::<Environment>();
+            assert!(bot.tip_anonymous(142, 2, Hash::from([0x07; 32])).is_ok());
+            assert_eq!(
+                bot.tip_receipt(0),
+                Some(TipRecord {
+                    from_tg: Some(42),
+                    to_tg: Some(142),
+                    amount: 1,
+                    at: 0,
+                })
+            );
+            assert_eq!(
+                bot.recent_tips(10),
+                vec![
+                    (
+                        1,
+                        TipRecord {
+                            from_tg: None,
+                            to_tg: Some(142),
+                            amount: 2,
+                            at: 1,
+                        }
+                    ),
+                    (0, bot.tip_receipt(0).unwrap()),
+                ]
+            );
+
+            // only the latest tips are kept.
+            for _ in 0..TIP_LOG_SIZE {
+                assert!(bot.tip(142, 1, None).is_ok());
+            }
+            assert_eq!(bot.tip_receipt(1), None);
+            assert!(bot.tip_receipt(2).is_some());
+            assert!(bot.tip_receipt(TIP_LOG_SIZE + 1).is_some());
+            assert_eq!(bot.tip_receipt(TIP_LOG_SIZE + 2), None);
+            let recent = bot.recent_tips(u32::MAX);
+            assert_eq!(recent.len(), MAX_RECENT_TIPS as usize);
+            assert_eq!(recent[0].0, TIP_LOG_SIZE + 1);
+        }
+
+        #[ink::test]
+        fn contest_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let salt = Hash::from([7; 32]);
+            for (account, tg_id) in [
+                (accounts.charlie, 43),
+                (accounts.django, 44),
+                (accounts.eve, 45),
+            ] {
+                set_sender(account, 100);
+                assert!(bot.bind(tg_id).is_ok());
+            }
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_sender(accounts.charlie, 1000);
+            assert_eq!(
+                bot.open_contest(10, vec![6_000, 4_000]),
+                Err(Error::NotAllowed)
+            );
+            endow(accounts.alice, 1000);
+            assert_eq!(
+                bot.open_contest(10, vec![6_000, 3_000]),
+                Err(Error::InvalidParamValue)
+            );
+            endow(accounts.alice, 1000);
+            let contest_id = bot.open_contest(10, vec![6_000, 4_000]).unwrap();
+            assert_eq!(bot.active_contest(), Some(contest_id));
+            endow(accounts.alice, 1000);
+            assert_eq!(
+                bot.open_contest(20, vec![10_000]),
+                Err(Error::ContestNotEnded { ends_at: 10 })
+            );
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 30, None).is_ok());
+            assert!(bot.tip(142, 30, None).is_ok());
+            set_caller(accounts.django);
+            assert!(bot.tip(142, 50, None).is_ok());
+            // anonymous tips do not count.
+            set_caller(accounts.eve);
+            assert!(bot.tip_anonymous(142, 90, salt).is_ok());
+            assert_eq!(bot.contest_volume_of(contest_id, accounts.charlie), 60);
+            assert_eq!(bot.contest_volume_of(contest_id, accounts.eve), 0);
+            assert_eq!(
+                bot.finalize_contest(),
+                Err(Error::ContestNotEnded { ends_at: 10 })
+            );
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            // tips after the end do not count either.
+            set_caller(accounts.django);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(bot.finalize_contest().is_ok());
+            assert_eq!(bot.balance_of(43), 40 + 600);
+            assert_eq!(bot.balance_of(44), 40 + 400);
+            assert_eq!(bot.active_contest(), None);
+            assert!(bot.contest(contest_id).unwrap().finalized);
+            assert_eq!(
+                bot.finalize_contest(),
+                Err(Error::NotFound {
+                    which: Entity::Contest,
+                })
+            );
+
+            // the places nobody took are paid back to the funder.
+            endow(accounts.alice, 1000);
+            let contest_id = bot.open_contest(30, vec![5_000, 5_000]).unwrap();
+            set_caller(accounts.eve);
+            assert!(bot.tip(142, 10, None).is_ok());
+            for _ in 0..20 {
+                test::advance_block::<Environment>();
+            }
+            let alice = get_balance(accounts.alice);
+            assert!(bot.finalize_contest().is_ok());
+            assert_eq!(bot.balance_of(45), 500);
+            assert_eq!(get_balance(accounts.alice), alice + 500);
+            assert!(matches!(
+                last_event(),
+                Event::ContestFinalized(ContestFinalized { contest_id: id, unclaimed: 500, .. })
+                    if id == contest_id
+            ));
+        }
+
+        #[ink::test]
+        fn failed_contest_prizes_go_back_to_the_funder() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            // the balance of charlie could not take thier prize.
+            set_sender(accounts.charlie, Balance::MAX - 100);
+            assert!(bot.bind(43).is_ok());
+            set_sender(accounts.django, 100);
+            assert!(bot.bind(44).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            endow(accounts.alice, 1000);
+            let contest_id = bot.open_contest(10, vec![6_000, 4_000]).unwrap();
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 30, None).is_ok());
+            set_caller(accounts.django);
+            assert!(bot.tip(142, 20, None).is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            let alice = get_balance(accounts.alice);
+            assert!(bot.finalize_contest().is_ok());
+            assert_eq!(bot.balance_of(43), Balance::MAX - 130);
+            assert_eq!(bot.balance_of(44), 80 + 400);
+            assert_eq!(get_balance(accounts.alice), alice + 600);
+            assert_eq!(bot.active_contest(), None);
+            assert!(matches!(
+                last_event(),
+                Event::ContestFinalized(ContestFinalized {
+                    contest_id: id,
+                    winners,
+                    unclaimed: 600,
+                }) if id == contest_id && winners == vec![(accounts.django, 400)]
+            ));
+        }
+
+        #[ink::test]
+        fn epoch_stats_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 1000);
+            assert!(bot.bind(43).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let salt = Hash::from([7; 32]);
+
+            assert_eq!(bot.current_epoch(), 0);
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 30, None).is_ok());
+            assert!(bot.tip_anonymous(142, 100, salt).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 20, None).is_ok());
+            assert!(bot.tip(142, 20, None).is_ok());
+
+            assert_eq!(
+                bot.epoch_stats(0),
+                EpochStats {
+                    epoch: 0,
+                    tips: 4,
+                    volume: 170,
+                    top_tipper: Some((43, 40)),
+                }
+            );
+            assert_eq!(
+                bot.epoch_stats_of(42, 0),
+                UserStats {
+                    tips_sent: 1,
+                    total_sent: 30,
+                    ..Default::default()
+                }
+            );
+            assert_eq!(bot.epoch_stats_of(142, 0).total_received, 170);
+            // the next epoch starts from zero.
+            assert_eq!(
+                bot.epoch_stats(1),
+                EpochStats {
+                    epoch: 1,
+                    ..Default::default()
+                }
+            );
+            assert_eq!(bot.epoch_stats_of(142, 1), UserStats::default());
+        }
+
+        #[ink::test]
+        fn tip_history_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::HistoryLimit(3))
+            )
+            .is_ok());
+
+            set_caller(accounts.charlie);
+            for _ in 0..4 {
+                assert!(bot.tip(142, 1, None).is_ok());
+            }
+            assert!(bot.tip_anonymous(142, 1, Hash::from([0x07; 32])).is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.tip(42, 1, None).is_ok());
+
+            // the oldest tip is pruned.
+            assert_eq!(bot.tips_sent_by(42, 0, 10), vec![3, 2, 1]);
+            assert_eq!(bot.tips_sent_by(42, 1, 1), vec![2]);
+            assert_eq!(bot.tips_sent_by(42, 5, 10), vec![]);
+            assert_eq!(bot.tips_received_by(142, 0, 10), vec![4, 3, 2]);
+            assert_eq!(bot.tips_sent_by(142, 0, 10), vec![5]);
+            assert_eq!(bot.tips_received_by(42, 0, 10), vec![5]);
+            assert_eq!(bot.tips_sent_by(7, 0, 10), vec![]);
+
+            // lowering the limit prunes the rest on the next tip.
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::HistoryLimit(1))
+            )
+            .is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 1, None).is_ok());
+            assert_eq!(bot.tips_sent_by(42, 0, 10), vec![6]);
+            assert!((0..4).all(|position| !bot
+                .sent_history
+                .contains((accounts.charlie, position))));
+        }
+
+        #[ink::test]
+        fn loyalty_points_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.charlie);
+            // earning points is disabled by default.
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert_eq!(bot.points_of(42), 0);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::PointsRate(3))
+            )
+            .is_ok());
+            assert_eq!(bot.points_rate(), 3);
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(bot.tip(142, 2, None).is_ok());
+            assert_eq!(bot.points_of(42), 3);
+            assert_eq!(bot.points_of(142), 0);
+            assert_eq!(bot.points_of(7), 0);
+        }
+
+        #[ink::test]
+        fn redeem_points_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::PointsRate(10))
+            )
+            .is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 50, None).is_ok());
+            assert_eq!(bot.points_of(42), 5);
+            assert_eq!(bot.redeem_points(1), Err(Error::RedemptionDisabled));
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RedemptionRate(4))
+            )
+            .is_ok());
+            set_sender(accounts.alice, 10);
+            assert!(bot.fund_rewards().is_ok());
+            set_sender(accounts.django, 10);
+            assert_eq!(bot.fund_rewards(), Err(Error::NotAllowed));
+            assert_eq!(bot.rewards_pool(), 10);
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.redeem_points(0), Err(Error::ZeroAmount));
+            assert_eq!(
+                bot.redeem_points(6),
+                Err(Error::InsufficientPoints { available: 5 })
+            );
+            assert_eq!(
+                bot.redeem_points(3),
+                Err(Error::InsufficientRewards { available: 10 })
+            );
+            assert!(bot.redeem_points(2).is_ok());
+            assert_eq!(bot.points_of(42), 3);
+            assert_eq!(bot.balance_of(42), 58);
+            assert_eq!(bot.rewards_pool(), 2);
+        }
+
+        #[ink::test]
+        fn badges_works() {
+            let mut bot = create_contract(1000 * EDG);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 200 * EDG);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.badges_of(42), 0);
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 1, None).is_ok());
+            assert_eq!(bot.badges_of(42), BADGE_FIRST_TIP);
+            assert!(test::recorded_events().any(|event| {
+                matches!(
+                    <Event as scale::Decode>::decode(&mut &event.data[..]),
+                    Ok(Event::BadgeEarned(event)) if event.badge == BADGE_FIRST_TIP
+                )
+            }));
+            for _ in 0..8 {
+                assert!(bot.tip(142, 1, None).is_ok());
+            }
+            assert!(bot.tip(142, 100 * EDG, None).is_ok());
+            assert_eq!(
+                bot.badges_of(42),
+                BADGE_FIRST_TIP | BADGE_TEN_TIPS | BADGE_HUNDRED_EDG
+            );
+
+            for _ in 0..YEAR {
+                test::advance_block::<Environment>();
+            }
+            // rebinding, or topping up, checks the age of the binding.
+            assert!(bot.bind(4242).is_ok());
+            assert_eq!(bot.badges_of(4242) & BADGE_ONE_YEAR, BADGE_ONE_YEAR);
+            assert_eq!(bot.badges_of(142), 0);
+        }
+
+        #[ink::test]
+        fn profile_of_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            assert_eq!(bot.profile_of(42), None);
+            assert_eq!(bot.balance_of_account(accounts.charlie), 0);
+
+            set_sender(accounts.charlie, 200);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 50, None).is_ok());
+
+            let profile = bot.profile_of(42).unwrap();
+            assert_eq!(profile.address, accounts.charlie);
+            assert_eq!(profile.balance, bot.balance_of(42));
+            assert_eq!(
+                profile.balance,
+                bot.balance_of_account(accounts.charlie)
+            );
+            assert_eq!(Some(profile.stats), bot.stats_of(42));
+            assert_eq!(Some(profile.settings), bot.settings_of(42));
+            assert_eq!(profile.badges, bot.badges_of(42));
+
+            // the balance stays hidden from others.
+            assert!(bot.set_privacy(false, true).is_ok());
+            assert!(bot.profile_of(42).unwrap().balance > 0);
+            set_caller(accounts.bob);
+            assert_eq!(bot.profile_of(42).unwrap().balance, 0);
+            assert_eq!(bot.balance_of_account(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn achievements_works() {
+            let mut bot = create_contract(2000 * EDG);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 2000 * EDG);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(bot.tokens_of(42).is_empty());
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 1, None).is_ok());
+            assert_eq!(bot.tokens_of(42), vec![0]);
+            assert!(test::recorded_events().any(|event| {
+                matches!(
+                    <Event as scale::Decode>::decode(&mut &event.data[..]),
+                    Ok(Event::AchievementMinted(event))
+                        if event.id == 0 && event.badge == BADGE_FIRST_TIP
+                )
+            }));
+            assert!(bot.tip(142, 1000 * EDG, None).is_ok());
+            assert_eq!(bot.tokens_of(42), vec![0, 1, 2]);
+            assert_eq!(
+                bot.achievement(2),
+                Some(Achievement {
+                    tg_id: 42,
+                    badge: BADGE_THOUSAND_EDG,
+                    minted_at: 0,
+                })
+            );
+            assert_eq!(bot.achievements_minted(), 3);
+            // earned badges are not minted again.
+            assert!(bot.tip(142, 1, None).is_ok());
+            assert_eq!(bot.achievements_minted(), 3);
+        }
+
+        #[ink::test]
+        fn tip_with_receipt_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.charlie);
+            let uri_hash = Hash::from([1; 32]);
+            assert_eq!(
+                bot.tip_with_receipt(143, 10, uri_hash),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert!(bot.tip_nfts_of(142, 0, 10).is_empty());
+            assert!(bot.tip_with_receipt(142, 10, uri_hash).is_ok());
+            assert!(bot.tip_with_receipt(142, 20, uri_hash).is_ok());
+            assert_eq!(bot.balance_of(42), 70);
+            assert_eq!(bot.tip_nfts_of(142, 0, 10), vec![1, 0]);
+            assert_eq!(bot.tip_nfts_of(142, 1, 10), vec![0]);
+            assert_eq!(
+                bot.tip_nft(1),
+                Some(TipNft {
+                    from: accounts.charlie,
+                    to: 142,
+                    amount: 20,
+                    uri_hash,
+                    minted_at: 0,
+                })
+            );
+            assert!(matches!(
+                last_event(),
+                Event::TipNftMinted(TipNftMinted { to: 142, id: 1, .. })
+            ));
+
+            // held tips get thier token once paid, never if revoked.
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip_with_receipt(142, 10, uri_hash).is_ok());
+            assert!(bot.tip_with_receipt(142, 5, uri_hash).is_ok());
+            assert_eq!(bot.tip_nfts_of(142, 0, 10), vec![1, 0]);
+            assert!(bot.revoke_tip(0).is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.finalize_tip(1).is_ok());
+            assert_eq!(bot.tip_nfts_of(142, 0, 10), vec![2, 1, 0]);
+            assert_eq!(bot.tip_nft(2).unwrap().amount, 5);
+
+            // no tokens publish the tips of a recipient who hides them.
+            set_caller(accounts.bob);
+            assert!(bot.set_privacy(true, false).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip_with_receipt(142, 5, uri_hash).is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.finalize_tip(2).is_ok());
+            assert_eq!(bot.tip_nfts_of(142, 0, 10), vec![2, 1, 0]);
+            assert_eq!(bot.balance_of(42), 60);
+        }
+
+        #[ink::test]
+        fn raffle_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            set_from_owner();
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RaffleRake(1000))
+            )
+            .is_ok());
+            let seed = Hash::from([7; 32]);
+            let seed_hash = seed_hash_of(seed);
+
+            set_from_noowner();
+            assert_eq!(
+                bot.create_raffle(10, 5, seed_hash),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert_eq!(
+                bot.create_raffle(10, 0, seed_hash),
+                Err(Error::RaffleEnded)
+            );
+            assert_eq!(bot.create_raffle(10, 5, seed_hash), Ok(0));
+            assert_eq!(bot.create_raffle(10, 5, seed_hash), Ok(1));
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.buy_ticket(0, 11),
+                Err(Error::InsufficientFunds {
+                    required: 110,
+                    available: 100
+                })
+            );
+            assert!(bot.buy_ticket(0, 3).is_ok());
+            assert!(bot.buy_ticket(1, 2).is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.buy_ticket(0, 2).is_ok());
+            assert_eq!(bot.balance_of(42), 50);
+            assert_eq!(bot.balance_of(142), 80);
+            assert_eq!(bot.raffle(0).map(|raffle| raffle.tickets), Some(5));
+            assert_eq!(bot.raffle_spent(0, accounts.charlie), 30);
+
+            assert_eq!(
+                bot.draw(0, seed),
+                Err(Error::RaffleNotEnded { ends_at: 5 })
+            );
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.buy_ticket(0, 1), Err(Error::RaffleEnded));
+            assert_eq!(
+                bot.draw(0, Hash::from([8; 32])),
+                Err(Error::InvalidSeed)
+            );
+            let winner = bot.draw(0, seed).unwrap().unwrap();
+            assert!(winner == accounts.charlie || winner == accounts.bob);
+            // the pot of 50 minus the 10% rake goes to the winner.
+            assert_eq!(bot.balance_of(42) + bot.balance_of(142), 175);
+            assert_eq!(bot.treasury(), 5);
+            assert!(matches!(
+                last_event(),
+                Event::RaffleDrawn(RaffleDrawn { prize: 45, .. })
+            ));
+            assert_eq!(
+                bot.draw(0, seed),
+                Err(Error::NotFound {
+                    which: Entity::Raffle
+                })
+            );
+
+            // the seed of the second raffle is never revealed.
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.claim_raffle_refund(1),
+                Err(Error::RefundNotOpen {
+                    at: 5 + RAFFLE_REVEAL_WINDOW
+                })
+            );
+            for _ in 0..RAFFLE_REVEAL_WINDOW {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.draw(1, seed), Err(Error::RaffleExpired));
+            let before = bot.balance_of(42);
+            assert!(bot.claim_raffle_refund(1).is_ok());
+            assert_eq!(bot.balance_of(42), before + 20);
+            assert_eq!(
+                bot.claim_raffle_refund(1),
+                Err(Error::NotFound {
+                    which: Entity::Raffle
+                })
+            );
+        }
+
+        #[ink::test]
+        fn challenge_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            let seed = Hash::from([1; 32]);
+            let seed_hash = seed_hash_of(seed);
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.challenge(42, 10, seed_hash), Err(Error::SelfTip));
+            assert_eq!(
+                bot.challenge(142, 101, seed_hash),
+                Err(Error::InsufficientFunds {
+                    required: 101,
+                    available: 100
+                })
+            );
+            assert_eq!(bot.challenge(142, 10, seed_hash), Ok(0));
+            assert_eq!(bot.challenge(142, 20, seed_hash), Ok(1));
+            assert_eq!(bot.balance_of(42), 70);
+            assert_eq!(bot.accept_challenge(0, seed), Err(Error::NotAllowed));
+            assert_eq!(bot.flip(0, seed), Err(Error::ChallengeNotAccepted));
+            // the challenger could take it back until it is accepted.
+            assert!(bot.cancel_challenge(1).is_ok());
+            assert_eq!(bot.balance_of(42), 90);
+
+            set_caller(accounts.bob);
+            assert!(bot.accept_challenge(0, Hash::from([2; 32])).is_ok());
+            assert_eq!(bot.balance_of(142), 90);
+            assert_eq!(
+                bot.accept_challenge(0, seed),
+                Err(Error::ChallengeAccepted)
+            );
+            assert_eq!(
+                bot.flip(0, Hash::from([2; 32])),
+                Err(Error::InvalidSeed)
+            );
+            assert_eq!(
+                bot.cancel_challenge(0),
+                Err(Error::RefundNotOpen {
+                    at: CHALLENGE_TIMEOUT
+                })
+            );
+            let winner = bot.flip(0, seed).unwrap();
+            assert_eq!(bot.balance_of(42) + bot.balance_of(142), 200);
+            let winner_tg = bot.telegram_id_of(Some(winner)).unwrap();
+            assert_eq!(bot.balance_of(winner_tg), 110);
+            assert_eq!(
+                bot.flip(0, seed),
+                Err(Error::NotFound {
+                    which: Entity::Challenge
+                })
+            );
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.challenge(142, 10, seed_hash), Ok(2));
+            set_caller(accounts.bob);
+            for _ in 0..CHALLENGE_TIMEOUT {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                bot.accept_challenge(2, seed),
+                Err(Error::ChallengeExpired)
+            );
+            let challenger = bot.balance_of(42);
+            assert!(bot.cancel_challenge(2).is_ok());
+            assert_eq!(bot.balance_of(42), challenger + 10);
+        }
+
+        #[ink::test]
+        fn challenge_flips_could_not_be_chosen() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            let seed = Hash::from([1; 32]);
+
+            // the same seeds flip the same way, whoever flips the coin and
+            // whenever they do.
+            for _ in 0..2 {
+                set_caller(accounts.charlie);
+                let challenge_id =
+                    bot.challenge(142, 10, seed_hash_of(seed)).unwrap();
+                set_caller(accounts.bob);
+                assert!(bot
+                    .accept_challenge(challenge_id, Hash::from([2; 32]))
+                    .is_ok());
+            }
+            set_caller(accounts.charlie);
+            let first = bot.flip(0, seed).unwrap();
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            set_caller(accounts.django);
+            assert_eq!(bot.flip(1, seed), Ok(first));
+
+            // a challenger who does not reveal thier seed to not lose forfeits
+            // both stakes.
+            set_caller(accounts.charlie);
+            let challenge_id =
+                bot.challenge(142, 10, seed_hash_of(seed)).unwrap();
+            set_caller(accounts.bob);
+            assert!(bot
+                .accept_challenge(challenge_id, Hash::from([3; 32]))
+                .is_ok());
+            for _ in 0..CHALLENGE_TIMEOUT {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                bot.flip(challenge_id, seed),
+                Err(Error::ChallengeExpired)
+            );
+            let opponent = bot.balance_of(142);
+            assert!(bot.cancel_challenge(challenge_id).is_ok());
+            assert_eq!(bot.balance_of(142), opponent + 20);
+        }
+
+        #[ink::test]
+        fn polls_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 100);
+            assert!(bot.bind(242).is_ok());
+
+            set_from_noowner();
+            assert_eq!(bot.create_poll(2, 5), Err(Error::NotAllowed));
+            set_from_owner();
+            assert_eq!(bot.create_poll(1, 5), Err(Error::InvalidOption));
+            assert_eq!(bot.create_poll(2, 0), Err(Error::PollClosed));
+            assert_eq!(bot.create_poll(3, 5), Ok(0));
+            assert_eq!(bot.create_poll(2, 5), Ok(1));
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.stake_on(0, 3, 10), Err(Error::InvalidOption));
+            assert!(bot.stake_on(0, 0, 30).is_ok());
+            assert!(bot.stake_on(0, 1, 10).is_ok());
+            assert!(bot.stake_on(1, 1, 10).is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.stake_on(0, 0, 10).is_ok());
+            set_caller(accounts.eve);
+            assert!(bot.stake_on(0, 2, 40).is_ok());
+            assert_eq!(bot.poll_option_stake(0, 0), 40);
+            assert_eq!(bot.poll(0).map(|poll| poll.total), Some(90));
+
+            set_from_owner();
+            assert_eq!(
+                bot.resolve(0, 0),
+                Err(Error::PollNotClosed { closes_at: 5 })
+            );
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            set_caller(accounts.eve);
+            assert_eq!(bot.stake_on(0, 2, 10), Err(Error::PollClosed));
+            assert_eq!(bot.claim_poll(0), Err(Error::PollNotResolved));
+            assert_eq!(bot.resolve(0, 0), Err(Error::NotAllowed));
+            set_from_owner();
+            assert_eq!(bot.resolve(0, 3), Err(Error::InvalidOption));
+            assert!(bot.resolve(0, 0).is_ok());
+            assert_eq!(bot.resolve(0, 1), Err(Error::PollResolved));
+            // no one staked on the winning option of the second poll.
+            assert!(bot.resolve(1, 0).is_ok());
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.claim_poll(0), Ok(67));
+            assert_eq!(bot.balance_of(42), 117);
+            assert_eq!(
+                bot.claim_poll(0),
+                Err(Error::NotFound {
+                    which: Entity::Stake
+                })
+            );
+            assert_eq!(bot.claim_poll(1), Ok(10));
+            set_caller(accounts.bob);
+            assert_eq!(bot.claim_poll(0), Ok(22));
+            assert_eq!(bot.balance_of(142), 112);
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.claim_poll(0),
+                Err(Error::NotFound {
+                    which: Entity::Stake
+                })
+            );
+        }
+
+        #[ink::test]
+        fn content_tips_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            let content_hash = Hash::from([3; 32]);
+
+            assert!(bot.tip_content(content_hash, 10).is_ok());
+            assert!(bot.tip_content(content_hash, 20).is_ok());
+            assert_eq!(bot.balance_of(42), 70);
+            assert_eq!(
+                bot.content_jar(content_hash),
+                Some(ContentJar {
+                    balance: 30,
+                    author: None
+                })
+            );
+
+            // the author binds after being tipped.
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(
+                bot.claim_content(content_hash),
+                Err(Error::AuthorNotAttested)
+            );
+            assert_eq!(
+                bot.attest_author(content_hash, 142),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert!(bot.attest_author(content_hash, 142).is_ok());
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.claim_content(content_hash), Err(Error::NotAllowed));
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.claim_content(Hash::from([4; 32])),
+                Err(Error::NotFound {
+                    which: Entity::Content
+                })
+            );
+            let before = get_balance(accounts.bob);
+            assert_eq!(bot.claim_content(content_hash), Ok(30));
+            assert_eq!(get_balance(accounts.bob), before + 30);
+            assert_eq!(bot.claim_content(content_hash), Err(Error::ZeroAmount));
+            assert!(matches!(
+                last_event(),
+                Event::ContentClaimed(ContentClaimed { received: 30, .. })
+            ));
+        }
+
+        #[ink::test]
+        fn aliases_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.charlie);
+            assert_eq!(bot.claim_alias("".into()), Err(Error::InvalidAlias));
+            assert_eq!(
+                bot.claim_alias("char lie".into()),
+                Err(Error::InvalidAlias)
+            );
+            assert_eq!(
+                bot.claim_alias("a".repeat(MAX_ALIAS_LEN + 1)),
+                Err(Error::InvalidAlias)
+            );
+            assert!(bot.claim_alias("@Charlie".into()).is_ok());
+            assert_eq!(bot.resolve_alias("charlie".into()), Some(42));
+            assert_eq!(bot.resolve_alias("@CHARLIE".into()), Some(42));
+            assert_eq!(bot.alias_of(42), Some("charlie".into()));
+            // claiming another alias releases the previous one.
+            assert!(bot.claim_alias("chárlie_2".into()).is_ok());
+            assert_eq!(bot.resolve_alias("charlie".into()), None);
+            assert_eq!(bot.resolve_alias("CHÁRLIE_2".into()), Some(42));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.claim_alias("chárlie_2".into()),
+                Err(Error::AliasTaken)
+            );
+            assert_eq!(
+                bot.release_alias("chárlie_2".into()),
+                Err(Error::NotFound {
+                    which: Entity::Alias
+                })
+            );
+            assert_eq!(
+                bot.reserve_alias("admin".into(), true, None),
+                Err(Error::NotAllowed)
+            );
+
+            set_from_owner();
+            assert!(bot.reserve_alias("Admin".into(), true, None).is_ok());
+            assert!(bot.reserve_alias("bob".into(), true, Some(142)).is_ok());
+            assert!(bot.is_alias_reserved("admin".into()));
+            assert_eq!(bot.resolve_alias("bob".into()), Some(142));
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.claim_alias("admin".into()),
+                Err(Error::AliasReserved)
+            );
+            assert!(bot.release_alias("bob".into()).is_ok());
+            assert_eq!(bot.resolve_alias("bob".into()), None);
+            assert_eq!(
+                bot.claim_alias("bob".into()),
+                Err(Error::AliasReserved)
+            );
+
+            // reserving a held alias takes it from its holder.
+            set_from_owner();
+            assert!(bot.reserve_alias("chárlie_2".into(), true, None).is_ok());
+            assert_eq!(bot.alias_of(42), None);
+            assert!(bot.reserve_alias("admin".into(), false, None).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.claim_alias("admin".into()).is_ok());
+        }
+
+        #[ink::test]
+        fn groups_works() {
+            let mut bot = create_contract(2000);
+            let accounts = default_accounts();
+            let group_id = -1001;
+
+            set_sender(accounts.charlie, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 1000);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(242).is_ok());
+            set_caller(accounts.frank);
+            assert_eq!(
+                bot.register_group(group_id),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_in_group(group_id, 142, 100),
+                Err(Error::NotFound {
+                    which: Entity::Group
+                })
+            );
+            assert!(bot.register_group(group_id).is_ok());
+            assert_eq!(
+                bot.register_group(group_id),
+                Err(Error::AlreadyRegistered)
+            );
+
+            set_sender(accounts.frank, 0);
+            assert_eq!(bot.donate_to_group(group_id), Err(Error::ZeroAmount));
+            set_sender(accounts.frank, 50);
+            assert!(bot.donate_to_group(group_id).is_ok());
+            assert_eq!(bot.group(group_id).unwrap().treasury, 50);
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip_in_group(group_id, 242, 100).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Tipped(Tipped {
+                    group: Some(id),
+                    ..
+                }) if id == group_id
+            ));
+            set_caller(accounts.bob);
+            assert!(bot.tip_in_group(group_id, 242, 300).is_ok());
+            assert!(bot.tip_in_group(group_id, 42, 50).is_ok());
+            // tips outside of the group are not counted.
+            assert!(bot.tip(42, 500, None).is_ok());
+
+            let group = bot.group(group_id).unwrap();
+            assert_eq!((group.tips, group.volume), (3, 450));
+            let stats = bot.group_stats_of(group_id, 142).unwrap();
+            assert_eq!((stats.tips_sent, stats.total_sent), (2, 350));
+            assert_eq!(
+                bot.group_leaderboard(group_id, Ranking::Senders),
+                vec![(142, 350), (42, 100)]
+            );
+            assert_eq!(
+                bot.group_leaderboard(group_id, Ranking::Receivers),
+                vec![(242, 400), (42, 50)]
+            );
+
+            // users who hide the tips they receive are left out.
+            set_caller(accounts.eve);
+            assert!(bot.set_privacy(true, false).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip_in_group(group_id, 242, 100).is_ok());
+            assert_eq!(
+                bot.group_stats_of(group_id, 242).unwrap().tips_received,
+                2
+            );
+            assert_eq!(
+                bot.group_leaderboard(group_id, Ranking::Senders),
+                vec![(142, 350), (42, 200)]
+            );
+        }
+
+        #[ink::test]
+        fn group_moderators_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let group_id = -1001;
+
+            set_sender(accounts.charlie, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(242).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.register_group(group_id).is_ok());
+            assert!(bot.register_group(-1002).is_ok());
+            set_sender(accounts.frank, 100);
+            assert!(bot.donate_to_group(group_id).is_ok());
+
+            // only the registrant manages the moderators.
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.add_group_moderator(group_id, accounts.bob),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                bot.rain_in_group(group_id, vec![242], 10),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.charlie);
+            assert!(bot.add_group_moderator(group_id, accounts.bob).is_ok());
+            assert_eq!(
+                bot.add_group_moderator(group_id, accounts.bob),
+                Err(Error::AlreadyRegistered)
+            );
+
+            // moderators could not touch other groups.
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.ban_from_group(-1002, 242, true),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                bot.add_group_moderator(group_id, accounts.eve),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                bot.rain_in_group(group_id, vec![42, 242], 60),
+                Err(Error::InsufficientFunds {
+                    required: 120,
+                    available: 100,
+                })
+            );
+            // listed twice, paid once.
+            assert!(bot
+                .rain_in_group(group_id, vec![42, 242, 242], 30)
+                .is_ok());
+            assert_eq!(bot.balance_of(42), 1030);
+            assert_eq!(bot.balance_of(242), 30);
+            assert_eq!(bot.group(group_id).unwrap().treasury, 40);
+
+            assert!(bot.ban_from_group(group_id, 242, true).is_ok());
+            assert!(bot.is_banned_from_group(group_id, 242));
+            assert!(!bot.is_banned_from_group(-1002, 242));
+            assert_eq!(
+                bot.rain_in_group(group_id, vec![242], 10),
+                Err(Error::BannedFromGroup { tg_id: 242 })
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.tip_in_group(group_id, 242, 10),
+                Err(Error::BannedFromGroup { tg_id: 242 })
+            );
+            assert!(bot.tip_in_group(-1002, 242, 10).is_ok());
+
+            // removed moderators lose thier permissions.
+            assert!(bot.remove_group_moderator(group_id, accounts.bob).is_ok());
+            assert_eq!(
+                bot.remove_group_moderator(group_id, accounts.bob),
+                Err(Error::NotFound {
+                    which: Entity::Moderator
+                })
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.ban_from_group(group_id, 242, false),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.charlie);
+            assert!(bot.ban_from_group(group_id, 242, false).is_ok());
+            assert!(bot.tip_in_group(group_id, 242, 10).is_ok());
+        }
+
+        #[ink::test]
+        fn tip_percent_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let bob = get_balance(accounts.bob);
+
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.tip_percent(142, MAX_BPS + 1),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.tip_percent(142, 2_500).is_ok());
+            assert_eq!(bot.balance_of(42), 75);
+            // the live balance is used, not the one the bot last saw.
+            assert!(bot.tip(142, 15, None).is_ok());
+            assert!(bot.tip_all(142).is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(get_balance(accounts.bob), bob + 100);
+            assert_eq!(bot.tip_all(142), Err(Error::ZeroAmount));
+        }
+
+        #[ink::test]
+        fn tip_all_rounds_down_to_fit_the_round_up() {
+            let mut bot = create_contract(10 * EDG);
+            let accounts = default_accounts();
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Charity(Some(accounts.frank)))
+            )
+            .is_ok());
+            set_sender(accounts.alice, 5 * EDG / 2);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    round_up: Some(true),
+                    ..Default::default()
+                })
+                .is_ok());
+            assert!(bot.tip_all(142).is_ok());
+            assert_eq!(bot.balance_of(42), EDG / 2);
+        }
+
+        #[ink::test]
+        fn reserve_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.set_reserve(60).is_ok());
+            assert_eq!(bot.settings_of(42).unwrap().reserve, 60);
+            assert_eq!(
+                bot.tip(142, 41, None),
+                Err(Error::InsufficientFunds {
+                    required: 41,
+                    available: 40,
+                })
+            );
+            assert!(bot.tip_all(142).is_ok());
+            assert_eq!(bot.balance_of(42), 60);
+            assert_eq!(
+                bot.tip(142, 1, None),
+                Err(Error::InsufficientFunds {
+                    required: 1,
+                    available: 0,
+                })
+            );
+            // the reserve is still there for withdrawals.
+            assert!(bot.request_withdrawal(60).is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+        }
+
+        #[ink::test]
+        fn auto_top_up_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 10);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let bob = get_balance(accounts.bob);
+
+            set_sender(accounts.alice, 20);
+            assert_eq!(bot.tip(142, 25, None), Err(Error::NotAllowed));
+            set_caller(accounts.alice);
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    auto_top_up: Some(true),
+                    ..Default::default()
+                })
+                .is_ok());
+            // the shortfall is taken from the transferred value, the rest is
+            // kept as tipEDG.
+            set_sender(accounts.alice, 20);
+            assert!(bot.tip(142, 25, None).is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(bot.receipt_balance_of(accounts.alice), 5);
+            assert_eq!(get_balance(accounts.bob), bob + 25);
+            // nothing is minted for a tip to an unbound user.
+            set_sender(accounts.alice, 20);
+            assert_eq!(
+                bot.tip(7, 10, None),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert_eq!(bot.receipt_balance_of(accounts.alice), 5);
+            // a failed tip reverts the call on chain, the value with it.
+            assert_eq!(
+                bot.tip(142, 30, None),
+                Err(Error::InsufficientFunds {
+                    required: 30,
+                    available: 25,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn category_stats_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.set_category(1, Some(String::from("dev"))),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.tip(142, 10, Some(1)),
+                Err(Error::NotFound {
+                    which: Entity::Category,
+                })
+            );
+            set_from_owner();
+            assert_eq!(
+                bot.set_category(1, Some(String::new())),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.set_category(1, Some(String::from("dev"))).is_ok());
+            assert!(bot.set_category(2, Some(String::from("meme"))).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 10, Some(1)).is_ok());
+            assert!(bot.tip(142, 5, Some(1)).is_ok());
+            assert!(bot.tip(142, 7, None).is_ok());
+            set_caller(bot.operator());
+            assert!(bot.tip_from(42, 142, 3, None, Some(2)).is_ok());
+            assert_eq!(
+                bot.category_stats(),
+                vec![
+                    (
+                        1,
+                        String::from("dev"),
+                        CategoryStats {
+                            tips: 2,
+                            volume: 15
+                        }
+                    ),
+                    (
+                        2,
+                        String::from("meme"),
+                        CategoryStats { tips: 1, volume: 3 }
+                    ),
+                ]
+            );
+
+            // removed categories keep thier totals when registered again.
+            set_from_owner();
+            assert!(bot.set_category(1, None).is_ok());
+            assert_eq!(bot.category_stats().len(), 1);
+            assert!(bot.set_category(1, Some(String::from("support"))).is_ok());
+            assert_eq!(bot.category_stats()[1].2.volume, 15);
+        }
+
+        #[ink::test]
+        fn asset_metadata_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let usdt = Some(accounts.frank);
+            let meta = AssetMeta {
+                symbol: String::from("USDT"),
+                decimals: 6,
+            };
+            assert_eq!(
+                bot.asset_metadata(None).unwrap().decimals,
+                EDG_DECIMALS
+            );
+            assert_eq!(bot.asset_metadata(usdt), None);
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.set_asset_metadata(usdt, Some(meta.clone())),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert_eq!(
+                bot.set_asset_metadata(
+                    usdt,
+                    Some(AssetMeta {
+                        symbol: String::new(),
+                        decimals: 6,
+                    })
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.set_asset_metadata(usdt, Some(meta.clone())).is_ok());
+            assert_eq!(bot.asset_metadata(usdt), Some(meta));
+            assert!(matches!(
+                nth_last_event(1),
+                Event::AssetMetadataSet(AssetMetadataSet { asset, .. })
+                    if asset == usdt
+            ));
+            assert!(bot.set_asset_metadata(usdt, None).is_ok());
+            assert_eq!(bot.asset_metadata(usdt), None);
+        }
+
+        #[ink::test]
+        fn moderator_notes_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let hash = Hash::from([7; 32]);
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.set_note(accounts.eve, Some((hash, b"spam".to_vec()))),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert_eq!(
+                bot.set_note(
+                    accounts.eve,
+                    Some((hash, vec![b'x'; MAX_NOTE_LEN + 1]))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot
+                .set_note(accounts.eve, Some((hash, b"spam".to_vec())))
+                .is_ok());
+            let note = bot.note_of(accounts.eve).unwrap();
+            assert_eq!(note.hash, hash);
+            assert_eq!(note.label, b"spam".to_vec());
+            assert_eq!(note.set_by, accounts.alice);
+            assert!(matches!(
+                nth_last_event(1),
+                Event::NoteSet(NoteSet { account, note: Some(_) })
+                    if account == accounts.eve
+            ));
+            assert!(bot.set_note(accounts.eve, None).is_ok());
+            assert_eq!(bot.note_of(accounts.eve), None);
+        }
+
+        #[ink::test]
+        fn deposit_caps_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.deposit_caps(), (None, None));
+            assert_eq!(bot.deposit_headroom_of(42), None);
+
+            let set_deposit_caps = |per_account, global| {
+                AdminAction::Configure(Setting::Admin(AdminSetting::Call(
+                    AdminCall::SetDepositCaps {
+                        per_account,
+                        global,
+                    },
+                )))
+            };
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_admin_call(set_deposit_caps(Some(100), Some(1200))),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert!(admin(&mut bot, set_deposit_caps(Some(100), Some(1200)))
+                .is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::DepositCapsSet(DepositCapsSet {
+                    per_account: Some(100),
+                    global: Some(1200),
+                })
+            ));
+            assert_eq!(bot.deposit_caps(), (Some(100), Some(1200)));
+            assert_eq!(bot.deposit_headroom_of(42), Some(100));
+
+            set_sender(accounts.eve, 150);
+            assert_eq!(
+                bot.deposit(),
+                Err(Error::DepositCapExceeded { headroom: 100 })
+            );
+            endow(accounts.eve, 100);
+            assert!(bot.deposit().is_ok());
+            assert_eq!(bot.deposit_headroom_of(42), Some(0));
+            // the contract could take 100 more.
+            assert_eq!(bot.deposit_headroom_of(7), Some(100));
+            endow(accounts.django, 100);
+            assert!(bot.bind(7).is_ok());
+            // tipEDG sent to a bound user counts towards the cap as a
+            // deposit would, while unwrapping it changes nothing.
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.receipt_transfer(accounts.django, 30, Vec::new()),
+                Err(PSP22Error::Custom(String::from("DepositCapExceeded")))
+            );
+            assert!(bot
+                .receipt_transfer(accounts.frank, 30, Vec::new())
+                .is_ok());
+            assert!(bot.unwrap(70).is_ok());
+            assert_eq!(bot.deposit_headroom_of(42), Some(0));
+            endow(accounts.frank, 1);
+            assert_eq!(
+                bot.bind(9),
+                Err(Error::DepositCapExceeded { headroom: 0 })
+            );
+            assert_eq!(bot.address_of(9), None);
+
+            set_from_owner();
+            assert!(admin(&mut bot, set_deposit_caps(None, None)).is_ok());
+            assert_eq!(bot.deposit_headroom_of(9), None);
+            endow(accounts.frank, 1);
+            assert!(bot.bind(9).is_ok());
+
+            // the tipEDG held before binding counts towards the cap too.
+            set_caller(accounts.frank);
+            assert!(bot
+                .receipt_transfer(accounts.charlie, 30, Vec::new())
+                .is_ok());
+            set_from_owner();
+            assert!(admin(&mut bot, set_deposit_caps(Some(50), None)).is_ok());
+            endow(accounts.charlie, 21);
+            assert_eq!(
+                bot.bind(11),
+                Err(Error::DepositCapExceeded { headroom: 20 })
+            );
+            endow(accounts.charlie, 20);
+            assert!(bot.bind(11).is_ok());
+        }
+
+        #[ink::test]
+        fn verification_levels_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(7).is_ok());
+            assert_eq!(
+                bot.verification_level_of(42),
+                Some(VerificationLevel::Unverified)
+            );
+
+            let set_level_limits = |level, limits| {
+                AdminAction::Configure(Setting::Admin(AdminSetting::Call(
+                    AdminCall::SetLevelLimits { level, limits },
+                )))
+            };
+            let set_verification_level = |tg_id, level| {
+                AdminAction::Configure(Setting::Admin(AdminSetting::Call(
+                    AdminCall::SetVerificationLevel { tg_id, level },
+                )))
+            };
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_admin_call(set_level_limits(
+                    VerificationLevel::Unverified,
+                    None
+                )),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            let limits = LevelLimits {
+                max_balance: 100,
+                max_tip: 10,
+                features: FEATURE_CHALLENGE,
+            };
+            assert!(admin(
+                &mut bot,
+                set_level_limits(VerificationLevel::Unverified, Some(limits))
+            )
+            .is_ok());
+            assert_eq!(
+                bot.level_limits(VerificationLevel::Unverified),
+                Some(limits)
+            );
+            assert_eq!(bot.level_limits(VerificationLevel::KycHashed), None);
+
+            set_sender(accounts.eve, 150);
+            assert_eq!(bot.deposit(), Err(Error::AboveMaxBalance { max: 100 }));
+            set_sender(accounts.eve, 100);
+            assert!(bot.deposit().is_ok());
+            // tipEDG sent to a bound user counts towards thier limits too.
+            assert!(bot
+                .receipt_transfer(accounts.django, 60, Vec::new())
+                .is_ok());
+            set_sender(accounts.eve, 60);
+            assert!(bot.deposit().is_ok());
+            assert_eq!(
+                bot.receipt_transfer(accounts.django, 50, Vec::new()),
+                Err(PSP22Error::Custom(String::from("AboveMaxBalance")))
+            );
+            set_sender(accounts.eve, 0);
+            assert_eq!(
+                bot.tip(7, 20, None),
+                Err(Error::AboveMaxTip { max: 10 })
+            );
+            assert_eq!(
+                bot.create_escrow(7, vec![20], None, 0),
+                Err(Error::FeatureLocked)
+            );
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_admin_call(set_verification_level(
+                    42,
+                    VerificationLevel::KycHashed
+                )),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    set_verification_level(9, VerificationLevel::KycHashed)
+                ),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            assert!(admin(
+                &mut bot,
+                set_verification_level(42, VerificationLevel::KycHashed)
+            )
+            .is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::VerificationLevelSet(VerificationLevelSet {
+                    level: VerificationLevel::KycHashed,
+                    ..
+                })
+            ));
+            set_sender(accounts.eve, 0);
+            assert!(bot.tip(7, 20, None).is_ok());
+            assert!(bot.unwrap(20).is_ok());
+            assert!(bot.create_escrow(7, vec![20], None, 0).is_ok());
+        }
+
+        #[ink::test]
+        fn inbox_policy_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(2).is_ok());
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(3).is_ok());
+
+            assert_eq!(bot.inbox_policy_of(3), None);
+            assert!(bot.block_sender(1).is_ok());
+            assert!(bot.block_sender(1).is_ok());
+            assert_eq!(bot.inbox_policy_of(3).unwrap().blocked, vec![1]);
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.tip(3, 10, None),
+                Err(Error::RecipientBlockedSender)
+            );
+            assert_eq!(
+                bot.can_tip(1, 3, 10, None),
+                Err(Error::RecipientBlockedSender)
+            );
+            set_caller(accounts.bob);
+            assert!(bot.tip(3, 10, None).is_ok());
+
+            set_caller(accounts.eve);
+            assert!(bot.set_allowlist_only(true).is_ok());
+            assert!(bot.unblock_sender(1).is_ok());
+            assert!(bot.allow_sender(1).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.tip(3, 10, None),
+                Err(Error::RecipientBlockedSender)
+            );
+            set_caller(accounts.alice);
+            assert!(bot.tip(3, 10, None).is_ok());
+
+            set_caller(accounts.eve);
+            assert!(bot.disallow_sender(1).is_ok());
+            assert!(bot.set_allowlist_only(false).is_ok());
+            assert_eq!(bot.inbox_policy_of(3), None);
+            set_caller(accounts.django);
+            assert_eq!(
+                bot.block_sender(1),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+        }
+
+        #[ink::test]
+        fn relayers_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            endow(accounts.eve, 100);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(2).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::RelayFee(5))
+            )
+            .is_ok());
+            let config = RelayerConfig {
+                name: String::from("discord-bridge"),
+                permissions: RelayerPermissions {
+                    can_tip_from: true,
+                    ..Default::default()
+                },
+                volume_cap: Some(30),
+            };
+
+            let set_relayer = |relayer, config| {
+                let setting = AdminSetting::Call(AdminCall::SetRelayer {
+                    relayer,
+                    config,
+                });
+                AdminAction::Configure(Setting::Admin(setting))
+            };
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.propose_admin_call(set_relayer(
+                    accounts.frank,
+                    Some(config.clone())
+                )),
+                Err(Error::NotAllowed)
+            );
+            let operator = bot.operator();
+            assert_eq!(
+                admin(&mut bot, set_relayer(operator, Some(config.clone()))),
+                Err(Error::InvalidParamValue)
+            );
+            // one owner could not add a relayer on thier own.
+            assert!(
+                admin(&mut bot, AdminAction::AddOwner(accounts.bob)).is_ok()
+            );
+            assert!(admin(&mut bot, AdminAction::SetThreshold(2)).is_ok());
+            let proposal_id = admin(
+                &mut bot,
+                set_relayer(accounts.frank, Some(config.clone())),
+            )
+            .unwrap();
+            assert_eq!(bot.relayer(accounts.frank), None);
+            set_caller(accounts.bob);
+            assert!(bot.approve(proposal_id).is_ok());
+            assert_eq!(bot.relayer(accounts.frank).unwrap().config, config);
+
+            set_caller(accounts.frank);
+            assert!(bot.tip_from(1, 2, 20, None, None).is_ok());
+            assert_eq!(bot.balance_of(1), 75);
+            assert_eq!(
+                bot.tip_from(1, 2, 20, None, None),
+                Err(Error::RelayerCapExceeded { remaining: 10 })
+            );
+            assert_eq!(bot.execute_withdrawal_for(1), Err(Error::NotAllowed));
+            assert_eq!(bot.relayer(accounts.frank).unwrap().reimbursement, 5);
+            assert_eq!(bot.relay_reimbursement(), 0);
+            let before = get_balance(accounts.frank);
+            assert!(bot.claim_relay_reimbursement().is_ok());
+            assert_eq!(get_balance(accounts.frank), before + 5);
+            assert_eq!(bot.relayer(accounts.frank).unwrap().reimbursement, 0);
+
+            let proposal_id =
+                admin(&mut bot, set_relayer(accounts.frank, None)).unwrap();
+            set_caller(accounts.bob);
+            assert!(bot.approve(proposal_id).is_ok());
+            assert!(matches!(
+                nth_last_event(2),
+                Event::RelayerSet(RelayerSet { config: None, .. })
+            ));
+            set_caller(accounts.frank);
+            assert_eq!(
+                bot.tip_from(1, 2, 5, None, None),
+                Err(Error::NotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn scheduled_tips_work() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            endow(accounts.eve, 100 + 2 * KEEPER_INCENTIVE);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(2).is_ok());
+
+            set_caller(accounts.eve);
+            let now = ink_env::block_timestamp::<Environment>();
+            assert_eq!(
+                bot.schedule_tip(2, 30, now),
+                Err(Error::InvalidParamValue)
+            );
+            assert_eq!(
+                bot.schedule_tip(3, 30, now + 1),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            let first = bot.schedule_tip(2, 30, now + 1).unwrap();
+            // more than the sender will have left when it is due.
+            let second = bot.schedule_tip(2, 90, now + 1).unwrap();
+            assert_eq!(bot.balance_of(1), 100);
+            assert_eq!(
+                bot.job(first).unwrap().kind,
+                JobKind::Tip { to: 2, amount: 30 }
+            );
+
+            set_caller(accounts.frank);
+            assert_eq!(bot.execute_due(10), Ok(0));
+            test::advance_block::<Environment>();
+            let before = get_balance(accounts.frank);
+            assert_eq!(bot.execute_due(10), Ok(2));
+            assert_eq!(
+                get_balance(accounts.frank),
+                before + 2 * KEEPER_INCENTIVE
+            );
+            assert_eq!(bot.job(first), None);
+            assert_eq!(bot.job(second), None);
+            assert!(matches!(
+                last_event(),
+                Event::JobExecuted(JobExecuted {
+                    error: Some(Error::InsufficientFunds { .. }),
+                    ..
+                })
+            ));
+            assert_eq!(bot.stats_of(1).unwrap().total_sent, 30);
+            assert_eq!(bot.execute_due(10), Ok(0));
+        }
+
+        #[ink::test]
+        fn failed_scheduled_tips_keep_receipts() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            endow(accounts.eve, 40 + KEEPER_INCENTIVE);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(2).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+
+            set_caller(accounts.eve);
+            let now = ink_env::block_timestamp::<Environment>();
+            let job_id = bot.schedule_tip(2, 30, now + 1).unwrap();
+            for _ in 0..MAX_PENDING_TIPS_PER_SENDER {
+                assert!(bot.tip(2, 10, None).is_ok());
+            }
+            endow(accounts.eve, 30);
+            assert!(bot.deposit().is_ok());
+            assert_eq!(bot.balance_of(1), 0);
+
+            // the tip could not be held, so the tipEDG covering it is kept.
+            test::advance_block::<Environment>();
+            set_caller(accounts.frank);
+            assert_eq!(bot.execute_due(10), Ok(1));
+            assert_eq!(bot.job(job_id), None);
+            assert!(matches!(
+                last_event(),
+                Event::JobExecuted(JobExecuted {
+                    error: Some(Error::TooManyPendingTips),
+                    ..
+                })
+            ));
+            assert_eq!(bot.receipt_balance_of(accounts.eve), 30);
+            assert_eq!(bot.receipt_total_supply(), 30);
+            assert_eq!(bot.balance_of(1), 0);
+        }
+
+        #[ink::test]
+        fn job_queue_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            endow(accounts.eve, 100 + 3 * KEEPER_INCENTIVE);
+            assert!(bot.bind(1).is_ok());
+            set_sender(accounts.django, 0);
+            assert!(bot.bind(2).is_ok());
+            endow(accounts.frank, KEEPER_INCENTIVE);
+            assert!(bot.bind(3).is_ok());
+
+            let now = ink_env::block_timestamp::<Environment>();
+            set_caller(accounts.eve);
+            let first = bot.schedule_tip(2, 10, now + 1).unwrap();
+            let second = bot.schedule_tip(2, 20, now + 1).unwrap();
+            set_caller(accounts.frank);
+            let third = bot.schedule_tip(2, 30, now + 1).unwrap();
+            let ids = |jobs: Vec<(JobId, Job)>| {
+                jobs.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(bot.jobs_of(1)), vec![first, second]);
+            assert_eq!(
+                ids(bot.pending_jobs(0, 10)),
+                vec![first, second, third]
+            );
+            assert_eq!(ids(bot.pending_jobs(1, 1)), vec![second]);
+
+            assert_eq!(bot.cancel_job(first), Err(Error::NotAllowed));
+            set_caller(accounts.eve);
+            assert!(bot.cancel_job(first).is_ok());
+            assert_eq!(
+                bot.cancel_job(first),
+                Err(Error::NotFound { which: Entity::Job })
+            );
+            assert_eq!(bot.balance_of(1), 100 + 2 * KEEPER_INCENTIVE);
+            assert_eq!(ids(bot.jobs_of(1)), vec![second]);
+            assert_eq!(ids(bot.pending_jobs(0, 10)), vec![second, third]);
+
+            test::advance_block::<Environment>();
+            set_caller(accounts.bob);
+            assert_eq!(bot.execute_due(1), Ok(1));
+            assert_eq!(bot.jobs_of(1), vec![]);
+            assert_eq!(ids(bot.pending_jobs(0, 10)), vec![third]);
+        }
+
+        #[ink::test]
+        fn donate_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            let metadata_hash = Hash::from([7; 32]);
+
+            assert_eq!(
+                bot.register_cause(1, accounts.frank, metadata_hash),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                bot.donate(1, 10),
+                Err(Error::NotFound {
+                    which: Entity::Cause,
+                })
+            );
+            set_from_owner();
+            assert!(bot
+                .register_cause(1, accounts.frank, metadata_hash)
+                .is_ok());
+
+            set_caller(accounts.bob);
+            let frank = get_balance(accounts.frank);
+            assert_eq!(bot.donate(1, 0), Err(Error::ZeroAmount));
+            assert!(matches!(
+                bot.donate(1, 101),
+                Err(Error::InsufficientFunds { .. })
+            ));
+            assert!(bot.donate(1, 30).is_ok());
+            assert!(bot.donate(1, 20).is_ok());
+            assert_eq!(bot.balance_of(142), 50);
+            assert_eq!(get_balance(accounts.frank), frank + 50);
+            assert_eq!(
+                bot.cause(1),
+                Some(Cause {
+                    account: accounts.frank,
+                    metadata_hash,
+                    total: 50,
+                })
+            );
+
+            // changing the account keeps the total.
+            set_from_owner();
+            assert!(bot.register_cause(1, accounts.eve, metadata_hash).is_ok());
+            assert_eq!(bot.cause(1).unwrap().total, 50);
+            assert_eq!(bot.cause(1).unwrap().account, accounts.eve);
+        }
+
+        #[ink::test]
+        fn audit_of_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_sender(accounts.alice, 50);
+            assert!(bot.deposit().is_ok());
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 130, None).is_ok());
+            assert!(bot.request_withdrawal(20).is_ok());
+            // the deposit is minted as tipEDG, which comes in as it is burned.
+            let report = bot.audit_of(42).unwrap();
+            assert_eq!(
+                report,
+                AuditReport {
+                    totals: AuditCounters {
+                        deposited: 100,
+                        withdrawn: 20,
+                        tips_out: 130,
+                        other_in: 50,
+                        ..Default::default()
+                    },
+                    balance: 0,
+                }
+            );
+            assert!(report.adds_up());
+            assert_eq!(bot.audit_of(7), None);
+
+            // hidden balances are audited only by the user and the owners.
+            assert!(bot.set_privacy(false, true).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(bot.audit_of(42), None);
+            set_from_owner();
+            assert_eq!(bot.audit_of(42), Some(report));
+        }
+
+        #[ink::test]
+        fn escrow_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.create_escrow(142, vec![10, 0], None, 5),
+                Err(Error::ZeroAmount)
+            );
+            assert_eq!(
+                bot.create_escrow(42, vec![10], None, 5),
+                Err(Error::SelfTip)
+            );
+            assert_eq!(
+                bot.create_escrow(142, vec![10; MAX_MILESTONES + 1], None, 5),
+                Err(Error::TooManyMilestones)
+            );
+            assert_eq!(
+                bot.create_escrow(
+                    142,
+                    vec![30, 20, 10],
+                    Some(accounts.frank),
+                    5
+                ),
+                Ok(0)
+            );
+            assert_eq!(bot.balance_of(42), 40);
+
+            // only the worker delivers, and the funder releases right away.
+            assert_eq!(bot.deliver_milestone(0, 0), Err(Error::NotAllowed));
+            assert_eq!(
+                bot.release_milestone(0, 0),
+                Err(Error::MilestoneNotDelivered)
+            );
+            set_caller(accounts.bob);
+            assert!(bot.deliver_milestone(0, 0).is_ok());
+            assert_eq!(
+                bot.deliver_milestone(0, 0),
+                Err(Error::AlreadyDelivered)
+            );
+            assert_eq!(
+                bot.deliver_milestone(0, 3),
+                Err(Error::NotFound {
+                    which: Entity::Milestone,
+                })
+            );
+            assert_eq!(bot.release_milestone(0, 0), Err(Error::NotAllowed));
+            set_caller(accounts.alice);
+            assert!(bot.release_milestone(0, 0).is_ok());
+            assert_eq!(bot.balance_of(142), 30);
+
+            // the arbiter releases only after the timeout.
+            set_caller(accounts.bob);
+            assert!(bot.deliver_milestone(0, 1).is_ok());
+            set_caller(accounts.frank);
+            assert_eq!(
+                bot.release_milestone(0, 1),
+                Err(Error::ReleaseNotOpen { at: 5 })
+            );
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.release_milestone(0, 1).is_ok());
+            assert_eq!(bot.balance_of(142), 50);
+            assert_eq!(
+                bot.release_milestone(0, 1),
+                Err(Error::NotFound {
+                    which: Entity::Milestone,
+                })
+            );
+
+            // the undelivered milestones are refunded to the funder.
+            assert_eq!(bot.refund_escrow(0), Err(Error::NotAllowed));
+            set_caller(accounts.alice);
+            assert!(bot.refund_escrow(0).is_ok());
+            assert_eq!(bot.balance_of(42), 50);
+            assert_eq!(
+                bot.refund_escrow(0),
+                Err(Error::NotFound {
+                    which: Entity::Milestone,
+                })
+            );
+            let states = bot
+                .escrow(0)
+                .unwrap()
+                .milestones
+                .into_iter()
+                .map(|milestone| milestone.state)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                states,
+                vec![
+                    MilestoneState::Released,
+                    MilestoneState::Released,
+                    MilestoneState::Refunded
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn dispute_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 10);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.create_escrow(142, vec![30, 20, 10], None, 5),
+                Ok(0)
+            );
+
+            assert_eq!(bot.open_dispute(0, 0), Err(Error::ArbitrationDisabled));
+            set_caller(accounts.bob);
+            let set_arbiters = |arbiters, dispute_fee| {
+                AdminAction::Configure(Setting::Admin(AdminSetting::Call(
+                    AdminCall::SetArbiters {
+                        arbiters,
+                        dispute_fee,
+                    },
+                )))
+            };
+            assert_eq!(
+                bot.propose_admin_call(set_arbiters(vec![accounts.charlie], 3)),
+                Err(Error::NotAllowed)
+            );
+            set_from_owner();
+            let panel = vec![accounts.charlie, accounts.django, accounts.eve];
+            assert!(admin(&mut bot, set_arbiters(panel.clone(), 3)).is_ok());
+
+            // a majority releases the milestone, and shares the fee.
+            set_caller(accounts.bob);
+            assert!(bot.deliver_milestone(0, 0).is_ok());
+            assert!(bot.open_dispute(0, 0).is_ok());
+            assert_eq!(bot.balance_of(142), 7);
+            assert_eq!(bot.open_dispute(0, 0), Err(Error::Disputed));
+            set_caller(accounts.alice);
+            assert_eq!(bot.release_milestone(0, 0), Err(Error::Disputed));
+            set_caller(accounts.frank);
+            assert_eq!(bot.vote_on_dispute(0, 0, true), Err(Error::NotAllowed));
+            let charlie = get_balance(accounts.charlie);
+            let eve = get_balance(accounts.eve);
+            set_caller(accounts.charlie);
+            assert!(bot.vote_on_dispute(0, 0, true).is_ok());
+            assert_eq!(
+                bot.vote_on_dispute(0, 0, true),
+                Err(Error::AlreadyVoted)
+            );
+            set_caller(accounts.django);
+            assert!(bot.vote_on_dispute(0, 0, false).is_ok());
+            set_caller(accounts.eve);
+            assert!(bot.vote_on_dispute(0, 0, true).is_ok());
+            assert_eq!(bot.balance_of(142), 37);
+            assert_eq!(get_balance(accounts.charlie), charlie + 1);
+            assert_eq!(get_balance(accounts.eve), eve + 1);
+            assert_eq!(bot.dispute(0, 0), None);
+
+            // after the voting ends the votes cast decide, a tie restores the
+            // milestone.
+            set_caller(accounts.alice);
+            assert!(bot.open_dispute(0, 1).is_ok());
+            assert_eq!(
+                bot.resolve_dispute(0, 1),
+                Err(Error::VotingOpen {
+                    ends_at: DISPUTE_VOTING_PERIOD,
+                })
+            );
+            for _ in 0..DISPUTE_VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.vote_on_dispute(0, 1, true),
+                Err(Error::VotingClosed)
+            );
+            assert!(bot.resolve_dispute(0, 1).is_ok());
+            assert_eq!(bot.balance_of(42), 40);
+            assert_eq!(
+                bot.escrow(0).unwrap().milestones[1].state,
+                MilestoneState::Pending
+            );
+
+            // a refund vote gives the milestone back to the funder.
+            set_caller(accounts.alice);
+            assert!(bot.open_dispute(0, 2).is_ok());
+            set_caller(accounts.django);
+            assert!(bot.vote_on_dispute(0, 2, false).is_ok());
+            for _ in 0..DISPUTE_VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.resolve_dispute(0, 2).is_ok());
+            assert_eq!(bot.balance_of(42), 47);
+            assert_eq!(
+                bot.escrow(0).unwrap().milestones[2].state,
+                MilestoneState::Refunded
+            );
+        }
+
+        #[ink::test]
+        fn newcomer_limits_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let limits = NewcomerLimits {
+                tips: 2,
+                blocks: 10,
+                max_tip: 20,
+                cooldown: 3,
+            };
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Binding(
+                    BindingSetting::NewcomerLimits(Some(limits))
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.newcomer_limits(), Some(limits));
+            set_sender(accounts.alice, 200);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.tip(142, 21, None),
+                Err(Error::AboveMaxTip { max: 20 })
+            );
+            assert!(bot.tip(142, 20, None).is_ok());
+            assert_eq!(
+                bot.tip(142, 5, None),
+                Err(Error::TipCooldown { until: 3 })
+            );
+            for _ in 0..3 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip(142, 5, None).is_ok());
+            // two tips are not enough before the blocks pass.
+            assert_eq!(
+                bot.tip(142, 21, None),
+                Err(Error::AboveMaxTip { max: 20 })
+            );
+            for _ in 0..7 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip(142, 21, None).is_ok());
+            assert!(bot.tip(142, 5, None).is_ok());
+        }
+
+        #[ink::test]
+        fn tip_weighted_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(242).is_ok());
+            let bob = get_balance(accounts.bob);
+            let charlie = get_balance(accounts.charlie);
+
+            set_caller(accounts.alice);
+            assert_eq!(
+                bot.tip_weighted(vec![(142, 1), (242, 0)], 10),
+                Err(Error::ZeroWeight)
+            );
+            assert_eq!(
+                bot.tip_weighted(vec![(142, 1), (343, 1)], 10),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert_eq!(
+                bot.tip_weighted((0..21).map(|tg_id| (tg_id, 1)).collect(), 10),
+                Err(Error::TooManyRecipients)
+            );
+            // none is paid unless all could be.
+            assert_eq!(
+                bot.tip_weighted(vec![(142, 1), (42, 1)], 10),
+                Err(Error::SelfTip)
+            );
+            assert_eq!(
+                bot.tip_weighted(vec![(142, 1), (242, 1)], 300),
+                Err(Error::InsufficientFunds {
+                    required: 150,
+                    available: 100,
+                })
+            );
+            assert_eq!(bot.balance_of(42), 100);
+
+            // 142 is listed twice, for a weight of 3 out of 4, and the
+            // rounding dust is kept.
+            assert!(bot
+                .tip_weighted(vec![(142, 2), (242, 1), (142, 1)], 50)
+                .is_ok());
+            assert_eq!(get_balance(accounts.bob), bob + 37);
+            assert_eq!(get_balance(accounts.charlie), charlie + 12);
+            assert_eq!(bot.balance_of(42), 51);
+        }
+
+        #[ink::test]
+        fn can_tip_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let group_id = -1001;
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(
+                bot.can_tip(43, 142, 10, None),
+                Err(Error::NotFound {
+                    which: Entity::Sender
+                })
+            );
+            assert_eq!(
+                bot.can_tip(42, 143, 10, None),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            assert_eq!(bot.can_tip(42, 42, 10, None), Err(Error::SelfTip));
+            assert_eq!(
+                bot.can_tip(42, 142, 200, None),
+                Err(Error::InsufficientFunds {
+                    required: 200,
+                    available: 100,
+                })
+            );
+            assert_eq!(
+                bot.can_tip(42, 142, 10, Some(group_id)),
+                Err(Error::NotFound {
+                    which: Entity::Group
+                })
+            );
+            assert_eq!(bot.can_tip(42, 142, 10, None), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    daily_limit: Some(Some(30)),
+                    ..Default::default()
+                })
+                .is_ok());
+            assert!(bot.tip(142, 20, None).is_ok());
+            assert_eq!(
+                bot.can_tip(42, 142, 20, None),
+                Err(Error::DailyLimitExceeded { remaining: 10 })
+            );
+            assert_eq!(bot.can_tip(42, 142, 10, None), Ok(()));
+
+            assert!(bot.register_group(group_id).is_ok());
+            assert!(bot
+                .set_group_config(
+                    group_id,
+                    GroupConfig {
+                        cooldown: Some(10),
+                        ..Default::default()
+                    }
+                )
+                .is_ok());
+            assert!(bot.ban_from_group(group_id, 142, true).is_ok());
+            assert_eq!(
+                bot.can_tip(42, 142, 10, Some(group_id)),
+                Err(Error::BannedFromGroup { tg_id: 142 })
+            );
+            assert!(bot.ban_from_group(group_id, 142, false).is_ok());
+            assert!(bot.tip_in_group(group_id, 142, 5).is_ok());
+            let now = ink_env::block_number::<Environment>();
+            assert_eq!(
+                bot.can_tip(42, 142, 5, Some(group_id)),
+                Err(Error::TipCooldown { until: now + 10 })
+            );
+            assert_eq!(bot.can_tip(42, 142, 5, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn group_config_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let group_id = -1001;
+
+            set_sender(accounts.charlie, 1000);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.register_group(group_id).is_ok());
+            let config = GroupConfig {
+                min_tip: Some(50),
+                fee_bps: Some(1_000),
+                cooldown: Some(10),
+            };
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.set_group_config(group_id, config.clone()),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.set_group_config(
+                    group_id,
+                    GroupConfig {
+                        fee_bps: Some(10_001),
+                        ..Default::default()
+                    }
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.set_group_config(group_id, config).is_ok());
+
+            assert_eq!(
+                bot.tip_in_group(group_id, 142, 40),
+                Err(Error::BelowMinTip { min: 50 })
+            );
+            assert!(bot.tip_in_group(group_id, 142, 100).is_ok());
+            assert_eq!(bot.treasury(), 10);
+            let now = ink_env::block_number::<Environment>();
+            assert_eq!(
+                bot.tip_in_group(group_id, 142, 100),
+                Err(Error::TipCooldown { until: now + 10 })
+            );
+            // the global policy still applies outside of the group.
+            assert!(bot.tip(142, 40, None).is_ok());
+            assert_eq!(bot.treasury(), 10);
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.tip_in_group(group_id, 142, 100).is_ok());
+            assert_eq!(bot.treasury(), 20);
+
+            // unset fields fall back to the global policy.
+            assert!(bot
+                .set_group_config(group_id, GroupConfig::default())
+                .is_ok());
+            assert!(bot.tip_in_group(group_id, 142, 40).is_ok());
+            assert!(bot.tip_in_group(group_id, 142, 40).is_ok());
+            assert_eq!(bot.treasury(), 20);
+
+            // the owners keep the fee of the group above a floor.
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    AdminAction::Configure(Setting::Admin(
+                        AdminSetting::GroupFeeFloor(10_001)
+                    ))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Admin(
+                    AdminSetting::GroupFeeFloor(2_000)
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.group_fee_floor(), 2_000);
+            set_caller(accounts.charlie);
+            let config = GroupConfig {
+                fee_bps: Some(1_000),
+                ..Default::default()
+            };
+            assert!(bot.set_group_config(group_id, config).is_ok());
+            assert!(bot.tip_in_group(group_id, 142, 100).is_ok());
+            assert_eq!(bot.treasury(), 40);
+        }
+
+        #[ink::test]
+        fn team_wallets_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let name_hash = Hash::from([1; 32]);
+
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 0);
+            assert!(bot.bind(242).is_ok());
+            assert_eq!(
+                bot.create_team(name_hash, vec![42, 142], 3),
+                Err(Error::InvalidThreshold)
+            );
+            assert_eq!(
+                bot.create_team(name_hash, vec![42, 142, 42], 0),
+                Err(Error::InvalidThreshold)
+            );
+            let team_id = bot.create_team(name_hash, vec![42, 142, 42], 2);
+            let team_id = team_id.unwrap();
+            assert_eq!(bot.team(team_id).unwrap().members, vec![42, 142]);
+            set_sender(accounts.frank, 500);
+            assert!(bot.fund_team(team_id).is_ok());
+
+            // only members could propose, or approve, a team tip.
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.propose_team_tip(team_id, 242, 100),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.charlie);
+            let tip_id = bot.propose_team_tip(team_id, 242, 100).unwrap();
+            assert_eq!(bot.team_tip(tip_id).unwrap().approvals, vec![42]);
+            assert_eq!(
+                bot.approve_team_tip(tip_id),
+                Err(Error::AlreadyApproved)
+            );
+            set_caller(accounts.eve);
+            assert_eq!(bot.approve_team_tip(tip_id), Err(Error::NotAllowed));
+            let before = get_balance(accounts.eve);
+            set_caller(accounts.bob);
+            assert!(bot.approve_team_tip(tip_id).is_ok());
+            assert_eq!(get_balance(accounts.eve), before + 100);
+            assert_eq!(bot.team(team_id).unwrap().balance, 400);
+            assert_eq!(bot.team_tip(tip_id), None);
+            assert_eq!(
+                bot.approve_team_tip(tip_id),
+                Err(Error::NotFound {
+                    which: Entity::TeamTip
+                })
+            );
+
+            // the last approval is not counted if the team can not pay.
+            let tip_id = bot.propose_team_tip(team_id, 242, 1000).unwrap();
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.approve_team_tip(tip_id),
+                Err(Error::InsufficientFunds {
+                    required: 1000,
+                    available: 400,
+                })
+            );
+            assert_eq!(bot.team_tip(tip_id).unwrap().approvals, vec![142]);
+        }
+
+        #[ink::test]
+        fn idle_team_could_be_cancelled() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.frank, 0);
+
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let team_id = bot.create_team(Hash::from([1; 32]), vec![42], 1);
+            let team_id = team_id.unwrap();
+            set_sender(accounts.frank, 300);
+            assert!(bot.fund_team(team_id).is_ok());
+            set_sender(accounts.charlie, 100);
+            assert!(bot.fund_team(team_id).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert!(bot.propose_team_tip(team_id, 142, 200).is_ok());
+            let idle_until =
+                ink_env::block_number::<Environment>() + TEAM_IDLE_PERIOD;
+
+            assert_eq!(
+                bot.cancel_team(team_id),
+                Err(Error::NotDormant { until: idle_until })
+            );
+            for _ in 0..TEAM_IDLE_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            // the rest is refunded in proportion to what each funded.
+            set_from_noowner();
+            assert!(bot.cancel_team(team_id).is_ok());
+            assert_eq!(get_balance(accounts.frank), 150);
+            assert_eq!(bot.balance_of(42), 50);
+            assert_eq!(bot.team(team_id), None);
+        }
+
+        #[ink::test]
+        fn wind_down_refunds_teams() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_balance(accounts.charlie, 0);
+
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(42).is_ok());
+            let team_id = bot.create_team(Hash::from([1; 32]), vec![42], 1);
+            set_sender(accounts.charlie, 100);
+            assert!(bot.fund_team(team_id.unwrap()).is_ok());
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::ScheduleTermination(accounts.eve)
+            )
+            .is_ok());
+            for _ in 0..TERMINATION_DELAY {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.wind_down(1), Ok(1));
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(bot.wind_down(10), Ok(1));
+            assert_eq!(get_balance(accounts.charlie), 100);
+        }
+
+        #[ink::test]
+        fn charity_round_up_works() {
+            let mut bot = create_contract(10 * EDG);
+            let accounts = default_accounts();
+            let charity = accounts.frank;
+
+            set_sender(accounts.charlie, 3 * EDG);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot
+                .update_settings(UserSettingsUpdate {
+                    round_up: Some(true),
+                    ..Default::default()
+                })
+                .is_ok());
+            // nothing to round up for without a charity.
+            assert!(bot.tip(142, EDG / 4, None).is_ok());
+            assert_eq!(bot.balance_of(42), 3 * EDG - EDG / 4);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Charity(Some(charity)))
+            )
+            .is_ok());
+            let before = get_balance(charity);
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, EDG / 4, None).is_ok());
+            assert_eq!(get_balance(charity), before + 3 * EDG / 4);
+            assert_eq!(bot.balance_of(42), 2 * EDG - EDG / 4);
+            assert_eq!(bot.charity_contributed(42), 3 * EDG / 4);
+            assert!(matches!(
+                last_event(),
+                Event::CharityDonated(CharityDonated {
+                    account: Some(account),
+                    amount,
+                    ..
+                }) if account == accounts.charlie && amount == 3 * EDG / 4
+            ));
+            // whole tips are not rounded up.
+            assert!(bot.tip(142, EDG, None).is_ok());
+            assert_eq!(bot.charity_contributed(42), 3 * EDG / 4);
+            // the round-up has to be covered by the balance too.
+            assert_eq!(
+                bot.tip(142, EDG / 2, None),
+                Err(Error::InsufficientFunds {
+                    required: EDG,
+                    available: 3 * EDG / 4,
+                })
+            );
+        }
 
-/// A macro to panic and abort any changes to the contract
-/// on error.
-/// Should be removed after this issue is resolved.
-/// https://github.com/paritytech/ink/issues/745
-macro_rules! panic_on_err {
-    ($result: expr) => {
-        match $result {
-            Ok(v) => Result::<_, Error>::Ok(v),
-            Err(e) => panic!("{:?}", e),
-        }
-    };
-}
+        #[ink::test]
+        fn burn_works() {
+            let mut bot = create_contract(1000);
+            set_param_quorum(&mut bot, 0);
+            let accounts = default_accounts();
+            let burn_address = accounts.frank;
 
-#[ink::contract]
-mod tipbot {
-    /// A Telegram User Id.
-    type TelegramId = u32;
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert_eq!(bot.burn(10), Err(Error::BurnDisabled));
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::BurnAddress(Some(
+                    burn_address
+                )))
+            )
+            .is_ok());
+            set_caller(accounts.charlie);
+            let id = bot.propose_param_change(Param::FeeBps, 1_000).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.enact(id).is_ok());
 
-    /// Edgeware Tipping Bot
-    #[ink(storage)]
-    pub struct Tipbot {
-        /// The contract owner, set to the account who deployed the contract
-        owner: AccountId,
-        address_tg: ink_storage::collections::HashMap<AccountId, TelegramId>,
-        tg_address: ink_storage::collections::HashMap<TelegramId, AccountId>,
-        balances: ink_storage::collections::HashMap<AccountId, Balance>,
-    }
+            let before = get_balance(burn_address);
+            assert_eq!(bot.burn(0), Err(Error::ZeroAmount));
+            assert_eq!(
+                bot.burn(101),
+                Err(Error::InsufficientFunds {
+                    required: 101,
+                    available: 100,
+                })
+            );
+            assert!(bot.burn(20).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::Burned(Burned {
+                    from: Some(from),
+                    amount: 20,
+                }) if from == accounts.charlie
+            ));
+            assert_eq!(bot.balance_of(42), 80);
+            assert!(bot.tip(142, 50, None).is_ok());
+            assert_eq!(bot.treasury(), 5);
 
-    /// The Error cases.
-    #[derive(Debug, PartialEq, Eq, scale::Encode)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        /// Returned if the AccountId is already bounded to a TelegramId.
-        AlreadyBounded,
-        /// Returned if the caller is not the owner of the contract.
-        NotAllowed,
-        /// Returned if the address/telegram id is not found.
-        NotFound,
-        /// Returned if the transfer failed.
-        TransferFailed,
-        /// Insufficient funds to execute transfer.
-        InsufficientFunds,
-        /// Transfer failed because it would have brought the contract's
-        /// balance below the subsistence threshold.
-        /// This is necessary to keep enough funds in the contract to
-        /// allow for a tombstone to be created.
-        BelowSubsistenceThreshold,
-    }
+            // only the owners could burn the treasury.
+            assert_eq!(
+                bot.propose_admin_call(AdminAction::Configure(Setting::Admin(
+                    AdminSetting::Call(AdminCall::BurnTreasury(5))
+                ))),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::BurnTreasury(6)),
+                Err(Error::InsufficientFunds {
+                    required: 6,
+                    available: 5,
+                })
+            );
+            assert!(admin_call(&mut bot, AdminCall::BurnTreasury(5)).is_ok());
+            assert_eq!(bot.treasury(), 0);
+            assert_eq!(bot.total_burned(), 25);
+            assert_eq!(get_balance(burn_address), before + 25);
+        }
 
-    impl Tipbot {
-        /// Create new Tipbot.
-        /// The `caller` of this constructor will be set as the Owner of the
-        /// contract.
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Self {
-                owner: Self::env().caller(),
-                address_tg: Default::default(),
-                tg_address: Default::default(),
-                balances: Default::default(),
+        #[ink::test]
+        fn payout_address_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let cold = accounts.frank;
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 50);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.payout_address_of(142), Some(accounts.bob));
+            assert!(bot.set_payout_address(cold).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::PayoutAddressChanged(PayoutAddressChanged {
+                    account,
+                    payout,
+                }) if account == accounts.bob && payout == cold
+            ));
+            assert_eq!(bot.payout_address_of(142), Some(cold));
+
+            let (bob, before) = (get_balance(accounts.bob), get_balance(cold));
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 30, None).is_ok());
+            assert_eq!(get_balance(cold), before + 30);
+            assert_eq!(get_balance(accounts.bob), bob);
+            assert_eq!(bot.stats_of(142).unwrap().total_received, 30);
+
+            set_caller(accounts.bob);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(get_balance(cold), before + 80);
+            assert_eq!(get_balance(accounts.bob), bob);
+
+            // setting it back to the bound account resets it.
+            set_caller(accounts.charlie);
+            assert!(bot.set_payout_address(cold).is_ok());
+            assert!(bot.set_payout_address(accounts.charlie).is_ok());
+            assert_eq!(bot.payout_address_of(42), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn dormant_balances_could_be_claimed() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let heir = accounts.frank;
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.charlie, 20);
+            assert!(bot.deposit().is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.set_beneficiary(heir, 0),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.set_beneficiary(heir, 10).is_ok());
+            assert_eq!(bot.beneficiary_of(42), Some((heir, 10)));
+
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            // tipping resets the inactivity period.
+            assert!(bot.tip(142, 40, None).is_ok());
+            let now = ink_env::block_number::<Environment>();
+            set_caller(accounts.bob);
+            assert_eq!(bot.claim_dormant(42), Err(Error::NotAllowed));
+            set_caller(heir);
+            assert_eq!(
+                bot.claim_dormant(42),
+                Err(Error::NotDormant { until: now + 10 })
+            );
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
+            // the tipEDG of the user is claimed with thier balance.
+            let before = get_balance(heir);
+            assert!(bot.claim_dormant(42).is_ok());
+            assert_eq!(get_balance(heir), before + 80);
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 0);
+            assert_eq!(bot.claim_dormant(42), Err(Error::ZeroAmount));
+
+            set_caller(accounts.charlie);
+            assert!(bot.remove_beneficiary().is_ok());
+            assert_eq!(bot.beneficiary_of(42), None);
         }
 
-        /// Query for the Telegram Id of some account.
-        /// if the account is not provided, will return the telegram id of the
-        /// caller.
-        #[ink(message)]
-        pub fn telegram_id_of(
-            &self,
-            account: Option<AccountId>,
-        ) -> Option<TelegramId> {
-            let address = account.unwrap_or_else(|| self.env().caller());
-            self.address_tg.get(&address).cloned()
+        #[ink::test]
+        fn dormant_claims_wait_for_withdrawals() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let heir = accounts.frank;
+            set_balance(accounts.charlie, 0);
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            assert!(bot.enable_withdrawal_delay(10 * BLOCK_TIME).is_ok());
+            // the beneficiary could not claim sooner than a withdrawal.
+            assert_eq!(
+                bot.set_beneficiary(heir, 9),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(bot.set_beneficiary(heir, 10).is_ok());
+            assert!(bot.request_withdrawal(30).is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
+            }
+            set_caller(heir);
+            assert_eq!(bot.claim_dormant(42), Err(Error::WithdrawalPending));
+
+            // unbinding refunds the queued withdrawal too.
+            assert!(admin(
+                &mut bot,
+                AdminAction::ForceUnbind(accounts.charlie)
+            )
+            .is_ok());
+            assert_eq!(get_balance(accounts.charlie), 100);
         }
 
-        /// Query The AccountId of the TelegramId.
-        #[ink(message)]
-        pub fn address_of(&self, tg_id: TelegramId) -> Option<AccountId> {
-            self.tg_address.get(&tg_id).cloned()
+        #[ink::test]
+        fn dust_could_be_swept() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            assert_eq!(bot.dust_dormancy(), 2 * YEAR);
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    AdminAction::Configure(Setting::DustDormancy(0))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::DustDormancy(100))
+            )
+            .is_ok());
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 2_000_000);
+            assert!(bot.bind(142).is_ok());
+            set_from_owner();
+            assert_eq!(
+                bot.announce_dust_sweep(42),
+                Err(Error::NotDormant { until: 100 })
+            );
+            for _ in 0..100 {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.announce_dust_sweep(142), Err(Error::NotDust));
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::SweepDust(42)),
+                Err(Error::NotFound {
+                    which: Entity::DustSweep
+                })
+            );
+            set_caller(accounts.django);
+            assert_eq!(bot.announce_dust_sweep(42), Err(Error::NotAllowed));
+            set_from_owner();
+            assert!(bot.announce_dust_sweep(42).is_ok());
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::SweepDust(42)),
+                Err(Error::NotDormant {
+                    until: 100 + DUST_GRACE_PERIOD
+                })
+            );
+            for _ in 0..DUST_GRACE_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            // a user with tips on the way could not be swept.
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.tip(42, 10, None).is_ok());
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::SweepDust(42)),
+                Err(Error::TipsPending)
+            );
+            set_caller(accounts.bob);
+            assert!(bot.revoke_tip(0).is_ok());
+            assert!(admin_call(&mut bot, AdminCall::SweepDust(42)).is_ok());
+            assert_eq!(bot.treasury(), 100);
+            assert_eq!(bot.address_of(42), None);
+            assert_eq!(bot.telegram_id_of(Some(accounts.charlie)), None);
+            assert_eq!(bot.address_of(142), Some(accounts.bob));
         }
 
-        /// Query The Balance of the TelegramId.
-        #[ink(message)]
-        pub fn balance_of(&self, tg_id: TelegramId) -> Balance {
-            if let Some(address) = self.address_of(tg_id) {
-                self.balances.get(&address).cloned().unwrap_or(0)
-            } else {
-                0
+        #[ink::test]
+        fn dust_sweeps_are_cancelled_by_activity() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::DustDormancy(100))
+            )
+            .is_ok());
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            for _ in 0..100 {
+                test::advance_block::<Environment>();
+            }
+            set_from_owner();
+            assert!(bot.announce_dust_sweep(42).is_ok());
+            test::advance_block::<Environment>();
+            set_caller(accounts.charlie);
+            assert!(bot.set_privacy(false, false).is_ok());
+            for _ in 0..DUST_GRACE_PERIOD {
+                test::advance_block::<Environment>();
             }
+            set_from_owner();
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::SweepDust(42)),
+                Err(Error::NotDormant { until: 101 + 100 })
+            );
+            assert_eq!(bot.address_of(42), Some(accounts.charlie));
         }
 
-        /// Bind the caller address to the provided TelegramId.
-        ///
-        /// Errors:
-        /// Returns `Error::AlreadyBounded` if the AccountId is already bounded
-        /// to a TelegramId.
-        #[ink(message, payable)]
-        pub fn bind(&mut self, tg_id: TelegramId) -> Result<(), Error> {
-            // if we already know this return an error, to prevent from
-            // account spoofing.
-            if self.tg_address.contains_key(&tg_id) {
-                return panic_on_err!(Err(Error::AlreadyBounded));
+        #[ink::test]
+        fn withdrawal_delay_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            // without a delay withdrawals are paid right away.
+            let before = get_balance(accounts.charlie);
+            assert!(bot.request_withdrawal(10).is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 10);
+            assert_eq!(bot.balance_of(42), 90);
+
+            assert!(bot.enable_withdrawal_delay(60).is_ok());
+            assert!(bot.request_withdrawal(30).is_ok());
+            let at = ink_env::block_timestamp::<Environment>() + 60;
+            assert_eq!(bot.pending_withdrawal_of(42), Some((30, at)));
+            assert_eq!(bot.balance_of(42), 60);
+            assert_eq!(
+                bot.request_withdrawal(10),
+                Err(Error::WithdrawalPending)
+            );
+            assert_eq!(
+                bot.execute_withdrawal(),
+                Err(Error::WithdrawalTimelocked { at })
+            );
+            assert_eq!(bot.unbind(), Err(Error::WithdrawalPending));
+            assert!(bot.cancel_withdrawal().is_ok());
+            assert_eq!(bot.balance_of(42), 90);
+            assert_eq!(
+                bot.execute_withdrawal(),
+                Err(Error::NotFound {
+                    which: Entity::Withdrawal
+                })
+            );
+            assert_eq!(bot.unbind(), Err(Error::WithdrawalDelayEnabled));
+
+            assert!(bot.request_withdrawal(30).is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
+            assert!(bot.execute_withdrawal().is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 40);
+            assert_eq!(bot.pending_withdrawal_of(42), None);
 
-            let caller = self.env().caller();
-            // add the new binding.
-            if let Some(old_caller) = self.tg_address.insert(tg_id, caller) {
-                // remove the old caller from the addresses.
-                let _ = self.address_tg.take(&old_caller);
+            // redeemed tipEDG is queued the same.
+            set_sender(accounts.charlie, 20);
+            assert!(bot.deposit().is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.redeem(20).is_ok());
+            let at = ink_env::block_timestamp::<Environment>() + 60;
+            assert_eq!(bot.pending_withdrawal_of(42), Some((20, at)));
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 0);
+            assert_eq!(get_balance(accounts.charlie), before + 40);
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
+            assert!(bot.execute_withdrawal().is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 60);
 
-            if let Some(old_tg_id) = self.address_tg.insert(caller, tg_id) {
-                // free the old tg_id.
-                //
-                // this ensures that we always have one address for one telegram
-                // id.
-                let _ = self.tg_address.take(&old_tg_id);
+            // disabling the delay waits for the current one.
+            assert!(bot.enable_withdrawal_delay(0).is_ok());
+            assert_eq!(bot.withdrawal_delay_of(42), 60);
+            assert!(bot.request_withdrawal(10).is_ok());
+            assert!(bot.cancel_withdrawal().is_ok());
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
-            // check if the user added some balance to thier account during the
-            // call.
-            let balance = self.env().transferred_balance();
-            if balance > 0 {
-                self.balances
-                    .entry(caller)
-                    .and_modify(|v| *v += balance)
-                    .or_insert(balance);
+            assert_eq!(bot.withdrawal_delay_of(42), 0);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 120);
+        }
+
+        #[ink::test]
+        fn self_lock_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert_eq!(bot.request_unlock(), Err(Error::NotAllowed));
+            assert!(bot.lock_my_account().is_ok());
+            assert!(bot.is_self_locked(42));
+            assert_eq!(bot.tip(142, 10, None), Err(Error::AccountSelfLocked));
+            assert_eq!(bot.burn(10), Err(Error::AccountSelfLocked));
+            assert_eq!(
+                bot.request_withdrawal(10),
+                Err(Error::AccountSelfLocked)
+            );
+            assert_eq!(
+                bot.set_payout_address(accounts.django),
+                Err(Error::AccountSelfLocked)
+            );
+            assert_eq!(bot.unbind(), Err(Error::AccountSelfLocked));
+            assert_eq!(bot.unlock_my_account(), Err(Error::UnlockNotRequested));
+            // nor could the tipEDG be taken out.
+            set_sender(accounts.charlie, 40);
+            assert!(bot.deposit().is_ok());
+            assert_eq!(bot.redeem(40), Err(Error::AccountSelfLocked));
+            assert_eq!(
+                bot.receipt_transfer(accounts.bob, 40, Vec::new()),
+                Err(PSP22Error::Custom(String::from("AccountSelfLocked")))
+            );
+            assert!(bot.receipt_approve(accounts.bob, 40).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.receipt_transfer_from(
+                    accounts.charlie,
+                    accounts.bob,
+                    40,
+                    Vec::new()
+                ),
+                Err(PSP22Error::Custom(String::from("AccountSelfLocked")))
+            );
+            set_caller(accounts.charlie);
+
+            // unlocking on its own takes the timelock.
+            assert!(bot.request_unlock().is_ok());
+            assert_eq!(
+                bot.unlock_my_account(),
+                Err(Error::UnlockTimelocked {
+                    at: SELF_LOCK_DELAY
+                })
+            );
+            for _ in 0..SELF_LOCK_DELAY {
+                test::advance_block::<Environment>();
             }
-            Ok(())
+            assert!(bot.unlock_my_account().is_ok());
+            assert!(bot.tip(142, 10, None).is_ok());
+
+            // or an owner could co-sign it right away.
+            assert!(bot.lock_my_account().is_ok());
+            assert_eq!(
+                admin_call(&mut bot, AdminCall::CosignUnlock(42)),
+                Err(Error::UnlockNotRequested)
+            );
+            set_caller(accounts.charlie);
+            assert!(bot.request_unlock().is_ok());
+            assert!(admin_call(&mut bot, AdminCall::CosignUnlock(42)).is_ok());
+            assert!(!bot.is_self_locked(42));
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 10, None).is_ok());
         }
 
-        /// Unbind the caller address from thier telegram account.
-        /// and _optionally_ transfer any balance if they have any.
-        ///
-        /// Errors:
-        /// Returns `Error::NotFound` if the caller's `AccountId` is not bounded
-        /// before.
-        #[ink(message)]
-        pub fn unbind(&mut self) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self.unbind_account(caller)
+        #[ink::test]
+        fn social_recovery_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let new_key = accounts.frank;
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_from_owner();
+            assert_eq!(
+                bot.recover_binding(42, new_key),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.set_guardians(vec![accounts.bob, accounts.eve], 3),
+                Err(Error::InvalidThreshold)
+            );
+            assert!(bot
+                .set_guardians(vec![accounts.bob, accounts.eve], 2)
+                .is_ok());
+
+            set_caller(accounts.django);
+            assert_eq!(
+                bot.recover_binding(42, new_key),
+                Err(Error::NotAllowed)
+            );
+            set_caller(accounts.bob);
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            assert_eq!(
+                bot.recover_binding(42, new_key),
+                Err(Error::AlreadyApproved)
+            );
+            set_caller(accounts.eve);
+            assert_eq!(
+                bot.recover_binding(42, accounts.eve),
+                Err(Error::RecoveryPending)
+            );
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            // the owner has to approve it too.
+            assert_eq!(
+                bot.execute_recovery(42),
+                Err(Error::RecoveryNotApproved)
+            );
+            set_from_owner();
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            assert_eq!(
+                bot.execute_recovery(42),
+                Err(Error::RecoveryTimelocked { at: RECOVERY_DELAY })
+            );
+
+            // the user could still cancel it with thier old key.
+            set_caller(accounts.charlie);
+            assert!(bot.cancel_recovery().is_ok());
+            assert_eq!(bot.recovery_of(42), None);
+            assert_eq!(
+                bot.execute_recovery(42),
+                Err(Error::NotFound {
+                    which: Entity::Recovery
+                })
+            );
+
+            set_caller(accounts.bob);
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            set_caller(accounts.eve);
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            set_from_owner();
+            assert!(bot.recover_binding(42, new_key).is_ok());
+            for _ in 0..RECOVERY_DELAY {
+                test::advance_block::<Environment>();
+            }
+            set_caller(accounts.django);
+            assert!(bot.execute_recovery(42).is_ok());
+            assert_eq!(bot.address_of(42), Some(new_key));
+            assert_eq!(bot.telegram_id_of(Some(accounts.charlie)), None);
+            assert_eq!(bot.balance_of(42), 100);
+            assert_eq!(bot.recovery_of(42), None);
+            let before = get_balance(new_key);
+            set_caller(new_key);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(get_balance(new_key), before + 100);
         }
 
-        /// Similar to unbind, but only the owner can call this function.
-        ///
-        /// Errors:
-        /// * Returns `Error::NotAllowed` if the caller is not the owner of the
-        ///   contract.
-        ///
-        /// * Returns `Error::NotFound` if the caller's `AccountId` is not
-        ///   bounded before.
-        #[ink(message)]
-        pub fn force_unbind(
-            &mut self,
-            account: AccountId,
-        ) -> Result<(), Error> {
-            self.ensure_owner()?;
-            self.unbind_account(account)
+        #[ink::test]
+        fn link_address_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let secret = SecretKey::from_slice(&[2; 32]).unwrap();
+            let public_key =
+                PublicKey::from_secret_key(SECP256K1, &secret).serialize();
+            let mut secondary = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public_key, &mut secondary);
+            let secondary = AccountId::from(secondary);
+            set_balance(secondary, 1000);
+            let sign = |caller: AccountId| {
+                let mut message = [0; 32];
+                ink_env::hash_encoded::<Blake2x256, _>(
+                    &(
+                        test::callee::<Environment>(),
+                        b"link",
+                        caller,
+                        secondary,
+                    ),
+                    &mut message,
+                );
+                let (recovery_id, compact) = SECP256K1
+                    .sign_ecdsa_recoverable(
+                        &Message::from_slice(&message).unwrap(),
+                        &secret,
+                    )
+                    .serialize_compact();
+                let mut signature = [0; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = recovery_id.to_i32() as u8;
+                signature
+            };
+
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.link_address(accounts.bob, sign(accounts.charlie)),
+                Err(Error::AccountAlreadyBound)
+            );
+            assert_eq!(
+                bot.link_address(secondary, sign(accounts.bob)),
+                Err(Error::InvalidSignature)
+            );
+            assert!(bot
+                .link_address(secondary, sign(accounts.charlie))
+                .is_ok());
+            assert_eq!(bot.linked_to(secondary), Some(accounts.charlie));
+            assert_eq!(bot.linked_addresses_of(42), vec![secondary]);
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.link_address(secondary, sign(accounts.bob)),
+                Err(Error::AccountAlreadyBound)
+            );
+
+            // the secondary address deposits, and tips, against the shared
+            // balance.
+            set_sender(secondary, 50);
+            assert_eq!(bot.bind(43), Err(Error::AccountAlreadyBound));
+            assert!(bot.deposit().is_ok());
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 50);
+            assert_eq!(bot.telegram_id_of(None), Some(42));
+            set_caller(secondary);
+            assert!(bot.tip(142, 130, None).is_ok());
+            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(bot.receipt_balance_of(accounts.charlie), 20);
+            assert_eq!(bot.stats_of(42).unwrap().tips_sent, 1);
+
+            // withdrawals go to the bound account.
+            let before = get_balance(accounts.charlie);
+            let secondary_before = get_balance(secondary);
+            assert!(bot.request_withdrawal(20).is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 20);
+            assert_eq!(get_balance(secondary), secondary_before);
+
+            assert!(bot.unlink_address(secondary).is_ok());
+            assert_eq!(bot.linked_to(secondary), None);
+            assert_eq!(
+                bot.tip(142, 10, None),
+                Err(Error::NotFound {
+                    which: Entity::Sender
+                })
+            );
+            // unbinding drops the links.
+            set_caller(accounts.charlie);
+            assert!(bot
+                .link_address(secondary, sign(accounts.charlie))
+                .is_ok());
+            assert!(bot.unbind().is_ok());
+            assert_eq!(bot.linked_to(secondary), None);
         }
 
-        /// Tip a Telegram user using thier `TelegramId`.
-        ///
-        /// This function should not be called directly by the user.
-        /// Instead, the user should chat with the Telegram bot and the bot
-        /// would call these function for them.
-        /// Errors:
-        /// * Returns `Error::NotFound` if the `tg_id` is not bounded to any
-        ///   `AccountId`. also, if the caller is not bounded to any telegram
-        ///   account.
-        ///
-        /// * Returns `Error::InsufficientFunds` when the caller does not have
-        ///   enough balance.
-        #[ink(message)]
-        pub fn tip(
-            &mut self,
-            tg_id: TelegramId,
-            amount: Balance,
-        ) -> Result<(), Error> {
-            let caller = self.env().caller();
-            let inputs = self
-                .telegram_id_of(Some(caller))
-                .zip(self.address_of(tg_id));
+        #[ink::test]
+        fn existential_deposit_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.alice, 200);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(bot.existential_deposit(), 0);
+
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Transfer(
+                    TransferSetting::ExistentialDeposit(None)
+                ))
+            )
+            .is_ok());
+            assert_eq!(
+                bot.existential_deposit(),
+                ink_env::minimum_balance::<Environment>()
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Transfer(
+                    TransferSetting::ExistentialDeposit(Some(100))
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.existential_deposit(), 100);
+
+            let bob_balance = get_balance(accounts.bob);
+            set_caller(accounts.alice);
+            assert!(bot.tip(142, 50, None).is_ok());
+            // kept in the contract, rather than reaped.
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(bot.balance_of(142), 50);
+            assert_eq!(bot.stats_of(142).unwrap().total_received, 50);
+
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(get_balance(accounts.bob), bob_balance + 100);
+            assert_eq!(bot.balance_of(142), 50);
+        }
+
+        #[ink::test]
+        fn bind_rate_limit_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            let limit = BindRateLimit {
+                window: 10,
+                per_account: 1,
+                total: 2,
+            };
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    AdminAction::Configure(Setting::Binding(
+                        BindingSetting::RateLimit(Some(BindRateLimit {
+                            window: 0,
+                            ..limit
+                        }))
+                    ))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Binding(
+                    BindingSetting::RateLimit(Some(limit))
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.bind_rate_limit(), Some(limit));
 
-            match inputs {
-                Some((_, target)) => self.tip_account(caller, target, amount),
-                None => panic_on_err!(Err(Error::NotFound)),
-            }
-        }
+            set_sender(accounts.charlie, 0);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.bind(43), Err(Error::RateLimited { until: 10 }));
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_sender(accounts.eve, 0);
+            assert_eq!(bot.bind(242), Err(Error::RateLimited { until: 10 }));
 
-        /// Similar to tip, but only the owner can call this function.
-        ///
-        /// Called in behalf of the `from` TelegramId owner using the bot.
-        ///
-        /// Errors:
-        /// * Returns `Error::NotAllowed` if the caller is not the owner of the
-        ///   contract.
-        ///
-        /// * Returns `Error::NotFound` if the `from` or `to` is not bounded to
-        ///   any telegram account.
-        #[ink(message)]
-        pub fn tip_from(
-            &mut self,
-            from: TelegramId,
-            to: TelegramId,
-            amount: Balance,
-        ) -> Result<(), Error> {
-            self.ensure_owner()?;
-            let inputs = self.address_of(from).zip(self.address_of(to));
-            match inputs {
-                Some((from, to)) => self.tip_account(from, to, amount),
-                None => panic_on_err!(Err(Error::NotFound)),
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
+            assert!(bot.bind(242).is_ok());
+            set_sender(accounts.charlie, 0);
+            assert_eq!(bot.bind(43), Ok(()));
         }
 
-        fn unbind_account(&mut self, account: AccountId) -> Result<(), Error> {
-            if !self.address_tg.contains_key(&account) {
-                return panic_on_err!(Err(Error::NotFound));
-            }
-            let tg_id = self
-                .address_tg
-                .take(&account)
-                .expect("the caller address exists");
-            let _ = self
-                .tg_address
-                .take(&tg_id)
-                .expect("the caller tg id exists");
-            // if the caller have some balance, transfer it back to them.
-            if let Some(v) = self.balances.take(&account) {
-                return panic_on_err! {
-                    self.env().transfer(account, v).map_err(|_| Error::BelowSubsistenceThreshold)
-                };
-            }
-            Ok(())
-        }
+        #[ink::test]
+        fn bind_deposit_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Binding(
+                    BindingSetting::Deposit(10)
+                ))
+            )
+            .is_ok());
+            assert_eq!(bot.bind_deposit(), 10);
 
-        fn tip_account(
-            &mut self,
-            caller: AccountId,
-            target: AccountId,
-            amount: Balance,
-        ) -> Result<(), Error> {
-            let balance = self.balances.get_mut(&caller);
-            match balance {
-                Some(value) if *value >= amount => {
-                    *value -= amount;
-                    panic_on_err! {
-                        self.env()
-                            .transfer(target, amount)
-                            .map_err(|_| Error::BelowSubsistenceThreshold)
-                    }
-                    // TODO(@shekohex): emit some events here.
-                },
-                Some(_) | None => panic_on_err!(Err(Error::InsufficientFunds)),
-            }
+            set_sender(accounts.charlie, 5);
+            assert_eq!(
+                bot.bind(42),
+                Err(Error::InsufficientFunds {
+                    required: 10,
+                    available: 5
+                })
+            );
+            set_sender(accounts.charlie, 25);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.balance_of(42), 15);
+            // binding to another TelegramId does not take another deposit.
+            set_caller(accounts.charlie);
+            assert!(bot.bind(43).is_ok());
+            assert_eq!(bot.balance_of(43), 15);
+
+            let before = get_balance(accounts.charlie);
+            assert!(bot.unbind().is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 25);
         }
 
-        /// Ensures that the caller is the owner of the contract.
-        /// otherwise, returns `Error::NotAllowed`.
-        #[inline(always)]
-        fn ensure_owner(&self) -> Result<(), Error> {
-            panic_on_err! {
-                self.env().caller().eq(&self.owner).then(|| ()).ok_or(Error::NotAllowed)
+        #[ink::test]
+        fn binding_expiry_works() {
+            let mut bot = create_contract(1000);
+            let accounts = default_accounts();
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.expires_at(42), None);
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::Binding(BindingSetting::Ttl(
+                    10
+                )))
+            )
+            .is_ok());
+            assert_eq!(bot.expires_at(42), Some(10));
+
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            set_sender(accounts.bob, 0);
+            assert_eq!(bot.bind(42), Err(Error::TelegramIdTaken));
+            set_caller(accounts.charlie);
+            assert!(bot.renew().is_ok());
+            assert_eq!(bot.expires_at(42), Some(15));
+            for _ in 0..10 {
+                test::advance_block::<Environment>();
             }
-        }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink_env::{call, test};
-        use ink_lang as ink;
+            // the binding expired, so bob takes the TelegramId over.
+            set_sender(accounts.bob, 10);
+            assert!(bot.bind(42).is_ok());
+            assert_eq!(bot.address_of(42), Some(accounts.bob));
+            assert_eq!(bot.balance_of(42), 10);
+            assert_eq!(bot.telegram_id_of(Some(accounts.charlie)), None);
+            assert!(test::recorded_events().any(|event| {
+                matches!(
+                    <Event as scale::Decode>::decode(&mut &event.data[..]),
+                    Ok(Event::BindingExpired(BindingExpired {
+                        tg_id: 42,
+                        balance: 100,
+                        ..
+                    }))
+                )
+            }));
 
-        type Accounts = test::DefaultAccounts<Environment>;
+            // the stale account withdraws what it had left.
+            set_caller(accounts.charlie);
+            assert_eq!(bot.expired_balance_of(accounts.charlie), 100);
+            let before = get_balance(accounts.charlie);
+            assert!(bot.withdraw_expired().is_ok());
+            assert_eq!(get_balance(accounts.charlie), before + 100);
+            assert_eq!(
+                bot.withdraw_expired(),
+                Err(Error::NotFound {
+                    which: Entity::ExpiredBalance
+                })
+            );
+            assert!(bot.bind(43).is_ok());
+        }
 
         #[ink::test]
-        fn happy_path() {
-            set_from_owner();
-            let mut bot = Tipbot::new();
+        fn rebind_works() {
+            let mut bot = create_contract(1000);
             let accounts = default_accounts();
+            let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+            let public_key =
+                PublicKey::from_secret_key(SECP256K1, &secret).serialize();
+            let mut new_key = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public_key, &mut new_key);
+            let new_key = AccountId::from(new_key);
+            let sign = |caller: AccountId, new_account: AccountId| {
+                let mut message = [0; 32];
+                ink_env::hash_encoded::<Blake2x256, _>(
+                    &(test::callee::<Environment>(), caller, new_account),
+                    &mut message,
+                );
+                let (recovery_id, compact) = SECP256K1
+                    .sign_ecdsa_recoverable(
+                        &Message::from_slice(&message).unwrap(),
+                        &secret,
+                    )
+                    .serialize_compact();
+                let mut signature = [0; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = recovery_id.to_i32() as u8;
+                signature
+            };
 
-            // ensures everything is clean.
-            assert_eq!(bot.telegram_id_of(Some(accounts.bob)), None);
-            assert_eq!(bot.balance_of(42), 0);
-            // bind bob.
-            set_sender(accounts.bob, 6969);
+            set_sender(accounts.charlie, 100);
             assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 40, None).is_ok());
+            let stats = bot.stats_of(42);
+            let sent = bot.tips_sent_by(42, 0, 10);
+            assert_eq!(sent.len(), 1);
 
-            assert_eq!(bot.telegram_id_of(Some(accounts.bob)), Some(42));
-            assert_eq!(bot.address_of(42), Some(accounts.bob));
-            assert_eq!(bot.balance_of(42), 6969);
+            // signed for another caller.
+            assert_eq!(
+                bot.rebind_to(new_key, sign(accounts.bob, new_key)),
+                Err(Error::InvalidSignature)
+            );
+            // signed by another key.
+            assert_eq!(
+                bot.rebind_to(accounts.frank, sign(accounts.charlie, new_key)),
+                Err(Error::InvalidSignature)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.rebind_to(new_key, sign(accounts.bob, new_key)),
+                Ok(())
+            );
+            assert!(matches!(
+                last_event(),
+                Event::Rebound(Rebound { account, tg_id: 142, .. })
+                    if account == accounts.bob
+            ));
+            set_caller(accounts.charlie);
+            assert_eq!(
+                bot.rebind_to(new_key, sign(accounts.charlie, new_key)),
+                Err(Error::AccountAlreadyBound)
+            );
+            set_caller(new_key);
+            assert!(bot.unbind().is_ok());
+
+            set_caller(accounts.charlie);
+            assert!(bot
+                .rebind_to(new_key, sign(accounts.charlie, new_key))
+                .is_ok());
+            assert_eq!(bot.address_of(42), Some(new_key));
+            assert_eq!(bot.telegram_id_of(Some(accounts.charlie)), None);
+            assert_eq!(bot.balance_of(42), 60);
+            assert_eq!(bot.stats_of(42), stats);
+            assert_eq!(bot.tips_sent_by(42, 0, 10), sent);
+            // the old key is free to bind again.
+            assert!(bot.bind(43).is_ok());
+            assert!(bot.tips_sent_by(43, 0, 10).is_empty());
         }
 
         #[ink::test]
-        #[should_panic(expected = "AlreadyBounded")]
-        fn already_bounded() {
-            set_from_owner();
-            let mut bot = Tipbot::new();
+        fn rebind_moves_positions() {
+            let mut bot = create_contract(1000);
             let accounts = default_accounts();
-            // bind bob.
-            set_sender(accounts.bob, 100);
+            let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+            let (new_key, signature) = sign_rebind(&secret, accounts.charlie);
+            let seed = Hash::from([7; 32]);
+            let seed_hash = seed_hash_of(seed);
+
+            set_sender(accounts.charlie, 1000);
             assert!(bot.bind(42).is_ok());
-            set_sender(accounts.eve, 1);
-            // 42 is already bounded.
-            assert!(bot.bind(42).is_err());
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::ReversalWindow(10))
+            )
+            .is_ok());
+            assert_eq!(bot.create_raffle(10, 5, seed_hash), Ok(0));
+            assert_eq!(bot.create_poll(2, 5), Ok(0));
+            set_caller(accounts.bob);
+            assert!(bot.register_group(8).is_ok());
+            assert!(bot.add_group_moderator(8, accounts.charlie).is_ok());
+
+            set_caller(accounts.charlie);
+            assert!(bot.tip(142, 10, None).is_ok());
+            assert!(bot.buy_ticket(0, 2).is_ok());
+            assert!(bot.stake_on(0, 0, 30).is_ok());
+            assert!(bot.register_group(7).is_ok());
+            assert!(bot.rebind_to(new_key, signature).is_ok());
+
+            // the roles in groups move to the new key.
+            assert_eq!(bot.group(7).unwrap().registrant, new_key);
+            assert_eq!(bot.group(8).unwrap().moderators, vec![new_key]);
+            // and so do the stakes, and the tickets.
+            assert_eq!(bot.raffle_spent(0, accounts.charlie), 0);
+            assert_eq!(bot.raffle_spent(0, new_key), 20);
+            assert_eq!(bot.poll_stake_of(0, accounts.charlie, 0), 0);
+            assert_eq!(bot.poll_stake_of(0, new_key, 0), 30);
+            // the held tip could only be revoked by the new key.
+            assert_eq!(bot.revoke_tip(0), Err(Error::NotAllowed));
+            set_caller(new_key);
+            assert!(bot.revoke_tip(0).is_ok());
+            assert_eq!(bot.balance_of(42), 950);
+
+            for _ in 0..5 {
+                test::advance_block::<Environment>();
+            }
+            assert_eq!(bot.draw(0, seed), Ok(Some(new_key)));
+            set_from_owner();
+            assert!(bot.resolve(0, 0).is_ok());
+            set_caller(new_key);
+            assert_eq!(bot.claim_poll(0), Ok(30));
+            assert_eq!(bot.balance_of(42), 1000);
         }
 
         #[ink::test]
-        fn bind_twice() {
-            set_from_owner();
-            let mut bot = Tipbot::new();
+        fn rebind_moves_jobs() {
+            let mut bot = create_contract(1000);
             let accounts = default_accounts();
-            // bind bob.
-            set_sender(accounts.bob, 100);
+            let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+            let (new_key, signature) = sign_rebind(&secret, accounts.charlie);
+
+            endow(accounts.charlie, 100 + 2 * KEEPER_INCENTIVE);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.address_of(42), Some(accounts.bob));
-            // rebind to 4242.
-            assert!(bot.bind(4242).is_ok());
-            assert_eq!(bot.address_of(4242), Some(accounts.bob));
-            // now 42 is gone.
-            assert_eq!(bot.address_of(42), None);
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let now = ink_env::block_timestamp::<Environment>();
+            set_caller(accounts.charlie);
+            let first = bot.schedule_tip(142, 10, now + 1).unwrap();
+            let second = bot.schedule_tip(142, 20, now + 1).unwrap();
+            assert!(bot.rebind_to(new_key, signature).is_ok());
+
+            // the jobs follow the binding, and only the new key could cancel
+            // them.
+            let ids = |jobs: Vec<(JobId, Job)>| {
+                jobs.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(bot.jobs_of(42)), vec![first, second]);
+            assert_eq!(bot.job(first).unwrap().owner, new_key);
+            assert_eq!(
+                bot.cancel_job(second),
+                Err(Error::NotFound {
+                    which: Entity::Account
+                })
+            );
+            set_caller(new_key);
+            assert!(bot.cancel_job(second).is_ok());
+            assert_eq!(bot.balance_of(42), 100 + KEEPER_INCENTIVE);
+
+            test::advance_block::<Environment>();
+            set_caller(accounts.bob);
+            assert_eq!(bot.execute_due(10), Ok(1));
+            assert!(matches!(
+                last_event(),
+                Event::JobExecuted(JobExecuted { error: None, .. })
+            ));
+            assert_eq!(bot.balance_of(42), 90 + KEEPER_INCENTIVE);
+            assert_eq!(bot.balance_of(142), KEEPER_INCENTIVE);
         }
 
         #[ink::test]
-        fn bind_twice_balance() {
-            set_from_owner();
-            let mut bot = Tipbot::new();
+        fn rebind_moves_inbox_policy() {
+            let mut bot = create_contract(1000);
             let accounts = default_accounts();
+            let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+            let (new_key, signature) = sign_rebind(&secret, accounts.charlie);
 
-            set_sender(accounts.bob, 100);
+            set_sender(accounts.charlie, 0);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            set_caller(accounts.charlie);
+            assert!(bot.block_sender(142).is_ok());
+            assert!(bot.rebind_to(new_key, signature).is_ok());
 
-            set_sender(accounts.bob, 200);
-            assert!(bot.bind(4242).is_ok());
-            assert_eq!(bot.balance_of(4242), 100 + 200);
+            assert_eq!(bot.inbox_policy_of(42).unwrap().blocked, vec![142]);
+            set_caller(accounts.bob);
+            assert_eq!(
+                bot.tip(42, 10, None),
+                Err(Error::RecipientBlockedSender)
+            );
+            // the new key manages it.
+            set_caller(new_key);
+            assert!(bot.unblock_sender(142).is_ok());
+            set_caller(accounts.bob);
+            assert!(bot.tip(42, 10, None).is_ok());
         }
 
         #[ink::test]
-        fn unbind_works() {
+        fn unbind_cancels_jobs() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
-            set_sender(accounts.bob, 100);
-
+            set_balance(accounts.charlie, 0);
+            endow(accounts.charlie, 100 + KEEPER_INCENTIVE);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            let now = ink_env::block_timestamp::<Environment>();
+            set_caller(accounts.charlie);
+            let job_id = bot.schedule_tip(142, 10, now + 1).unwrap();
 
+            // the incentive is refunded with the balance.
             assert!(bot.unbind().is_ok());
-            assert_eq!(bot.balance_of(42), 0);
+            assert_eq!(get_balance(accounts.charlie), 100 + KEEPER_INCENTIVE);
+            assert_eq!(bot.job(job_id), None);
+            assert!(bot.pending_jobs(0, 10).is_empty());
+            assert!(test::recorded_events().any(|event| {
+                matches!(
+                    <Event as scale::Decode>::decode(&mut &event.data[..]),
+                    Ok(Event::JobCancelled(JobCancelled { job_id: 0 }))
+                )
+            }));
+            test::advance_block::<Environment>();
+            set_caller(accounts.bob);
+            assert_eq!(bot.execute_due(10), Ok(0));
         }
 
         #[ink::test]
-        #[should_panic(expected = "NotFound")]
-        fn unbind_not_found() {
+        fn positions_are_capped() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
-            set_sender(accounts.bob, 100);
 
-            assert!(bot.unbind().is_err());
-            assert_eq!(bot.balance_of(42), 0);
+            set_sender(accounts.charlie, 100);
+            assert!(bot.bind(42).is_ok());
+            set_sender(accounts.bob, 100);
+            assert!(bot.bind(142).is_ok());
+            for group_id in 0..MAX_POSITIONS as GroupId {
+                assert!(bot.register_group(group_id).is_ok());
+                assert!(bot
+                    .add_group_moderator(group_id, accounts.charlie)
+                    .is_ok());
+            }
+            assert_eq!(
+                bot.register_group(MAX_POSITIONS as GroupId),
+                Err(Error::TooManyPositions)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(bot.register_group(100), Err(Error::TooManyPositions));
+            // leaving a group frees its position.
+            assert!(bot.remove_group_moderator(0, accounts.charlie).is_ok());
+            assert!(bot.register_group(100).is_ok());
         }
 
         #[ink::test]
-        fn force_unbind_works() {
+        fn streaks_works() {
             let mut bot = create_contract(1000);
+            set_param_quorum(&mut bot, 0);
             let accounts = default_accounts();
-            set_sender(accounts.bob, 100);
 
+            set_sender(accounts.charlie, 1000);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
+            assert_eq!(
+                admin(
+                    &mut bot,
+                    AdminAction::Configure(Setting::StreakRebate(Some(
+                        StreakRebate {
+                            min_streak: 2,
+                            rebate_bps: 10_001,
+                        }
+                    )))
+                ),
+                Err(Error::InvalidParamValue)
+            );
+            assert!(admin(
+                &mut bot,
+                AdminAction::Configure(Setting::StreakRebate(Some(
+                    StreakRebate {
+                        min_streak: 2,
+                        rebate_bps: 5_000,
+                    }
+                )))
+            )
+            .is_ok());
+            set_caller(accounts.charlie);
+            let id = bot.propose_param_change(Param::FeeBps, 1_000).unwrap();
+            assert!(bot.vote(id, true).is_ok());
+            for _ in 0..VOTING_PERIOD {
+                test::advance_block::<Environment>();
+            }
+            assert!(bot.enact(id).is_ok());
+            assert_eq!(bot.streak_of(42), 0);
 
-            set_from_owner();
-            assert!(bot.force_unbind(accounts.bob).is_ok());
-            assert_eq!(bot.balance_of(42), 0);
+            // the last block of a day, then the first block of the next one.
+            advance_to(DAY - 6);
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(bot.streak_of(42), 1);
+            assert_eq!(bot.treasury(), 20);
+            advance_to(DAY);
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(bot.streak_of(42), 2);
+            // half of the 10 fee is given back.
+            assert_eq!(bot.treasury(), 25);
+            assert_eq!(bot.balance_of(42), 1000 - 300 + 5);
+
+            // the streak holds through the next day, then breaks.
+            advance_to(2 * DAY);
+            assert_eq!(bot.streak_of(42), 2);
+            advance_to(3 * DAY);
+            assert_eq!(bot.streak_of(42), 0);
+            assert!(bot.tip(142, 100, None).is_ok());
+            assert_eq!(bot.streak_of(42), 1);
+            assert_eq!(bot.treasury(), 35);
         }
 
         #[ink::test]
-        #[should_panic(expected = "NotAllowed")]
-        fn force_unbind_noowner() {
+        fn reentrancy_guard_works() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
-            set_sender(accounts.bob, 100);
-
+            set_sender(accounts.alice, 100);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
 
-            set_from_noowner();
-            assert!(bot.force_unbind(accounts.bob).is_err());
+            // as if a called contract calls back into the contract.
+            set_caller(accounts.alice);
+            ink_env::set_contract_storage_return_size(&REENTRANCY_LOCK, &());
+            assert_eq!(bot.tip(142, 50, None), Err(Error::Reentered));
+            assert_eq!(bot.unbind(), Err(Error::Reentered));
+            assert_eq!(
+                bot.receipt_transfer(accounts.bob, 10, Vec::new()),
+                Err(PSP22Error::Custom(String::from("Reentered")))
+            );
             assert_eq!(bot.balance_of(42), 100);
+
+            ink_env::clear_contract_storage(&REENTRANCY_LOCK);
+            assert!(bot.tip(142, 50, None).is_ok());
+            assert_eq!(call_out(|| bot.unbind()), Err(Error::Reentered));
+            assert!(bot.unbind().is_ok());
+        }
+
+        #[test]
+        fn random_operations_keep_ledger_invariants() {
+            let tg_ids: [TelegramId; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+            for seed in 1..=16 {
+                // every sequence starts over with an empty storage.
+                test::run_test::<Environment, _>(|accounts| {
+                    let users = [
+                        accounts.alice,
+                        accounts.bob,
+                        accounts.charlie,
+                        accounts.django,
+                        accounts.eve,
+                        accounts.frank,
+                    ];
+                    let mut rng = Rng(seed);
+                    let mut bot = create_contract(1000);
+                    for _ in 0..64 {
+                        let user =
+                            users[rng.below(users.len() as u64) as usize];
+                        let tg_id =
+                            tg_ids[rng.below(tg_ids.len() as u64) as usize];
+                        let amount = rng.below(200) as Balance;
+                        let _ = match rng.below(8) {
+                            0 | 1 => {
+                                endow(user, amount);
+                                bot.bind(tg_id)
+                            },
+                            2 => {
+                                endow(user, amount);
+                                bot.deposit()
+                            },
+                            3..=5 => {
+                                set_caller(user);
+                                bot.tip(tg_id, amount, None)
+                            },
+                            6 => {
+                                set_caller(user);
+                                bot.request_withdrawal(amount)
+                            },
+                            _ => {
+                                set_caller(user);
+                                bot.unbind()
+                            },
+                        };
+                        test::advance_block::<Environment>();
+                        check_ledger(&bot, &users, &tg_ids);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
         }
 
+        /// The off-chain environment does not meter gas, so we pin the
+        /// storage accesses of a `tip` instead, which dominate its cost.
+        ///
+        /// Before packing the per-user data into a `UserRecord`, a `tip` did 3
+        /// reads and 1 write (`address_tg`, `tg_address` and `balances`) while
+        /// not tracking any statistics; now it is one read-modify-write per
+        /// party, and for the epoch statistics of each party, plus the
+        /// reverse index lookup and the inbox policy of the recipient and the
+        /// writes of the tip receipt and the sent and received histories.
+        /// Being the first tip of the sender, it also mints thier first
+        /// achievement token. The totals of the era and the epoch are cached
+        /// in one cell, and only written every `STATS_FLUSH_INTERVAL` tips,
+        /// see `stats_flush_works`.
         #[ink::test]
-        fn tipping_works() {
+        fn tipping_storage_access() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
 
-            // bind alice account.
             set_sender(accounts.alice, 100);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
-
-            // now bind bob account.
             set_sender(accounts.bob, 0);
             assert!(bot.bind(142).is_ok());
-            assert_eq!(bot.balance_of(142), 0);
-            set_balance(accounts.bob, 1); // set that they are have only 1 token.
 
             set_caller(accounts.alice);
-            assert!(bot.tip(142, 50).is_ok()); // tip bob with 50.
-            assert_eq!(bot.balance_of(42), 50); // now we have 50.
-            assert_eq!(bot.balance_of(142), 0); // bob is still zero.
-            assert_eq!(get_balance(accounts.bob), 51); // they have balance now.
+            let (reads, writes) = storage_rw();
+            assert!(bot.tip(142, 50, None).is_ok());
+            // including the reentrancy lock.
+            assert_eq!(storage_rw(), (reads + 8, writes + 9));
+            // on chain the root of the storage, 74 cells, is loaded before
+            // the message and stored after it too, and the tips after the
+            // first one mint no achievement token.
+            assert_eq!(
+                dispatched_rw(&mut bot, |bot| bot.tip(142, 1, None)),
+                (72 + 8, 72 + 8)
+            );
         }
 
         #[ink::test]
-        #[should_panic(expected = "NotFound")]
-        fn tipping_not_found() {
+        fn stats_flush_works() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
-
-            // bind alice account.
-            set_sender(accounts.alice, 100);
+            set_sender(accounts.alice, 1000);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
+            set_sender(accounts.bob, 0);
+            assert!(bot.bind(142).is_ok());
 
             set_caller(accounts.alice);
-            assert!(bot.tip(142, 50).is_err()); // tip `142` with 50.
-            assert_eq!(bot.balance_of(42), 100); // still 100.
+            for _ in 0..STATS_FLUSH_INTERVAL {
+                assert!(bot.tip(142, 1, None).is_ok());
+            }
+            // the queries see the totals before they are written.
+            assert_eq!(bot.fee_history.get(0), None);
+            assert_eq!(bot.epoch_stats.get(0), None);
+            assert_eq!(bot.treasury_report(1)[0].volume, 16);
+            assert_eq!(bot.epoch_stats(0).tips, 16);
+
+            // the tips after the first one mint no achievement token, and
+            // the next one writes the totals of the last 16.
+            let (reads, writes) = storage_rw();
+            assert!(bot.tip(142, 1, None).is_ok());
+            assert_eq!(storage_rw(), (reads + 8, writes + 10));
+            assert_eq!(bot.fee_history.get(0).unwrap().volume, 16);
+            assert_eq!(bot.epoch_stats.get(0).unwrap().tips, 16);
+
+            assert!(bot.flush_stats().is_ok());
+            assert_eq!(bot.fee_history.get(0).unwrap().volume, 17);
+            assert_eq!(bot.epoch_stats.get(0), Some(bot.epoch_stats(0)));
         }
 
+        /// The sender of a `tip` is verified and charged from the same read
+        /// of thier `UserRecord`, so a tip to an unbound TelegramId costs only
+        /// that read and the lookup of the recipient.
         #[ink::test]
-        #[should_panic(expected = "InsufficientFunds")]
-        fn tipping_no_balance() {
+        fn tip_sender_lookup_storage_access() {
             let mut bot = create_contract(1000);
             let accounts = default_accounts();
 
-            // bind alice account.
             set_sender(accounts.alice, 100);
             assert!(bot.bind(42).is_ok());
-            assert_eq!(bot.balance_of(42), 100);
-            // now bind bob account.
-            set_sender(accounts.bob, 0);
-            assert!(bot.bind(142).is_ok());
 
             set_caller(accounts.alice);
-            assert!(bot.tip(142, 150).is_err()); // tip bob with 150.
-            assert_eq!(bot.balance_of(42), 100); // still 100.
+            let (reads, writes) = storage_rw();
+            assert_eq!(
+                bot.tip(142, 50, None),
+                Err(Error::NotFound {
+                    which: Entity::Recipient
+                })
+            );
+            // including the reentrancy lock.
+            assert_eq!(storage_rw(), (reads + 3, writes));
+        }
+
+        /// The stored structs encode thier balances and counters compactly,
+        /// pin the layout so an upgrade could not change it by accident.
+        #[ink::test]
+        fn compact_encoding_is_stable() {
+            use scale::{Decode, Encode};
+
+            let stats = UserStats {
+                tips_sent: 1,
+                tips_received: 2,
+                total_sent: 3 * EDG,
+                total_received: 4,
+            };
+            let encoded = stats.encode();
+            assert_eq!(encoded[..2], [1 << 2, 2 << 2]);
+            assert_eq!(encoded.len(), 2 + 9 + 1);
+            assert_eq!(UserStats::decode(&mut &encoded[..]), Ok(stats.clone()));
+
+            let audit = AuditCounters::default();
+            assert_eq!(audit.encode(), vec![0; 7]);
+
+            let fees = EraFees {
+                era: 7,
+                volume: 100,
+                fees: 1,
+            };
+            let encoded = fees.encode();
+            assert_eq!(
+                encoded,
+                [&7u32.to_le_bytes()[..], &[0x91, 0x01, 0x04]].concat()
+            );
+            assert_eq!(EraFees::decode(&mut &encoded[..]), Ok(fees));
+
+            let record = UserRecord {
+                tg_id: 42,
+                balance: 1000 * EDG,
+                stats,
+                sent_seq: 3,
+                relay_nonce: 70,
+                audit: AuditCounters {
+                    deposited: 1000 * EDG,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let encoded = record.encode();
+            assert_eq!(encoded.len(), 147);
+            assert_eq!(UserRecord::decode(&mut &encoded[..]), Ok(record));
+        }
+
+        /// Check that the bindings are one to one both ways, that every
+        /// balance adds up to its audit totals, and that the contract holds
+        /// everything it owes.
+        fn check_ledger(
+            bot: &Tipbot,
+            users: &[AccountId],
+            tg_ids: &[TelegramId],
+        ) {
+            for tg_id in tg_ids {
+                if let Some(account) = bot.address_of(*tg_id) {
+                    assert_eq!(bot.telegram_id_of(Some(account)), Some(*tg_id));
+                }
+            }
+            let mut owed = bot.treasury() + bot.receipt_total_supply();
+            for account in users {
+                if let Some(record) = bot.users.get(account) {
+                    assert_eq!(bot.address_of(record.tg_id), Some(*account));
+                    assert!(record.pending_slash <= record.balance);
+                    let report = AuditReport {
+                        totals: record.audit.clone(),
+                        balance: record.balance,
+                    };
+                    assert!(report.adds_up(), "{:?}", report);
+                    let pending =
+                        record.pending_withdrawal.map_or(0, |(a, _)| a);
+                    owed += record.balance + record.bind_deposit + pending;
+                }
+                owed += bot.expired_balance_of(*account);
+            }
+            assert!(owed <= get_balance(contract_id()));
+        }
+
+        /// Call the next message from `sender` with `value`, which, unlike
+        /// on chain, has to be added to the balance of the contract by hand.
+        fn endow(sender: AccountId, value: Balance) {
+            set_sender(sender, value);
+            set_balance(contract_id(), get_balance(contract_id()) + value);
+        }
+
+        /// A xorshift generator for the randomized tests, so they are
+        /// reproducible from thier seed.
+        struct Rng(u64);
+
+        impl Rng {
+            /// The next random number below `n`.
+            fn below(&mut self, n: u64) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0 % n
+            }
+        }
+
+        /// Report the storage reads and writes of the hot messages with 100,
+        /// 10k and 100k bound users, run with
+        /// `cargo test --features bench storage_benchmark -- --nocapture`.
+        ///
+        /// The off-chain environment does not meter gas, while storage
+        /// accesses are most of the weight of these messages.
+        #[cfg(feature = "bench")]
+        #[test]
+        fn storage_benchmark() {
+            let user = |i: u32| {
+                let mut id = [0xBE; 32];
+                id[..4].copy_from_slice(&i.to_le_bytes());
+                AccountId::from(id)
+            };
+            println!(
+                "\n{:<22} {:>8} {:>6} {:>6}",
+                "message", "users", "reads", "writes"
+            );
+            for users in [100, 10_000, 100_000] {
+                test::run_test::<Environment, _>(|accounts| {
+                    let mut bot = create_contract(1000);
+                    for i in 0..users - 1 {
+                        set_sender(user(i), 100);
+                        assert!(bot.bind(1000 + TelegramId::from(i)).is_ok());
+                    }
+                    set_sender(accounts.bob, 100);
+                    measure("bind", users, &mut bot, |bot| bot.bind(1));
+
+                    set_caller(user(0));
+                    measure("tip", users, &mut bot, |bot| {
+                        bot.tip(1001, 1, None)
+                    });
+                    set_from_owner();
+                    measure("tip_from", users, &mut bot, |bot| {
+                        bot.tip_from(1000, 1001, 1, None, None)
+                    });
+
+                    set_caller(user(0));
+                    assert!(bot.register_group(-1).is_ok());
+                    set_sender(user(0), 100);
+                    assert!(bot.donate_to_group(-1).is_ok());
+                    let recipients = (1001..1011).collect();
+                    measure("rain_in_group(10)", users, &mut bot, |bot| {
+                        bot.rain_in_group(-1, recipients, 1)
+                    });
+                    let unbound = (1..11).map(user).collect();
+                    measure("force_unbind_many(10)", users, &mut bot, |bot| {
+                        admin_call(bot, AdminCall::ForceUnbindMany(unbound))
+                    });
+                    Ok(())
+                })
+                .unwrap();
+            }
+        }
+
+        /// Print the storage accesses made by `call`, a message that has to
+        /// succeed, with `users` bound users, loading and storing the root of
+        /// the storage included, see `dispatched_rw`.
+        #[cfg(feature = "bench")]
+        fn measure<T>(
+            message: &str,
+            users: u32,
+            bot: &mut Tipbot,
+            call: impl FnOnce(&mut Tipbot) -> Result<T, Error>,
+        ) {
+            let (reads, writes) = dispatched_rw(bot, call);
+            println!("{:<22} {:>8} {:>6} {:>6}", message, users, reads, writes);
         }
 
         fn create_contract(initial_balance: Balance) -> Tipbot {
@@ -490,18 +22397,39 @@ mod tipbot {
             Tipbot::new()
         }
 
-        fn set_caller(account: AccountId) { set_sender(account, 100_000); }
+        /// Set the `param_quorum` as the only owner.
+        fn set_param_quorum(bot: &mut Tipbot, quorum: Balance) {
+            let setting = AdminSetting::ParamQuorum(quorum);
+            assert!(admin(
+                bot,
+                AdminAction::Configure(Setting::Admin(setting))
+            )
+            .is_ok());
+        }
+
+        /// Propose, and execute, a privileged call as the only owner.
+        fn admin_call(
+            bot: &mut Tipbot,
+            call: AdminCall,
+        ) -> Result<ProposalId, Error> {
+            let setting = AdminSetting::Call(call);
+            admin(bot, AdminAction::Configure(Setting::Admin(setting)))
+        }
+
+        /// Propose, and execute, an admin action as the only owner.
+        fn admin(
+            bot: &mut Tipbot,
+            action: AdminAction,
+        ) -> Result<ProposalId, Error> {
+            set_from_owner();
+            bot.propose_admin_call(action)
+        }
+
+        fn set_caller(account: AccountId) { set_sender(account, 0); }
 
         fn set_sender(sender: AccountId, endowment: Balance) {
-            let mut data = test::CallData::new(call::Selector::new([0x00; 4]));
-            data.push_arg(&sender);
-            test::push_execution_context::<Environment>(
-                sender,
-                contract_id(),
-                1000000,   // gas
-                endowment, // endowment
-                data,
-            );
+            test::set_caller::<Environment>(sender);
+            test::set_value_transferred::<Environment>(endowment);
         }
 
         fn set_from_owner() {
@@ -514,27 +22442,93 @@ mod tipbot {
             set_caller(accounts.django);
         }
 
-        fn default_accounts() -> Accounts {
-            test::default_accounts()
-                .expect("Test environment is expected to be initialized.")
+        fn default_accounts() -> Accounts { test::default_accounts() }
+
+        /// Advance the blocks until the block timestamp reaches `timestamp`.
+        fn advance_to(timestamp: Timestamp) {
+            while ink_env::block_timestamp::<Environment>() < timestamp {
+                test::advance_block::<Environment>();
+            }
+        }
+
+        fn last_event() -> Event { nth_last_event(0) }
+
+        fn nth_last_event(n: usize) -> Event {
+            let events = test::recorded_events().collect::<Vec<_>>();
+            let event = events.iter().rev().nth(n).expect("no events");
+            <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("invalid event")
         }
 
-        fn contract_id() -> AccountId {
-            test::get_current_contract_account_id::<ink_env::DefaultEnvironment>(
-            )
-            .expect("Cannot get contract id")
+        fn contract_id() -> AccountId { test::callee::<Environment>() }
+
+        /// Sign the rebinding of `caller` to the ECDSA key of `secret`, as
+        /// `rebind_to` checks it, returning the new account and the
+        /// signature.
+        fn sign_rebind(
+            secret: &SecretKey,
+            caller: AccountId,
+        ) -> (AccountId, [u8; 65]) {
+            let public_key =
+                PublicKey::from_secret_key(SECP256K1, secret).serialize();
+            let mut new_key = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public_key, &mut new_key);
+            let new_key = AccountId::from(new_key);
+            let mut message = [0; 32];
+            ink_env::hash_encoded::<Blake2x256, _>(
+                &(contract_id(), caller, new_key),
+                &mut message,
+            );
+            let (recovery_id, compact) = SECP256K1
+                .sign_ecdsa_recoverable(
+                    &Message::from_slice(&message).unwrap(),
+                    secret,
+                )
+                .serialize_compact();
+            let mut signature = [0; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            (new_key, signature)
+        }
+
+        /// The commitment to the `seed` of a raffle, or of a challenge.
+        fn seed_hash_of(seed: Hash) -> Hash {
+            let mut seed_hash = [0; 32];
+            ink_env::hash_encoded::<Blake2x256, _>(&seed, &mut seed_hash);
+            Hash::from(seed_hash)
+        }
+
+        fn storage_rw() -> (usize, usize) {
+            test::get_contract_storage_rw::<Environment>(&contract_id())
+        }
+
+        /// Call `message` as a mutating message is dispatched on chain, the
+        /// root of the storage loaded before it and stored back after, which
+        /// calling it directly skips, and return its storage reads and writes.
+        fn dispatched_rw<T>(
+            bot: &mut Tipbot,
+            message: impl FnOnce(&mut Tipbot) -> Result<T, Error>,
+        ) -> (usize, usize) {
+            use ink_storage::traits::{pull_spread_root, push_spread_root};
+
+            let root = ink_primitives::Key::from([0x00; 32]);
+            push_spread_root(&*bot, &root);
+            let (reads, writes) = storage_rw();
+            let mut contract = pull_spread_root::<Tipbot>(&root);
+            assert!(message(&mut contract).is_ok());
+            push_spread_root(&contract, &root);
+            *bot = contract;
+            let (after_reads, after_writes) = storage_rw();
+            (after_reads - reads, after_writes - writes)
         }
 
         fn set_balance(account_id: AccountId, balance: Balance) {
-            test::set_account_balance::<ink_env::DefaultEnvironment>(
-                account_id, balance,
-            )
-            .expect("Cannot set account balance");
+            test::set_account_balance::<Environment>(account_id, balance)
         }
 
         fn get_balance(account_id: AccountId) -> Balance {
-            test::get_account_balance::<ink_env::DefaultEnvironment>(account_id)
-                .expect("Cannot set account balance")
+            test::get_account_balance::<Environment>(account_id)
+                .expect("Cannot get account balance")
         }
     }
 }
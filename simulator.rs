@@ -0,0 +1,539 @@
+//! An in-memory mirror of the ledger of the contract, so the bot could check
+//! a batch of tips and rains locally, before paying for a dry run on chain.
+//!
+//! The simulator is fed from a `Snapshot` of the contract, built from its
+//! `export_users`, `params` and `group` queries, and applies the checks of
+//! the contract on the balances, the tip limits, the self-lock, the daily
+//! limit, the relay fee and the users who disabled tipping by the bot,
+//! keeping the bindings and the balances in a `ledger::Ledger`.
+//!
+//! It does not know about the reserve, the round-up, the newcomer and
+//! verification level limits, the inbox policies, the bans and policies of
+//! the groups, nor the tipEDG burned to cover a short balance, so its outcome
+//! is only a hint of what the contract would do.
+
+pub use crate::ledger::TelegramId;
+use crate::ledger::{
+    self, Balance, BlockNumber, EraIndex, Ledger, LedgerError,
+};
+use std::collections::HashMap;
+
+/// The bytes of an `AccountId`.
+pub type AccountId = [u8; 32];
+
+/// A Telegram group chat Id.
+pub type GroupId = i64;
+
+/// The fields of a `UserRecord`, as returned by `export_users`, the
+/// simulator needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSnapshot {
+    /// The account of the user.
+    pub account: AccountId,
+    /// The Telegram Id the user is bound to.
+    pub tg_id: TelegramId,
+    /// The balance the user holds in the contract.
+    pub balance: Balance,
+    /// How much of the balance is held by pending slashes.
+    pub pending_slash: Balance,
+    /// How much of the balance is locked by votes.
+    pub locked: Balance,
+    /// The block the lock expires at.
+    pub locked_until: BlockNumber,
+    /// How much the user tipped during `spent_era`.
+    pub spent_today: Balance,
+    /// The era `spent_today` is counted in.
+    pub spent_era: EraIndex,
+    /// The daily spending limit of the user, if any.
+    pub daily_limit: Option<Balance>,
+    /// Whether the user locked thier account.
+    pub self_locked: bool,
+    /// Whether the bot could tip on behalf of the user.
+    pub bot_tipping_enabled: bool,
+}
+
+/// The state of the contract the simulator starts from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Snapshot {
+    /// The block the snapshot was taken at.
+    pub block: BlockNumber,
+    /// How many blocks an era of the treasury history spans.
+    pub era_length: BlockNumber,
+    /// The fee taken from every tip, in basis points.
+    pub fee_bps: u16,
+    /// The smallest allowed tip.
+    pub min_tip: Balance,
+    /// The largest allowed tip.
+    pub max_tip: Balance,
+    /// The flat fee charged to the sender of every tip the bot relays.
+    pub relay_fee: Balance,
+    /// The bound users.
+    pub users: Vec<UserSnapshot>,
+    /// The pots of the groups to rain from.
+    pub group_pots: Vec<(GroupId, Balance)>,
+}
+
+/// An operation of a batch to simulate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// A tip of `amount` from one user to another, as in `tip_from`.
+    Tip {
+        /// The sender of the tip.
+        from: TelegramId,
+        /// The recipient of the tip.
+        to: TelegramId,
+        /// The amount to tip.
+        amount: Balance,
+    },
+    /// A rain of `amount` to each of the `recipients` from the pot of a
+    /// group, as in `rain_in_group`.
+    Rain {
+        /// The group to rain in.
+        group_id: GroupId,
+        /// The recipients of the rain.
+        recipients: Vec<TelegramId>,
+        /// The amount every recipient gets.
+        amount: Balance,
+    },
+}
+
+/// Why a simulated operation would fail on chain, named after the error the
+/// contract returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    /// The user, or the group, is not known.
+    NotFound,
+    /// The user tips themselves.
+    SelfTip,
+    /// The amount is zero, or there are no recipients.
+    ZeroAmount,
+    /// The sender locked thier account.
+    AccountSelfLocked,
+    /// The sender disabled tipping by the bot.
+    NotAllowed,
+    /// The tip is smaller than the minimum tip.
+    BelowMinTip {
+        /// The smallest allowed tip.
+        min: Balance,
+    },
+    /// The tip is larger than the maximum tip.
+    AboveMaxTip {
+        /// The largest allowed tip.
+        max: Balance,
+    },
+    /// The sender, or the group pot, can not cover the operation.
+    InsufficientFunds {
+        /// How much is needed.
+        required: Balance,
+        /// How much is available.
+        available: Balance,
+    },
+    /// The tip would exceed the daily limit of the sender.
+    DailyLimitExceeded {
+        /// How much is left of the limit.
+        remaining: Balance,
+    },
+    /// The rain has more than `MAX_RAIN_RECIPIENTS` recipients.
+    TooManyRecipients,
+    /// A balance would overflow.
+    Overflow,
+}
+
+impl From<LedgerError> for SimError {
+    fn from(error: LedgerError) -> Self {
+        match error {
+            LedgerError::ZeroAmount => SimError::ZeroAmount,
+            LedgerError::InsufficientFunds {
+                required,
+                available,
+            } => SimError::InsufficientFunds {
+                required,
+                available,
+            },
+            LedgerError::Overflow => SimError::Overflow,
+            // the simulator does not bind, nor unbind.
+            LedgerError::AccountAlreadyBound
+            | LedgerError::TelegramIdTaken
+            | LedgerError::NotFound => SimError::NotFound,
+        }
+    }
+}
+
+/// The daily spending of a user, and whether they locked thier account.
+#[derive(Debug, Clone)]
+struct Spending {
+    spent_today: Balance,
+    spent_era: EraIndex,
+    daily_limit: Option<Balance>,
+    self_locked: bool,
+    bot_tipping_enabled: bool,
+}
+
+/// The ledger of the contract, in memory.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    block: BlockNumber,
+    era_length: BlockNumber,
+    fee_bps: u16,
+    min_tip: Balance,
+    max_tip: Balance,
+    relay_fee: Balance,
+    ledger: Ledger<AccountId>,
+    spending: HashMap<TelegramId, Spending>,
+    group_pots: HashMap<GroupId, Balance>,
+    fees: Balance,
+    relay_fees: Balance,
+}
+
+impl Simulator {
+    /// Start from the `snapshot`.
+    ///
+    /// A user whose account, or TelegramId, is already in the snapshot is
+    /// left out, as the contract could not have bound it.
+    pub fn new(snapshot: Snapshot) -> Self {
+        let mut ledger = Ledger::new();
+        let mut spending = HashMap::new();
+        for user in snapshot.users {
+            if ledger.bind(user.account, user.tg_id).is_err() {
+                continue;
+            }
+            *ledger
+                .entry_mut(user.tg_id)
+                .expect("the user was just bound") = ledger::Entry {
+                balance: user.balance,
+                pending_slash: user.pending_slash,
+                locked: user.locked,
+                locked_until: user.locked_until,
+            };
+            spending.insert(
+                user.tg_id,
+                Spending {
+                    spent_today: user.spent_today,
+                    spent_era: user.spent_era,
+                    daily_limit: user.daily_limit,
+                    self_locked: user.self_locked,
+                    bot_tipping_enabled: user.bot_tipping_enabled,
+                },
+            );
+        }
+        Self {
+            block: snapshot.block,
+            era_length: snapshot.era_length.max(1),
+            fee_bps: snapshot.fee_bps,
+            min_tip: snapshot.min_tip,
+            max_tip: snapshot.max_tip,
+            relay_fee: snapshot.relay_fee,
+            ledger,
+            spending,
+            group_pots: snapshot.group_pots.into_iter().collect(),
+            fees: 0,
+            relay_fees: 0,
+        }
+    }
+
+    /// The balance of the TelegramId, zero if it is not bound.
+    pub fn balance_of(&self, tg_id: TelegramId) -> Balance {
+        self.ledger.balance_of(tg_id)
+    }
+
+    /// The pot of the group, zero if it is not known.
+    pub fn group_pot(&self, group_id: GroupId) -> Balance {
+        self.group_pots.get(&group_id).copied().unwrap_or(0)
+    }
+
+    /// The fees the simulated tips paid into the treasury.
+    pub fn fees(&self) -> Balance { self.fees }
+
+    /// The relay fees the simulated tips paid to the bot.
+    pub fn relay_fees(&self) -> Balance { self.relay_fees }
+
+    /// Move the simulation to the `block`, which expires locks and starts
+    /// new eras.
+    pub fn advance_to(&mut self, block: BlockNumber) {
+        self.block = self.block.max(block);
+    }
+
+    /// Apply the `operations` in order on a copy of the simulator, returning
+    /// the copy if they all succeed.
+    ///
+    /// Errors:
+    /// Returns the index of the first failing operation, and why it fails.
+    pub fn simulate(
+        &self,
+        operations: &[Operation],
+    ) -> Result<Simulator, (usize, SimError)> {
+        let mut simulator = self.clone();
+        for (index, operation) in operations.iter().enumerate() {
+            simulator.apply(operation).map_err(|error| (index, error))?;
+        }
+        Ok(simulator)
+    }
+
+    /// Apply one operation.
+    pub fn apply(&mut self, operation: &Operation) -> Result<(), SimError> {
+        match operation {
+            Operation::Tip { from, to, amount } => {
+                self.tip(*from, *to, *amount).map(|_| ())
+            },
+            Operation::Rain {
+                group_id,
+                recipients,
+                amount,
+            } => self.rain(*group_id, recipients, *amount),
+        }
+    }
+
+    /// Tip `amount` from one user to another, as the bot does with
+    /// `tip_from`, returning the fee.
+    ///
+    /// The tip is paid out of the contract, so only the balance of the
+    /// sender changes, and it is charged the relay fee on top of the tip.
+    pub fn tip(
+        &mut self,
+        from: TelegramId,
+        to: TelegramId,
+        amount: Balance,
+    ) -> Result<Balance, SimError> {
+        if self.ledger.address_of(to).is_none() {
+            return Err(SimError::NotFound);
+        }
+        let era = self.block / self.era_length;
+        let block = self.block;
+        let (min_tip, max_tip) = (self.min_tip, self.max_tip);
+        let entry = self.ledger.entry(from).ok_or(SimError::NotFound)?;
+        let user = self
+            .spending
+            .get_mut(&from)
+            .expect("every bound user has thier spending");
+        if !user.bot_tipping_enabled {
+            return Err(SimError::NotAllowed);
+        }
+        let relay_fee = self.relay_fee;
+        let available = entry.spendable(block);
+        if available < relay_fee {
+            return Err(SimError::InsufficientFunds {
+                required: relay_fee,
+                available,
+            });
+        }
+        let available = available - relay_fee;
+        if user.self_locked {
+            return Err(SimError::AccountSelfLocked);
+        }
+        if amount == 0 {
+            return Err(SimError::ZeroAmount);
+        }
+        if from == to {
+            return Err(SimError::SelfTip);
+        }
+        if amount < min_tip {
+            return Err(SimError::BelowMinTip { min: min_tip });
+        }
+        if amount > max_tip {
+            return Err(SimError::AboveMaxTip { max: max_tip });
+        }
+        if available < amount {
+            return Err(SimError::InsufficientFunds {
+                required: amount,
+                available,
+            });
+        }
+        let spent = ledger::spent_in(user.spent_today, user.spent_era, era);
+        ledger::check_daily_limit(spent, amount, user.daily_limit)
+            .map_err(|remaining| SimError::DailyLimitExceeded { remaining })?;
+        user.spent_today = spent + amount;
+        user.spent_era = era;
+        self.ledger.debit(from, amount + relay_fee, block)?;
+        let fee = ledger::fee_of(amount, self.fee_bps);
+        self.fees = self.fees.checked_add(fee).ok_or(SimError::Overflow)?;
+        self.relay_fees = self.relay_fees.saturating_add(relay_fee);
+        Ok(fee)
+    }
+
+    /// Rain `amount` from the pot of a group into the balance of every one
+    /// of the `recipients`, once each.
+    pub fn rain(
+        &mut self,
+        group_id: GroupId,
+        recipients: &[TelegramId],
+        amount: Balance,
+    ) -> Result<(), SimError> {
+        let pot = *self.group_pots.get(&group_id).ok_or(SimError::NotFound)?;
+        let mut recipients = recipients.to_vec();
+        recipients.sort_unstable();
+        recipients.dedup();
+        if amount == 0 || recipients.is_empty() {
+            return Err(SimError::ZeroAmount);
+        }
+        if recipients.len() > ledger::MAX_RAIN_RECIPIENTS {
+            return Err(SimError::TooManyRecipients);
+        }
+        let total = amount
+            .checked_mul(recipients.len() as Balance)
+            .filter(|total| *total <= pot)
+            .ok_or(SimError::InsufficientFunds {
+                required: amount.saturating_mul(recipients.len() as Balance),
+                available: pot,
+            })?;
+        for tg_id in &recipients {
+            let entry = self.ledger.entry(*tg_id).ok_or(SimError::NotFound)?;
+            entry
+                .balance
+                .checked_add(amount)
+                .ok_or(SimError::Overflow)?;
+        }
+        for tg_id in &recipients {
+            self.ledger.credit(*tg_id, amount)?;
+        }
+        self.group_pots.insert(group_id, pot - total);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(tg_id: TelegramId, balance: Balance) -> UserSnapshot {
+        let mut account = [0; 32];
+        account[..4].copy_from_slice(&tg_id.to_le_bytes());
+        UserSnapshot {
+            account,
+            tg_id,
+            balance,
+            pending_slash: 0,
+            locked: 0,
+            locked_until: 0,
+            spent_today: 0,
+            spent_era: 0,
+            daily_limit: None,
+            self_locked: false,
+            bot_tipping_enabled: true,
+        }
+    }
+
+    fn simulator() -> Simulator {
+        Simulator::new(Snapshot {
+            block: 10,
+            era_length: 100,
+            fee_bps: 1_000,
+            min_tip: 1,
+            max_tip: Balance::MAX,
+            relay_fee: 0,
+            users: vec![
+                user(42, 100),
+                user(142, 0),
+                UserSnapshot {
+                    daily_limit: Some(50),
+                    ..user(242, 100)
+                },
+            ],
+            group_pots: vec![(-1001, 30)],
+        })
+    }
+
+    #[test]
+    fn simulate_works() {
+        let simulator = simulator();
+        let after = simulator
+            .simulate(&[
+                Operation::Tip {
+                    from: 42,
+                    to: 142,
+                    amount: 60,
+                },
+                Operation::Rain {
+                    group_id: -1001,
+                    recipients: vec![142, 242, 142],
+                    amount: 15,
+                },
+            ])
+            .unwrap();
+        assert_eq!(after.balance_of(42), 40);
+        assert_eq!(after.balance_of(142), 15);
+        assert_eq!(after.balance_of(242), 115);
+        assert_eq!(after.group_pot(-1001), 0);
+        assert_eq!(after.fees(), 6);
+        // the simulator itself is left as is.
+        assert_eq!(simulator.balance_of(42), 100);
+    }
+
+    #[test]
+    fn simulate_reports_the_failing_operation() {
+        let mut simulator = simulator();
+        let tip = |from, amount| Operation::Tip {
+            from,
+            to: 142,
+            amount,
+        };
+        assert_eq!(
+            simulator.simulate(&[tip(42, 60), tip(42, 60)]).err(),
+            Some((
+                1,
+                SimError::InsufficientFunds {
+                    required: 60,
+                    available: 40,
+                }
+            ))
+        );
+        assert_eq!(
+            simulator.simulate(&[tip(242, 30), tip(242, 30)]).err(),
+            Some((1, SimError::DailyLimitExceeded { remaining: 20 }))
+        );
+        assert_eq!(
+            simulator.simulate(&[tip(43, 1)]).err(),
+            Some((0, SimError::NotFound))
+        );
+        assert_eq!(
+            simulator
+                .simulate(&[Operation::Rain {
+                    group_id: -1001,
+                    recipients: vec![42, 142, 242],
+                    amount: 11,
+                }])
+                .err(),
+            Some((
+                0,
+                SimError::InsufficientFunds {
+                    required: 33,
+                    available: 30,
+                }
+            ))
+        );
+        // a new era starts the daily limit over.
+        assert!(simulator.tip(242, 142, 50).is_ok());
+        assert!(simulator.tip(242, 142, 1).is_err());
+        simulator.advance_to(100);
+        assert!(simulator.tip(242, 142, 50).is_ok());
+    }
+
+    #[test]
+    fn tip_charges_the_relay_fee() {
+        let mut simulator = Simulator::new(Snapshot {
+            max_tip: Balance::MAX,
+            relay_fee: 5,
+            users: vec![
+                user(42, 100),
+                user(142, 0),
+                UserSnapshot {
+                    bot_tipping_enabled: false,
+                    ..user(242, 100)
+                },
+            ],
+            ..Default::default()
+        });
+        assert_eq!(simulator.tip(42, 142, 90), Ok(0));
+        assert_eq!(simulator.balance_of(42), 5);
+        assert_eq!(simulator.relay_fees(), 5);
+        // the fee comes first, and the tip has to fit in what is left.
+        assert_eq!(
+            simulator.tip(42, 142, 1),
+            Err(SimError::InsufficientFunds {
+                required: 1,
+                available: 0,
+            })
+        );
+        assert_eq!(simulator.tip(242, 142, 1), Err(SimError::NotAllowed));
+        assert_eq!(simulator.balance_of(242), 100);
+    }
+}